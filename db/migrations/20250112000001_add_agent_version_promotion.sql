@@ -0,0 +1,13 @@
+-- FerrumDeck Agent Version Promotion
+-- =============================================================================
+-- Versions are created as drafts; promotion marks exactly one version per
+-- agent as the production default used when a run does not pin a version.
+-- =============================================================================
+
+ALTER TABLE agent_versions ADD COLUMN promoted BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE agent_versions ADD COLUMN promoted_at TIMESTAMPTZ;
+
+-- At most one promoted version per agent
+CREATE UNIQUE INDEX idx_agent_versions_promoted
+    ON agent_versions(agent_id)
+    WHERE promoted;
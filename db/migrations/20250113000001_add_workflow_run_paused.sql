@@ -0,0 +1,7 @@
+-- FerrumDeck Workflow Run Pause
+-- =============================================================================
+-- Operators can pause a workflow run (e.g., during an incident) and resume it
+-- later. While paused no new steps are enqueued; in-flight steps may finish.
+-- =============================================================================
+
+ALTER TYPE workflow_run_status ADD VALUE IF NOT EXISTS 'paused';
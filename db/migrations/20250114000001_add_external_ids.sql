@@ -0,0 +1,17 @@
+-- FerrumDeck External IDs
+-- =============================================================================
+-- Integrators may supply their own stable IDs when provisioning agents and
+-- workflows so repeated provisioning is idempotent. External IDs are unique
+-- per project.
+-- =============================================================================
+
+ALTER TABLE agents ADD COLUMN external_id TEXT;
+ALTER TABLE workflows ADD COLUMN external_id TEXT;
+
+CREATE UNIQUE INDEX idx_agents_project_external_id
+    ON agents(project_id, external_id)
+    WHERE external_id IS NOT NULL;
+
+CREATE UNIQUE INDEX idx_workflows_project_external_id
+    ON workflows(project_id, external_id)
+    WHERE external_id IS NOT NULL;
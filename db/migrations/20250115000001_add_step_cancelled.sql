@@ -0,0 +1,7 @@
+-- FerrumDeck Step Cancellation
+-- =============================================================================
+-- When a run is force-terminated (cancel, budget kill, policy block) its
+-- outstanding steps are moved to 'cancelled' so they no longer look active.
+-- =============================================================================
+
+ALTER TYPE step_status ADD VALUE IF NOT EXISTS 'cancelled';
@@ -0,0 +1,12 @@
+-- FerrumDeck Per-Tenant Airlock Configuration
+-- =============================================================================
+-- Tenants can override the gateway-wide Airlock configuration (mode, target
+-- tools, velocity limits, allowed domains). Tenants without a row use the
+-- gateway default.
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS tenant_airlock_configs (
+    tenant_id TEXT PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    config JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,22 @@
+-- FerrumDeck Run Archival
+-- =============================================================================
+-- Terminal runs older than a retention cutoff are moved out of the hot runs
+-- table. The archived row keeps the full run and its steps as JSON, and its
+-- id/status columns act as a tombstone so archived runs can still be looked up.
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS archived_runs (
+    id TEXT PRIMARY KEY,
+    project_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Full snapshots of the run row and its steps
+    run JSONB NOT NULL,
+    steps JSONB NOT NULL DEFAULT '[]'::jsonb
+);
+
+CREATE INDEX idx_archived_runs_project_id ON archived_runs(project_id);
+CREATE INDEX idx_archived_runs_archived_at ON archived_runs(archived_at);
@@ -0,0 +1,9 @@
+-- FerrumDeck Per-Agent Concurrency Limit
+-- =============================================================================
+-- Caps the number of active (non-terminal) runs an agent may have at once.
+-- NULL means unlimited.
+-- =============================================================================
+
+ALTER TABLE agents
+    ADD COLUMN max_concurrent_runs INTEGER
+    CHECK (max_concurrent_runs IS NULL OR max_concurrent_runs > 0);
@@ -0,0 +1,11 @@
+-- FerrumDeck Audit Event Severity
+-- =============================================================================
+-- Airlock-sourced audit events carry a syslog-style severity and the numeric
+-- risk score so SIEM rules can prioritize them.
+-- =============================================================================
+
+ALTER TABLE audit_events
+    ADD COLUMN severity TEXT,
+    ADD COLUMN risk_score INTEGER CHECK (risk_score IS NULL OR risk_score BETWEEN 0 AND 100);
+
+CREATE INDEX idx_audit_events_severity ON audit_events(severity) WHERE severity IS NOT NULL;
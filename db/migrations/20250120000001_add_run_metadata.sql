@@ -0,0 +1,7 @@
+-- FerrumDeck Run Metadata
+-- =============================================================================
+-- Caller-supplied metadata (e.g. request_id, user_email) attached to a run
+-- and propagated to its step jobs and audit events for correlation.
+-- =============================================================================
+
+ALTER TABLE runs ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,7 @@
+-- FerrumDeck Workflow Run Scheduler State
+-- =============================================================================
+-- Serialized DAG scheduler snapshot (step statuses, outputs, iteration
+-- count), so a gateway can resume a run exactly where another left off.
+-- =============================================================================
+
+ALTER TABLE workflow_runs ADD COLUMN scheduler_state JSONB;
@@ -0,0 +1,9 @@
+-- FerrumDeck Approval Quorum
+-- =============================================================================
+-- Approval requests can require several distinct approvers. Individual
+-- approvals are tracked until the quorum is reached; any rejection fails it.
+-- =============================================================================
+
+ALTER TABLE approval_requests
+    ADD COLUMN required_approvals INTEGER NOT NULL DEFAULT 1 CHECK (required_approvals >= 1),
+    ADD COLUMN approvals JSONB NOT NULL DEFAULT '[]'::jsonb;
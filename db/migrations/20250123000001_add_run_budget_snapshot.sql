@@ -0,0 +1,8 @@
+-- FerrumDeck Run Budget Snapshot
+-- =============================================================================
+-- The effective budget captured when a run is created, so budget decisions
+-- stay reproducible after the configured default changes. NULL for runs
+-- created before snapshots were recorded.
+-- =============================================================================
+
+ALTER TABLE runs ADD COLUMN budget_snapshot JSONB;
@@ -0,0 +1,13 @@
+-- FerrumDeck Allow-Once Approval Tokens
+-- =============================================================================
+-- Approval tokens are signed and self-describing; this table only records
+-- which ones have been spent so each authorizes a single tool call.
+-- =============================================================================
+
+CREATE TABLE approval_token_uses (
+    token_id TEXT PRIMARY KEY,  -- ULID format: apt_xxxxx
+    approval_id TEXT NOT NULL REFERENCES approval_requests(id) ON DELETE CASCADE,
+    used_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_approval_token_uses_approval ON approval_token_uses(approval_id);
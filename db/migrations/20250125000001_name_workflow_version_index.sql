@@ -0,0 +1,11 @@
+-- FerrumDeck Workflow Name/Version Uniqueness
+-- =============================================================================
+-- A workflow's name and version identify it within a project. The original
+-- table constraint is replaced by an explicitly named unique index so the
+-- gateway can recognize duplicates and report them as conflicts.
+-- =============================================================================
+
+ALTER TABLE workflows DROP CONSTRAINT IF EXISTS workflows_project_id_name_version_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_workflows_project_name_version
+    ON workflows(project_id, name, version);
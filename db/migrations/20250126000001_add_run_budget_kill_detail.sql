@@ -0,0 +1,8 @@
+-- FerrumDeck Run Budget Kill Detail
+-- =============================================================================
+-- Which budget dimension a budget-killed run exceeded, as
+-- {"metric": ..., "used": ..., "limit": ...}, alongside the human-readable
+-- status_reason. NULL for runs that were not budget-killed.
+-- =============================================================================
+
+ALTER TABLE runs ADD COLUMN budget_kill_detail JSONB;
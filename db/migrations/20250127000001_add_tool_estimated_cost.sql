@@ -0,0 +1,9 @@
+-- FerrumDeck Tool Cost Estimates
+-- =============================================================================
+-- Optional per-call cost estimate (in cents) set when a tool is registered.
+-- Used for velocity tracking when a worker's tool check carries no estimate
+-- of its own. NULL when the tool has no estimate.
+-- =============================================================================
+
+ALTER TABLE tools ADD COLUMN estimated_cost_cents INTEGER
+    CHECK (estimated_cost_cents >= 0);
@@ -0,0 +1,19 @@
+-- FerrumDeck Run Archive Evidence
+-- =============================================================================
+-- Archiving deletes a run from the hot tables, which used to cascade away its
+-- threats, velocity events and approvals and null out the run linkage of its
+-- audit events and policy decisions. Those records are now snapshotted into
+-- the archive, and audit events and policy decisions keep their run_id
+-- (pointing at the archived_runs tombstone) instead of being detached.
+-- =============================================================================
+
+ALTER TABLE archived_runs
+    ADD COLUMN evidence JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+COMMENT ON COLUMN archived_runs.evidence IS
+    'Snapshots of the run''s threats, approval_requests, policy_decisions and velocity_events';
+
+-- Audit events and policy decisions outlive the runs they reference
+ALTER TABLE audit_events DROP CONSTRAINT IF EXISTS audit_events_run_id_fkey;
+ALTER TABLE policy_decisions DROP CONSTRAINT IF EXISTS policy_decisions_run_id_fkey;
+ALTER TABLE policy_decisions DROP CONSTRAINT IF EXISTS policy_decisions_step_id_fkey;
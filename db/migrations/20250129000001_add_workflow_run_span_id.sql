@@ -0,0 +1,7 @@
+-- FerrumDeck Workflow Run Span ID
+-- =============================================================================
+-- Span ID of the run's root span, stored beside trace_id so step spans
+-- created later (and on other gateways) can be parented on it.
+-- =============================================================================
+
+ALTER TABLE workflow_runs ADD COLUMN span_id TEXT;
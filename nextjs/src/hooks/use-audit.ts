@@ -223,6 +223,7 @@ export function getEventTypeDisplayName(eventType: AuditEventType): string {
     "run.cancelled": "Run Cancelled",
     "run.timeout": "Run Timeout",
     "run.budget_killed": "Run Budget Killed",
+    "run.summary": "Run Summary",
     // Step events
     "step.started": "Step Started",
     "step.completed": "Step Completed",
@@ -328,6 +329,7 @@ export function getEventTypesByCategory(): Record<string, AuditEventType[]> {
       "run.cancelled",
       "run.timeout",
       "run.budget_killed",
+      "run.summary",
     ],
     step: ["step.started", "step.completed", "step.failed", "step.retry"],
     policy: [
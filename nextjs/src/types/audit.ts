@@ -8,6 +8,7 @@ export type AuditEventType =
   | "run.cancelled"
   | "run.timeout"
   | "run.budget_killed"
+  | "run.summary"
   // Step events
   | "step.started"
   | "step.completed"
@@ -266,7 +266,11 @@ class WorkflowEngine:
             return StepResult(
                 step_id=step.id,
                 status=StepStatus.SKIPPED,
-                output={"skipped": True, "reason": "Condition not met"},
+                output={
+                    "skipped": True,
+                    "cause": "condition_false",
+                    "detail": f"Condition not met: {step.condition}",
+                },
             )
 
         try:
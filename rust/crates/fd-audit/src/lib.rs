@@ -5,6 +5,8 @@
 
 pub mod event;
 pub mod redaction;
+pub mod sampling;
 
 pub use event::{AuditEvent, AuditEventKind};
 pub use redaction::{redact_json, redact_metadata, redact_string, REDACTED_PLACEHOLDER};
+pub use sampling::{AuditSampler, InvalidSampleRate};
@@ -0,0 +1,159 @@
+//! Per-action audit sampling
+//!
+//! High-volume actions such as `policy.allowed` can be recorded at a
+//! fraction of their rate. Security-relevant actions (denials, rejections,
+//! violations, kills, revocations and anything approval-related) are always
+//! recorded, whatever rate is configured for them.
+//!
+//! Sampling is keyed on the event ID rather than a random draw, so the same
+//! event always gets the same verdict.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Action verbs that are never sampled
+const ALWAYS_RECORDED_VERBS: &[&str] = &[
+    "denied",
+    "rejected",
+    "approval_required",
+    "break_glass",
+    "violation_detected",
+    "killed",
+    "revoked",
+];
+
+/// Whether an action must be recorded regardless of sampling
+pub fn is_security_relevant(action: &str) -> bool {
+    let (namespace, verb) = action.rsplit_once('.').unwrap_or(("", action));
+    namespace == "approval" || ALWAYS_RECORDED_VERBS.contains(&verb)
+}
+
+/// Error parsing a sampling spec
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("invalid audit sample rate '{0}' (expected action=rate with rate between 0 and 1)")]
+pub struct InvalidSampleRate(pub String);
+
+/// Decides which audit events are written
+#[derive(Debug, Default)]
+pub struct AuditSampler {
+    /// Fraction of events recorded per action; unlisted actions are always recorded
+    rates: HashMap<String, f64>,
+    suppressed: AtomicU64,
+}
+
+impl AuditSampler {
+    /// A sampler that records everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `action` at `rate` (clamped to `0.0..=1.0`)
+    ///
+    /// Ignored for security-relevant actions.
+    pub fn with_rate(mut self, action: impl Into<String>, rate: f64) -> Self {
+        let action = action.into();
+        if !is_security_relevant(&action) {
+            self.rates.insert(action, rate.clamp(0.0, 1.0));
+        }
+        self
+    }
+
+    /// Parse a comma-separated `action=rate` list, e.g. `policy.allowed=0.01`
+    pub fn from_spec(spec: &str) -> Result<Self, InvalidSampleRate> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .try_fold(Self::new(), |sampler, entry| {
+                let (action, rate) = entry
+                    .split_once('=')
+                    .ok_or_else(|| InvalidSampleRate(entry.to_string()))?;
+                let rate = rate
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|rate| (0.0..=1.0).contains(rate))
+                    .ok_or_else(|| InvalidSampleRate(entry.to_string()))?;
+                Ok(sampler.with_rate(action.trim(), rate))
+            })
+    }
+
+    /// Whether the event with `event_id` should be written
+    ///
+    /// Counts the event as suppressed when it is not.
+    pub fn should_record(&self, action: &str, event_id: &str) -> bool {
+        let Some(&rate) = self.rates.get(action) else {
+            return true;
+        };
+        let record = Self::bucket(event_id) < rate;
+        if !record {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+        }
+        record
+    }
+
+    /// Number of events suppressed so far
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed.load(Ordering::Relaxed)
+    }
+
+    /// Map an event ID onto `[0, 1)`
+    fn bucket(event_id: &str) -> f64 {
+        let mut hasher = DefaultHasher::new();
+        event_id.hash(&mut hasher);
+        (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_rate_suppresses_action_but_never_denials() {
+        let sampler = AuditSampler::from_spec("policy.allowed=0, policy.denied=0").unwrap();
+
+        for i in 0..50 {
+            let id = format!("aud_{}", i);
+            assert!(!sampler.should_record("policy.allowed", &id));
+            assert!(sampler.should_record("policy.denied", &id));
+            assert!(sampler.should_record("run.created", &id));
+        }
+        assert_eq!(sampler.suppressed(), 50);
+    }
+
+    #[test]
+    fn test_partial_rate_is_stable_per_event() {
+        let sampler = AuditSampler::new().with_rate("policy.allowed", 0.5);
+        let recorded = (0..1000)
+            .filter(|i| sampler.should_record("policy.allowed", &format!("aud_{}", i)))
+            .count();
+        assert!((350..650).contains(&recorded), "recorded {}", recorded);
+        assert_eq!(sampler.suppressed(), 1000 - recorded as u64);
+
+        let once = sampler.should_record("policy.allowed", "aud_fixed");
+        assert_eq!(sampler.should_record("policy.allowed", "aud_fixed"), once);
+    }
+
+    #[test]
+    fn test_security_relevant_actions() {
+        assert!(is_security_relevant("policy.denied"));
+        assert!(is_security_relevant("policy.break_glass"));
+        assert!(is_security_relevant("approval.approved"));
+        assert!(is_security_relevant("airlock.violation_detected"));
+        assert!(!is_security_relevant("policy.allowed"));
+        assert!(!is_security_relevant("api_key.used"));
+    }
+
+    #[test]
+    fn test_invalid_spec_is_rejected() {
+        assert!(AuditSampler::from_spec("").is_ok());
+        assert_eq!(
+            AuditSampler::from_spec("policy.allowed").unwrap_err(),
+            InvalidSampleRate("policy.allowed".to_string())
+        );
+        assert!(AuditSampler::from_spec("policy.allowed=1.5").is_err());
+        assert!(AuditSampler::from_spec("policy.allowed=lots").is_err());
+    }
+}
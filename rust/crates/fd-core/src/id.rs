@@ -15,6 +15,9 @@ macro_rules! define_id {
         pub struct $name(Ulid);
 
         impl $name {
+            /// Type prefix used in the string form (`<prefix>_<ulid>`)
+            pub const PREFIX: &'static str = $prefix;
+
             /// Create a new ID
             pub fn new() -> Self {
                 Self(Ulid::new())
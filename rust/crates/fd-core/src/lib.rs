@@ -2,6 +2,7 @@
 //!
 //! Core primitives for the FerrumDeck AgentOps Control Plane:
 //! - ID types (RunId, StepId, AgentId, etc.)
+//! - Tool risk levels
 //! - Error types
 //! - Configuration
 //! - Time utilities
@@ -9,8 +10,10 @@
 pub mod config;
 pub mod error;
 pub mod id;
+pub mod risk;
 pub mod time;
 
 pub use config::Config;
 pub use error::{Error, Result};
 pub use id::*;
+pub use risk::{ToolRiskLevel, UnknownRiskLevel};
@@ -0,0 +1,88 @@
+//! Tool risk classification shared by the registry, storage and policy layers
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How much damage a tool call can do, ordered `Read < Write < Destructive`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "tool_risk_level", rename_all = "snake_case")
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolRiskLevel {
+    /// Read-only operations
+    Read,
+    /// Mutations with limited blast radius
+    Write,
+    /// Irreversible or security-sensitive operations
+    Destructive,
+}
+
+impl ToolRiskLevel {
+    /// Every level, least risky first
+    pub const ALL: [ToolRiskLevel; 3] = [Self::Read, Self::Write, Self::Destructive];
+
+    /// Wire name of the level
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Destructive => "destructive",
+        }
+    }
+}
+
+impl fmt::Display for ToolRiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error parsing an unknown risk level
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown tool risk level '{0}' (expected read, write or destructive)")]
+pub struct UnknownRiskLevel(pub String);
+
+impl FromStr for ToolRiskLevel {
+    type Err = UnknownRiskLevel;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.as_str() == s)
+            .ok_or_else(|| UnknownRiskLevel(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_levels_round_trip() {
+        for level in ToolRiskLevel::ALL {
+            assert_eq!(level.as_str().parse::<ToolRiskLevel>(), Ok(level));
+            assert_eq!(
+                serde_json::to_value(level).unwrap(),
+                serde_json::json!(level.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_levels_are_ordered_by_risk() {
+        assert!(ToolRiskLevel::Destructive > ToolRiskLevel::Write);
+        assert!(ToolRiskLevel::Write > ToolRiskLevel::Read);
+    }
+
+    #[test]
+    fn test_unknown_level_is_rejected() {
+        let err = "critical".parse::<ToolRiskLevel>().unwrap_err();
+        assert_eq!(err, UnknownRiskLevel("critical".to_string()));
+        assert!("Read".parse::<ToolRiskLevel>().is_err());
+        assert!(serde_json::from_str::<ToolRiskLevel>("\"admin\"").is_err());
+    }
+}
@@ -0,0 +1,589 @@
+//! Condition expressions for conditional steps
+//!
+//! Conditions compare step outputs against literals and can be combined:
+//!
+//! ```text
+//! $.check.ok == true && ($.count.total > 0 || $.override.force == true)
+//! ```
+//!
+//! `&&`/`and` binds tighter than `||`/`or`, both short-circuit, and
+//! parentheses group. A bare operand is tested for truthiness. Set
+//! membership is written `$.check.status in ["ok", "retry"]` (or
+//! `not_in`); the right side is a literal list or a path to an array. Any
+//! comparison involving a path that doesn't resolve evaluates to false.
+//!
+//! Expressions are limited to [`MAX_CONDITION_DEPTH`] levels of parentheses
+//! and [`MAX_CONDITION_TERMS`] operands, which also bounds how deep parsing,
+//! evaluation and dropping a condition recurse.
+
+use crate::DagError;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Deepest parenthesis nesting a condition may use
+pub const MAX_CONDITION_DEPTH: usize = 32;
+
+/// Most comparisons and bare operands a condition may combine
+pub const MAX_CONDITION_TERMS: usize = 256;
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    /// Left side equals some element of the right-hand array
+    In,
+    /// Left side equals no element of the right-hand array
+    NotIn,
+}
+
+/// Side of a comparison
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// `$.step_id.field` reference into step outputs
+    Path(String),
+    /// Literal value (`true`, `42`, `"healthy"`, ...)
+    Literal(Value),
+}
+
+/// Parsed condition expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Compare {
+        left: Operand,
+        op: CompareOp,
+        right: Operand,
+    },
+    /// Bare operand, true when it resolves to a truthy value
+    Truthy(Operand),
+}
+
+impl Condition {
+    /// Parse a condition expression
+    pub fn parse(expression: &str) -> Result<Self, DagError> {
+        let invalid = |reason: String| DagError::InvalidCondition {
+            condition: expression.to_string(),
+            reason,
+        };
+
+        let tokens = tokenize(expression).map_err(invalid)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+            terms: 0,
+        };
+        let condition = parser.parse_or().map_err(invalid)?;
+        match parser.peek() {
+            None => Ok(condition),
+            Some(token) => Err(invalid(format!("unexpected {}", token))),
+        }
+    }
+
+    /// Evaluate the condition, resolving `$.` paths with `resolve`
+    ///
+    /// `resolve` returns `None` for paths that don't exist. Recursion follows
+    /// the expression tree, which [`Condition::parse`] keeps within
+    /// [`MAX_CONDITION_DEPTH`] and [`MAX_CONDITION_TERMS`].
+    pub fn evaluate<F>(&self, resolve: &F) -> bool
+    where
+        F: Fn(&str) -> Option<Value>,
+    {
+        match self {
+            Condition::And(left, right) => left.evaluate(resolve) && right.evaluate(resolve),
+            Condition::Or(left, right) => left.evaluate(resolve) || right.evaluate(resolve),
+            Condition::Compare { left, op, right } => {
+                match (left.resolve(resolve), right.resolve(resolve)) {
+                    (Some(left), Some(right)) => compare(&left, *op, &right),
+                    _ => false,
+                }
+            }
+            Condition::Truthy(operand) => operand.resolve(resolve).is_some_and(|v| truthy(&v)),
+        }
+    }
+
+    /// Evaluate the condition against step outputs keyed by step ID
+    pub fn evaluate_outputs(&self, outputs: &HashMap<String, Value>) -> bool {
+        self.evaluate(&|path| resolve_output_path(outputs, path))
+    }
+}
+
+/// Resolve a `$.step_id.field` path against step outputs keyed by step ID
+pub(crate) fn resolve_output_path(outputs: &HashMap<String, Value>, path: &str) -> Option<Value> {
+    let mut parts = path.strip_prefix("$.")?.split('.');
+    let mut current = outputs.get(parts.next()?)?;
+    for part in parts {
+        current = current.get(part)?;
+    }
+    Some(current.clone())
+}
+
+impl Operand {
+    fn resolve<F>(&self, resolve: &F) -> Option<Value>
+    where
+        F: Fn(&str) -> Option<Value>,
+    {
+        match self {
+            Operand::Path(path) => resolve(path),
+            Operand::Literal(value) => Some(value.clone()),
+        }
+    }
+}
+
+fn compare(left: &Value, op: CompareOp, right: &Value) -> bool {
+    let ordering = match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l
+            .as_f64()
+            .zip(r.as_f64())
+            .and_then(|(l, r)| l.partial_cmp(&r)),
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    };
+
+    match op {
+        CompareOp::In | CompareOp::NotIn => {
+            let Value::Array(items) = right else {
+                return false;
+            };
+            let found = items.iter().any(|item| compare(left, CompareOp::Eq, item));
+            found == (op == CompareOp::In)
+        }
+        CompareOp::Eq => ordering.map_or(left == right, |o| o == Ordering::Equal),
+        CompareOp::Ne => ordering.map_or(left != right, |o| o != Ordering::Equal),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+/// Parse a literal operand
+fn parse_literal(s: &str) -> Value {
+    match s {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        "null" => return Value::Null,
+        _ => {}
+    }
+    if let Ok(n) = s.parse::<i64>() {
+        return Value::Number(n.into());
+    }
+    if let Some(n) = s.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+        return Value::Number(n);
+    }
+    Value::String(s.to_string())
+}
+
+// =============================================================================
+// Tokenizer
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    And,
+    Or,
+    Op(CompareOp),
+    Operand(Operand),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::LBracket => write!(f, "'['"),
+            Token::RBracket => write!(f, "']'"),
+            Token::Comma => write!(f, "','"),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Op(op) => write!(f, "operator {:?}", op),
+            Token::Operand(Operand::Path(path)) => write!(f, "'{}'", path),
+            Token::Operand(Operand::Literal(value)) => write!(f, "'{}'", value),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        let two_char = match (c, next) {
+            ('&', Some('&')) => Some(Token::And),
+            ('|', Some('|')) => Some(Token::Or),
+            ('=', Some('=')) => Some(Token::Op(CompareOp::Eq)),
+            ('!', Some('=')) => Some(Token::Op(CompareOp::Ne)),
+            ('>', Some('=')) => Some(Token::Op(CompareOp::Ge)),
+            ('<', Some('=')) => Some(Token::Op(CompareOp::Le)),
+            _ => None,
+        };
+        if let Some(token) = two_char {
+            tokens.push(token);
+            i += 2;
+            continue;
+        }
+
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '[' => {
+                tokens.push(Token::LBracket);
+                i += 1;
+            }
+            ']' => {
+                tokens.push(Token::RBracket);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '>' => {
+                tokens.push(Token::Op(CompareOp::Gt));
+                i += 1;
+            }
+            '<' => {
+                tokens.push(Token::Op(CompareOp::Lt));
+                i += 1;
+            }
+            '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '"')
+                    .ok_or_else(|| "unterminated string literal".to_string())?;
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                tokens.push(Token::Operand(Operand::Literal(Value::String(text))));
+                i += end + 2;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && !is_delimiter(chars[i]) {
+                    i += 1;
+                }
+                if start == i {
+                    return Err(format!("unexpected character '{}'", c));
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "in" => Token::Op(CompareOp::In),
+                    "not_in" => Token::Op(CompareOp::NotIn),
+                    w if w.starts_with("$.") => Token::Operand(Operand::Path(word)),
+                    w => Token::Operand(Operand::Literal(parse_literal(w))),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(
+            c,
+            '(' | ')' | '[' | ']' | ',' | '=' | '!' | '<' | '>' | '&' | '|' | '"'
+        )
+}
+
+// =============================================================================
+// Parser
+// =============================================================================
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Parentheses currently open
+    depth: usize,
+    /// Operands parsed so far
+    terms: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Condition, String> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            let right = self.parse_and()?;
+            left = Condition::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Condition, String> {
+        let mut left = self.parse_primary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            let right = self.parse_primary()?;
+            left = Condition::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_primary(&mut self) -> Result<Condition, String> {
+        match self.next() {
+            Some(Token::LParen) => {
+                if self.depth == MAX_CONDITION_DEPTH {
+                    return Err(format!(
+                        "nested more than {} parentheses deep",
+                        MAX_CONDITION_DEPTH
+                    ));
+                }
+                self.depth += 1;
+                let inner = self.parse_or()?;
+                self.depth -= 1;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => Err("missing closing ')'".to_string()),
+                }
+            }
+            Some(Token::Operand(_)) if self.terms == MAX_CONDITION_TERMS => Err(format!(
+                "more than {} terms in one condition",
+                MAX_CONDITION_TERMS
+            )),
+            Some(Token::Operand(left)) => {
+                self.terms += 1;
+                self.parse_term(left)
+            }
+            Some(token) => Err(format!("unexpected {}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+
+    /// Comparison or bare operand after its left-hand operand
+    fn parse_term(&mut self, left: Operand) -> Result<Condition, String> {
+        match self.peek() {
+            Some(Token::Op(op)) => {
+                let op = *op;
+                self.pos += 1;
+                match self.next() {
+                    Some(Token::LBracket) if matches!(op, CompareOp::In | CompareOp::NotIn) => {
+                        let right = Operand::Literal(self.parse_list()?);
+                        Ok(Condition::Compare { left, op, right })
+                    }
+                    Some(Token::Operand(right)) => Ok(Condition::Compare { left, op, right }),
+                    _ => Err(format!("expected a value after {:?}", op)),
+                }
+            }
+            _ => Ok(Condition::Truthy(left)),
+        }
+    }
+
+    /// Literal list after its opening `[`: `"a", "b"]`
+    fn parse_list(&mut self) -> Result<Value, String> {
+        let mut items = Vec::new();
+        if self.peek() == Some(&Token::RBracket) {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            match self.next() {
+                Some(Token::Operand(Operand::Literal(value))) => items.push(value),
+                Some(token) => return Err(format!("list items must be literals, found {}", token)),
+                None => return Err("missing closing ']'".to_string()),
+            }
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::RBracket) => return Ok(Value::Array(items)),
+                _ => return Err("missing closing ']'".to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::cell::RefCell;
+
+    fn outputs() -> Value {
+        json!({
+            "a": {"ok": true, "name": "alpha", "tags": ["x", "y"]},
+            "b": {"count": 3},
+            "c": {"ok": false, "count": 0}
+        })
+    }
+
+    fn eval(expression: &str) -> bool {
+        let outputs = outputs();
+        let resolve = |path: &str| {
+            let mut current = &outputs;
+            for part in path.strip_prefix("$.")?.split('.') {
+                current = current.get(part)?;
+            }
+            Some(current.clone())
+        };
+        Condition::parse(expression).unwrap().evaluate(&resolve)
+    }
+
+    #[test]
+    fn test_compound_condition_requires_both_clauses() {
+        assert!(eval("$.a.ok == true && $.b.count > 0"));
+        assert!(!eval("$.c.ok == true && $.b.count > 0"));
+        assert!(!eval("$.a.ok == true && $.c.count > 0"));
+        assert!(eval("$.a.ok == true and $.b.count >= 3"));
+    }
+
+    #[test]
+    fn test_and_short_circuits_when_first_clause_false() {
+        let outputs = outputs();
+        let resolved = RefCell::new(Vec::new());
+        let resolve = |path: &str| {
+            resolved.borrow_mut().push(path.to_string());
+            let (step, field) = path.strip_prefix("$.")?.split_once('.')?;
+            outputs.get(step)?.get(field).cloned()
+        };
+
+        let condition = Condition::parse("$.c.ok == true && $.b.count > 0").unwrap();
+        assert!(!condition.evaluate(&resolve));
+        assert_eq!(*resolved.borrow(), vec!["$.c.ok"]);
+
+        resolved.borrow_mut().clear();
+        let condition = Condition::parse("$.a.ok == true || $.b.count > 0").unwrap();
+        assert!(condition.evaluate(&resolve));
+        assert_eq!(*resolved.borrow(), vec!["$.a.ok"]);
+    }
+
+    #[test]
+    fn test_or_and_parentheses() {
+        assert!(eval("$.c.ok == true || $.b.count == 3"));
+        // && binds tighter than ||
+        assert!(eval("$.a.ok == true || $.c.ok == true && $.c.count > 0"));
+        assert!(!eval("($.a.ok == true || $.c.ok == true) && $.c.count > 0"));
+        assert!(eval("($.a.name == \"alpha\")"));
+    }
+
+    #[test]
+    fn test_missing_paths_evaluate_to_false() {
+        assert!(!eval("$.missing.field == true"));
+        assert!(!eval("$.missing.field != true"));
+        assert!(!eval("$.b.missing > 0"));
+        assert!(!eval("$.missing.field"));
+        assert!(eval("$.missing.field == 1 || $.a.ok"));
+    }
+
+    #[test]
+    fn test_numeric_and_string_comparisons() {
+        assert!(eval("$.b.count == 3.0"));
+        assert!(eval("$.b.count < 10"));
+        assert!(eval("$.b.count <= 3"));
+        assert!(!eval("$.b.count > 3"));
+        assert!(eval("$.a.name != \"beta\""));
+        assert!(eval("$.a.name < \"beta\""));
+        // Ordering across types is never true
+        assert!(!eval("$.a.name > 1"));
+    }
+
+    #[test]
+    fn test_set_membership() {
+        let status = |value: &str| {
+            let outputs = json!({"check": {"status": value}});
+            move |path: &str| match path {
+                "$.check.status" => outputs["check"].get("status").cloned(),
+                _ => None,
+            }
+        };
+        let is_in = Condition::parse(r#"$.check.status in ["ok", "retry"]"#).unwrap();
+        let not_in = Condition::parse(r#"$.check.status not_in ["ok","retry"]"#).unwrap();
+
+        assert!(is_in.evaluate(&status("ok")));
+        assert!(is_in.evaluate(&status("retry")));
+        assert!(!is_in.evaluate(&status("failed")));
+        assert!(!not_in.evaluate(&status("ok")));
+        assert!(not_in.evaluate(&status("failed")));
+
+        assert!(eval("$.b.count in [1, 3.0, \"three\"]"));
+        assert!(eval("\"x\" in $.a.tags"));
+        assert!(eval("$.a.name not_in []"));
+        // Missing paths and non-array right sides are never members
+        assert!(!eval("$.missing.field not_in [1]"));
+        assert!(!eval("$.a.name in $.a.name"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for expression in [
+            "$.a.name in [\"alpha\"",
+            "$.a.name in [$.a.name]",
+            "$.a.name in [1 2]",
+            "$.a.ok ==",
+            "($.a.ok == true",
+            "$.a.ok == true &&",
+            "$.a.ok == \"open",
+            "$.a.ok == true )",
+        ] {
+            assert!(
+                matches!(
+                    Condition::parse(expression),
+                    Err(DagError::InvalidCondition { .. })
+                ),
+                "expected parse error for {expression}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_oversized_expressions_are_rejected() {
+        let nested = |depth: usize| format!("{}$.a.ok{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Condition::parse(&nested(MAX_CONDITION_DEPTH)).is_ok());
+
+        let chain = |terms: usize| vec!["true"; terms].join(" && ");
+        assert!(Condition::parse(&chain(MAX_CONDITION_TERMS)).is_ok());
+
+        // Inputs that would otherwise overflow a worker thread's stack
+        for expression in [nested(100_000), chain(50_000)] {
+            assert!(matches!(
+                Condition::parse(&expression),
+                Err(DagError::InvalidCondition { .. })
+            ));
+        }
+        assert!(Condition::parse(&nested(MAX_CONDITION_DEPTH + 1)).is_err());
+        assert!(Condition::parse(&chain(MAX_CONDITION_TERMS + 1)).is_err());
+    }
+}
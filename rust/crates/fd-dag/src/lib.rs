@@ -12,9 +12,11 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use thiserror::Error;
 use tracing::{debug, instrument};
 
+mod condition;
 mod scheduler;
 
-pub use scheduler::{DagScheduler, SchedulerState, StepCompletionResult};
+pub use condition::{CompareOp, Condition, Operand, MAX_CONDITION_DEPTH, MAX_CONDITION_TERMS};
+pub use scheduler::{DagScheduler, SchedulerState, StepCompletionResult, NO_PROGRESS_POSSIBLE};
 
 /// DAG-related errors
 #[derive(Debug, Error)]
@@ -33,10 +35,57 @@ pub enum DagError {
 
     #[error("Invalid step configuration: {0}")]
     InvalidConfiguration(String),
+
+    #[error("Maximum loop iterations exceeded: {0}")]
+    MaxIterationsExceeded(u32),
+
+    #[error("Invalid condition '{condition}': {reason}")]
+    InvalidCondition { condition: String, reason: String },
+
+    #[error("Output projection '{projection}' failed for step '{step}': {reason}")]
+    InvalidProjection {
+        step: String,
+        projection: String,
+        reason: String,
+    },
+}
+
+/// Non-fatal problem found while validating a workflow DAG
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DagWarning {
+    /// The step can never run because some dependency can never complete
+    UnreachableStep {
+        step: String,
+        /// Dependencies that are themselves unreachable
+        blocked_by: Vec<String>,
+    },
+}
+
+impl DagWarning {
+    /// The step the warning is about
+    pub fn step(&self) -> &str {
+        match self {
+            DagWarning::UnreachableStep { step, .. } => step,
+        }
+    }
+}
+
+impl std::fmt::Display for DagWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DagWarning::UnreachableStep { step, blocked_by } => write!(
+                f,
+                "step '{}' is unreachable: blocked by {}",
+                step,
+                blocked_by.join(", ")
+            ),
+        }
+    }
 }
 
 /// Step type in workflow
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum StepType {
     Llm,
@@ -60,6 +109,27 @@ impl std::fmt::Display for StepType {
     }
 }
 
+/// How a step failure affects the rest of the workflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnError {
+    /// Fail the workflow and cancel every pending step
+    Fail,
+    /// Skip the failed step's dependents and keep running independent steps
+    Continue,
+}
+
+impl OnError {
+    /// Interpret a workflow-level `on_error` string (anything but "fail" continues)
+    pub fn from_policy(policy: &str) -> Self {
+        if policy == "fail" {
+            OnError::Fail
+        } else {
+            OnError::Continue
+        }
+    }
+}
+
 /// Step definition in a workflow DAG
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StepDefinition {
@@ -79,16 +149,238 @@ pub struct StepDefinition {
     /// Optional condition expression for conditional execution
     #[serde(default)]
     pub condition: Option<String>,
-    /// Timeout in milliseconds
-    #[serde(default = "default_timeout")]
-    pub timeout_ms: u64,
-    /// Retry configuration
+    /// Timeout in milliseconds (filled from [`StepTypeDefaults`] on build when omitted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Retry configuration (filled from [`StepTypeDefaults`] on build when omitted)
+    #[serde(default)]
+    pub retry: Option<RetryConfig>,
+    /// Conditional edges taken on completion based on the step's outcome
+    #[serde(default)]
+    pub branches: Option<ConditionalBranches>,
+    /// Dependencies that are loop back-edges rather than ordering constraints
+    ///
+    /// Each entry names the step that closes a loop back to this one. These
+    /// edges are excluded from cycle detection and readiness; the scheduler
+    /// re-arms the loop body with `DagScheduler::reset_loop_body`.
+    #[serde(default)]
+    pub loop_back: Vec<String>,
+    /// Failure policy for this step, overriding the workflow's `on_error`
+    #[serde(default)]
+    pub on_error: Option<OnError>,
+}
+
+/// Conditional edges out of a branching step
+///
+/// On completion the step's outcome selects one list; the steps in the other
+/// list (and anything reachable only through them) are skipped. Branch targets
+/// implicitly depend on the branching step.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConditionalBranches {
+    /// Steps to run when the outcome is true
+    #[serde(rename = "true", default)]
+    pub on_true: Vec<String>,
+    /// Steps to run when the outcome is false
+    #[serde(rename = "false", default)]
+    pub on_false: Vec<String>,
+}
+
+impl ConditionalBranches {
+    /// All branch targets, taken or not
+    pub fn targets(&self) -> impl Iterator<Item = &String> {
+        self.on_true.iter().chain(self.on_false.iter())
+    }
+
+    /// Targets of the branch not taken for the given outcome
+    pub fn untaken(&self, outcome: bool) -> Vec<String> {
+        let (taken, untaken) = if outcome {
+            (&self.on_true, &self.on_false)
+        } else {
+            (&self.on_false, &self.on_true)
+        };
+        untaken
+            .iter()
+            .filter(|id| !taken.contains(id))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Timeout for steps whose type has no configured default
+pub const DEFAULT_STEP_TIMEOUT_MS: u64 = 30000;
+
+/// Timeout and retry defaults for one step type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StepDefaults {
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
     #[serde(default)]
     pub retry: Option<RetryConfig>,
 }
 
-fn default_timeout() -> u64 {
-    30000
+/// Per-step-type defaults applied to steps that omit `timeout_ms` or `retry`
+///
+/// Keyed by step type, e.g. `{"llm": {"timeout_ms": 120000}, "tool": {"retry": {...}}}`.
+/// Types without an entry fall back to [`DEFAULT_STEP_TIMEOUT_MS`] and no retry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StepTypeDefaults {
+    pub by_type: HashMap<StepType, StepDefaults>,
+}
+
+impl StepTypeDefaults {
+    /// Set the defaults for one step type
+    pub fn with(mut self, step_type: StepType, defaults: StepDefaults) -> Self {
+        self.by_type.insert(step_type, defaults);
+        self
+    }
+
+    /// Fill in whichever of `timeout_ms` and `retry` the step left unset
+    pub fn apply(&self, step: &mut StepDefinition) {
+        let defaults = self.by_type.get(&step.step_type);
+        if step.timeout_ms.is_none() {
+            step.timeout_ms = Some(
+                defaults
+                    .and_then(|d| d.timeout_ms)
+                    .unwrap_or(DEFAULT_STEP_TIMEOUT_MS),
+            );
+        }
+        if step.retry.is_none() {
+            step.retry = defaults.and_then(|d| d.retry.clone());
+        }
+    }
+}
+
+impl StepDefinition {
+    /// Effective timeout in milliseconds
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout_ms.unwrap_or(DEFAULT_STEP_TIMEOUT_MS)
+    }
+
+    /// Validate the step's configuration against what its type requires
+    pub fn validate_config(&self) -> Result<(), DagError> {
+        let invalid = |msg: &str| {
+            Err(DagError::InvalidConfiguration(format!(
+                "step '{}': {}",
+                self.id, msg
+            )))
+        };
+
+        if let Some(condition) = &self.condition {
+            Condition::parse(condition)?;
+        }
+
+        let config = match &self.config {
+            serde_json::Value::Null => return Ok(()),
+            serde_json::Value::Object(map) => map,
+            _ => return invalid("config must be an object"),
+        };
+
+        match self.step_type {
+            StepType::Tool => match config.get("tool_name") {
+                Some(serde_json::Value::String(name)) if !name.is_empty() => {}
+                _ => return invalid("tool steps require a non-empty 'tool_name'"),
+            },
+            StepType::Loop => {
+                if let Some(max) = config.get("max_iterations") {
+                    if !max.as_u64().is_some_and(|n| n > 0) {
+                        return invalid("'max_iterations' must be a positive integer");
+                    }
+                }
+                for key in ["while", "until"] {
+                    match config.get(key) {
+                        None => {}
+                        Some(serde_json::Value::String(condition)) => {
+                            Condition::parse(condition)?;
+                        }
+                        Some(_) => {
+                            return invalid(&format!("'{}' must be a condition expression", key))
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// JSONPath-style projection applied to the step's output, if configured
+    pub fn output_projection(&self) -> Option<&str> {
+        self.config.get("output_projection")?.as_str()
+    }
+
+    /// Whether the unprojected output should be kept on the step execution
+    pub fn keep_raw_output(&self) -> bool {
+        self.config
+            .get("keep_raw_output")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Apply the configured output projection (identity when unset)
+    ///
+    /// Supports `$`, dotted field access and array indexes, e.g.
+    /// `$.data.items[0].id`. A malformed projection, or one that selects
+    /// nothing, is an error.
+    pub fn project_output(
+        &self,
+        output: &serde_json::Value,
+    ) -> Result<serde_json::Value, DagError> {
+        let Some(projection) = self.output_projection() else {
+            return Ok(output.clone());
+        };
+
+        project(output, projection).map_err(|reason| DagError::InvalidProjection {
+            step: self.id.clone(),
+            projection: projection.to_string(),
+            reason,
+        })
+    }
+}
+
+/// Resolve a projection path against a value
+fn project(value: &serde_json::Value, projection: &str) -> Result<serde_json::Value, String> {
+    let rest = projection
+        .strip_prefix('$')
+        .ok_or_else(|| "projection must start with '$'".to_string())?;
+    if rest.is_empty() {
+        return Ok(value.clone());
+    }
+    let rest = rest
+        .strip_prefix('.')
+        .ok_or_else(|| "expected '.' after '$'".to_string())?;
+
+    let mut current = value;
+    for segment in rest.split('.') {
+        let (field, indexes) = match segment.find('[') {
+            Some(idx) => segment.split_at(idx),
+            None => (segment, ""),
+        };
+        if field.is_empty() {
+            return Err(format!("empty field name in segment '{}'", segment));
+        }
+        current = current
+            .get(field)
+            .ok_or_else(|| format!("field '{}' not found", field))?;
+
+        let mut indexes = indexes;
+        while !indexes.is_empty() {
+            let close = indexes
+                .find(']')
+                .filter(|_| indexes.starts_with('['))
+                .ok_or_else(|| format!("malformed index in segment '{}'", segment))?;
+            let index: usize = indexes[1..close]
+                .parse()
+                .map_err(|_| format!("invalid index '{}'", &indexes[1..close]))?;
+            current = current
+                .get(index)
+                .ok_or_else(|| format!("index {} out of range in '{}'", index, field))?;
+            indexes = &indexes[close + 1..];
+        }
+    }
+
+    Ok(current.clone())
 }
 
 /// Retry configuration for a step
@@ -157,16 +449,69 @@ pub struct WorkflowDag {
     entry_points: Vec<String>,
     /// Topologically sorted order
     topological_order: Vec<String>,
+    /// Loop back-edges: closing step -> loop heads it jumps back to
+    back_edges: HashMap<String, Vec<String>>,
 }
 
 impl WorkflowDag {
     /// Build a DAG from a list of step definitions
+    ///
+    /// An empty step list is rejected; see [`WorkflowDag::build_allow_empty`].
     #[instrument(skip(steps))]
     pub fn build(steps: Vec<StepDefinition>) -> Result<Self, DagError> {
+        Self::build_with_defaults(steps, &StepTypeDefaults::default())
+    }
+
+    /// Build a DAG, accepting an empty step list as an empty DAG
+    #[instrument(skip(steps))]
+    pub fn build_allow_empty(steps: Vec<StepDefinition>) -> Result<Self, DagError> {
+        Self::build_with(steps, false, true, &StepTypeDefaults::default())
+    }
+
+    /// Build a DAG, filling omitted timeouts and retries from per-type defaults
+    #[instrument(skip(steps, defaults))]
+    pub fn build_with_defaults(
+        steps: Vec<StepDefinition>,
+        defaults: &StepTypeDefaults,
+    ) -> Result<Self, DagError> {
+        Self::build_with(steps, false, false, defaults)
+    }
+
+    /// Build a DAG without failing on cycles, reporting dead steps instead
+    ///
+    /// Steps caught in (or downstream of) a cycle are kept but can never
+    /// run; they are reported by [`WorkflowDag::validate_reachability`],
+    /// whose warnings are returned alongside the DAG.
+    #[instrument(skip(steps))]
+    pub fn build_lenient(steps: Vec<StepDefinition>) -> Result<(Self, Vec<DagWarning>), DagError> {
+        let dag = Self::build_with(steps, true, false, &StepTypeDefaults::default())?;
+        let warnings = dag.validate_reachability();
+        Ok((dag, warnings))
+    }
+
+    fn build_with(
+        mut steps: Vec<StepDefinition>,
+        lenient: bool,
+        allow_empty: bool,
+        defaults: &StepTypeDefaults,
+    ) -> Result<Self, DagError> {
+        if steps.is_empty() && !allow_empty {
+            return Err(DagError::InvalidConfiguration(
+                "workflow has no steps".to_string(),
+            ));
+        }
+
         let mut step_map: HashMap<String, StepDefinition> = HashMap::new();
         let mut children: HashMap<String, Vec<String>> = HashMap::new();
         let mut parents: HashMap<String, Vec<String>> = HashMap::new();
 
+        for step in &mut steps {
+            defaults.apply(step);
+        }
+
+        Self::add_branch_dependencies(&mut steps)?;
+        let back_edges = Self::take_back_edges(&mut steps)?;
+
         // Index steps
         for step in steps {
             children.insert(step.id.clone(), Vec::new());
@@ -190,7 +535,11 @@ impl WorkflowDag {
         // Compute topological order using Kahn's algorithm
         // This will detect cycles (including the case where all nodes are in a cycle,
         // which results in no entry points)
-        let topological_order = Self::topological_sort(&step_map, &children)?;
+        let topological_order = if lenient {
+            Self::kahn_order(&step_map, &children)
+        } else {
+            Self::topological_sort(&step_map, &children)?
+        };
 
         // Find entry points (steps with no dependencies)
         let entry_points: Vec<String> = step_map
@@ -211,13 +560,53 @@ impl WorkflowDag {
             "Built workflow DAG"
         );
 
-        Ok(Self {
+        let dag = Self {
             steps: step_map,
             children,
             parents,
             entry_points,
             topological_order,
-        })
+            back_edges,
+        };
+
+        // A back-edge must close a loop: its head has to lead to its tail
+        for (tail, heads) in &dag.back_edges {
+            if let Some(head) = heads.iter().find(|head| !dag.is_reachable(head, tail)) {
+                return Err(DagError::InvalidConfiguration(format!(
+                    "step '{}' loops back to '{}', which does not lead to it",
+                    tail, head
+                )));
+            }
+        }
+
+        Ok(dag)
+    }
+
+    /// Pull loop back-edges out of `depends_on`, keyed by the closing step
+    fn take_back_edges(
+        steps: &mut [StepDefinition],
+    ) -> Result<HashMap<String, Vec<String>>, DagError> {
+        let ids: HashSet<String> = steps.iter().map(|s| s.id.clone()).collect();
+        let mut back_edges: HashMap<String, Vec<String>> = HashMap::new();
+
+        for step in steps.iter_mut() {
+            for tail in &step.loop_back {
+                if !ids.contains(tail) {
+                    return Err(DagError::MissingDependency {
+                        step: step.id.clone(),
+                        dependency: tail.clone(),
+                    });
+                }
+                back_edges
+                    .entry(tail.clone())
+                    .or_default()
+                    .push(step.id.clone());
+            }
+            let loop_back = &step.loop_back;
+            step.depends_on.retain(|dep| !loop_back.contains(dep));
+        }
+
+        Ok(back_edges)
     }
 
     /// Topological sort using Kahn's algorithm
@@ -225,6 +614,30 @@ impl WorkflowDag {
         steps: &HashMap<String, StepDefinition>,
         children: &HashMap<String, Vec<String>>,
     ) -> Result<Vec<String>, DagError> {
+        let order = Self::kahn_order(steps, children);
+
+        // Check for cycles
+        if order.len() != steps.len() {
+            // Find steps involved in cycle
+            let in_order: HashSet<_> = order.iter().collect();
+            let cycle_steps: Vec<_> = steps
+                .keys()
+                .filter(|k| !in_order.contains(k))
+                .cloned()
+                .collect();
+            return Err(DagError::CycleDetected(cycle_steps.join(", ")));
+        }
+
+        Ok(order)
+    }
+
+    /// Order every step whose dependencies can all complete
+    ///
+    /// Steps in or downstream of a cycle are left out.
+    fn kahn_order(
+        steps: &HashMap<String, StepDefinition>,
+        children: &HashMap<String, Vec<String>>,
+    ) -> Vec<String> {
         let mut in_degree: HashMap<String, usize> = HashMap::new();
         let mut queue: VecDeque<String> = VecDeque::new();
         let mut order: Vec<String> = Vec::new();
@@ -252,19 +665,34 @@ impl WorkflowDag {
             }
         }
 
-        // Check for cycles
-        if order.len() != steps.len() {
-            // Find steps involved in cycle
-            let in_order: HashSet<_> = order.iter().collect();
-            let cycle_steps: Vec<_> = steps
-                .keys()
-                .filter(|k| !in_order.contains(k))
-                .cloned()
-                .collect();
-            return Err(DagError::CycleDetected(cycle_steps.join(", ")));
+        order
+    }
+
+    /// Make every conditional branch target depend on its branching step
+    fn add_branch_dependencies(steps: &mut [StepDefinition]) -> Result<(), DagError> {
+        let edges: Vec<(String, String)> = steps
+            .iter()
+            .filter_map(|step| step.branches.as_ref().map(|b| (step, b)))
+            .flat_map(|(step, branches)| {
+                branches
+                    .targets()
+                    .map(|target| (step.id.clone(), target.clone()))
+            })
+            .collect();
+
+        for (branching, target) in edges {
+            let step = steps.iter_mut().find(|s| s.id == target).ok_or_else(|| {
+                DagError::InvalidConfiguration(format!(
+                    "step '{}' branches to unknown step '{}'",
+                    branching, target
+                ))
+            })?;
+            if !step.depends_on.contains(&branching) {
+                step.depends_on.push(branching);
+            }
         }
 
-        Ok(order)
+        Ok(())
     }
 
     /// Get step definition by ID
@@ -282,6 +710,107 @@ impl WorkflowDag {
         &self.entry_points
     }
 
+    /// Get exit points (steps that no other step depends on), sorted by ID
+    pub fn exit_points(&self) -> Vec<String> {
+        let mut exits: Vec<String> = self
+            .children
+            .iter()
+            .filter(|(_, children)| children.is_empty())
+            .map(|(id, _)| id.clone())
+            .collect();
+        exits.sort();
+        exits
+    }
+
+    /// Check whether `to` can be reached from `from` by following dependents
+    ///
+    /// A step is considered reachable from itself. Unknown step IDs are never reachable.
+    pub fn is_reachable(&self, from: &str, to: &str) -> bool {
+        if !self.steps.contains_key(from) || !self.steps.contains_key(to) {
+            return false;
+        }
+
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<&str> = VecDeque::new();
+        queue.push_back(from);
+
+        while let Some(step_id) = queue.pop_front() {
+            if step_id == to {
+                return true;
+            }
+            if !visited.insert(step_id) {
+                continue;
+            }
+            for child in self.children(step_id) {
+                if !visited.contains(child.as_str()) {
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Report steps that can never run from any entry point
+    ///
+    /// A step is reachable once all of its dependencies are; anything
+    /// depending, directly or transitively, on a cycle never is. DAGs from
+    /// [`WorkflowDag::build`] are acyclic, so this only finds steps in DAGs
+    /// built leniently. Warnings are sorted by step ID.
+    pub fn validate_reachability(&self) -> Vec<DagWarning> {
+        let reachable: HashSet<String> = Self::kahn_order(&self.steps, &self.children)
+            .into_iter()
+            .collect();
+
+        let mut warnings: Vec<DagWarning> = self
+            .steps
+            .keys()
+            .filter(|id| !reachable.contains(*id))
+            .map(|id| {
+                let mut blocked_by: Vec<String> = self
+                    .parents(id)
+                    .iter()
+                    .filter(|parent| !reachable.contains(*parent))
+                    .cloned()
+                    .collect();
+                blocked_by.sort();
+                DagWarning::UnreachableStep {
+                    step: id.clone(),
+                    blocked_by,
+                }
+            })
+            .collect();
+        warnings.sort_by(|a, b| a.step().cmp(b.step()));
+        warnings
+    }
+
+    /// Loop heads that a step jumps back to when it iterates
+    pub fn loop_heads(&self, step_id: &str) -> &[String] {
+        self.back_edges
+            .get(step_id)
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Steps re-run when `tail` loops back, sorted by ID
+    ///
+    /// The body is every step on a path from one of `tail`'s loop heads to
+    /// `tail` itself, both ends included. Empty if `tail` closes no loop.
+    pub fn loop_body(&self, tail: &str) -> Vec<String> {
+        let mut body: Vec<String> = self
+            .steps
+            .keys()
+            .filter(|id| {
+                self.loop_heads(tail)
+                    .iter()
+                    .any(|head| self.is_reachable(head, id) && self.is_reachable(id, tail))
+            })
+            .cloned()
+            .collect();
+        body.sort();
+        body
+    }
+
     /// Get the topologically sorted order
     pub fn topological_order(&self) -> &[String] {
         &self.topological_order
@@ -303,7 +832,29 @@ impl WorkflowDag {
             .unwrap_or(&[])
     }
 
+    /// Compute each step's generation (longest dependency depth)
+    ///
+    /// Entry points are generation 0 and every other step is one more than
+    /// its deepest parent, giving the canonical earliest start position.
+    pub fn generations(&self) -> HashMap<String, usize> {
+        let mut generations: HashMap<String, usize> = HashMap::with_capacity(self.steps.len());
+
+        for step_id in &self.topological_order {
+            let generation = self
+                .parents(step_id)
+                .iter()
+                .filter_map(|parent| generations.get(parent))
+                .max()
+                .map_or(0, |deepest| deepest + 1);
+            generations.insert(step_id.clone(), generation);
+        }
+
+        generations
+    }
+
     /// Compute execution layers (steps that can run in parallel)
+    ///
+    /// Each layer is sorted by step ID so the result is reproducible.
     pub fn execution_layers(&self) -> Vec<Vec<String>> {
         let mut layers: Vec<Vec<String>> = Vec::new();
         let mut completed: HashSet<String> = HashSet::new();
@@ -311,7 +862,7 @@ impl WorkflowDag {
 
         while !remaining.is_empty() {
             // Find all steps whose dependencies are all satisfied
-            let ready: Vec<String> = remaining
+            let mut ready: Vec<String> = remaining
                 .iter()
                 .filter(|id| {
                     self.parents
@@ -321,6 +872,7 @@ impl WorkflowDag {
                 })
                 .cloned()
                 .collect();
+            ready.sort();
 
             if ready.is_empty() {
                 // Should not happen if topological sort succeeded
@@ -338,6 +890,95 @@ impl WorkflowDag {
         layers
     }
 
+    /// DAG of `step_id` and every step it transitively depends on
+    ///
+    /// Edges between the kept steps are preserved, so the subgraph can be
+    /// scheduled or visualized on its own.
+    pub fn ancestors_subgraph(&self, step_id: &str) -> Result<WorkflowDag, DagError> {
+        let keep = self.closure(step_id, |id| self.parents(id))?;
+        Ok(self.induced_subgraph(&keep))
+    }
+
+    /// DAG of `step_id` and every step that transitively depends on it
+    ///
+    /// `step_id` becomes the only entry point: its own dependencies are
+    /// dropped along with everything else outside the subgraph.
+    pub fn descendants_subgraph(&self, step_id: &str) -> Result<WorkflowDag, DagError> {
+        let keep = self.closure(step_id, |id| self.children(id))?;
+        Ok(self.induced_subgraph(&keep))
+    }
+
+    /// `start` plus every step reachable through `next`
+    fn closure<'a>(
+        &'a self,
+        start: &str,
+        next: impl Fn(&str) -> &'a [String],
+    ) -> Result<HashSet<String>, DagError> {
+        if !self.steps.contains_key(start) {
+            return Err(DagError::StepNotFound(start.to_string()));
+        }
+
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut queue: VecDeque<String> = VecDeque::from([start.to_string()]);
+        while let Some(id) = queue.pop_front() {
+            if seen.insert(id.clone()) {
+                queue.extend(next(&id).iter().cloned());
+            }
+        }
+        Ok(seen)
+    }
+
+    /// Copy of this DAG restricted to `keep`, with edges leaving it removed
+    fn induced_subgraph(&self, keep: &HashSet<String>) -> WorkflowDag {
+        let within = |ids: &[String]| -> Vec<String> {
+            ids.iter()
+                .filter(|id| keep.contains(*id))
+                .cloned()
+                .collect()
+        };
+
+        let steps: HashMap<String, StepDefinition> = self
+            .steps
+            .iter()
+            .filter(|(id, _)| keep.contains(*id))
+            .map(|(id, step)| {
+                let mut step = step.clone();
+                step.depends_on = within(&step.depends_on);
+                step.loop_back = within(&step.loop_back);
+                if let Some(branches) = step.branches.as_mut() {
+                    branches.on_true = within(&branches.on_true);
+                    branches.on_false = within(&branches.on_false);
+                }
+                (id.clone(), step)
+            })
+            .collect();
+        let restrict = |edges: &HashMap<String, Vec<String>>| -> HashMap<String, Vec<String>> {
+            edges
+                .iter()
+                .filter(|(id, _)| keep.contains(*id))
+                .map(|(id, ids)| (id.clone(), within(ids)))
+                .collect()
+        };
+        let mut back_edges = restrict(&self.back_edges);
+        back_edges.retain(|_, heads| !heads.is_empty());
+
+        let topological_order = within(&self.topological_order);
+        let entry_points = topological_order
+            .iter()
+            .filter(|id| steps[*id].depends_on.is_empty())
+            .cloned()
+            .collect();
+
+        WorkflowDag {
+            children: restrict(&self.children),
+            parents: restrict(&self.parents),
+            back_edges,
+            entry_points,
+            topological_order,
+            steps,
+        }
+    }
+
     /// Get the number of steps
     pub fn len(&self) -> usize {
         self.steps.len()
@@ -387,8 +1028,11 @@ mod tests {
             config: serde_json::json!({}),
             depends_on: depends_on.into_iter().map(String::from).collect(),
             condition: None,
-            timeout_ms: 30000,
+            timeout_ms: None,
             retry: None,
+            branches: None,
+            loop_back: vec![],
+            on_error: None,
         }
     }
 
@@ -415,6 +1059,20 @@ mod tests {
         assert!(pos("c") < pos("d"));
     }
 
+    #[test]
+    fn test_empty_workflow_rejected_unless_allowed() {
+        match WorkflowDag::build(vec![]) {
+            Err(DagError::InvalidConfiguration(msg)) => assert_eq!(msg, "workflow has no steps"),
+            other => panic!("expected empty workflow to be rejected, got {:?}", other),
+        }
+        assert!(WorkflowDag::build_lenient(vec![]).is_err());
+
+        let dag = WorkflowDag::build_allow_empty(vec![]).unwrap();
+        assert!(dag.is_empty());
+        assert!(dag.entry_points().is_empty());
+        assert!(dag.execution_layers().is_empty());
+    }
+
     #[test]
     fn test_parallel_steps() {
         let steps = vec![
@@ -436,6 +1094,33 @@ mod tests {
         assert_eq!(layers[2], vec!["final"]);
     }
 
+    #[test]
+    fn test_execution_layers_are_sorted_and_reproducible() {
+        let steps = || {
+            vec![
+                make_step("zeta", vec![]),
+                make_step("alpha", vec![]),
+                make_step("mid", vec![]),
+                make_step("delta", vec!["zeta", "alpha"]),
+                make_step("beta", vec!["mid"]),
+                make_step("omega", vec!["delta", "beta"]),
+            ]
+        };
+
+        let first = WorkflowDag::build(steps()).unwrap().execution_layers();
+        let second = WorkflowDag::build(steps()).unwrap().execution_layers();
+
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            vec![
+                vec!["alpha", "mid", "zeta"],
+                vec!["beta", "delta"],
+                vec!["omega"],
+            ]
+        );
+    }
+
     #[test]
     fn test_cycle_detection() {
         let steps = vec![
@@ -448,6 +1133,204 @@ mod tests {
         assert!(matches!(result, Err(DagError::CycleDetected(_))));
     }
 
+    #[test]
+    fn test_lenient_build_reports_step_behind_cycle() {
+        let steps = vec![
+            make_step("start", vec![]),
+            make_step("done", vec!["start"]),
+            // Isolated cluster that can never start
+            make_step("x", vec!["y"]),
+            make_step("y", vec!["x"]),
+            make_step("orphan", vec!["x"]),
+        ];
+
+        assert!(matches!(
+            WorkflowDag::build(steps.clone()),
+            Err(DagError::CycleDetected(_))
+        ));
+
+        let (dag, warnings) = WorkflowDag::build_lenient(steps).unwrap();
+        assert_eq!(dag.len(), 5);
+        assert_eq!(dag.topological_order(), &["start", "done"]);
+
+        let unreachable: Vec<&str> = warnings.iter().map(DagWarning::step).collect();
+        assert_eq!(unreachable, vec!["orphan", "x", "y"]);
+        assert_eq!(
+            warnings[0],
+            DagWarning::UnreachableStep {
+                step: "orphan".to_string(),
+                blocked_by: vec!["x".to_string()],
+            }
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "step 'orphan' is unreachable: blocked by x"
+        );
+    }
+
+    #[test]
+    fn test_strict_dag_has_no_reachability_warnings() {
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec!["a"]),
+            make_step("c", vec!["a", "b"]),
+        ];
+
+        let dag = WorkflowDag::build(steps).unwrap();
+        assert!(dag.validate_reachability().is_empty());
+    }
+
+    #[test]
+    fn test_declared_back_edge_is_not_a_cycle() {
+        // start -> draft -> review, with review looping back to draft
+        let mut draft = make_step("draft", vec!["start", "review"]);
+        draft.loop_back = vec!["review".to_string()];
+        let steps = vec![
+            make_step("start", vec![]),
+            draft,
+            make_step("review", vec!["draft"]),
+            make_step("publish", vec!["review"]),
+        ];
+
+        let dag = WorkflowDag::build(steps).unwrap();
+        assert_eq!(dag.parents("draft"), &["start"]);
+        assert_eq!(dag.get_step("draft").unwrap().depends_on, vec!["start"]);
+        assert_eq!(dag.loop_heads("review"), &["draft"]);
+        assert_eq!(dag.loop_body("review"), vec!["draft", "review"]);
+        assert!(dag.loop_body("publish").is_empty());
+    }
+
+    #[test]
+    fn test_back_edge_must_close_a_loop() {
+        let mut a = make_step("a", vec![]);
+        a.loop_back = vec!["b".to_string()];
+        let result = WorkflowDag::build(vec![a.clone(), make_step("b", vec![])]);
+        assert!(matches!(result, Err(DagError::InvalidConfiguration(_))));
+
+        a.loop_back = vec!["missing".to_string()];
+        let result = WorkflowDag::build(vec![a]);
+        assert!(matches!(result, Err(DagError::MissingDependency { .. })));
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let mut step = make_step("fetch", vec![]);
+        assert!(step.validate_config().is_ok());
+
+        step.config = serde_json::json!("not an object");
+        assert!(matches!(
+            step.validate_config(),
+            Err(DagError::InvalidConfiguration(_))
+        ));
+
+        step.step_type = StepType::Tool;
+        step.config = serde_json::json!({});
+        assert!(step.validate_config().is_err());
+
+        step.config = serde_json::json!({"tool_name": "http_get"});
+        assert!(step.validate_config().is_ok());
+
+        step.step_type = StepType::Loop;
+        step.config = serde_json::json!({"max_iterations": 0});
+        assert!(step.validate_config().is_err());
+
+        step.config = serde_json::json!({"max_iterations": 3, "until": "$.check.done == true"});
+        assert!(step.validate_config().is_ok());
+
+        step.config = serde_json::json!({"while": 3});
+        assert!(step.validate_config().is_err());
+    }
+
+    #[test]
+    fn test_validate_config_rejects_malformed_condition() {
+        let mut step = make_step("check", vec![]);
+        step.condition = Some("($.check.ok == true && $.check.count > 0)".to_string());
+        assert!(step.validate_config().is_ok());
+
+        step.condition = Some("($.check.ok == true".to_string());
+        assert!(matches!(
+            step.validate_config(),
+            Err(DagError::InvalidCondition { .. })
+        ));
+    }
+
+    #[test]
+    fn test_output_projection_extracts_nested_value() {
+        let mut step = make_step("fetch", vec![]);
+        let output = serde_json::json!({
+            "data": {"result": {"score": 0.9}, "items": [{"id": "a"}, {"id": "b"}]},
+            "debug": "large payload"
+        });
+
+        // No projection keeps the output as-is
+        assert_eq!(step.project_output(&output).unwrap(), output);
+
+        step.config = serde_json::json!({"output_projection": "$.data.result"});
+        assert_eq!(
+            step.project_output(&output).unwrap(),
+            serde_json::json!({"score": 0.9})
+        );
+
+        step.config = serde_json::json!({"output_projection": "$.data.items[1].id"});
+        assert_eq!(step.project_output(&output).unwrap(), "b");
+        assert!(!step.keep_raw_output());
+    }
+
+    #[test]
+    fn test_invalid_output_projection_errors() {
+        let mut step = make_step("fetch", vec![]);
+        let output = serde_json::json!({"data": {"items": []}});
+
+        for projection in [
+            "data.result",
+            "$.data.missing",
+            "$..data",
+            "$.data.items[0]",
+            "$.data.items[x]",
+        ] {
+            step.config = serde_json::json!({"output_projection": projection});
+            let err = step.project_output(&output).unwrap_err();
+            assert!(matches!(err, DagError::InvalidProjection { .. }));
+        }
+
+        step.config = serde_json::json!({"output_projection": "$.data.missing"});
+        let message = step.project_output(&output).unwrap_err().to_string();
+        assert!(message.contains("'$.data.missing'"));
+        assert!(message.contains("step 'fetch'"));
+        assert!(message.contains("field 'missing' not found"));
+    }
+
+    #[test]
+    fn test_branch_targets_depend_on_branching_step() {
+        let mut check = make_step("check", vec![]);
+        check.branches = Some(ConditionalBranches {
+            on_true: vec!["deploy".to_string()],
+            on_false: vec!["notify".to_string()],
+        });
+        let steps = vec![
+            check,
+            make_step("deploy", vec![]),
+            make_step("notify", vec![]),
+        ];
+
+        let dag = WorkflowDag::build(steps).unwrap();
+        assert_eq!(dag.entry_points(), &["check"]);
+        assert_eq!(dag.parents("deploy"), &["check"]);
+        assert_eq!(dag.parents("notify"), &["check"]);
+    }
+
+    #[test]
+    fn test_branch_to_unknown_step() {
+        let mut check = make_step("check", vec![]);
+        check.branches = Some(ConditionalBranches {
+            on_true: vec!["missing".to_string()],
+            on_false: vec![],
+        });
+
+        let result = WorkflowDag::build(vec![check]);
+        assert!(matches!(result, Err(DagError::InvalidConfiguration(_))));
+    }
+
     #[test]
     fn test_missing_dependency() {
         let steps = vec![make_step("a", vec!["nonexistent"])];
@@ -487,4 +1370,178 @@ mod tests {
         let ready = compute_ready_steps(&dag, &completed);
         assert_eq!(ready, vec!["d"]);
     }
+
+    #[test]
+    fn test_exit_points_diamond() {
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec!["a"]),
+            make_step("c", vec!["a"]),
+            make_step("d", vec!["b", "c"]),
+        ];
+
+        let dag = WorkflowDag::build(steps).unwrap();
+        assert_eq!(dag.exit_points(), vec!["d"]);
+    }
+
+    #[test]
+    fn test_ancestors_subgraph_of_diamond() {
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec!["a"]),
+            make_step("c", vec!["a"]),
+            make_step("d", vec!["b", "c"]),
+        ];
+        let dag = WorkflowDag::build(steps).unwrap();
+
+        let sink = dag.ancestors_subgraph("d").unwrap();
+        assert_eq!(sink.len(), 4);
+        assert_eq!(sink.entry_points(), ["a"]);
+        assert_eq!(sink.parents("d"), ["b", "c"]);
+        assert_eq!(sink.execution_layers(), dag.execution_layers());
+
+        let entry = dag.ancestors_subgraph("a").unwrap();
+        assert_eq!(entry.len(), 1);
+        assert!(entry.children("a").is_empty());
+
+        let branch = dag.ancestors_subgraph("b").unwrap();
+        assert_eq!(branch.topological_order(), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_descendants_subgraph_drops_outside_dependencies() {
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec!["a"]),
+            make_step("c", vec!["a"]),
+            make_step("d", vec!["b", "c"]),
+        ];
+        let dag = WorkflowDag::build(steps).unwrap();
+
+        let sub = dag.descendants_subgraph("b").unwrap();
+        let mut ids: Vec<&String> = sub.step_ids();
+        ids.sort();
+        assert_eq!(ids, ["b", "d"]);
+        assert_eq!(sub.entry_points(), ["b"]);
+        assert_eq!(sub.parents("d"), ["b"]);
+        assert!(sub.get_step("b").unwrap().depends_on.is_empty());
+
+        assert!(matches!(
+            dag.descendants_subgraph("missing"),
+            Err(DagError::StepNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_generations_diamond() {
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec!["a"]),
+            make_step("c", vec!["a"]),
+            make_step("d", vec!["b", "c"]),
+        ];
+
+        let generations = WorkflowDag::build(steps).unwrap().generations();
+        assert_eq!(generations.len(), 4);
+        assert_eq!(generations["a"], 0);
+        assert_eq!(generations["b"], 1);
+        assert_eq!(generations["c"], 1);
+        assert_eq!(generations["d"], 2);
+    }
+
+    #[test]
+    fn test_generations_use_longest_path() {
+        // d depends on a directly and through the chain a -> b -> c
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec!["a"]),
+            make_step("c", vec!["b"]),
+            make_step("d", vec!["a", "c"]),
+            make_step("e", vec![]),
+        ];
+
+        let generations = WorkflowDag::build(steps).unwrap().generations();
+        assert_eq!(generations["d"], 3);
+        assert_eq!(generations["e"], 0);
+    }
+
+    #[test]
+    fn test_exit_points_multiple_sinks() {
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("c", vec!["a"]),
+            make_step("b", vec!["a"]),
+        ];
+
+        let dag = WorkflowDag::build(steps).unwrap();
+        assert_eq!(dag.exit_points(), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_is_reachable() {
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec!["a"]),
+            make_step("c", vec!["a"]),
+            make_step("d", vec!["b", "c"]),
+            make_step("e", vec![]),
+        ];
+
+        let dag = WorkflowDag::build(steps).unwrap();
+
+        assert!(dag.is_reachable("a", "d"));
+        assert!(dag.is_reachable("b", "d"));
+        assert!(dag.is_reachable("a", "a"));
+
+        // Edges are directed: dependencies are not reachable from dependents
+        assert!(!dag.is_reachable("d", "a"));
+        // Sibling branches do not reach each other
+        assert!(!dag.is_reachable("b", "c"));
+        // Disconnected entry point
+        assert!(!dag.is_reachable("e", "d"));
+        // Unknown steps
+        assert!(!dag.is_reachable("a", "missing"));
+    }
+
+    #[test]
+    fn test_step_type_defaults_fill_omitted_values_only() {
+        let defaults = StepTypeDefaults::default()
+            .with(
+                StepType::Llm,
+                StepDefaults {
+                    timeout_ms: Some(120_000),
+                    retry: None,
+                },
+            )
+            .with(
+                StepType::Tool,
+                StepDefaults {
+                    timeout_ms: None,
+                    retry: Some(RetryConfig {
+                        max_attempts: 2,
+                        delay_ms: 500,
+                        backoff_multiplier: 2.0,
+                    }),
+                },
+            );
+
+        let mut explicit = make_step("explicit", vec![]);
+        explicit.timeout_ms = Some(5_000);
+        let mut tool = make_step("tool", vec![]);
+        tool.step_type = StepType::Tool;
+        let steps = vec![make_step("llm", vec![]), explicit, tool];
+
+        let dag = WorkflowDag::build_with_defaults(steps, &defaults).unwrap();
+        assert_eq!(dag.get_step("llm").unwrap().timeout_ms, Some(120_000));
+        assert_eq!(dag.get_step("explicit").unwrap().timeout_ms, Some(5_000));
+
+        let tool = dag.get_step("tool").unwrap();
+        assert_eq!(tool.timeout_ms(), DEFAULT_STEP_TIMEOUT_MS);
+        assert_eq!(tool.retry.as_ref().unwrap().max_attempts, 2);
+        assert!(dag.get_step("llm").unwrap().retry.is_none());
+
+        let parsed: StepTypeDefaults =
+            serde_json::from_value(serde_json::json!({"llm": {"timeout_ms": 90000}})).unwrap();
+        assert_eq!(parsed.by_type[&StepType::Llm].timeout_ms, Some(90_000));
+    }
 }
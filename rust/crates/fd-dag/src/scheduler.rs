@@ -3,13 +3,18 @@
 use std::collections::{HashMap, HashSet};
 use tracing::{debug, info, instrument, warn};
 
-use crate::{DagError, StepDefinition, StepStatus, WorkflowDag};
+use crate::{Condition, DagError, OnError, StepDefinition, StepStatus, StepType, WorkflowDag};
+
+/// Failure reason reported when a run deadlocks
+pub const NO_PROGRESS_POSSIBLE: &str = "no progress possible";
 
 /// Result of a step completion
 #[derive(Debug, Clone)]
 pub struct StepCompletionResult {
     /// Steps that are now ready to execute
     pub ready_steps: Vec<String>,
+    /// Steps skipped as a side effect (e.g. an untaken conditional branch)
+    pub skipped_steps: Vec<String>,
     /// Whether the workflow is complete
     pub workflow_complete: bool,
     /// Whether the workflow has failed
@@ -31,6 +36,12 @@ pub struct SchedulerState {
     pub max_iterations: u32,
     /// Current iteration count
     pub iteration_count: u32,
+    /// Iterations run by each loop, keyed by the step that closes it
+    #[serde(default)]
+    pub loop_iterations: HashMap<String, u32>,
+    /// Whether the run is paused (no new steps are scheduled)
+    #[serde(default)]
+    pub paused: bool,
 }
 
 /// Scheduler for managing workflow DAG execution
@@ -45,11 +56,13 @@ pub struct DagScheduler {
     /// On-error policy: "fail" or "continue"
     on_error: String,
     /// Maximum iterations (for loop detection)
-    #[allow(dead_code)]
     max_iterations: u32,
     /// Current iteration count
-    #[allow(dead_code)]
     iteration_count: u32,
+    /// Iterations run by each loop, keyed by the step that closes it
+    loop_iterations: HashMap<String, u32>,
+    /// While paused, transitions are recorded but no ready steps are released
+    paused: bool,
 }
 
 impl DagScheduler {
@@ -68,6 +81,8 @@ impl DagScheduler {
             on_error: on_error.to_string(),
             max_iterations,
             iteration_count: 0,
+            loop_iterations: HashMap::new(),
+            paused: false,
         }
     }
 
@@ -132,6 +147,121 @@ impl DagScheduler {
         ready
     }
 
+    /// Dependencies of a step that are not yet satisfied, with their status
+    ///
+    /// Returned in `depends_on` order. Empty for unknown steps and for steps
+    /// whose dependencies have all completed or been skipped.
+    pub fn blocking_dependencies(&self, step_id: &str) -> Vec<(String, StepStatus)> {
+        let Some(step) = self.dag.get_step(step_id) else {
+            return Vec::new();
+        };
+
+        step.depends_on
+            .iter()
+            .filter_map(|dep| {
+                let status = self
+                    .step_status
+                    .get(dep)
+                    .copied()
+                    .unwrap_or(StepStatus::Pending);
+                (!status.is_successful()).then(|| (dep.clone(), status))
+            })
+            .collect()
+    }
+
+    /// Check if the scheduler is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause scheduling; in-flight steps may still complete
+    pub fn pause(&mut self) {
+        self.paused = true;
+        info!("Scheduler paused");
+    }
+
+    /// Resume scheduling and return the steps that are now ready
+    pub fn resume(&mut self) -> Vec<String> {
+        self.paused = false;
+        info!("Scheduler resumed");
+        self.get_ready_steps()
+    }
+
+    /// Ready steps to release for a transition (none while paused)
+    fn release_ready_steps(&self, ready_steps: Vec<String>) -> Vec<String> {
+        if self.paused {
+            debug!(held = ready_steps.len(), "Holding ready steps while paused");
+            Vec::new()
+        } else {
+            ready_steps
+        }
+    }
+
+    /// Outcome of a transition: the steps it released and whether the run is over
+    ///
+    /// A run that is neither complete nor able to schedule anything is
+    /// deadlocked; its remaining steps are cancelled and the run fails.
+    fn progress(&mut self, skipped_steps: Vec<String>) -> StepCompletionResult {
+        let ready_steps = self.get_ready_steps();
+        let all_terminal = self.step_status.values().all(|status| status.is_terminal());
+
+        let mut result = StepCompletionResult {
+            ready_steps: Vec::new(),
+            skipped_steps,
+            workflow_complete: all_terminal && ready_steps.is_empty(),
+            workflow_failed: false,
+            error: None,
+        };
+
+        if !all_terminal && self.is_deadlocked_with(&ready_steps) {
+            let blocked = self.cancel_remaining();
+            warn!(blocked = ?blocked, "Workflow deadlocked");
+            result.workflow_failed = true;
+            result.error = Some(format!(
+                "{}: blocked steps {}",
+                NO_PROGRESS_POSSIBLE,
+                blocked.join(", ")
+            ));
+            return result;
+        }
+
+        result.ready_steps = self.release_ready_steps(ready_steps);
+        result
+    }
+
+    /// Whether the run can make no further progress
+    ///
+    /// True when the run is not complete, no step is ready, and nothing is
+    /// running or waiting for approval that could unblock the rest.
+    pub fn is_deadlocked(&self) -> bool {
+        !self.is_complete() && self.is_deadlocked_with(&self.get_ready_steps())
+    }
+
+    fn is_deadlocked_with(&self, ready_steps: &[String]) -> bool {
+        ready_steps.is_empty()
+            && !self.step_status.values().any(|status| {
+                matches!(
+                    status,
+                    StepStatus::Ready | StepStatus::Running | StepStatus::WaitingApproval
+                )
+            })
+    }
+
+    /// Cancel every non-terminal step, returning their IDs sorted
+    fn cancel_remaining(&mut self) -> Vec<String> {
+        let mut cancelled: Vec<String> = self
+            .step_status
+            .iter_mut()
+            .filter(|(_, status)| !status.is_terminal())
+            .map(|(id, status)| {
+                *status = StepStatus::Cancelled;
+                id.clone()
+            })
+            .collect();
+        cancelled.sort();
+        cancelled
+    }
+
     /// Get the initial steps to execute (entry points)
     pub fn get_initial_steps(&self) -> Vec<String> {
         self.dag.entry_points().to_vec()
@@ -164,29 +294,43 @@ impl DagScheduler {
             .insert(step_id.to_string(), StepStatus::Completed);
         self.step_outputs.insert(step_id.to_string(), output);
 
-        info!(step_id, "Step completed");
-
-        // Compute ready steps
-        let ready_steps = self.get_ready_steps();
+        if self.loop_should_repeat(step_id) {
+            return self.repeat_loop(step_id);
+        }
 
-        // Check if workflow is complete
-        let all_terminal = self.step_status.values().all(|status| status.is_terminal());
+        // Conditions may reference the step's own output, so evaluate after storing it
+        let untaken = self.dag.get_step(step_id).and_then(|step| {
+            let branches = step.branches.as_ref()?;
+            let outcome = match &step.condition {
+                Some(condition) => self.evaluate_condition(condition),
+                None => branch_outcome(&self.step_outputs[step_id]),
+            };
+            debug!(step_id, outcome, "Evaluated conditional branch");
+            Some(branches.untaken(outcome))
+        });
+
+        let skipped_steps = untaken
+            .map(|targets| self.skip_branch(&targets))
+            .unwrap_or_default();
 
-        let workflow_complete = all_terminal && ready_steps.is_empty();
+        info!(step_id, "Step completed");
 
-        if workflow_complete {
+        let result = self.progress(skipped_steps);
+        if result.workflow_complete {
             info!("Workflow completed successfully");
         }
+        Ok(result)
+    }
 
-        Ok(StepCompletionResult {
-            ready_steps,
-            workflow_complete,
-            workflow_failed: false,
-            error: None,
-        })
+    /// Failure policy for a step: its own `on_error`, else the workflow's
+    pub fn on_error_for(&self, step_id: &str) -> OnError {
+        self.dag
+            .get_step(step_id)
+            .and_then(|step| step.on_error)
+            .unwrap_or_else(|| OnError::from_policy(&self.on_error))
     }
 
-    /// Mark a step as failed and compute next steps based on on_error policy
+    /// Mark a step as failed and compute next steps based on its on_error policy
     #[instrument(skip(self))]
     pub fn fail_step(
         &mut self,
@@ -201,9 +345,9 @@ impl DagScheduler {
             .insert(step_id.to_string(), StepStatus::Failed);
         warn!(step_id, error, "Step failed");
 
-        if self.on_error == "fail" {
+        if self.on_error_for(step_id) == OnError::Fail {
             // Cancel all pending steps
-            for (_id, status) in self.step_status.iter_mut() {
+            for status in self.step_status.values_mut() {
                 if *status == StepStatus::Pending || *status == StepStatus::Ready {
                     *status = StepStatus::Cancelled;
                 }
@@ -211,30 +355,37 @@ impl DagScheduler {
 
             return Ok(StepCompletionResult {
                 ready_steps: vec![],
+                skipped_steps: vec![],
                 workflow_complete: false,
                 workflow_failed: true,
                 error: Some(format!("Step '{}' failed: {}", step_id, error)),
             });
         }
 
-        // on_error == "continue": skip dependent steps and continue
-        self.skip_dependents(step_id);
+        // on_error == continue: skip dependent steps and continue
+        let skipped_steps = self.skip_dependents(step_id);
 
-        let ready_steps = self.get_ready_steps();
-        let all_terminal = self.step_status.values().all(|status| status.is_terminal());
-        let workflow_complete = all_terminal && ready_steps.is_empty();
-
-        Ok(StepCompletionResult {
-            ready_steps,
-            workflow_complete,
-            workflow_failed: false,
-            error: None,
-        })
+        Ok(self.progress(skipped_steps))
     }
 
     /// Skip a step (e.g., due to condition not met)
-    #[instrument(skip(self))]
+    ///
+    /// Cascades like [`mark_skipped_with_cascade`](Self::mark_skipped_with_cascade).
     pub fn skip_step(&mut self, step_id: &str) -> Result<StepCompletionResult, DagError> {
+        self.mark_skipped_with_cascade(step_id)
+    }
+
+    /// Skip a step along with the subtree only it leads to
+    ///
+    /// Dependents are skipped once all of their dependencies are skipped,
+    /// the same rule as for an untaken branch, so a step that also depends
+    /// on a live branch still runs. `skipped_steps` lists the cascade, not
+    /// the step itself.
+    #[instrument(skip(self))]
+    pub fn mark_skipped_with_cascade(
+        &mut self,
+        step_id: &str,
+    ) -> Result<StepCompletionResult, DagError> {
         if !self.step_status.contains_key(step_id) {
             return Err(DagError::StepNotFound(step_id.to_string()));
         }
@@ -243,16 +394,16 @@ impl DagScheduler {
             .insert(step_id.to_string(), StepStatus::Skipped);
         debug!(step_id, "Step skipped");
 
-        let ready_steps = self.get_ready_steps();
-        let all_terminal = self.step_status.values().all(|status| status.is_terminal());
-        let workflow_complete = all_terminal && ready_steps.is_empty();
+        let orphaned: Vec<String> = self
+            .dag
+            .children(step_id)
+            .iter()
+            .filter(|child| self.has_only_skipped_parents(child))
+            .cloned()
+            .collect();
+        let skipped_steps = self.skip_branch(&orphaned);
 
-        Ok(StepCompletionResult {
-            ready_steps,
-            workflow_complete,
-            workflow_failed: false,
-            error: None,
-        })
+        Ok(self.progress(skipped_steps))
     }
 
     /// Mark a step as waiting for approval
@@ -277,8 +428,73 @@ impl DagScheduler {
         Ok(())
     }
 
-    /// Skip all steps that depend on a failed step
-    fn skip_dependents(&mut self, failed_step_id: &str) {
+    /// Handle a rejected approval according to the on_error policy
+    ///
+    /// Under `fail` the gated step fails and the workflow fails with it.
+    /// Under `continue` the gated step is skipped along with everything that
+    /// depends on it, and independent steps keep running.
+    #[instrument(skip(self))]
+    pub fn reject_approval(&mut self, step_id: &str) -> Result<StepCompletionResult, DagError> {
+        match self.step_status.get(step_id) {
+            None => return Err(DagError::StepNotFound(step_id.to_string())),
+            Some(StepStatus::WaitingApproval) => {}
+            Some(status) => {
+                return Err(DagError::InvalidConfiguration(format!(
+                    "Step '{}' is not waiting for approval (status: {:?})",
+                    step_id, status
+                )))
+            }
+        }
+
+        if self.on_error_for(step_id) == OnError::Fail {
+            return self.fail_step(step_id, "approval rejected");
+        }
+
+        self.step_status
+            .insert(step_id.to_string(), StepStatus::Skipped);
+        debug!(step_id, "Step skipped after approval rejected");
+        let skipped_steps = self.skip_dependents(step_id);
+
+        Ok(self.progress(skipped_steps))
+    }
+
+    /// Skip an untaken branch and every step reachable only through it.
+    ///
+    /// A step is skipped once all of its dependencies are skipped, so join
+    /// steps shared with the taken branch still run.
+    fn skip_branch(&mut self, targets: &[String]) -> Vec<String> {
+        let mut skipped = Vec::new();
+        let mut queue = targets.to_vec();
+
+        while let Some(step_id) = queue.pop() {
+            if self.step_status.get(&step_id) != Some(&StepStatus::Pending) {
+                continue;
+            }
+            self.step_status
+                .insert(step_id.clone(), StepStatus::Skipped);
+            debug!(step_id = %step_id, "Skipped untaken branch step");
+
+            for child_id in self.dag.children(&step_id) {
+                if self.has_only_skipped_parents(child_id) {
+                    queue.push(child_id.clone());
+                }
+            }
+            skipped.push(step_id);
+        }
+
+        skipped
+    }
+
+    /// Whether every dependency of a step has been skipped
+    fn has_only_skipped_parents(&self, step_id: &str) -> bool {
+        self.dag
+            .parents(step_id)
+            .iter()
+            .all(|p| self.step_status.get(p) == Some(&StepStatus::Skipped))
+    }
+
+    /// Skip all steps that depend on a failed step, returning the skipped IDs
+    fn skip_dependents(&mut self, failed_step_id: &str) -> Vec<String> {
         let mut to_skip = vec![];
         let mut visited = HashSet::new();
         let mut queue = vec![failed_step_id.to_string()];
@@ -297,100 +513,176 @@ impl DagScheduler {
             }
         }
 
+        let mut skipped = Vec::new();
         for step_id in to_skip {
             if let Some(status) = self.step_status.get_mut(&step_id) {
                 if *status == StepStatus::Pending {
                     *status = StepStatus::Skipped;
                     debug!(step_id = %step_id, "Skipped dependent step");
+                    skipped.push(step_id);
                 }
             }
         }
+        skipped
     }
 
-    /// Evaluate a condition expression against step outputs
+    /// Re-arm a loop body after its closing step completes an iteration
+    ///
+    /// Every step in `tail`'s loop body is reset to pending and its output
+    /// cleared, then the steps that are ready again are returned. Each call
+    /// counts as one iteration against the workflow's `max_iterations` and
+    /// against `tail`'s own `max_iterations` config, when it has one.
     #[instrument(skip(self))]
-    pub fn evaluate_condition(&self, condition: &str) -> bool {
-        // Simple condition evaluation
-        // Format: $.step_id.field == value
-        if condition.is_empty() {
-            return true;
+    pub fn reset_loop_body(&mut self, tail: &str) -> Result<Vec<String>, DagError> {
+        match self.step_status.get(tail) {
+            None => return Err(DagError::StepNotFound(tail.to_string())),
+            Some(StepStatus::Completed) => {}
+            Some(status) => {
+                return Err(DagError::InvalidConfiguration(format!(
+                    "step '{}' must be completed to loop, but is {:?}",
+                    tail, status
+                )))
+            }
         }
 
-        // Parse condition
-        for (op_str, _) in &[("==", true), ("!=", true), (">=", true), ("<=", true)] {
-            if let Some(idx) = condition.find(op_str) {
-                let left = condition[..idx].trim();
-                let right = condition[idx + op_str.len()..].trim();
-
-                let left_val = self.resolve_path(left);
-                let right_val = self.parse_literal(right);
-
-                let result = match *op_str {
-                    "==" => left_val == right_val,
-                    "!=" => left_val != right_val,
-                    _ => true, // For >= and <= we'd need numeric comparison
-                };
-
-                debug!(
-                    condition,
-                    ?left_val,
-                    ?right_val,
-                    result,
-                    "Evaluated condition"
+        let body = self.dag.loop_body(tail);
+        if body.is_empty() {
+            return Err(DagError::InvalidConfiguration(format!(
+                "step '{}' does not close a loop",
+                tail
+            )));
+        }
+        if self.iteration_count >= self.max_iterations {
+            warn!(
+                tail,
+                iterations = self.iteration_count,
+                "Loop iteration limit reached"
+            );
+            return Err(DagError::MaxIterationsExceeded(self.max_iterations));
+        }
+        let loop_iterations = self.loop_iterations.get(tail).copied().unwrap_or(0);
+        if let Some(loop_max) = self.loop_max_iterations(tail) {
+            if loop_iterations >= loop_max {
+                warn!(
+                    tail,
+                    iterations = loop_iterations,
+                    "Loop iteration limit reached"
                 );
-                return result;
+                return Err(DagError::MaxIterationsExceeded(loop_max));
             }
         }
 
-        true
+        self.iteration_count += 1;
+        self.loop_iterations
+            .insert(tail.to_string(), loop_iterations + 1);
+        for step_id in &body {
+            self.step_status
+                .insert(step_id.clone(), StepStatus::Pending);
+            self.step_outputs.remove(step_id);
+        }
+        info!(
+            tail,
+            iteration = self.iteration_count,
+            body = ?body,
+            "Re-armed loop body"
+        );
+
+        let ready_steps = self.get_ready_steps();
+        Ok(self.release_ready_steps(ready_steps))
     }
 
-    /// Resolve a JSONPath-like expression
-    fn resolve_path(&self, path: &str) -> Option<serde_json::Value> {
-        if !path.starts_with("$.") {
-            return Some(serde_json::Value::String(path.to_string()));
+    /// The `max_iterations` a loop step configures for its own loop
+    fn loop_max_iterations(&self, tail: &str) -> Option<u32> {
+        let step = self.dag.get_step(tail)?;
+        if step.step_type != StepType::Loop {
+            return None;
         }
+        let max = step.config.get("max_iterations")?.as_u64()?;
+        Some(u32::try_from(max).unwrap_or(u32::MAX))
+    }
 
-        let parts: Vec<&str> = path[2..].split('.').collect();
-        if parts.is_empty() {
-            return None;
+    /// Whether a just-completed loop step should run its body again
+    ///
+    /// Only loop steps that close a loop and configure a `while` or `until`
+    /// condition repeat on their own; `while` repeats while it holds, `until`
+    /// repeats until it does.
+    fn loop_should_repeat(&self, step_id: &str) -> bool {
+        let Some(step) = self.dag.get_step(step_id) else {
+            return false;
+        };
+        if step.step_type != StepType::Loop || self.dag.loop_heads(step_id).is_empty() {
+            return false;
         }
 
-        let step_id = parts[0];
-        let output = self.step_outputs.get(step_id)?;
+        let condition = |key: &str| step.config.get(key).and_then(|c| c.as_str());
+        let repeat = match (condition("while"), condition("until")) {
+            (Some(condition), _) => self.evaluate_condition(condition),
+            (None, Some(condition)) => !self.evaluate_condition(condition),
+            (None, None) => return false,
+        };
+        debug!(step_id, repeat, "Evaluated loop condition");
+        repeat
+    }
 
-        let mut current = output.clone();
-        for part in &parts[1..] {
-            current = current.get(part)?.clone();
+    /// Start another iteration of the loop closed by `tail`
+    ///
+    /// Fails the run, cancelling what is left of it, once the loop would go
+    /// past `max_iterations`.
+    fn repeat_loop(&mut self, tail: &str) -> Result<StepCompletionResult, DagError> {
+        match self.reset_loop_body(tail) {
+            Ok(ready_steps) => Ok(StepCompletionResult {
+                ready_steps,
+                skipped_steps: vec![],
+                workflow_complete: false,
+                workflow_failed: false,
+                error: None,
+            }),
+            Err(DagError::MaxIterationsExceeded(_)) => {
+                self.step_status
+                    .insert(tail.to_string(), StepStatus::Failed);
+                self.cancel_remaining();
+                Ok(StepCompletionResult {
+                    ready_steps: vec![],
+                    skipped_steps: vec![],
+                    workflow_complete: false,
+                    workflow_failed: true,
+                    error: Some(format!("loop '{}' exceeded max_iterations", tail)),
+                })
+            }
+            Err(e) => Err(e),
         }
-
-        Some(current)
     }
 
-    /// Parse a literal value
-    fn parse_literal(&self, s: &str) -> Option<serde_json::Value> {
-        let s = s.trim();
+    /// Current loop iteration count
+    pub fn iteration_count(&self) -> u32 {
+        self.iteration_count
+    }
 
-        if s == "true" {
-            return Some(serde_json::Value::Bool(true));
-        }
-        if s == "false" {
-            return Some(serde_json::Value::Bool(false));
-        }
-        if s == "null" {
-            return Some(serde_json::Value::Null);
-        }
-        if let Ok(n) = s.parse::<i64>() {
-            return Some(serde_json::Value::Number(n.into()));
-        }
-        if let Ok(f) = s.parse::<f64>() {
-            return serde_json::Number::from_f64(f).map(serde_json::Value::Number);
+    /// Evaluate a condition expression against step outputs
+    ///
+    /// Empty conditions pass; unparseable ones evaluate to false.
+    #[instrument(skip(self))]
+    pub fn evaluate_condition(&self, condition: &str) -> bool {
+        if condition.trim().is_empty() {
+            return true;
         }
-        if s.starts_with('"') && s.ends_with('"') && s.len() >= 2 {
-            return Some(serde_json::Value::String(s[1..s.len() - 1].to_string()));
+
+        match Condition::parse(condition) {
+            Ok(parsed) => {
+                let result = parsed.evaluate(&|path| self.resolve_path(path));
+                debug!(condition, result, "Evaluated condition");
+                result
+            }
+            Err(e) => {
+                warn!(condition, error = %e, "Invalid condition expression");
+                false
+            }
         }
+    }
 
-        Some(serde_json::Value::String(s.to_string()))
+    /// Resolve a `$.step_id.field` path against step outputs
+    fn resolve_path(&self, path: &str) -> Option<serde_json::Value> {
+        crate::condition::resolve_output_path(&self.step_outputs, path)
     }
 
     /// Check if workflow is complete (all steps terminal)
@@ -417,14 +709,121 @@ impl DagScheduler {
         self.dag.execution_layers()
     }
 
-    /// Save scheduler state for persistence
-    pub fn save_state(&self) -> SchedulerState {
+    /// Execution layers forecast from what is already known
+    ///
+    /// Unlike [`DagScheduler::execution_layers`], skipped steps are left out,
+    /// and so is the untaken side of any branch whose condition can already
+    /// be decided from recorded outputs, as is any step not yet started whose
+    /// own condition is already false (along with steps reachable only
+    /// through them). Layers are sorted for stable display.
+    pub fn projected_layers(&self) -> Vec<Vec<String>> {
+        let mut pruned: HashSet<String> = self
+            .step_status
+            .iter()
+            .filter(|(_, status)| **status == StepStatus::Skipped)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for step_id in self.dag.step_ids() {
+            let Some(step) = self.dag.get_step(step_id) else {
+                continue;
+            };
+            let Some(condition) = &step.condition else {
+                continue;
+            };
+            if pruned.contains(step_id) {
+                continue;
+            }
+            let status = self.step_status(step_id);
+            match &step.branches {
+                Some(branches) if status != Some(StepStatus::Completed) => {
+                    if let Some(outcome) = self.try_evaluate_condition(condition) {
+                        self.prune_exclusive(&branches.untaken(outcome), &mut pruned);
+                    }
+                }
+                // A plain condition gates the step itself until it starts
+                None if matches!(status, Some(StepStatus::Pending | StepStatus::Ready))
+                    && self.try_evaluate_condition(condition) == Some(false) =>
+                {
+                    self.prune_exclusive(std::slice::from_ref(step_id), &mut pruned);
+                }
+                _ => {}
+            }
+        }
+
+        let mut placed: HashSet<String> = pruned.clone();
+        let mut remaining: Vec<&String> = self
+            .dag
+            .step_ids()
+            .into_iter()
+            .filter(|id| !pruned.contains(*id))
+            .collect();
+        let mut layers = Vec::new();
+
+        while !remaining.is_empty() {
+            let mut layer: Vec<String> = remaining
+                .iter()
+                .filter(|id| self.dag.parents(id).iter().all(|p| placed.contains(p)))
+                .map(|id| (*id).clone())
+                .collect();
+            if layer.is_empty() {
+                break;
+            }
+            layer.sort();
+            remaining.retain(|id| !layer.contains(id));
+            placed.extend(layer.iter().cloned());
+            layers.push(layer);
+        }
+
+        layers
+    }
+
+    /// Evaluate a condition only if every path it reads is already known
+    fn try_evaluate_condition(&self, condition: &str) -> Option<bool> {
+        let parsed = Condition::parse(condition).ok()?;
+        let missing = std::cell::Cell::new(false);
+        let result = parsed.evaluate(&|path| {
+            let value = self.resolve_path(path);
+            if value.is_none() {
+                missing.set(true);
+            }
+            value
+        });
+        (!missing.get()).then_some(result)
+    }
+
+    /// Add `targets` and every step reachable only through pruned steps
+    fn prune_exclusive(&self, targets: &[String], pruned: &mut HashSet<String>) {
+        let mut queue = targets.to_vec();
+        while let Some(step_id) = queue.pop() {
+            if !pruned.insert(step_id.clone()) {
+                continue;
+            }
+            for child_id in self.dag.children(&step_id) {
+                let only_pruned_parents = self
+                    .dag
+                    .parents(child_id)
+                    .iter()
+                    .all(|p| pruned.contains(p));
+                if only_pruned_parents {
+                    queue.push(child_id.clone());
+                }
+            }
+        }
+    }
+
+    /// Snapshot scheduler state for persistence
+    ///
+    /// Restore it with [`DagScheduler::from_dag_with_state`].
+    pub fn snapshot(&self) -> SchedulerState {
         SchedulerState {
             step_status: self.step_status.clone(),
             step_outputs: self.step_outputs.clone(),
             on_error: self.on_error.clone(),
             max_iterations: self.max_iterations,
             iteration_count: self.iteration_count,
+            loop_iterations: self.loop_iterations.clone(),
+            paused: self.paused,
         }
     }
 
@@ -434,25 +833,67 @@ impl DagScheduler {
         self.step_outputs = state.step_outputs;
         self.on_error = state.on_error;
         self.iteration_count = state.iteration_count;
+        self.loop_iterations = state.loop_iterations;
+        self.paused = state.paused;
     }
 
     /// Create a scheduler from a DAG and restore state
+    ///
+    /// Steps the snapshot doesn't know about (e.g. added to the definition
+    /// since) start out pending.
     pub fn from_dag_with_state(dag: WorkflowDag, state: SchedulerState) -> Self {
+        let mut step_status = state.step_status;
+        for id in dag.step_ids() {
+            step_status.entry(id.clone()).or_insert(StepStatus::Pending);
+        }
+
         Self {
             dag,
-            step_status: state.step_status,
+            step_status,
             step_outputs: state.step_outputs,
             on_error: state.on_error,
             max_iterations: state.max_iterations,
             iteration_count: state.iteration_count,
+            loop_iterations: state.loop_iterations,
+            paused: state.paused,
         }
     }
 }
 
+/// Outcome of a branching step without a condition expression.
+///
+/// Uses a boolean output directly, or its `result` field if present.
+fn branch_outcome(output: &serde_json::Value) -> bool {
+    output
+        .as_bool()
+        .or_else(|| output.get("result").and_then(|v| v.as_bool()))
+        .unwrap_or(false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::StepType;
+    use crate::ConditionalBranches;
+
+    /// check -> (true: deploy -> verify) / (false: rollback -> alert), both -> report
+    fn branching_steps(condition: Option<&str>) -> Vec<StepDefinition> {
+        let mut check = make_step("check", vec![]);
+        check.step_type = StepType::Condition;
+        check.condition = condition.map(String::from);
+        check.branches = Some(ConditionalBranches {
+            on_true: vec!["deploy".to_string()],
+            on_false: vec!["rollback".to_string()],
+        });
+
+        vec![
+            check,
+            make_step("deploy", vec![]),
+            make_step("verify", vec!["deploy"]),
+            make_step("rollback", vec![]),
+            make_step("alert", vec!["rollback"]),
+            make_step("report", vec!["verify", "alert"]),
+        ]
+    }
 
     fn make_step(id: &str, depends_on: Vec<&str>) -> StepDefinition {
         StepDefinition {
@@ -462,9 +903,252 @@ mod tests {
             config: serde_json::json!({}),
             depends_on: depends_on.into_iter().map(String::from).collect(),
             condition: None,
-            timeout_ms: 30000,
+            timeout_ms: None,
             retry: None,
+            branches: None,
+            loop_back: vec![],
+            on_error: None,
+        }
+    }
+
+    /// start -> draft -> review -> publish, with review looping back to draft
+    fn loop_steps() -> Vec<StepDefinition> {
+        let mut draft = make_step("draft", vec!["start", "review"]);
+        draft.loop_back = vec!["review".to_string()];
+        vec![
+            make_step("start", vec![]),
+            draft,
+            make_step("review", vec!["draft"]),
+            make_step("publish", vec!["review"]),
+        ]
+    }
+
+    #[test]
+    fn test_reset_loop_body_rearms_loop() {
+        let mut scheduler = DagScheduler::from_steps(loop_steps(), "fail", 2).unwrap();
+
+        assert_eq!(scheduler.get_ready_steps(), vec!["start"]);
+        scheduler
+            .complete_step("start", serde_json::json!({}))
+            .unwrap();
+        let result = scheduler
+            .complete_step("draft", serde_json::json!({"text": "v1"}))
+            .unwrap();
+        assert_eq!(result.ready_steps, vec!["review"]);
+        scheduler
+            .complete_step("review", serde_json::json!({"approved": false}))
+            .unwrap();
+
+        // Iterating re-arms draft and review; start stays completed
+        let ready = scheduler.reset_loop_body("review").unwrap();
+        assert_eq!(ready, vec!["draft"]);
+        assert_eq!(scheduler.iteration_count(), 1);
+        assert_eq!(scheduler.step_status("start"), Some(StepStatus::Completed));
+        assert_eq!(scheduler.step_status("review"), Some(StepStatus::Pending));
+        assert!(scheduler.step_output("draft").is_none());
+        assert!(!scheduler.get_ready_steps().contains(&"publish".to_string()));
+
+        // Second pass through the body
+        scheduler
+            .complete_step("draft", serde_json::json!({"text": "v2"}))
+            .unwrap();
+        scheduler
+            .complete_step("review", serde_json::json!({"approved": false}))
+            .unwrap();
+        assert_eq!(scheduler.reset_loop_body("review").unwrap(), vec!["draft"]);
+
+        // The iteration budget is exhausted on the next pass
+        scheduler
+            .complete_step("draft", serde_json::json!({}))
+            .unwrap();
+        scheduler
+            .complete_step("review", serde_json::json!({}))
+            .unwrap();
+        assert!(matches!(
+            scheduler.reset_loop_body("review"),
+            Err(DagError::MaxIterationsExceeded(2))
+        ));
+    }
+
+    #[test]
+    fn test_reset_loop_body_requires_completed_loop_tail() {
+        let mut scheduler = DagScheduler::from_steps(loop_steps(), "fail", 5).unwrap();
+
+        assert!(scheduler.reset_loop_body("review").is_err());
+        assert!(matches!(
+            scheduler.reset_loop_body("missing"),
+            Err(DagError::StepNotFound(_))
+        ));
+
+        scheduler
+            .complete_step("start", serde_json::json!({}))
+            .unwrap();
+        assert!(matches!(
+            scheduler.reset_loop_body("start"),
+            Err(DagError::InvalidConfiguration(_))
+        ));
+    }
+
+    /// start -> increment -> check -> done, with check looping back to increment
+    fn counter_loop_steps(config: serde_json::Value) -> Vec<StepDefinition> {
+        let mut increment = make_step("increment", vec!["start", "check"]);
+        increment.loop_back = vec!["check".to_string()];
+        let mut check = make_step("check", vec!["increment"]);
+        check.step_type = StepType::Loop;
+        check.config = config;
+        vec![
+            make_step("start", vec![]),
+            increment,
+            check,
+            make_step("done", vec!["check"]),
+        ]
+    }
+
+    #[test]
+    fn test_loop_step_repeats_until_condition_is_met() {
+        let steps = counter_loop_steps(serde_json::json!({"while": "$.check.count < 3"}));
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 5).unwrap();
+        scheduler
+            .complete_step("start", serde_json::json!({}))
+            .unwrap();
+
+        for count in 1..3 {
+            scheduler
+                .complete_step("increment", serde_json::json!({}))
+                .unwrap();
+            let result = scheduler
+                .complete_step("check", serde_json::json!({"count": count}))
+                .unwrap();
+            assert_eq!(result.ready_steps, vec!["increment"]);
+            assert!(!result.workflow_complete && !result.workflow_failed);
+            assert_eq!(scheduler.step_status("check"), Some(StepStatus::Pending));
+        }
+
+        scheduler
+            .complete_step("increment", serde_json::json!({}))
+            .unwrap();
+        let result = scheduler
+            .complete_step("check", serde_json::json!({"count": 3}))
+            .unwrap();
+        assert_eq!(result.ready_steps, vec!["done"]);
+        assert_eq!(scheduler.iteration_count(), 2);
+
+        let result = scheduler
+            .complete_step("done", serde_json::json!({}))
+            .unwrap();
+        assert!(result.workflow_complete);
+    }
+
+    #[test]
+    fn test_loop_step_fails_run_past_max_iterations() {
+        let steps = counter_loop_steps(serde_json::json!({"until": "$.check.done == true"}));
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 2).unwrap();
+        scheduler
+            .complete_step("start", serde_json::json!({}))
+            .unwrap();
+
+        for _ in 0..2 {
+            scheduler
+                .complete_step("increment", serde_json::json!({}))
+                .unwrap();
+            let result = scheduler
+                .complete_step("check", serde_json::json!({"done": false}))
+                .unwrap();
+            assert_eq!(result.ready_steps, vec!["increment"]);
         }
+
+        scheduler
+            .complete_step("increment", serde_json::json!({}))
+            .unwrap();
+        let result = scheduler
+            .complete_step("check", serde_json::json!({"done": false}))
+            .unwrap();
+        assert!(result.workflow_failed);
+        assert!(result.ready_steps.is_empty());
+        assert_eq!(
+            result.error.as_deref(),
+            Some("loop 'check' exceeded max_iterations")
+        );
+        assert_eq!(scheduler.step_status("check"), Some(StepStatus::Failed));
+        assert_eq!(scheduler.step_status("done"), Some(StepStatus::Cancelled));
+        assert!(scheduler.is_complete());
+    }
+
+    #[test]
+    fn test_each_loop_stops_at_its_own_max_iterations() {
+        // start -> draft <-> polish (cap 1), start -> fetch <-> verify (cap 3)
+        let mut draft = make_step("draft", vec!["start", "polish"]);
+        draft.loop_back = vec!["polish".to_string()];
+        let mut polish = make_step("polish", vec!["draft"]);
+        polish.step_type = StepType::Loop;
+        polish.config = serde_json::json!({"until": "$.polish.done == true", "max_iterations": 1});
+        let mut fetch = make_step("fetch", vec!["start", "verify"]);
+        fetch.loop_back = vec!["verify".to_string()];
+        let mut verify = make_step("verify", vec!["fetch"]);
+        verify.step_type = StepType::Loop;
+        verify.config = serde_json::json!({"until": "$.verify.done == true", "max_iterations": 3});
+        let steps = vec![make_step("start", vec![]), draft, polish, fetch, verify];
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+        scheduler
+            .complete_step("start", serde_json::json!({}))
+            .unwrap();
+
+        // The capped-at-3 loop runs past the other loop's cap
+        for _ in 0..3 {
+            scheduler
+                .complete_step("fetch", serde_json::json!({}))
+                .unwrap();
+            let result = scheduler
+                .complete_step("verify", serde_json::json!({"done": false}))
+                .unwrap();
+            assert!(result.ready_steps.contains(&"fetch".to_string()));
+        }
+
+        scheduler
+            .complete_step("draft", serde_json::json!({}))
+            .unwrap();
+        let result = scheduler
+            .complete_step("polish", serde_json::json!({"done": false}))
+            .unwrap();
+        assert!(result.ready_steps.contains(&"draft".to_string()));
+        scheduler
+            .complete_step("draft", serde_json::json!({}))
+            .unwrap();
+        let result = scheduler
+            .complete_step("polish", serde_json::json!({"done": false}))
+            .unwrap();
+        assert!(result.workflow_failed);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("loop 'polish' exceeded max_iterations")
+        );
+        assert_eq!(scheduler.iteration_count(), 4);
+    }
+
+    #[test]
+    fn test_loop_cap_above_workflow_cap_stops_at_workflow_cap() {
+        let steps = counter_loop_steps(
+            serde_json::json!({"until": "$.check.done == true", "max_iterations": 5}),
+        );
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 1).unwrap();
+        scheduler
+            .complete_step("start", serde_json::json!({}))
+            .unwrap();
+
+        scheduler
+            .complete_step("increment", serde_json::json!({}))
+            .unwrap();
+        let result = scheduler
+            .complete_step("check", serde_json::json!({"done": false}))
+            .unwrap();
+        assert_eq!(result.ready_steps, vec!["increment"]);
+        scheduler
+            .complete_step("increment", serde_json::json!({}))
+            .unwrap();
+        let result = scheduler
+            .complete_step("check", serde_json::json!({"done": false}))
+            .unwrap();
+        assert!(result.workflow_failed);
     }
 
     #[test]
@@ -505,6 +1189,68 @@ mod tests {
         assert!(result.workflow_complete);
     }
 
+    #[test]
+    fn test_blocking_dependencies_reports_unsatisfied_deps() {
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec![]),
+            make_step("c", vec!["a", "b"]),
+        ];
+
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+        assert_eq!(
+            scheduler.blocking_dependencies("c"),
+            vec![
+                ("a".to_string(), StepStatus::Pending),
+                ("b".to_string(), StepStatus::Pending),
+            ]
+        );
+
+        scheduler.mark_running("a").unwrap();
+        scheduler.complete_step("a", serde_json::json!({})).unwrap();
+        scheduler.mark_running("b").unwrap();
+        scheduler.fail_step("b", "boom").unwrap();
+
+        assert_eq!(
+            scheduler.blocking_dependencies("c"),
+            vec![("b".to_string(), StepStatus::Failed)]
+        );
+        assert!(scheduler.blocking_dependencies("a").is_empty());
+        assert!(scheduler.blocking_dependencies("missing").is_empty());
+    }
+
+    #[test]
+    fn test_deadlock_fails_run_when_remaining_steps_are_blocked() {
+        // join waits on a step that was cancelled, so it can never become ready
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec![]),
+            make_step("c", vec![]),
+            make_step("join", vec!["a", "b"]),
+        ];
+        let mut scheduler = DagScheduler::from_steps(steps, "continue", 10).unwrap();
+        let mut state = scheduler.snapshot();
+        state.step_status = HashMap::from([
+            ("a".to_string(), StepStatus::Completed),
+            ("b".to_string(), StepStatus::Cancelled),
+            ("c".to_string(), StepStatus::Running),
+            ("join".to_string(), StepStatus::Pending),
+        ]);
+        scheduler.restore_state(state);
+        assert!(!scheduler.is_deadlocked(), "c is still running");
+
+        let result = scheduler.complete_step("c", serde_json::json!({})).unwrap();
+        assert!(result.workflow_failed);
+        assert!(!result.workflow_complete);
+        assert!(result.ready_steps.is_empty());
+        assert_eq!(
+            result.error.as_deref(),
+            Some("no progress possible: blocked steps join")
+        );
+        assert_eq!(scheduler.step_status("join"), Some(StepStatus::Cancelled));
+        assert!(!scheduler.is_deadlocked());
+    }
+
     #[test]
     fn test_scheduler_fail_policy() {
         let steps = vec![
@@ -524,6 +1270,39 @@ mod tests {
         assert_eq!(scheduler.step_status("c"), Some(StepStatus::Cancelled));
     }
 
+    #[test]
+    fn test_step_on_error_overrides_workflow_policy() {
+        let mut optional = make_step("optional", vec![]);
+        optional.on_error = Some(OnError::Continue);
+        let steps = vec![
+            optional,
+            make_step("enrich", vec!["optional"]),
+            make_step("main", vec![]),
+            make_step("publish", vec!["main"]),
+        ];
+
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+        assert_eq!(scheduler.on_error_for("optional"), OnError::Continue);
+        assert_eq!(scheduler.on_error_for("main"), OnError::Fail);
+
+        scheduler.mark_running("optional").unwrap();
+        scheduler.mark_running("main").unwrap();
+        let result = scheduler.fail_step("optional", "flaky").unwrap();
+
+        assert!(!result.workflow_failed);
+        assert_eq!(result.skipped_steps, vec!["enrich"]);
+        assert_eq!(scheduler.step_status("enrich"), Some(StepStatus::Skipped));
+        assert_eq!(scheduler.step_status("main"), Some(StepStatus::Running));
+
+        // Other steps still follow the workflow default
+        let result = scheduler.fail_step("main", "boom").unwrap();
+        assert!(result.workflow_failed);
+        assert_eq!(
+            scheduler.step_status("publish"),
+            Some(StepStatus::Cancelled)
+        );
+    }
+
     #[test]
     fn test_scheduler_continue_policy() {
         let steps = vec![
@@ -544,6 +1323,69 @@ mod tests {
         assert_eq!(scheduler.step_status("b"), Some(StepStatus::Skipped));
     }
 
+    #[test]
+    fn test_reject_approval_fail_policy_fails_workflow() {
+        let steps = vec![
+            make_step("gate", vec![]),
+            make_step("deploy", vec!["gate"]),
+            make_step("lint", vec![]),
+        ];
+
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+        scheduler.mark_waiting_approval("gate").unwrap();
+
+        let result = scheduler.reject_approval("gate").unwrap();
+
+        assert!(result.workflow_failed);
+        assert!(result.ready_steps.is_empty());
+        assert!(result.error.unwrap().contains("approval rejected"));
+        assert_eq!(scheduler.step_status("gate"), Some(StepStatus::Failed));
+        assert_eq!(scheduler.step_status("deploy"), Some(StepStatus::Cancelled));
+    }
+
+    #[test]
+    fn test_reject_approval_continue_policy_skips_dependents() {
+        let steps = vec![
+            make_step("gate", vec![]),
+            make_step("deploy", vec!["gate"]),
+            make_step("verify", vec!["deploy"]),
+            make_step("lint", vec![]),
+        ];
+
+        let mut scheduler = DagScheduler::from_steps(steps, "continue", 10).unwrap();
+        scheduler.mark_running("lint").unwrap();
+        scheduler.mark_waiting_approval("gate").unwrap();
+
+        let mut result = scheduler.reject_approval("gate").unwrap();
+        result.skipped_steps.sort();
+
+        assert!(!result.workflow_failed);
+        assert!(!result.workflow_complete);
+        assert_eq!(result.skipped_steps, vec!["deploy", "verify"]);
+        assert_eq!(scheduler.step_status("gate"), Some(StepStatus::Skipped));
+        assert_eq!(scheduler.step_status("verify"), Some(StepStatus::Skipped));
+
+        let result = scheduler
+            .complete_step("lint", serde_json::json!({}))
+            .unwrap();
+        assert!(result.workflow_complete);
+    }
+
+    #[test]
+    fn test_reject_approval_requires_waiting_step() {
+        let steps = vec![make_step("gate", vec![])];
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+
+        assert!(matches!(
+            scheduler.reject_approval("gate"),
+            Err(DagError::InvalidConfiguration(_))
+        ));
+        assert!(matches!(
+            scheduler.reject_approval("missing"),
+            Err(DagError::StepNotFound(_))
+        ));
+    }
+
     #[test]
     fn test_scheduler_parallel_execution() {
         let steps = vec![
@@ -579,4 +1421,265 @@ mod tests {
         let ready = scheduler.get_ready_steps();
         assert_eq!(ready, vec!["final"]);
     }
+
+    #[test]
+    fn test_scheduler_pause_holds_dependents() {
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec!["a"]),
+            make_step("c", vec!["b"]),
+        ];
+
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+        scheduler.mark_running("a").unwrap();
+        scheduler.pause();
+
+        // In-flight step completes, but its dependent is not released
+        let result = scheduler.complete_step("a", serde_json::json!({})).unwrap();
+        assert!(result.ready_steps.is_empty());
+        assert!(!result.workflow_complete);
+        assert_eq!(scheduler.step_status("a"), Some(StepStatus::Completed));
+
+        // Resuming releases exactly the held ready set
+        let ready = scheduler.resume();
+        assert_eq!(ready, vec!["b"]);
+        assert!(!scheduler.is_paused());
+
+        scheduler.mark_running("b").unwrap();
+        let result = scheduler.complete_step("b", serde_json::json!({})).unwrap();
+        assert_eq!(result.ready_steps, vec!["c"]);
+    }
+
+    #[test]
+    fn test_snapshot_restore_round_trip() {
+        let steps = vec![
+            make_step("a", vec![]),
+            make_step("b", vec!["a"]),
+            make_step("c", vec!["b"]),
+        ];
+        let mut scheduler = DagScheduler::from_steps(steps.clone(), "continue", 5).unwrap();
+        scheduler.mark_running("a").unwrap();
+        scheduler
+            .complete_step("a", serde_json::json!({"rows": 3}))
+            .unwrap();
+        scheduler.mark_running("b").unwrap();
+        scheduler.iteration_count = 2;
+        scheduler.loop_iterations.insert("b".to_string(), 2);
+
+        let json = serde_json::to_value(scheduler.snapshot()).unwrap();
+        let state: SchedulerState = serde_json::from_value(json).unwrap();
+        let restored = DagScheduler::from_dag_with_state(WorkflowDag::build(steps).unwrap(), state);
+
+        assert_eq!(restored.step_status("a"), Some(StepStatus::Completed));
+        assert_eq!(restored.step_status("b"), Some(StepStatus::Running));
+        assert_eq!(restored.step_status("c"), Some(StepStatus::Pending));
+        assert_eq!(
+            restored.step_output("a"),
+            Some(&serde_json::json!({"rows": 3}))
+        );
+        assert_eq!(restored.iteration_count(), 2);
+        assert_eq!(restored.loop_iterations["b"], 2);
+        assert_eq!(restored.on_error, "continue");
+        assert_eq!(restored.max_iterations, 5);
+    }
+
+    #[test]
+    fn test_restore_defaults_new_steps_to_pending() {
+        let mut scheduler =
+            DagScheduler::from_steps(vec![make_step("a", vec![])], "fail", 10).unwrap();
+        scheduler.mark_running("a").unwrap();
+        let state = scheduler.snapshot();
+
+        let dag =
+            WorkflowDag::build(vec![make_step("a", vec![]), make_step("b", vec!["a"])]).unwrap();
+        let restored = DagScheduler::from_dag_with_state(dag, state);
+        assert_eq!(restored.step_status("a"), Some(StepStatus::Running));
+        assert_eq!(restored.step_status("b"), Some(StepStatus::Pending));
+    }
+
+    #[test]
+    fn test_scheduler_paused_state_persists() {
+        let steps = vec![make_step("a", vec![]), make_step("b", vec!["a"])];
+        let mut scheduler = DagScheduler::from_steps(steps.clone(), "fail", 10).unwrap();
+        scheduler.pause();
+
+        let state = scheduler.snapshot();
+        assert!(state.paused);
+
+        let dag = WorkflowDag::build(steps).unwrap();
+        let restored = DagScheduler::from_dag_with_state(dag, state);
+        assert!(restored.is_paused());
+    }
+
+    #[test]
+    fn test_conditional_branch_true_skips_false_branch() {
+        let mut scheduler = DagScheduler::from_steps(branching_steps(None), "fail", 10).unwrap();
+        assert_eq!(scheduler.get_initial_steps(), vec!["check"]);
+
+        scheduler.mark_running("check").unwrap();
+        let result = scheduler
+            .complete_step("check", serde_json::json!({"result": true}))
+            .unwrap();
+        assert_eq!(result.ready_steps, vec!["deploy"]);
+        let mut skipped = result.skipped_steps.clone();
+        skipped.sort();
+        assert_eq!(skipped, vec!["alert", "rollback"]);
+
+        scheduler.mark_running("deploy").unwrap();
+        let result = scheduler
+            .complete_step("deploy", serde_json::json!({}))
+            .unwrap();
+        assert_eq!(result.ready_steps, vec!["verify"]);
+
+        // The join step runs once the taken branch finishes
+        scheduler.mark_running("verify").unwrap();
+        let result = scheduler
+            .complete_step("verify", serde_json::json!({}))
+            .unwrap();
+        assert_eq!(result.ready_steps, vec!["report"]);
+
+        scheduler.mark_running("report").unwrap();
+        let result = scheduler
+            .complete_step("report", serde_json::json!({}))
+            .unwrap();
+        assert!(result.workflow_complete);
+        assert_eq!(scheduler.step_status("rollback"), Some(StepStatus::Skipped));
+        assert_eq!(scheduler.step_status("alert"), Some(StepStatus::Skipped));
+    }
+
+    #[test]
+    fn test_conditional_branch_false_with_condition_expression() {
+        let steps = branching_steps(Some("$.check.status == \"healthy\""));
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+
+        scheduler.mark_running("check").unwrap();
+        let result = scheduler
+            .complete_step("check", serde_json::json!({"status": "degraded"}))
+            .unwrap();
+        assert_eq!(result.ready_steps, vec!["rollback"]);
+        let mut skipped = result.skipped_steps.clone();
+        skipped.sort();
+        assert_eq!(skipped, vec!["deploy", "verify"]);
+        assert_eq!(scheduler.step_status("report"), Some(StepStatus::Pending));
+    }
+
+    #[test]
+    fn test_skipping_branch_head_cascades_but_keeps_shared_fanin() {
+        // start -> (left -> left_tail) / (right), both -> join
+        let steps = vec![
+            make_step("start", vec![]),
+            make_step("left", vec!["start"]),
+            make_step("left_tail", vec!["left"]),
+            make_step("right", vec!["start"]),
+            make_step("join", vec!["left_tail", "right"]),
+        ];
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+
+        scheduler.mark_running("start").unwrap();
+        let mut ready = scheduler
+            .complete_step("start", serde_json::json!({}))
+            .unwrap()
+            .ready_steps;
+        ready.sort();
+        assert_eq!(ready, vec!["left", "right"]);
+        scheduler.mark_running("right").unwrap();
+
+        let result = scheduler.mark_skipped_with_cascade("left").unwrap();
+        assert_eq!(result.skipped_steps, vec!["left_tail"]);
+        assert!(result.ready_steps.is_empty());
+        assert_eq!(
+            scheduler.step_status("left_tail"),
+            Some(StepStatus::Skipped)
+        );
+        assert_eq!(scheduler.step_status("join"), Some(StepStatus::Pending));
+
+        // The fan-in still runs once the live branch completes
+        let result = scheduler
+            .complete_step("right", serde_json::json!({}))
+            .unwrap();
+        assert_eq!(result.ready_steps, vec!["join"]);
+
+        scheduler.mark_running("join").unwrap();
+        let result = scheduler
+            .complete_step("join", serde_json::json!({}))
+            .unwrap();
+        assert!(result.workflow_complete);
+    }
+
+    #[test]
+    fn test_projected_layers_exclude_branch_decided_false() {
+        let mut steps = branching_steps(Some("$.probe.ok == true"));
+        steps[0].depends_on = vec!["probe".to_string()];
+        steps.push(make_step("probe", vec![]));
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+
+        // Nothing is known yet, so both branches are forecast
+        let all: usize = scheduler.projected_layers().iter().map(Vec::len).sum();
+        assert_eq!(all, 7);
+
+        scheduler.mark_running("probe").unwrap();
+        scheduler
+            .complete_step("probe", serde_json::json!({"ok": false}))
+            .unwrap();
+
+        assert_eq!(
+            scheduler.projected_layers(),
+            vec![
+                vec!["probe".to_string()],
+                vec!["check".to_string()],
+                vec!["rollback".to_string()],
+                vec!["alert".to_string()],
+                vec!["report".to_string()],
+            ]
+        );
+        // The plain layers still show every step
+        let all: usize = scheduler.execution_layers().iter().map(Vec::len).sum();
+        assert_eq!(all, 7);
+    }
+
+    #[test]
+    fn test_projected_layers_exclude_step_whose_condition_is_false() {
+        // probe -> deploy -> verify; report depends on deploy and probe
+        let mut deploy = make_step("deploy", vec!["probe"]);
+        deploy.condition = Some("$.probe.ok == true".to_string());
+        let steps = vec![
+            make_step("probe", vec![]),
+            deploy,
+            make_step("verify", vec!["deploy"]),
+            make_step("report", vec!["deploy", "probe"]),
+        ];
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+
+        let all: usize = scheduler.projected_layers().iter().map(Vec::len).sum();
+        assert_eq!(all, 4);
+
+        scheduler.mark_running("probe").unwrap();
+        scheduler
+            .complete_step("probe", serde_json::json!({"ok": false}))
+            .unwrap();
+
+        // verify only follows deploy; report still has a live parent
+        assert_eq!(
+            scheduler.projected_layers(),
+            vec![vec!["probe".to_string()], vec!["report".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_compound_condition_gates_branch_on_multiple_outputs() {
+        let mut steps = branching_steps(Some("$.probe.ok == true && $.check.count > 0"));
+        steps[0].depends_on = vec!["probe".to_string()];
+        steps.push(make_step("probe", vec![]));
+        let mut scheduler = DagScheduler::from_steps(steps, "fail", 10).unwrap();
+
+        scheduler.mark_running("probe").unwrap();
+        scheduler
+            .complete_step("probe", serde_json::json!({"ok": true}))
+            .unwrap();
+        scheduler.mark_running("check").unwrap();
+        let result = scheduler
+            .complete_step("check", serde_json::json!({"count": 0}))
+            .unwrap();
+        assert_eq!(result.ready_steps, vec!["rollback"]);
+    }
 }
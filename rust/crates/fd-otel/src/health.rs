@@ -0,0 +1,91 @@
+//! Exporter connectivity probes
+//!
+//! The OTLP exporter drops spans quietly when its collector is unreachable,
+//! so readiness checks probe the endpoint directly.
+
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tracing::debug;
+
+/// How long a probe waits for the collector to accept a connection
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Port assumed when the endpoint names none (OTLP over gRPC)
+const DEFAULT_OTLP_PORT: u16 = 4317;
+
+/// `host:port` the exporter connects to for an endpoint URL
+fn exporter_address(endpoint: &str) -> Option<String> {
+    let (scheme, rest) = endpoint.split_once("://").unwrap_or(("", endpoint));
+    let authority = rest.split('/').next().unwrap_or_default();
+    if authority.is_empty() {
+        return None;
+    }
+
+    let has_port = authority
+        .rsplit_once(':')
+        .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+    if has_port {
+        return Some(authority.to_string());
+    }
+    let port = if scheme == "https" {
+        443
+    } else {
+        DEFAULT_OTLP_PORT
+    };
+    Some(format!("{}:{}", authority, port))
+}
+
+/// Whether the collector at `endpoint` accepts TCP connections
+///
+/// A lightweight probe: it opens and drops a connection without sending any
+/// telemetry, so a healthy result does not prove exports succeed.
+pub async fn check_exporter_health(endpoint: &str) -> bool {
+    let Some(address) = exporter_address(endpoint) else {
+        debug!(endpoint, "Unparseable OTLP endpoint");
+        return false;
+    };
+    match tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect(&address)).await {
+        Ok(Ok(_)) => true,
+        Ok(Err(e)) => {
+            debug!(address, error = %e, "OTLP exporter unreachable");
+            false
+        }
+        Err(_) => {
+            debug!(address, "OTLP exporter probe timed out");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_exporter_address_defaults_port() {
+        assert_eq!(
+            exporter_address("http://collector:4318/v1/traces").as_deref(),
+            Some("collector:4318")
+        );
+        assert_eq!(
+            exporter_address("http://collector").as_deref(),
+            Some("collector:4317")
+        );
+        assert_eq!(
+            exporter_address("https://otel.example.com").as_deref(),
+            Some("otel.example.com:443")
+        );
+        assert_eq!(exporter_address("http://"), None);
+    }
+
+    #[tokio::test]
+    async fn test_probe_reports_listening_and_closed_endpoints() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(check_exporter_health(&format!("http://127.0.0.1:{}", port)).await);
+
+        drop(listener);
+        assert!(!check_exporter_health(&format!("http://127.0.0.1:{}", port)).await);
+    }
+}
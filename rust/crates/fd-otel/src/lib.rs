@@ -4,6 +4,13 @@
 //! for tracing LLM calls, tool invocations, and agent steps.
 
 pub mod genai;
+pub mod health;
+pub mod redaction;
 pub mod setup;
+pub mod span;
 
+pub use health::check_exporter_health;
+pub use opentelemetry::trace::SpanContext;
+pub use redaction::Redactor;
 pub use setup::init_telemetry;
+pub use span::{parent_context, run_span, span_ids, step_span};
@@ -0,0 +1,138 @@
+//! Per-step spans
+//!
+//! Every step gets its own span, a child of the run's span, so trace views
+//! show a run as a waterfall of its steps. The parent is rebuilt from the
+//! `trace_id`/`span_id` stored with the run (or step job), since steps are
+//! scheduled and processed outside the request that created the run.
+
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Rebuild a remote parent span context from stored hex IDs
+///
+/// Returns `None` unless both IDs are present and valid.
+pub fn parent_context(trace_id: Option<&str>, span_id: Option<&str>) -> Option<SpanContext> {
+    let trace_id = TraceId::from_hex(trace_id?).ok()?;
+    let span_id = SpanId::from_hex(span_id?).ok()?;
+    let context = SpanContext::new(
+        trace_id,
+        span_id,
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    context.is_valid().then_some(context)
+}
+
+/// Start the root span of a workflow run
+///
+/// Its IDs are stored with the run so every step span, whenever it is
+/// scheduled, can be parented on it.
+pub fn run_span(run_id: &str, workflow_id: &str) -> Span {
+    tracing::info_span!(
+        "workflow_run",
+        otel.name = "workflow run",
+        otel.kind = "internal",
+        ferrumdeck.run.id = %run_id,
+        ferrumdeck.workflow.id = %workflow_id,
+    )
+}
+
+/// Start a span for one step of a run
+///
+/// With a `parent`, the span joins that trace; otherwise it is a child of
+/// the current span.
+pub fn step_span(
+    run_id: &str,
+    step_id: &str,
+    step_type: &str,
+    parent: Option<&SpanContext>,
+) -> Span {
+    let span = tracing::info_span!(
+        "step",
+        otel.name = %format!("step {}", step_type),
+        otel.kind = "internal",
+        ferrumdeck.run.id = %run_id,
+        ferrumdeck.step.id = %step_id,
+        ferrumdeck.step.type = %step_type,
+    );
+    if let Some(parent) = parent {
+        span.set_parent(Context::new().with_remote_span_context(parent.clone()));
+    }
+    span
+}
+
+/// Hex trace and span IDs of a span, for storing alongside a step
+///
+/// `None` when no OpenTelemetry layer is installed.
+pub fn span_ids(span: &Span) -> Option<(String, String)> {
+    let context = span.context();
+    let span_context = context.span().span_context().clone();
+    span_context.is_valid().then(|| {
+        (
+            span_context.trace_id().to_string(),
+            span_context.span_id().to_string(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TracerProvider as _;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const SPAN_ID: &str = "00f067aa0ba902b7";
+
+    #[test]
+    fn test_parent_context_requires_valid_ids() {
+        assert!(parent_context(Some(TRACE_ID), Some(SPAN_ID)).is_some());
+        assert!(parent_context(Some(TRACE_ID), None).is_none());
+        assert!(parent_context(Some("not-hex"), Some(SPAN_ID)).is_none());
+        assert!(parent_context(Some(&"0".repeat(32)), Some(SPAN_ID)).is_none());
+    }
+
+    #[test]
+    fn test_step_span_carries_parent_trace_id() {
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let parent = parent_context(Some(TRACE_ID), Some(SPAN_ID)).unwrap();
+            let span = step_span("run_01", "stp_01", "tool", Some(&parent));
+
+            let (trace_id, span_id) = span_ids(&span).expect("span has OTel context");
+            assert_eq!(trace_id, TRACE_ID);
+            assert_ne!(span_id, SPAN_ID, "the step gets its own span");
+        });
+    }
+
+    #[test]
+    fn test_step_spans_join_the_stored_run_span() {
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let run = run_span("wfr_01", "wf_01");
+            let (run_trace_id, run_span_id) = span_ids(&run).expect("run span has OTel context");
+            drop(run);
+
+            let parent = parent_context(Some(&run_trace_id), Some(&run_span_id)).unwrap();
+            let step = step_span("wfr_01", "stp_01", "tool", Some(&parent));
+
+            let (trace_id, _) = span_ids(&step).expect("step span has OTel context");
+            assert_eq!(trace_id, run_trace_id);
+        });
+    }
+
+    #[test]
+    fn test_span_ids_absent_without_otel_layer() {
+        let span = step_span("run_01", "stp_01", "llm", None);
+        assert!(span_ids(&span).is_none());
+    }
+}
@@ -1,6 +1,7 @@
 //! Airlock configuration types
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Airlock operation mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -31,6 +32,10 @@ pub struct AirlockConfig {
     /// Data exfiltration shield configuration
     #[serde(default)]
     pub exfiltration: ExfiltrationConfig,
+
+    /// Tool output content-type allowlist configuration
+    #[serde(default)]
+    pub output: OutputConfig,
 }
 
 impl Default for AirlockConfig {
@@ -40,6 +45,7 @@ impl Default for AirlockConfig {
             rce: RceConfig::default(),
             velocity: VelocityConfig::default(),
             exfiltration: ExfiltrationConfig::default(),
+            output: OutputConfig::default(),
         }
     }
 }
@@ -58,6 +64,21 @@ pub struct RceConfig {
     /// Custom patterns to add (in addition to built-in)
     #[serde(default)]
     pub custom_patterns: Vec<String>,
+
+    /// Risk score overrides for built-in patterns, keyed by pattern name
+    /// (e.g. `template_injection`)
+    #[serde(default)]
+    pub pattern_score_overrides: HashMap<String, u8>,
+
+    /// Extra path prefixes treated as sensitive, in addition to the built-in
+    /// Unix set (e.g. `/run/secrets`, `C:\Windows`)
+    #[serde(default)]
+    pub sensitive_paths: Vec<String>,
+
+    /// How many nested base64 layers are decoded and rescanned; content
+    /// wrapped deeper than this is reported but not inspected
+    #[serde(default = "default_max_base64_depth")]
+    pub max_base64_depth: usize,
 }
 
 impl Default for RceConfig {
@@ -66,6 +87,9 @@ impl Default for RceConfig {
             enabled: true,
             target_tools: default_rce_tools(),
             custom_patterns: Vec::new(),
+            pattern_score_overrides: HashMap::new(),
+            sensitive_paths: Vec::new(),
+            max_base64_depth: default_max_base64_depth(),
         }
     }
 }
@@ -88,6 +112,15 @@ pub struct VelocityConfig {
     /// Max identical calls before loop detection triggers
     #[serde(default = "default_loop_threshold")]
     pub loop_threshold: u32,
+
+    /// Calls costing less than this (in cents) are not recorded (0 records all)
+    #[serde(default)]
+    pub min_cost_to_track: u64,
+
+    /// Still record sub-threshold calls, at zero cost, so they count toward
+    /// loop detection
+    #[serde(default)]
+    pub track_cheap_calls_for_loops: bool,
 }
 
 impl Default for VelocityConfig {
@@ -97,6 +130,8 @@ impl Default for VelocityConfig {
             max_cost_cents: default_max_cost_cents(),
             window_seconds: default_window_seconds(),
             loop_threshold: default_loop_threshold(),
+            min_cost_to_track: 0,
+            track_cheap_calls_for_loops: false,
         }
     }
 }
@@ -119,6 +154,18 @@ pub struct ExfiltrationConfig {
     /// Block raw IP addresses (prevents direct C2 connections)
     #[serde(default = "default_true")]
     pub block_ip_addresses: bool,
+
+    /// URL schemes network tools may use (e.g. blocks file:// and gopher://)
+    #[serde(default = "default_allowed_schemes")]
+    pub allowed_schemes: Vec<String>,
+
+    /// Require HTTPS even for allowed domains (flags plain http:// URLs)
+    #[serde(default)]
+    pub require_https: bool,
+
+    /// Maximum URLs inspected per call; the rest are ignored and flagged
+    #[serde(default = "default_max_urls")]
+    pub max_urls: usize,
 }
 
 impl Default for ExfiltrationConfig {
@@ -128,6 +175,32 @@ impl Default for ExfiltrationConfig {
             target_tools: default_network_tools(),
             allowed_domains: Vec::new(),
             block_ip_addresses: true,
+            allowed_schemes: default_allowed_schemes(),
+            require_https: false,
+            max_urls: default_max_urls(),
+        }
+    }
+}
+
+/// Tool output content-type allowlist configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputConfig {
+    /// Enable output content-type checking
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Allowed output content types per tool (e.g. `"http_get": ["application/json"]`)
+    ///
+    /// Tools without an entry may return any content type.
+    #[serde(default)]
+    pub allowed_content_types: HashMap<String, Vec<String>>,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allowed_content_types: HashMap::new(),
         }
     }
 }
@@ -148,10 +221,18 @@ fn default_window_seconds() -> u64 {
     10
 }
 
+fn default_max_urls() -> usize {
+    50
+}
+
 fn default_loop_threshold() -> u32 {
     3
 }
 
+fn default_max_base64_depth() -> usize {
+    2
+}
+
 fn default_rce_tools() -> Vec<String> {
     vec![
         "write_file".to_string(),
@@ -177,6 +258,9 @@ fn default_network_tools() -> Vec<String> {
         "send_email".to_string(),
     ]
 }
+fn default_allowed_schemes() -> Vec<String> {
+    vec!["http".to_string(), "https".to_string()]
+}
 
 #[cfg(test)]
 mod tests {
@@ -205,4 +289,13 @@ mod tests {
         assert_eq!(config.window_seconds, 10);
         assert_eq!(config.loop_threshold, 3);
     }
+
+    #[test]
+    fn test_exfiltration_default_schemes() {
+        let config = ExfiltrationConfig::default();
+        assert_eq!(config.allowed_schemes, vec!["http", "https"]);
+
+        let parsed: ExfiltrationConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(parsed.allowed_schemes, vec!["http", "https"]);
+    }
 }
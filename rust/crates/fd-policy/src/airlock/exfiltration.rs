@@ -3,10 +3,13 @@
 //! Protects against unauthorized network access:
 //! - Domain whitelist for network tools
 //! - Blocks raw IP addresses (prevents C2 connections)
+//! - Blocks URL schemes outside the allowed list (file://, gopher://, etc.)
+//! - Optionally requires HTTPS, even for allowed domains
 //! - Detects suspicious URL patterns
+//! - Caps the URLs inspected per call so URL floods cannot stall inspection
 
 use super::config::ExfiltrationConfig;
-use super::inspector::{AirlockViolation, RiskLevel, ViolationType};
+use super::inspector::{AirlockViolation, RiskLevel, ViolationType, MAX_INSPECTION_DEPTH};
 use regex::Regex;
 use std::net::IpAddr;
 use std::sync::OnceLock;
@@ -16,8 +19,9 @@ use tracing::debug;
 fn get_url_regex() -> &'static Regex {
     static URL_REGEX: OnceLock<Regex> = OnceLock::new();
     URL_REGEX.get_or_init(|| {
-        // Match http:// or https:// URLs
-        Regex::new(r#"https?://([^/\s:'"]+)(:\d+)?(/[^\s'"]*)?"#).unwrap()
+        // Match scheme://host URLs, capturing the scheme for inspection.
+        // The host may be empty (file:///etc/passwd).
+        Regex::new(r#"([A-Za-z][A-Za-z0-9+.\-]*)://([^/\s:'"]*)(:\d+)?(/[^\s'"]*)?"#).unwrap()
     })
 }
 
@@ -30,11 +34,24 @@ fn get_ip_regex() -> &'static Regex {
     })
 }
 
+/// URLs pulled out of a tool input
+#[derive(Debug, Default)]
+struct ExtractedUrls {
+    urls: Vec<String>,
+    /// More URLs were present than the limit
+    truncated: bool,
+    /// Some content was nested too deep to inspect
+    too_deep: bool,
+}
+
 /// Data exfiltration shield
 pub struct ExfiltrationShield {
     target_tools: Vec<String>,
     allowed_domains: Vec<String>,
     block_ip_addresses: bool,
+    allowed_schemes: Vec<String>,
+    require_https: bool,
+    max_urls: usize,
 }
 
 impl ExfiltrationShield {
@@ -48,6 +65,13 @@ impl ExfiltrationShield {
                 .map(|d| d.to_lowercase())
                 .collect(),
             block_ip_addresses: config.block_ip_addresses,
+            allowed_schemes: config
+                .allowed_schemes
+                .iter()
+                .map(|s| s.to_lowercase())
+                .collect(),
+            require_https: config.require_https,
+            max_urls: config.max_urls,
         }
     }
 
@@ -71,60 +95,91 @@ impl ExfiltrationShield {
             .any(|allowed| domain == *allowed || domain.ends_with(&format!(".{}", allowed)))
     }
 
+    /// Check if a URL scheme is allowed
+    fn is_scheme_allowed(&self, scheme: &str) -> bool {
+        let scheme = scheme.to_lowercase();
+        self.allowed_schemes.contains(&scheme)
+    }
+
     /// Check if a string is an IP address
     fn is_ip_address(host: &str) -> bool {
         // Try to parse as IP address
         host.parse::<IpAddr>().is_ok() || get_ip_regex().is_match(host)
     }
 
-    /// Extract URLs from JSON value
-    fn extract_urls(value: &serde_json::Value) -> Vec<String> {
-        let mut urls = Vec::new();
+    /// Extract up to `limit` URLs from a JSON value
+    ///
+    /// Extraction stops as soon as the limit is passed, so huge payloads are
+    /// not fully scanned, and never descends past [`MAX_INSPECTION_DEPTH`].
+    fn extract_urls(value: &serde_json::Value, limit: usize) -> ExtractedUrls {
+        let mut extracted = ExtractedUrls::default();
+        Self::collect_urls(value, limit + 1, 0, &mut extracted);
+        extracted.truncated = extracted.urls.len() > limit;
+        extracted.urls.truncate(limit);
+        extracted
+    }
+
+    fn collect_urls(
+        value: &serde_json::Value,
+        cap: usize,
+        depth: usize,
+        extracted: &mut ExtractedUrls,
+    ) {
+        let urls = &mut extracted.urls;
+        if urls.len() >= cap {
+            return;
+        }
 
         match value {
             serde_json::Value::String(s) => {
                 // Extract URLs from string content
-                for cap in get_url_regex().captures_iter(s) {
-                    urls.push(cap[0].to_string());
+                for cap_match in get_url_regex().captures_iter(s) {
+                    if urls.len() >= cap {
+                        return;
+                    }
+                    urls.push(cap_match[0].to_string());
                 }
             }
+            serde_json::Value::Array(_) | serde_json::Value::Object(_)
+                if depth >= MAX_INSPECTION_DEPTH =>
+            {
+                extracted.too_deep = true;
+            }
             serde_json::Value::Array(arr) => {
                 for item in arr {
-                    urls.extend(Self::extract_urls(item));
+                    Self::collect_urls(item, cap, depth + 1, extracted);
                 }
             }
             serde_json::Value::Object(obj) => {
                 // Special handling for common URL fields
-                if let Some(serde_json::Value::String(url)) = obj.get("url") {
-                    urls.push(url.clone());
-                }
-                if let Some(serde_json::Value::String(url)) = obj.get("endpoint") {
-                    urls.push(url.clone());
-                }
-                if let Some(serde_json::Value::String(url)) = obj.get("webhook") {
-                    urls.push(url.clone());
-                }
-                if let Some(serde_json::Value::String(url)) = obj.get("callback") {
-                    urls.push(url.clone());
+                for field in ["url", "endpoint", "webhook", "callback"] {
+                    if let Some(serde_json::Value::String(url)) = obj.get(field) {
+                        if urls.len() >= cap {
+                            return;
+                        }
+                        urls.push(url.clone());
+                    }
                 }
 
                 // Recursively check all values
                 for v in obj.values() {
-                    urls.extend(Self::extract_urls(v));
+                    Self::collect_urls(v, cap, depth + 1, extracted);
                 }
             }
             _ => {}
         }
+    }
 
-        urls
+    /// Extract scheme from URL
+    fn extract_scheme(url: &str) -> Option<String> {
+        let caps = get_url_regex().captures(url)?;
+        Some(caps[1].to_lowercase())
     }
 
     /// Extract domain from URL
     fn extract_domain(url: &str) -> Option<String> {
         // Strip protocol
-        let url = url
-            .strip_prefix("http://")
-            .or_else(|| url.strip_prefix("https://"))?;
+        let (_, url) = url.split_once("://")?;
 
         // Get host part (before path)
         let host = url.split('/').next()?;
@@ -145,9 +200,50 @@ impl ExfiltrationShield {
             return None;
         }
 
-        let urls = Self::extract_urls(tool_input);
+        let ExtractedUrls {
+            urls,
+            truncated,
+            too_deep,
+        } = Self::extract_urls(tool_input, self.max_urls);
 
         for url in urls {
+            if let Some(scheme) = Self::extract_scheme(&url) {
+                if !self.is_scheme_allowed(&scheme) {
+                    debug!(
+                        tool = tool_name,
+                        scheme = scheme,
+                        "Disallowed URL scheme detected"
+                    );
+
+                    return Some(AirlockViolation {
+                        violation_type: ViolationType::DisallowedScheme,
+                        risk_score: 85,
+                        risk_level: RiskLevel::Critical,
+                        details: format!(
+                            "Disallowed URL scheme '{}' in {}. \
+                             Add the scheme to allowed_schemes if this is expected behavior.",
+                            scheme, url
+                        ),
+                        trigger: format!("disallowed_scheme:{}", scheme),
+                    });
+                }
+
+                if self.require_https && scheme == "http" {
+                    debug!(tool = tool_name, url = url, "Insecure transport detected");
+
+                    return Some(AirlockViolation {
+                        violation_type: ViolationType::InsecureTransport,
+                        risk_score: 65,
+                        risk_level: RiskLevel::High,
+                        details: format!(
+                            "Plain HTTP used for {}. HTTPS is required for network tools.",
+                            url
+                        ),
+                        trigger: "insecure_transport:http".to_string(),
+                    });
+                }
+            }
+
             if let Some(domain) = Self::extract_domain(&url) {
                 // Check for IP address (potential C2 connection)
                 if self.block_ip_addresses && Self::is_ip_address(&domain) {
@@ -193,6 +289,34 @@ impl ExfiltrationShield {
             }
         }
 
+        if truncated {
+            debug!(
+                tool = tool_name,
+                max_urls = self.max_urls,
+                "URL limit reached, remaining URLs not inspected"
+            );
+
+            return Some(AirlockViolation {
+                violation_type: ViolationType::UrlLimitExceeded,
+                risk_score: 30,
+                risk_level: RiskLevel::Low,
+                details: format!(
+                    "Tool input contains more than {} URLs; only the first {} were inspected",
+                    self.max_urls, self.max_urls
+                ),
+                trigger: format!("url_limit:{}", self.max_urls),
+            });
+        }
+
+        if too_deep {
+            debug!(
+                tool = tool_name,
+                max_depth = MAX_INSPECTION_DEPTH,
+                "Depth limit reached, deeper URLs not inspected"
+            );
+            return Some(AirlockViolation::depth_limit_exceeded());
+        }
+
         None
     }
 }
@@ -211,6 +335,9 @@ mod tests {
             ],
             allowed_domains: domains.into_iter().map(String::from).collect(),
             block_ip_addresses: true,
+            allowed_schemes: vec!["http".to_string(), "https".to_string()],
+            require_https: false,
+            max_urls: 50,
         })
     }
 
@@ -220,6 +347,9 @@ mod tests {
             target_tools: vec!["http_get".to_string()],
             allowed_domains: vec![],
             block_ip_addresses: true,
+            allowed_schemes: vec!["http".to_string(), "https".to_string()],
+            require_https: false,
+            max_urls: 50,
         })
     }
 
@@ -355,6 +485,55 @@ mod tests {
         assert!(violation.details.contains("blocked.io"));
     }
 
+    #[test]
+    fn test_url_flood_inspects_only_max_urls_and_flags_truncation() {
+        let shield = ExfiltrationShield::new(&ExfiltrationConfig {
+            target_tools: vec!["http_get".to_string()],
+            allowed_domains: vec!["allowed.com".to_string()],
+            max_urls: 10,
+            ..ExfiltrationConfig::default()
+        });
+
+        let mut urls: Vec<String> = (0..100)
+            .map(|i| format!("https://allowed.com/{}", i))
+            .collect();
+        // A bad URL past the cap is never inspected
+        urls[50] = "https://evil.com/steal".to_string();
+        let input = serde_json::json!({ "urls": urls });
+
+        let extracted = ExfiltrationShield::extract_urls(&input, 10);
+        assert_eq!(extracted.urls.len(), 10);
+        assert_eq!(extracted.urls[0], "https://allowed.com/0");
+        assert!(extracted.truncated);
+
+        let violation = shield.check("http_get", &input).unwrap();
+        assert_eq!(violation.violation_type, ViolationType::UrlLimitExceeded);
+        assert_eq!(violation.risk_level, RiskLevel::Low);
+
+        // Within the cap nothing is flagged
+        assert!(!ExfiltrationShield::extract_urls(&input, 100).truncated);
+    }
+
+    #[test]
+    fn test_violation_within_cap_wins_over_truncation_flag() {
+        let shield = ExfiltrationShield::new(&ExfiltrationConfig {
+            target_tools: vec!["http_get".to_string()],
+            allowed_domains: vec!["allowed.com".to_string()],
+            max_urls: 5,
+            ..ExfiltrationConfig::default()
+        });
+
+        let mut urls: Vec<String> = (0..20)
+            .map(|i| format!("https://allowed.com/{}", i))
+            .collect();
+        urls[2] = "https://evil.com/steal".to_string();
+
+        let violation = shield
+            .check("http_get", &serde_json::json!({ "urls": urls }))
+            .unwrap();
+        assert_eq!(violation.violation_type, ViolationType::ExfiltrationAttempt);
+    }
+
     #[test]
     fn test_domain_extraction() {
         assert_eq!(
@@ -398,4 +577,105 @@ mod tests {
         let result = shield.check("http_get", &input);
         assert!(result.is_none()); // Should be allowed (case insensitive)
     }
+
+    #[test]
+    fn test_file_scheme_blocked() {
+        let shield = create_shield_no_whitelist();
+
+        let input = serde_json::json!({
+            "url": "file:///etc/passwd"
+        });
+
+        let result = shield.check("http_get", &input);
+        assert!(result.is_some());
+        let violation = result.unwrap();
+        assert_eq!(violation.violation_type, ViolationType::DisallowedScheme);
+        assert_eq!(violation.trigger, "disallowed_scheme:file");
+    }
+
+    #[test]
+    fn test_gopher_scheme_blocked() {
+        let shield = create_shield_no_whitelist();
+
+        let input = serde_json::json!({
+            "body": "connect to gopher://x for details"
+        });
+
+        let result = shield.check("http_get", &input);
+        assert!(result.is_some());
+        assert_eq!(
+            result.unwrap().violation_type,
+            ViolationType::DisallowedScheme
+        );
+    }
+
+    #[test]
+    fn test_allowed_scheme_passes() {
+        let shield = create_shield_with_whitelist(vec!["allowed.com"]);
+
+        let input = serde_json::json!({
+            "url": "https://allowed.com/data"
+        });
+
+        assert!(shield.check("http_get", &input).is_none());
+        assert_eq!(
+            ExfiltrationShield::extract_scheme("HTTPS://allowed.com").as_deref(),
+            Some("https")
+        );
+    }
+
+    #[test]
+    fn test_require_https_flags_plain_http_to_allowed_domain() {
+        let shield = ExfiltrationShield::new(&ExfiltrationConfig {
+            enabled: true,
+            target_tools: vec!["http_get".to_string()],
+            allowed_domains: vec!["allowed.com".to_string()],
+            require_https: true,
+            ..ExfiltrationConfig::default()
+        });
+
+        let violation = shield
+            .check(
+                "http_get",
+                &serde_json::json!({"url": "http://allowed.com/data"}),
+            )
+            .unwrap();
+        assert_eq!(violation.violation_type, ViolationType::InsecureTransport);
+
+        let result = shield.check(
+            "http_get",
+            &serde_json::json!({"url": "https://allowed.com/data"}),
+        );
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_http_allowed_when_https_not_required() {
+        let shield = create_shield_with_whitelist(vec!["allowed.com"]);
+
+        for url in ["http://allowed.com/data", "https://allowed.com/data"] {
+            let result = shield.check("http_get", &serde_json::json!({ "url": url }));
+            assert!(result.is_none(), "{} should pass", url);
+        }
+    }
+
+    #[test]
+    fn test_deeply_nested_urls_are_cut_off_and_flagged() {
+        let shield = create_shield_with_whitelist(vec!["allowed.com"]);
+        let mut input = serde_json::json!({"url": "https://evil.com/steal"});
+        for _ in 0..200 {
+            input = serde_json::json!([input]);
+        }
+
+        let extracted = ExfiltrationShield::extract_urls(&input, 50);
+        assert!(extracted.urls.is_empty());
+        assert!(extracted.too_deep);
+
+        let violation = shield.check("http_get", &input).unwrap();
+        assert_eq!(violation.violation_type, ViolationType::DepthLimitExceeded);
+        assert_eq!(
+            violation.trigger,
+            format!("depth_limit:{}", MAX_INSPECTION_DEPTH)
+        );
+    }
 }
@@ -9,11 +9,13 @@
 
 use super::config::{AirlockConfig, AirlockMode};
 use super::exfiltration::ExfiltrationShield;
+use super::output::OutputContentGuard;
 use super::patterns::RcePatternMatcher;
 use super::velocity::VelocityTracker;
 use fd_core::RunId;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::Semaphore;
 use tracing::{debug, info, warn};
 
 /// Violation type categories
@@ -30,8 +32,31 @@ pub enum ViolationType {
     ExfiltrationAttempt,
     /// Raw IP address used instead of domain
     IpAddressUsed,
+    /// URL scheme outside the allowed list (file://, gopher://, etc.)
+    DisallowedScheme,
+    /// Plain HTTP used where HTTPS is required
+    InsecureTransport,
+    /// Tool output content type outside the tool's allowlist
+    DisallowedContentType,
+    /// More URLs than the exfiltration shield inspects; the excess was ignored
+    UrlLimitExceeded,
+    /// Tool input nested (as JSON or base64) deeper than the layers inspect;
+    /// blocked outside shadow mode, since the excess went uninspected
+    DepthLimitExceeded,
 }
 
+impl ViolationType {
+    /// Advisory violations are reported but never block the call
+    pub fn is_advisory(self) -> bool {
+        matches!(self, ViolationType::UrlLimitExceeded)
+    }
+}
+
+/// Deepest JSON nesting the inspection layers descend into
+///
+/// Bounding recursion keeps adversarial payloads from overflowing the stack.
+pub const MAX_INSPECTION_DEPTH: usize = 64;
+
 /// Risk level for violations
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -66,6 +91,16 @@ impl RiskLevel {
             RiskLevel::Critical => "critical",
         }
     }
+
+    /// Syslog-style severity used on audit events
+    pub fn audit_severity(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "info",
+            RiskLevel::Medium => "notice",
+            RiskLevel::High => "warning",
+            RiskLevel::Critical => "alert",
+        }
+    }
 }
 
 /// A detected violation
@@ -83,6 +118,36 @@ pub struct AirlockViolation {
     pub trigger: String,
 }
 
+impl AirlockViolation {
+    /// Raised when tool input nests deeper than [`MAX_INSPECTION_DEPTH`]
+    pub(crate) fn depth_limit_exceeded() -> Self {
+        Self {
+            violation_type: ViolationType::DepthLimitExceeded,
+            risk_score: 30,
+            risk_level: RiskLevel::Low,
+            details: format!(
+                "Tool input is nested more than {} levels deep; deeper content was not inspected",
+                MAX_INSPECTION_DEPTH
+            ),
+            trigger: format!("depth_limit:{}", MAX_INSPECTION_DEPTH),
+        }
+    }
+
+    /// Raised when base64 is still nested after `max_depth` decode passes
+    pub(crate) fn base64_depth_limit_exceeded(max_depth: usize) -> Self {
+        Self {
+            violation_type: ViolationType::DepthLimitExceeded,
+            risk_score: 30,
+            risk_level: RiskLevel::Low,
+            details: format!(
+                "Tool input wraps base64 more than {} layers deep; deeper layers were not inspected",
+                max_depth
+            ),
+            trigger: format!("base64_depth_limit:{}", max_depth),
+        }
+    }
+}
+
 /// Context for inspection
 #[derive(Debug, Clone)]
 pub struct InspectionContext {
@@ -136,6 +201,12 @@ pub struct AirlockInspector {
     velocity_tracker: Arc<VelocityTracker>,
     /// Data exfiltration shield
     exfiltration_shield: ExfiltrationShield,
+    /// Tool output content-type allowlist
+    output_guard: OutputContentGuard,
+    /// Bound on concurrent `inspect` calls, shared by derived inspectors
+    inspection_limit: Option<Arc<Semaphore>>,
+    /// Inspector for the other mode, built on the first run that overrides it
+    mode_override: OnceLock<Arc<AirlockInspector>>,
 }
 
 impl AirlockInspector {
@@ -144,6 +215,7 @@ impl AirlockInspector {
         let rce_matcher = RcePatternMatcher::new(&config.rce);
         let velocity_tracker = Arc::new(VelocityTracker::new(config.velocity.clone()));
         let exfiltration_shield = ExfiltrationShield::new(&config.exfiltration);
+        let output_guard = OutputContentGuard::new(&config.output);
 
         info!(
             mode = ?config.mode,
@@ -158,7 +230,63 @@ impl AirlockInspector {
             rce_matcher,
             velocity_tracker,
             exfiltration_shield,
+            output_guard,
+            inspection_limit: None,
+            mode_override: OnceLock::new(),
+        }
+    }
+
+    /// Allow at most `max` inspections to run at once
+    ///
+    /// Further `inspect` calls wait for a running one to finish, which bounds
+    /// CPU spent on bursts of large payloads. The limit is shared with
+    /// inspectors derived via [`with_mode`](Self::with_mode) and
+    /// [`sharing_inspection_limit`](Self::sharing_inspection_limit).
+    pub fn with_max_concurrent_inspections(mut self, max: usize) -> Self {
+        self.inspection_limit = Some(Arc::new(Semaphore::new(max.max(1))));
+        self
+    }
+
+    /// Count this inspector's inspections against `other`'s concurrency limit
+    pub fn sharing_inspection_limit(mut self, other: &AirlockInspector) -> Self {
+        self.inspection_limit = other.inspection_limit.clone();
+        self
+    }
+
+    /// Create an inspector for the same configuration but a different mode
+    ///
+    /// Used for per-run mode overrides. The velocity tracker is shared with
+    /// `self` so spend and loop history stay consistent across inspectors.
+    pub fn with_mode(&self, mode: AirlockMode) -> Self {
+        let config = AirlockConfig {
+            mode,
+            ..self.config.clone()
+        };
+
+        Self {
+            rce_matcher: RcePatternMatcher::new(&config.rce),
+            velocity_tracker: Arc::clone(&self.velocity_tracker),
+            exfiltration_shield: ExfiltrationShield::new(&config.exfiltration),
+            output_guard: OutputContentGuard::new(&config.output),
+            inspection_limit: self.inspection_limit.clone(),
+            mode_override: OnceLock::new(),
+            config,
+        }
+    }
+
+    /// Get the inspector to use for a run in `mode`
+    ///
+    /// Returns `self` when the mode already matches. Otherwise the inspector
+    /// from [`with_mode`](Self::with_mode) is built once and reused, so runs
+    /// with a mode override don't recompile the inspection layers per call.
+    pub fn for_mode(self: &Arc<Self>, mode: AirlockMode) -> Arc<Self> {
+        if mode == self.config.mode {
+            return Arc::clone(self);
         }
+        Arc::clone(
+            self.mode_override
+                .get_or_init(|| Arc::new(self.with_mode(mode))),
+        )
     }
 
     /// Check if Airlock is in shadow mode (log-only, don't block)
@@ -179,8 +307,22 @@ impl AirlockInspector {
     /// Inspect a tool call through all layers
     ///
     /// Returns an AirlockResult indicating whether the call should be allowed
-    /// and any detected violations.
+    /// and any detected violations. With a concurrency limit configured, the
+    /// call waits for a free slot first.
     pub async fn inspect(&self, ctx: &InspectionContext) -> AirlockResult {
+        let _permit = match &self.inspection_limit {
+            Some(limit) => Some(
+                limit
+                    .acquire()
+                    .await
+                    .expect("inspection semaphore is never closed"),
+            ),
+            None => None,
+        };
+        self.inspect_layers(ctx).await
+    }
+
+    async fn inspect_layers(&self, ctx: &InspectionContext) -> AirlockResult {
         let shadow_mode = self.is_shadow_mode();
 
         debug!(
@@ -190,26 +332,35 @@ impl AirlockInspector {
             "Inspecting tool call"
         );
 
+        // Advisories from earlier layers don't stop the later ones
+        let mut advisory = None;
+
         // Layer 1: Anti-RCE pattern detection
         if self.config.rce.enabled {
-            if let Some(violation) = self.rce_matcher.check(&ctx.tool_name, &ctx.tool_input) {
-                warn!(
-                    run_id = %ctx.run_id,
-                    tool = %ctx.tool_name,
-                    violation_type = ?violation.violation_type,
-                    risk_score = violation.risk_score,
-                    trigger = %violation.trigger,
-                    shadow_mode = shadow_mode,
-                    "RCE pattern detected"
-                );
-
-                return AirlockResult {
-                    allowed: shadow_mode, // Block if enforce mode
-                    violation: Some(violation.clone()),
-                    shadow_mode,
-                    risk_score: violation.risk_score,
-                    risk_level: violation.risk_level,
-                };
+            match self.rce_matcher.check(&ctx.tool_name, &ctx.tool_input) {
+                Some(violation) if violation.violation_type.is_advisory() => {
+                    advisory = Some(violation);
+                }
+                Some(violation) => {
+                    warn!(
+                        run_id = %ctx.run_id,
+                        tool = %ctx.tool_name,
+                        violation_type = ?violation.violation_type,
+                        risk_score = violation.risk_score,
+                        trigger = %violation.trigger,
+                        shadow_mode = shadow_mode,
+                        "RCE pattern detected"
+                    );
+
+                    return AirlockResult {
+                        allowed: shadow_mode, // Block if enforce mode
+                        violation: Some(violation.clone()),
+                        shadow_mode,
+                        risk_score: violation.risk_score,
+                        risk_level: violation.risk_level,
+                    };
+                }
+                None => {}
             }
         }
 
@@ -252,7 +403,7 @@ impl AirlockInspector {
                 );
 
                 return AirlockResult {
-                    allowed: shadow_mode,
+                    allowed: shadow_mode || violation.violation_type.is_advisory(),
                     violation: Some(violation.clone()),
                     shadow_mode,
                     risk_score: violation.risk_score,
@@ -261,6 +412,16 @@ impl AirlockInspector {
             }
         }
 
+        if let Some(violation) = advisory {
+            return AirlockResult {
+                allowed: true,
+                shadow_mode,
+                risk_score: violation.risk_score,
+                risk_level: violation.risk_level,
+                violation: Some(violation),
+            };
+        }
+
         // All checks passed
         debug!(
             run_id = %ctx.run_id,
@@ -271,13 +432,66 @@ impl AirlockInspector {
         AirlockResult::default()
     }
 
-    /// Record a completed tool call for velocity tracking
+    /// Inspect the declared content type of a tool's output
+    ///
+    /// Checked when a tool step submits its result, so executable content
+    /// (HTML with scripts, binaries) can be stopped before it flows back to
+    /// the model or UI. Outputs without a declared content type pass.
+    pub fn inspect_output(
+        &self,
+        run_id: &str,
+        tool_name: &str,
+        content_type: Option<&str>,
+    ) -> AirlockResult {
+        let shadow_mode = self.is_shadow_mode();
+
+        if self.config.output.enabled {
+            if let Some(violation) = content_type
+                .and_then(|content_type| self.output_guard.check(tool_name, content_type))
+            {
+                warn!(
+                    run_id = %run_id,
+                    tool = %tool_name,
+                    violation_type = ?violation.violation_type,
+                    risk_score = violation.risk_score,
+                    trigger = %violation.trigger,
+                    shadow_mode = shadow_mode,
+                    "Disallowed tool output content type"
+                );
+
+                return AirlockResult {
+                    allowed: shadow_mode,
+                    violation: Some(violation.clone()),
+                    shadow_mode,
+                    risk_score: violation.risk_score,
+                    risk_level: violation.risk_level,
+                };
+            }
+        }
+
+        AirlockResult::default()
+    }
+
+    /// Record a tool call for velocity tracking
     ///
-    /// Should be called after a tool call completes successfully.
-    pub async fn record_call(&self, ctx: &InspectionContext) {
+    /// Calls recorded without `actual_cost_cents` count at their estimate
+    /// until the completed step reports the actual cost.
+    pub async fn record_call(&self, ctx: &InspectionContext, actual_cost_cents: Option<u64>) {
         if self.config.velocity.enabled {
-            self.velocity_tracker.record(ctx).await;
+            self.velocity_tracker.record(ctx, actual_cost_cents).await;
+        }
+    }
+
+    /// Inspect a tool call and record it for velocity tracking in one step
+    ///
+    /// The call is recorded whenever it is allowed to proceed, which includes
+    /// violations logged in shadow mode. Blocked calls are not recorded.
+    pub async fn inspect_and_record(&self, ctx: &InspectionContext) -> AirlockResult {
+        let result = self.inspect(ctx).await;
+        if result.allowed {
+            self.record_call(ctx, None).await;
         }
+        result
     }
 
     /// Clear velocity tracking data for a completed run
@@ -291,12 +505,17 @@ impl AirlockInspector {
     pub async fn velocity_stats(&self) -> super::velocity::VelocityStats {
         self.velocity_tracker.stats().await
     }
+
+    /// Drop expired velocity records, see [`VelocityTracker::compact`]
+    pub async fn compact_velocity(&self) -> super::velocity::VelocityCompaction {
+        self.velocity_tracker.compact().await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::airlock::config::{ExfiltrationConfig, RceConfig, VelocityConfig};
+    use crate::airlock::config::{ExfiltrationConfig, OutputConfig, RceConfig, VelocityConfig};
 
     fn create_test_config() -> AirlockConfig {
         AirlockConfig {
@@ -304,6 +523,7 @@ mod tests {
             rce: RceConfig::default(),
             velocity: VelocityConfig::default(),
             exfiltration: ExfiltrationConfig::default(),
+            output: OutputConfig::default(),
         }
     }
 
@@ -340,6 +560,30 @@ mod tests {
         assert_eq!(result.risk_score, 0);
     }
 
+    #[tokio::test]
+    async fn test_inspections_beyond_limit_wait_for_a_slot() {
+        let inspector = AirlockInspector::new(create_test_config())
+            .with_max_concurrent_inspections(1)
+            .with_mode(AirlockMode::Shadow);
+        let ctx = create_context("read_file", serde_json::json!({"path": "/tmp/a"}));
+
+        // Occupy the only slot, as a long-running inspection would
+        let limit = inspector.inspection_limit.clone().unwrap();
+        let held = limit.acquire().await.unwrap();
+
+        let waiting = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            inspector.inspect(&ctx),
+        )
+        .await;
+        assert!(waiting.is_err(), "inspection should queue while saturated");
+
+        drop(held);
+        let result = inspector.inspect(&ctx).await;
+        assert!(result.allowed);
+        assert_eq!(limit.available_permits(), 1);
+    }
+
     #[tokio::test]
     async fn test_rce_pattern_blocked_enforce() {
         let inspector = AirlockInspector::new(create_test_config());
@@ -388,7 +632,9 @@ mod tests {
                 target_tools: vec!["http_get".to_string()],
                 allowed_domains: vec!["allowed.com".to_string()],
                 block_ip_addresses: true,
+                ..ExfiltrationConfig::default()
             },
+            output: OutputConfig::default(),
         };
 
         let inspector = AirlockInspector::new(config);
@@ -409,6 +655,77 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_input_nested_past_inspection_depth_is_blocked() {
+        let mut input = serde_json::json!({"content": "result = eval(user_input)"});
+        for _ in 0..70 {
+            input = serde_json::json!({"nested": input});
+        }
+
+        let result = AirlockInspector::new(create_test_config())
+            .inspect(&create_context("write_file", input.clone()))
+            .await;
+        assert!(!result.allowed);
+        let violation = result.violation.unwrap();
+        assert_eq!(violation.violation_type, ViolationType::DepthLimitExceeded);
+        assert_eq!(violation.risk_level, RiskLevel::Low);
+
+        // Shadow mode still only reports it
+        let result = AirlockInspector::new(create_shadow_config())
+            .inspect(&create_context("write_file", input))
+            .await;
+        assert!(result.allowed);
+        assert!(result.violation.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_base64_wrapped_past_max_depth_is_blocked() {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        // One layer more than the default max_base64_depth of 2
+        let payload = (0..3).fold("eval(user_input)".to_string(), |text, _| {
+            STANDARD.encode(text)
+        });
+        let input = serde_json::json!({"content": payload});
+
+        let result = AirlockInspector::new(create_test_config())
+            .inspect(&create_context("write_file", input.clone()))
+            .await;
+        assert!(!result.allowed);
+        assert_eq!(result.violation.unwrap().trigger, "base64_depth_limit:2");
+
+        let result = AirlockInspector::new(create_shadow_config())
+            .inspect(&create_context("write_file", input))
+            .await;
+        assert!(result.allowed);
+    }
+
+    #[tokio::test]
+    async fn test_url_limit_flag_does_not_block() {
+        let config = AirlockConfig {
+            mode: AirlockMode::Enforce,
+            exfiltration: ExfiltrationConfig {
+                target_tools: vec!["http_get".to_string()],
+                max_urls: 2,
+                ..ExfiltrationConfig::default()
+            },
+            ..AirlockConfig::default()
+        };
+        let inspector = AirlockInspector::new(config);
+
+        let ctx = create_context(
+            "http_get",
+            serde_json::json!({"urls": ["https://a.com", "https://b.com", "https://c.com"]}),
+        );
+
+        let result = inspector.inspect(&ctx).await;
+        assert!(result.allowed);
+        assert_eq!(
+            result.violation.unwrap().violation_type,
+            ViolationType::UrlLimitExceeded
+        );
+    }
+
     #[tokio::test]
     async fn test_ip_address_blocked() {
         let config = AirlockConfig {
@@ -420,7 +737,9 @@ mod tests {
                 target_tools: vec!["http_get".to_string()],
                 allowed_domains: vec![], // No whitelist
                 block_ip_addresses: true,
+                ..ExfiltrationConfig::default()
             },
+            output: OutputConfig::default(),
         };
 
         let inspector = AirlockInspector::new(config);
@@ -451,8 +770,10 @@ mod tests {
                 max_cost_cents: 1000,
                 window_seconds: 60,
                 loop_threshold: 3,
+                ..Default::default()
             },
             exfiltration: ExfiltrationConfig::default(),
+            output: OutputConfig::default(),
         };
 
         let inspector = AirlockInspector::new(config);
@@ -466,7 +787,7 @@ mod tests {
 
         // Record 3 identical calls (threshold)
         for _ in 0..3 {
-            inspector.record_call(&ctx).await;
+            inspector.record_call(&ctx, None).await;
         }
 
         // 4th call should trigger loop detection
@@ -491,6 +812,14 @@ mod tests {
         assert_eq!(RiskLevel::from_score(100), RiskLevel::Critical);
     }
 
+    #[test]
+    fn test_risk_level_audit_severity() {
+        assert_eq!(RiskLevel::Low.audit_severity(), "info");
+        assert_eq!(RiskLevel::Medium.audit_severity(), "notice");
+        assert_eq!(RiskLevel::High.audit_severity(), "warning");
+        assert_eq!(RiskLevel::Critical.audit_severity(), "alert");
+    }
+
     #[tokio::test]
     async fn test_clear_run() {
         let inspector = AirlockInspector::new(create_test_config());
@@ -504,8 +833,8 @@ mod tests {
         };
 
         // Record some calls
-        inspector.record_call(&ctx).await;
-        inspector.record_call(&ctx).await;
+        inspector.record_call(&ctx, None).await;
+        inspector.record_call(&ctx, None).await;
 
         let stats = inspector.velocity_stats().await;
         assert_eq!(stats.tracked_runs, 1);
@@ -516,4 +845,124 @@ mod tests {
         let stats = inspector.velocity_stats().await;
         assert_eq!(stats.tracked_runs, 0);
     }
+
+    #[tokio::test]
+    async fn test_with_mode_overrides_mode() {
+        let inspector = AirlockInspector::new(create_test_config());
+        let shadow = inspector.with_mode(AirlockMode::Shadow);
+
+        assert!(!inspector.is_shadow_mode());
+        assert!(shadow.is_shadow_mode());
+
+        let ctx = create_context(
+            "write_file",
+            serde_json::json!({
+                "content": "result = eval(user_input)"
+            }),
+        );
+
+        let result = shadow.inspect(&ctx).await;
+        assert!(result.allowed);
+        assert!(result.shadow_mode);
+        assert!(result.violation.is_some());
+
+        let result = inspector.inspect(&ctx).await;
+        assert!(!result.allowed);
+    }
+
+    #[tokio::test]
+    async fn test_with_mode_shares_velocity_tracker() {
+        let inspector = AirlockInspector::new(create_test_config());
+        let shadow = inspector.with_mode(AirlockMode::Shadow);
+
+        let ctx = create_context("tool", serde_json::json!({}));
+        shadow.record_call(&ctx, None).await;
+
+        assert_eq!(inspector.velocity_stats().await.tracked_runs, 1);
+    }
+
+    #[test]
+    fn test_for_mode_reuses_derived_inspector() {
+        let inspector = Arc::new(AirlockInspector::new(create_test_config()));
+
+        let same = inspector.for_mode(AirlockMode::Enforce);
+        assert!(Arc::ptr_eq(&same, &inspector));
+
+        let shadow = inspector.for_mode(AirlockMode::Shadow);
+        assert!(shadow.is_shadow_mode());
+        assert!(Arc::ptr_eq(
+            &shadow,
+            &inspector.for_mode(AirlockMode::Shadow)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_inspect_and_record_clean_call() {
+        let inspector = AirlockInspector::new(create_test_config());
+        let ctx = create_context("read_file", serde_json::json!({"path": "/tmp/a.txt"}));
+
+        let result = inspector.inspect_and_record(&ctx).await;
+        assert!(result.allowed);
+        assert!(result.violation.is_none());
+
+        let stats = inspector.velocity_stats().await;
+        assert_eq!(stats.tracked_runs, 1);
+        assert_eq!(stats.total_records, 1);
+
+        inspector.inspect_and_record(&ctx).await;
+        assert_eq!(inspector.velocity_stats().await.total_records, 2);
+    }
+
+    #[tokio::test]
+    async fn test_inspect_and_record_skips_blocked_call() {
+        let inspector = AirlockInspector::new(create_test_config());
+        let ctx = create_context(
+            "write_file",
+            serde_json::json!({"content": "result = eval(user_input)"}),
+        );
+
+        let result = inspector.inspect_and_record(&ctx).await;
+        assert!(!result.allowed);
+        assert_eq!(inspector.velocity_stats().await.total_records, 0);
+
+        // Shadow mode lets the call through, so it counts toward velocity
+        let shadow = inspector.with_mode(AirlockMode::Shadow);
+        let result = shadow.inspect_and_record(&ctx).await;
+        assert!(result.allowed);
+        assert_eq!(inspector.velocity_stats().await.total_records, 1);
+    }
+
+    #[test]
+    fn test_inspect_output_disallowed_content_type() {
+        let mut config = create_test_config();
+        config
+            .output
+            .allowed_content_types
+            .insert("http_get".to_string(), vec!["application/json".to_string()]);
+
+        let result = AirlockInspector::new(config.clone()).inspect_output(
+            "run_1",
+            "http_get",
+            Some("text/html"),
+        );
+        assert!(!result.allowed);
+        assert_eq!(
+            result.violation.unwrap().violation_type,
+            ViolationType::DisallowedContentType
+        );
+
+        let result = AirlockInspector::new(config.clone()).inspect_output(
+            "run_1",
+            "http_get",
+            Some("application/json"),
+        );
+        assert!(result.allowed);
+        assert!(result.violation.is_none());
+
+        config.mode = AirlockMode::Shadow;
+        let result =
+            AirlockInspector::new(config).inspect_output("run_1", "http_get", Some("text/html"));
+        assert!(result.allowed);
+        assert!(result.violation.is_some());
+    }
 }
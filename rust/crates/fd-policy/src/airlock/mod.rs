@@ -20,6 +20,9 @@
 //!    - Blocks raw IP addresses (prevents C2 connections)
 //!    - URL extraction from nested JSON payloads
 //!
+//! Tool results are additionally checked against a per-tool allowlist of
+//! output content types (`output.rs`) when they are submitted.
+//!
 //! ## Operating Modes
 //!
 //! - **Shadow Mode** (default): Log violations but don't block - safe for rollout
@@ -49,8 +52,14 @@
 //!     println!("Blocked: {:?}", result.violation);
 //! }
 //!
-//! // Record successful call for velocity tracking
-//! inspector.record_call(&ctx).await;
+//! // Record successful call for velocity tracking (at its estimate)
+//! inspector.record_call(&ctx, None).await;
+//!
+//! // Or inspect and record in one call
+//! let result = inspector.inspect_and_record(&ctx).await;
+//!
+//! // Once the call completes, settle it with its actual cost
+//! inspector.record_call(&ctx, Some(12)).await;
 //!
 //! // Cleanup when run completes
 //! inspector.clear_run(&run_id.to_string()).await;
@@ -59,12 +68,19 @@
 pub mod config;
 pub mod exfiltration;
 pub mod inspector;
+pub mod output;
 pub mod patterns;
+mod registry;
+pub mod tenants;
 pub mod velocity;
 
 // Re-export main types for convenience
-pub use config::{AirlockConfig, AirlockMode, ExfiltrationConfig, RceConfig, VelocityConfig};
+pub use config::{
+    AirlockConfig, AirlockMode, ExfiltrationConfig, OutputConfig, RceConfig, VelocityConfig,
+};
 pub use inspector::{
     AirlockInspector, AirlockResult, AirlockViolation, InspectionContext, RiskLevel, ViolationType,
 };
-pub use velocity::VelocityStats;
+pub use registry::AirlockRegistry;
+pub use tenants::TenantAirlockCache;
+pub use velocity::{VelocityCompaction, VelocityStats};
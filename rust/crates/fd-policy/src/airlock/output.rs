@@ -0,0 +1,111 @@
+//! Tool output content-type guard
+//!
+//! Tools can declare an allowlist of output content types. Results whose
+//! declared `content_type` falls outside it (e.g. `text/html` from a tool
+//! expected to return JSON) are flagged before they reach the model or UI.
+
+use super::config::OutputConfig;
+use super::inspector::{AirlockViolation, RiskLevel, ViolationType};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Risk score for an output content type outside the tool's allowlist
+const DISALLOWED_CONTENT_TYPE_SCORE: u8 = 70;
+
+/// Per-tool output content-type allowlist
+pub struct OutputContentGuard {
+    allowed_content_types: HashMap<String, Vec<String>>,
+}
+
+impl OutputContentGuard {
+    /// Create a new output guard from config
+    pub fn new(config: &OutputConfig) -> Self {
+        Self {
+            allowed_content_types: config
+                .allowed_content_types
+                .iter()
+                .map(|(tool, types)| {
+                    let types = types.iter().map(|t| normalize_content_type(t)).collect();
+                    (tool.clone(), types)
+                })
+                .collect(),
+        }
+    }
+
+    /// Check a tool's declared output content type against its allowlist
+    pub fn check(&self, tool_name: &str, content_type: &str) -> Option<AirlockViolation> {
+        let allowed = self.allowed_content_types.get(tool_name)?;
+        let content_type = normalize_content_type(content_type);
+
+        if allowed.iter().any(|t| t == &content_type) {
+            return None;
+        }
+
+        debug!(
+            tool = %tool_name,
+            content_type = %content_type,
+            "Output content type not on tool allowlist"
+        );
+
+        Some(AirlockViolation {
+            violation_type: ViolationType::DisallowedContentType,
+            risk_score: DISALLOWED_CONTENT_TYPE_SCORE,
+            risk_level: RiskLevel::from_score(DISALLOWED_CONTENT_TYPE_SCORE),
+            details: format!(
+                "Tool '{}' returned content type '{}', allowed: {}",
+                tool_name,
+                content_type,
+                allowed.join(", ")
+            ),
+            trigger: content_type,
+        })
+    }
+}
+
+/// Lowercase a media type and drop its parameters (`; charset=utf-8`)
+fn normalize_content_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard() -> OutputContentGuard {
+        OutputContentGuard::new(&OutputConfig {
+            enabled: true,
+            allowed_content_types: HashMap::from([(
+                "http_get".to_string(),
+                vec!["application/json".to_string()],
+            )]),
+        })
+    }
+
+    #[test]
+    fn test_html_flagged_when_only_json_allowed() {
+        let violation = guard().check("http_get", "text/html").unwrap();
+        assert_eq!(
+            violation.violation_type,
+            ViolationType::DisallowedContentType
+        );
+        assert_eq!(violation.risk_level, RiskLevel::High);
+        assert_eq!(violation.trigger, "text/html");
+    }
+
+    #[test]
+    fn test_allowed_content_type_ignores_case_and_parameters() {
+        assert!(guard()
+            .check("http_get", "Application/JSON; charset=utf-8")
+            .is_none());
+    }
+
+    #[test]
+    fn test_tool_without_allowlist_is_unrestricted() {
+        assert!(guard().check("read_file", "text/html").is_none());
+    }
+}
@@ -6,10 +6,15 @@
 //! - Shell injection (pipes, redirects, command substitution)
 //! - Python injection (__import__, subprocess, os.system)
 //! - Path traversal
+//!
+//! Base64 runs in the payload are decoded and rescanned, up to
+//! `RceConfig::max_base64_depth` nested layers.
 
 use super::config::RceConfig;
-use super::inspector::{AirlockViolation, RiskLevel, ViolationType};
+use super::inspector::{AirlockViolation, RiskLevel, ViolationType, MAX_INSPECTION_DEPTH};
+use base64::{engine::general_purpose::STANDARD, Engine};
 use regex::Regex;
+use std::collections::HashMap;
 use std::sync::OnceLock;
 use tracing::debug;
 
@@ -234,10 +239,57 @@ fn get_builtin_patterns() -> &'static [CompiledPattern] {
     })
 }
 
+/// Compile configured sensitive path prefixes into one matcher
+///
+/// A prefix matches at the start of a string, after a quote, whitespace, `=`
+/// or `(`, so `cat /run/secrets/db` and `open('/run/secrets/db')` both hit.
+fn compile_sensitive_paths(paths: &[String]) -> Option<CompiledPattern> {
+    let alternation = paths
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join("|");
+    if alternation.is_empty() {
+        return None;
+    }
+
+    let regex = Regex::new(&format!(r#"(?i)(?:^|['"\s=(])(?:{})"#, alternation)).ok()?;
+    Some(CompiledPattern {
+        regex,
+        name: "sensitive_path_access",
+        risk_score: 70,
+        description: "Access to configured sensitive path detected",
+    })
+}
+
+/// Runs of base64 long enough to hide a payload
+fn base64_run_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r#"[A-Za-z0-9+/]{16,}={0,2}"#).unwrap())
+}
+
+/// Decode every base64 run in `text` that holds UTF-8 text
+///
+/// Returns `None` when nothing decodes, so binary blobs and identifiers that
+/// merely look like base64 end the rescan.
+fn decode_base64_runs(text: &str) -> Option<String> {
+    let decoded: Vec<String> = base64_run_pattern()
+        .find_iter(text)
+        .filter_map(|run| STANDARD.decode(run.as_str()).ok())
+        .filter_map(|bytes| String::from_utf8(bytes).ok())
+        .collect();
+    (!decoded.is_empty()).then(|| decoded.join("\n"))
+}
+
 /// RCE pattern matcher
 pub struct RcePatternMatcher {
     target_tools: Vec<String>,
+    sensitive_paths: Option<CompiledPattern>,
     custom_patterns: Vec<(Regex, String)>,
+    score_overrides: HashMap<String, u8>,
+    max_base64_depth: usize,
 }
 
 impl RcePatternMatcher {
@@ -251,62 +303,92 @@ impl RcePatternMatcher {
 
         Self {
             target_tools: config.target_tools.clone(),
+            sensitive_paths: compile_sensitive_paths(&config.sensitive_paths),
             custom_patterns,
+            score_overrides: config.pattern_score_overrides.clone(),
+            max_base64_depth: config.max_base64_depth,
         }
     }
 
+    /// Risk score for a built-in pattern, honoring configured overrides
+    fn pattern_score(&self, pattern: &CompiledPattern) -> u8 {
+        self.score_overrides
+            .get(pattern.name)
+            .copied()
+            .unwrap_or(pattern.risk_score)
+            .min(100)
+    }
+
     /// Check if this tool should be inspected
     fn should_inspect(&self, tool_name: &str) -> bool {
         self.target_tools.iter().any(|t| t == tool_name)
     }
 
     /// Extract all text content from JSON for pattern matching
-    fn extract_text_content(value: &serde_json::Value) -> String {
-        match value {
-            serde_json::Value::String(s) => s.clone(),
-            serde_json::Value::Array(arr) => arr
-                .iter()
-                .map(Self::extract_text_content)
-                .collect::<Vec<_>>()
-                .join("\n"),
-            serde_json::Value::Object(obj) => obj
-                .values()
-                .map(Self::extract_text_content)
-                .collect::<Vec<_>>()
-                .join("\n"),
-            _ => String::new(),
-        }
+    ///
+    /// Returns the text and whether nesting past [`MAX_INSPECTION_DEPTH`]
+    /// was left out.
+    fn extract_text_content(value: &serde_json::Value) -> (String, bool) {
+        let mut too_deep = false;
+        let text = Self::collect_text(value, 0, &mut too_deep);
+        (text, too_deep)
     }
 
-    /// Check tool input for RCE patterns
-    pub fn check(
-        &self,
-        tool_name: &str,
-        tool_input: &serde_json::Value,
-    ) -> Option<AirlockViolation> {
-        if !self.should_inspect(tool_name) {
-            return None;
+    fn collect_text(value: &serde_json::Value, depth: usize, too_deep: &mut bool) -> String {
+        let items: Vec<&serde_json::Value> = match value {
+            serde_json::Value::String(s) => return s.clone(),
+            serde_json::Value::Array(arr) => arr.iter().collect(),
+            serde_json::Value::Object(obj) => obj.values().collect(),
+            _ => return String::new(),
+        };
+        if depth >= MAX_INSPECTION_DEPTH {
+            *too_deep = true;
+            return String::new();
         }
+        items
+            .into_iter()
+            .map(|item| Self::collect_text(item, depth + 1, too_deep))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
-        let text = Self::extract_text_content(tool_input);
-        if text.is_empty() {
-            return None;
+    /// Violation details, noting how many base64 layers hid the match
+    fn layer_details(description: &str, base64_depth: usize) -> String {
+        match base64_depth {
+            0 => description.to_string(),
+            1 => format!("{} (inside base64)", description),
+            n => format!("{} (inside {} base64 layers)", description, n),
         }
+    }
 
-        // Check built-in patterns
-        for pattern in get_builtin_patterns() {
-            if pattern.regex.is_match(&text) {
+    /// Match one layer of text against the built-in and custom patterns
+    ///
+    /// `base64_depth` is how many base64 decodes produced the layer.
+    fn match_patterns(
+        &self,
+        tool_name: &str,
+        text: &str,
+        base64_depth: usize,
+    ) -> Option<AirlockViolation> {
+        // Check built-in patterns, then configured sensitive paths
+        for pattern in get_builtin_patterns()
+            .iter()
+            .chain(self.sensitive_paths.as_ref())
+        {
+            if pattern.regex.is_match(text) {
                 debug!(
                     tool = tool_name,
                     pattern = pattern.name,
+                    base64_depth,
                     "RCE pattern detected"
                 );
 
+                let risk_score = self.pattern_score(pattern);
                 return Some(AirlockViolation {
                     violation_type: ViolationType::RcePattern,
-                    risk_score: pattern.risk_score,
-                    risk_level: RiskLevel::from_score(pattern.risk_score),
-                    details: pattern.description.to_string(),
+                    risk_score,
+                    risk_level: RiskLevel::from_score(risk_score),
+                    details: Self::layer_details(pattern.description, base64_depth),
                     trigger: pattern.name.to_string(),
                 });
             }
@@ -314,10 +396,11 @@ impl RcePatternMatcher {
 
         // Check custom patterns
         for (regex, pattern_str) in &self.custom_patterns {
-            if regex.is_match(&text) {
+            if regex.is_match(text) {
                 debug!(
                     tool = tool_name,
                     pattern = pattern_str,
+                    base64_depth,
                     "Custom RCE pattern detected"
                 );
 
@@ -325,7 +408,10 @@ impl RcePatternMatcher {
                     violation_type: ViolationType::RcePattern,
                     risk_score: 80, // Default score for custom patterns
                     risk_level: RiskLevel::High,
-                    details: format!("Custom pattern match: {}", pattern_str),
+                    details: Self::layer_details(
+                        &format!("Custom pattern match: {}", pattern_str),
+                        base64_depth,
+                    ),
                     trigger: format!("custom:{}", pattern_str),
                 });
             }
@@ -333,6 +419,61 @@ impl RcePatternMatcher {
 
         None
     }
+
+    /// Check tool input for RCE patterns
+    pub fn check(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> Option<AirlockViolation> {
+        if !self.should_inspect(tool_name) {
+            return None;
+        }
+
+        let (text, too_deep) = Self::extract_text_content(tool_input);
+        if text.is_empty() {
+            return too_deep.then(AirlockViolation::depth_limit_exceeded);
+        }
+
+        // Scan the payload, then each decoded base64 layer beneath it
+        let mut layer = text;
+        let mut base64_truncated = false;
+        for base64_depth in 0..=self.max_base64_depth {
+            if let Some(violation) = self.match_patterns(tool_name, &layer, base64_depth) {
+                return Some(violation);
+            }
+            let Some(decoded) = decode_base64_runs(&layer) else {
+                break;
+            };
+            if base64_depth == self.max_base64_depth {
+                base64_truncated = true;
+                break;
+            }
+            layer = decoded;
+        }
+
+        if base64_truncated {
+            debug!(
+                tool = tool_name,
+                max_base64_depth = self.max_base64_depth,
+                "Base64 depth limit reached, deeper layers not inspected"
+            );
+            return Some(AirlockViolation::base64_depth_limit_exceeded(
+                self.max_base64_depth,
+            ));
+        }
+
+        if too_deep {
+            debug!(
+                tool = tool_name,
+                max_depth = MAX_INSPECTION_DEPTH,
+                "Depth limit reached, deeper content not inspected"
+            );
+            return Some(AirlockViolation::depth_limit_exceeded());
+        }
+
+        None
+    }
 }
 
 #[cfg(test)]
@@ -511,4 +652,126 @@ mod tests {
         let result = matcher.check("python_repl", &input);
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_pattern_score_override_applies_only_to_named_pattern() {
+        let config = RceConfig {
+            pattern_score_overrides: HashMap::from([("template_injection".to_string(), 90)]),
+            ..RceConfig::default()
+        };
+        let matcher = RcePatternMatcher::new(&config);
+
+        let template = matcher
+            .check(
+                "python_repl",
+                &serde_json::json!({"code": "{{ user.name }}"}),
+            )
+            .unwrap();
+        assert_eq!(template.trigger, "template_injection");
+        assert_eq!(template.risk_score, 90);
+        assert_eq!(template.risk_level, RiskLevel::Critical);
+
+        let interpolation = matcher
+            .check("python_repl", &serde_json::json!({"code": "echo ${HOME}"}))
+            .unwrap();
+        assert_eq!(interpolation.trigger, "string_interpolation");
+        assert_eq!(interpolation.risk_score, 55);
+        assert_eq!(interpolation.risk_level, RiskLevel::Medium);
+    }
+
+    #[test]
+    fn test_configured_sensitive_paths_augment_builtin_list() {
+        let secrets = serde_json::json!({"command": "cat /run/secrets/db_password"});
+        let etc = serde_json::json!({"code": "open('/etc/shadow')"});
+
+        let default = create_matcher();
+        assert!(default.check("bash", &secrets).is_none());
+        assert_eq!(
+            default.check("python_repl", &etc).unwrap().trigger,
+            "sensitive_path_access"
+        );
+
+        let config = RceConfig {
+            sensitive_paths: vec!["/run/secrets".to_string()],
+            ..RceConfig::default()
+        };
+        let matcher = RcePatternMatcher::new(&config);
+        let violation = matcher.check("bash", &secrets).unwrap();
+        assert_eq!(violation.trigger, "sensitive_path_access");
+        assert_eq!(violation.risk_score, 70);
+        assert_eq!(
+            matcher.check("python_repl", &etc).unwrap().trigger,
+            "sensitive_path_access"
+        );
+    }
+
+    fn wrap_base64(payload: &str, layers: usize) -> String {
+        (0..layers).fold(payload.to_string(), |text, _| STANDARD.encode(text))
+    }
+
+    #[test]
+    fn test_double_base64_eval_is_caught_at_depth_two() {
+        let matcher = create_matcher();
+        let input = serde_json::json!({"code": wrap_base64("eval(payload)", 2)});
+
+        let violation = matcher.check("python_repl", &input).unwrap();
+        assert_eq!(violation.violation_type, ViolationType::RcePattern);
+        assert_eq!(violation.trigger, "python_eval");
+        assert!(violation.details.ends_with("(inside 2 base64 layers)"));
+    }
+
+    #[test]
+    fn test_triple_base64_eval_flags_depth_limit_at_depth_two() {
+        let matcher = create_matcher();
+        let input = serde_json::json!({"code": wrap_base64("eval(payload)", 3)});
+
+        let violation = matcher.check("python_repl", &input).unwrap();
+        assert_eq!(violation.violation_type, ViolationType::DepthLimitExceeded);
+        assert!(!violation.violation_type.is_advisory());
+        assert_eq!(violation.trigger, "base64_depth_limit:2");
+
+        // A deeper limit reaches the payload
+        let deeper = RcePatternMatcher::new(&RceConfig {
+            max_base64_depth: 3,
+            ..RceConfig::default()
+        });
+        assert_eq!(
+            deeper.check("python_repl", &input).unwrap().trigger,
+            "python_eval"
+        );
+    }
+
+    #[test]
+    fn test_template_injection_default_score() {
+        let violation = create_matcher()
+            .check(
+                "python_repl",
+                &serde_json::json!({"code": "{{ user.name }}"}),
+            )
+            .unwrap();
+        assert_eq!(violation.trigger, "template_injection");
+        assert_eq!(violation.risk_level, RiskLevel::High);
+    }
+
+    #[test]
+    fn test_deeply_nested_input_is_cut_off_and_flagged() {
+        let mut input = serde_json::json!({"code": "eval(payload)"});
+        for _ in 0..200 {
+            input = serde_json::json!({"nested": input, "note": "harmless"});
+        }
+
+        let (text, too_deep) = RcePatternMatcher::extract_text_content(&input);
+        assert!(too_deep);
+        assert!(!text.contains("eval"));
+
+        let violation = create_matcher().check("python_repl", &input).unwrap();
+        assert_eq!(violation.violation_type, ViolationType::DepthLimitExceeded);
+        assert_eq!(violation.risk_level, RiskLevel::Low);
+        assert!(!violation.violation_type.is_advisory());
+
+        // A pattern within reach still wins over the depth note
+        input["code"] = serde_json::json!("exec(payload)");
+        let violation = create_matcher().check("python_repl", &input).unwrap();
+        assert_eq!(violation.violation_type, ViolationType::RcePattern);
+    }
 }
@@ -0,0 +1,201 @@
+//! Shared Airlock inspectors keyed by configuration
+//!
+//! Inspectors carry per-run velocity state, so every request inspecting a
+//! run has to reach the same inspector. The registry hands out one
+//! inspector per distinct configuration: concurrent lookups for the same
+//! config get the same instance, and reloading an unchanged config keeps
+//! its velocity state. All inspectors share the base inspector's
+//! concurrency limit.
+
+use super::config::AirlockConfig;
+use super::inspector::AirlockInspector;
+use super::velocity::VelocityCompaction;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+/// Inspectors shared by every tenant with the same configuration
+pub struct AirlockRegistry {
+    /// Inspector built from the gateway's own configuration
+    base: Arc<AirlockInspector>,
+    /// Inspectors keyed by their canonical configuration
+    inspectors: RwLock<HashMap<String, Arc<AirlockInspector>>>,
+}
+
+impl AirlockRegistry {
+    /// Create a registry seeded with `base`
+    pub fn new(base: Arc<AirlockInspector>) -> Self {
+        let mut inspectors = HashMap::new();
+        inspectors.insert(Self::config_key(base.config()), Arc::clone(&base));
+        Self {
+            base,
+            inspectors: RwLock::new(inspectors),
+        }
+    }
+
+    /// The inspector the registry was seeded with
+    pub fn base(&self) -> Arc<AirlockInspector> {
+        Arc::clone(&self.base)
+    }
+
+    /// Canonical form of a configuration
+    ///
+    /// Going through `serde_json::Value` sorts object keys, so configs with
+    /// equal maps produce equal keys regardless of hash order.
+    fn config_key(config: &AirlockConfig) -> String {
+        serde_json::to_value(config)
+            .expect("airlock config always serializes")
+            .to_string()
+    }
+
+    /// Get the inspector for `config`, creating it on first use
+    pub async fn get_or_create(&self, config: AirlockConfig) -> Arc<AirlockInspector> {
+        let key = Self::config_key(&config);
+        if let Some(inspector) = self.inspectors.read().await.get(&key) {
+            return Arc::clone(inspector);
+        }
+
+        let mut inspectors = self.inspectors.write().await;
+        let inspector = inspectors.entry(key).or_insert_with(|| {
+            debug!("Registered airlock inspector for new configuration");
+            Arc::new(AirlockInspector::new(config).sharing_inspection_limit(&self.base))
+        });
+        Arc::clone(inspector)
+    }
+
+    /// Free a finished run's velocity data in every inspector
+    ///
+    /// The inspectors themselves stay registered.
+    pub async fn clear_run(&self, run_id: &str) {
+        for inspector in self.inspectors.read().await.values() {
+            inspector.clear_run(run_id).await;
+        }
+    }
+
+    /// Compact velocity records, then drop inspectors nothing else holds
+    ///
+    /// An inspector is only dropped once it tracks no runs, so state for a
+    /// run in flight is never lost. The base inspector is always kept.
+    pub async fn compact_velocity(&self) -> VelocityCompaction {
+        let inspectors: Vec<_> = self.inspectors.read().await.values().cloned().collect();
+        let mut reclaimed = VelocityCompaction::default();
+        for inspector in &inspectors {
+            reclaimed += inspector.compact_velocity().await;
+        }
+        drop(inspectors);
+
+        let mut unused = Vec::new();
+        for (key, inspector) in self.inspectors.read().await.iter() {
+            let idle = inspector.velocity_stats().await.tracked_runs == 0;
+            if idle && Arc::strong_count(inspector) == 1 && !Arc::ptr_eq(inspector, &self.base) {
+                unused.push(key.clone());
+            }
+        }
+        if !unused.is_empty() {
+            let mut inspectors = self.inspectors.write().await;
+            for key in unused {
+                if inspectors
+                    .get(&key)
+                    .is_some_and(|inspector| Arc::strong_count(inspector) == 1)
+                {
+                    inspectors.remove(&key);
+                }
+            }
+        }
+        reclaimed
+    }
+
+    /// Number of registered inspectors, including the base
+    pub async fn len(&self) -> usize {
+        self.inspectors.read().await.len()
+    }
+
+    /// Whether the registry holds no inspectors
+    pub async fn is_empty(&self) -> bool {
+        self.inspectors.read().await.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::airlock::config::{AirlockMode, VelocityConfig};
+    use crate::airlock::inspector::{InspectionContext, ViolationType};
+    use fd_core::RunId;
+
+    fn looping_config() -> AirlockConfig {
+        AirlockConfig {
+            mode: AirlockMode::Enforce,
+            velocity: VelocityConfig {
+                enabled: true,
+                loop_threshold: 3,
+                ..VelocityConfig::default()
+            },
+            ..AirlockConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_for_same_config_share_velocity_state() {
+        let registry =
+            AirlockRegistry::new(Arc::new(AirlockInspector::new(AirlockConfig::default())));
+        let ctx = InspectionContext {
+            run_id: RunId::new(),
+            tool_name: "search".to_string(),
+            tool_input: serde_json::json!({"query": "same"}),
+            estimated_cost_cents: None,
+        };
+
+        // Two concurrent requests resolve the tenant's config independently
+        let (first, second) = tokio::join!(
+            registry.get_or_create(looping_config()),
+            registry.get_or_create(looping_config())
+        );
+        assert!(Arc::ptr_eq(&first, &second));
+
+        // The loop spans both requests
+        for inspector in [&first, &second, &first] {
+            assert!(inspector.inspect_and_record(&ctx).await.allowed);
+        }
+        let result = second.inspect(&ctx).await;
+        assert!(!result.allowed);
+        assert_eq!(
+            result.violation.map(|v| v.violation_type),
+            Some(ViolationType::LoopDetection)
+        );
+
+        // Clearing the run frees its records but keeps the inspector
+        registry.clear_run(&ctx.run_id.to_string()).await;
+        assert!(second.inspect(&ctx).await.allowed);
+        assert!(Arc::ptr_eq(
+            &registry.get_or_create(looping_config()).await,
+            &first
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_compaction_drops_only_unused_inspectors() {
+        let registry =
+            AirlockRegistry::new(Arc::new(AirlockInspector::new(AirlockConfig::default())));
+        let held = registry.get_or_create(looping_config()).await;
+        let released = registry
+            .get_or_create(AirlockConfig {
+                velocity: VelocityConfig {
+                    loop_threshold: 5,
+                    ..looping_config().velocity
+                },
+                ..looping_config()
+            })
+            .await;
+        drop(released);
+        assert_eq!(registry.len().await, 3);
+
+        registry.compact_velocity().await;
+        assert_eq!(registry.len().await, 2);
+        assert!(Arc::ptr_eq(
+            &registry.get_or_create(looping_config()).await,
+            &held
+        ));
+    }
+}
@@ -19,6 +19,8 @@ struct CallRecord {
     tool_name: String,
     input_hash: u64,
     cost_cents: u64,
+    /// Whether `cost_cents` is the actual cost rather than an estimate
+    settled: bool,
     timestamp: Instant,
 }
 
@@ -156,8 +158,18 @@ impl VelocityTracker {
         None
     }
 
-    /// Record a completed call for future velocity checks
-    pub async fn record(&self, ctx: &InspectionContext) {
+    /// Record a call for future velocity checks
+    ///
+    /// Without `actual_cost_cents` the call is recorded provisionally at its
+    /// estimated cost. Passing the actual cost (once the step completes)
+    /// settles the most recent provisional record for the same tool and
+    /// input, correcting the window sum; if there is none, a settled record
+    /// is added.
+    ///
+    /// Calls cheaper than `min_cost_to_track` are dropped, unless
+    /// `track_cheap_calls_for_loops` keeps them (at zero cost) for loop
+    /// detection.
+    pub async fn record(&self, ctx: &InspectionContext, actual_cost_cents: Option<u64>) {
         let run_key = ctx.run_id.to_string();
         let input_hash = Self::hash_input(&ctx.tool_input);
         let window = Duration::from_secs(self.config.window_seconds);
@@ -169,11 +181,47 @@ impl VelocityTracker {
         // Cleanup old records (keep 2x window for safety)
         tracker.cleanup(window * 2);
 
+        if let Some(actual) = actual_cost_cents {
+            let provisional =
+                tracker.calls.iter_mut().rev().find(|c| {
+                    !c.settled && c.tool_name == ctx.tool_name && c.input_hash == input_hash
+                });
+            if let Some(record) = provisional {
+                debug!(
+                    run_id = %ctx.run_id,
+                    tool = %ctx.tool_name,
+                    estimated = record.cost_cents,
+                    actual,
+                    "Settled velocity record with actual cost"
+                );
+                record.cost_cents = actual;
+                record.settled = true;
+                return;
+            }
+        }
+
+        let cost_cents = actual_cost_cents.unwrap_or(ctx.estimated_cost_cents.unwrap_or(0));
+        if cost_cents < self.config.min_cost_to_track {
+            if !self.config.track_cheap_calls_for_loops {
+                return;
+            }
+            // Kept for loop detection only; the cost never enters the window sum
+            tracker.calls.push(CallRecord {
+                tool_name: ctx.tool_name.clone(),
+                input_hash,
+                cost_cents: 0,
+                settled: true,
+                timestamp: Instant::now(),
+            });
+            return;
+        }
+
         // Add new record
         tracker.calls.push(CallRecord {
             tool_name: ctx.tool_name.clone(),
             input_hash,
-            cost_cents: ctx.estimated_cost_cents.unwrap_or(0),
+            cost_cents,
+            settled: actual_cost_cents.is_some(),
             timestamp: Instant::now(),
         });
     }
@@ -192,6 +240,36 @@ impl VelocityTracker {
             total_records: runs.values().map(|t| t.calls.len()).sum(),
         }
     }
+
+    /// Drop records older than twice the window, and runs left with none
+    ///
+    /// `record` only prunes the run it is recording for, so runs that stop
+    /// making calls keep their records until this runs.
+    pub async fn compact(&self) -> VelocityCompaction {
+        self.compact_at(Instant::now()).await
+    }
+
+    /// [`compact`](Self::compact) as of `now`
+    pub async fn compact_at(&self, now: Instant) -> VelocityCompaction {
+        let retention = Duration::from_secs(self.config.window_seconds) * 2;
+        let mut runs = self.runs.write().await;
+        let mut reclaimed = VelocityCompaction::default();
+
+        runs.retain(|_, tracker| {
+            let before = tracker.calls.len();
+            tracker
+                .calls
+                .retain(|c| now.saturating_duration_since(c.timestamp) < retention);
+            reclaimed.records_removed += before - tracker.calls.len();
+
+            let keep = !tracker.calls.is_empty();
+            if !keep {
+                reclaimed.runs_removed += 1;
+            }
+            keep
+        });
+        reclaimed
+    }
 }
 
 /// Statistics about velocity tracker state
@@ -201,6 +279,20 @@ pub struct VelocityStats {
     pub total_records: usize,
 }
 
+/// What a velocity compaction pass reclaimed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VelocityCompaction {
+    pub records_removed: usize,
+    pub runs_removed: usize,
+}
+
+impl std::ops::AddAssign for VelocityCompaction {
+    fn add_assign(&mut self, other: Self) {
+        self.records_removed += other.records_removed;
+        self.runs_removed += other.runs_removed;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,6 +304,7 @@ mod tests {
             max_cost_cents: 100, // $1.00
             window_seconds: 10,
             loop_threshold: 3,
+            ..Default::default()
         })
     }
 
@@ -235,7 +328,7 @@ mod tests {
         assert!(result.is_none());
 
         // Record the call
-        tracker.record(&ctx).await;
+        tracker.record(&ctx, None).await;
 
         // Second call with low cost should still pass
         let ctx2 = create_context(&run_id, "test_tool", Some(40));
@@ -251,7 +344,7 @@ mod tests {
         // Record several expensive calls
         for _ in 0..3 {
             let ctx = create_context(&run_id, "expensive_tool", Some(40));
-            tracker.record(&ctx).await;
+            tracker.record(&ctx, None).await;
         }
 
         // Next call should trigger velocity limit (40*3 = 120, plus new 40 = 160 > 100)
@@ -264,6 +357,108 @@ mod tests {
         assert!(violation.risk_score >= 80);
     }
 
+    #[tokio::test]
+    async fn test_actual_cost_corrects_window_sum() {
+        let tracker = create_tracker();
+        let run_id = RunId::new();
+
+        // Provisionally recorded at its 30 cent estimate
+        let ctx = create_context(&run_id, "expensive_tool", Some(30));
+        tracker.record(&ctx, None).await;
+
+        // A 20 cent call fits under the estimate-based sum (30 + 20)
+        let next = InspectionContext {
+            tool_input: serde_json::json!({"next": "call"}),
+            ..create_context(&run_id, "expensive_tool", Some(20))
+        };
+        assert!(tracker.check(&next).await.is_none());
+
+        // The call actually cost 90 cents; the window now sums to 90
+        tracker.record(&ctx, Some(90)).await;
+        assert_eq!(tracker.stats().await.total_records, 1);
+
+        let violation = tracker.check(&next).await.unwrap();
+        assert_eq!(violation.violation_type, ViolationType::VelocityBreach);
+        assert!(violation.details.contains("$1.10"));
+    }
+
+    #[tokio::test]
+    async fn test_actual_cost_without_provisional_record_is_added() {
+        let tracker = create_tracker();
+        let run_id = RunId::new();
+
+        let ctx = create_context(&run_id, "tool", None);
+        tracker.record(&ctx, Some(95)).await;
+
+        // A second actual cost for the same call is a new record
+        tracker.record(&ctx, Some(1)).await;
+        assert_eq!(tracker.stats().await.total_records, 2);
+
+        let result = tracker
+            .check(&create_context(&run_id, "other_tool", Some(5)))
+            .await;
+        assert!(result.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_min_cost_to_track_skips_cheap_calls() {
+        let tracker = VelocityTracker::new(VelocityConfig {
+            max_cost_cents: 100,
+            min_cost_to_track: 10,
+            ..Default::default()
+        });
+        let run_id = RunId::new();
+
+        // Twenty 9-cent calls would breach the limit if they were summed
+        for i in 0..20 {
+            let ctx = InspectionContext {
+                tool_input: serde_json::json!({"i": i}),
+                ..create_context(&run_id, "cheap_tool", Some(9))
+            };
+            tracker.record(&ctx, None).await;
+        }
+        assert_eq!(tracker.stats().await.total_records, 0);
+
+        // Calls at or above the threshold are stored and summed
+        for i in 0..2 {
+            let ctx = InspectionContext {
+                tool_input: serde_json::json!({"i": i}),
+                ..create_context(&run_id, "pricey_tool", Some(40))
+            };
+            tracker.record(&ctx, None).await;
+        }
+        assert_eq!(tracker.stats().await.total_records, 2);
+
+        let next = create_context(&run_id, "pricey_tool", Some(30));
+        let violation = tracker.check(&next).await.unwrap();
+        assert_eq!(violation.violation_type, ViolationType::VelocityBreach);
+        assert!(violation.details.contains("$1.10"));
+    }
+
+    #[tokio::test]
+    async fn test_cheap_calls_can_still_count_toward_loops() {
+        let tracker = VelocityTracker::new(VelocityConfig {
+            max_cost_cents: 100,
+            loop_threshold: 3,
+            min_cost_to_track: 10,
+            track_cheap_calls_for_loops: true,
+            ..Default::default()
+        });
+        let run_id = RunId::new();
+
+        let ctx = create_context(&run_id, "cheap_tool", Some(5));
+        for _ in 0..3 {
+            tracker.record(&ctx, None).await;
+        }
+
+        let violation = tracker.check(&ctx).await.unwrap();
+        assert_eq!(violation.violation_type, ViolationType::LoopDetection);
+
+        // The recorded calls contribute nothing to the spending window
+        let other = create_context(&run_id, "other_tool", Some(100));
+        assert!(tracker.check(&other).await.is_none());
+    }
+
     #[tokio::test]
     async fn test_loop_detection() {
         let tracker = create_tracker();
@@ -279,7 +474,7 @@ mod tests {
 
         // Record 3 identical calls (at threshold)
         for _ in 0..3 {
-            tracker.record(&ctx).await;
+            tracker.record(&ctx, None).await;
         }
 
         // 4th identical call should trigger loop detection
@@ -303,7 +498,7 @@ mod tests {
                 tool_input: serde_json::json!({"iteration": i}),
                 estimated_cost_cents: Some(1),
             };
-            tracker.record(&ctx).await;
+            tracker.record(&ctx, None).await;
 
             let result = tracker.check(&ctx).await;
             assert!(result.is_none());
@@ -317,8 +512,8 @@ mod tests {
 
         // Record some calls
         let ctx = create_context(&run_id, "tool", Some(50));
-        tracker.record(&ctx).await;
-        tracker.record(&ctx).await;
+        tracker.record(&ctx, None).await;
+        tracker.record(&ctx, None).await;
 
         // Verify data exists
         let stats = tracker.stats().await;
@@ -341,7 +536,7 @@ mod tests {
         // Record expensive calls for run1
         for _ in 0..3 {
             let ctx = create_context(&run1, "tool", Some(40));
-            tracker.record(&ctx).await;
+            tracker.record(&ctx, None).await;
         }
 
         // Run2 should start fresh and not be affected by run1's costs
@@ -367,4 +562,50 @@ mod tests {
 
         assert_ne!(hash1, hash3);
     }
+
+    #[tokio::test]
+    async fn test_compact_drops_expired_records_and_idle_runs() {
+        // window_seconds = 10, so records are kept for 20 seconds
+        let tracker = create_tracker();
+        let stale_run = RunId::new();
+        let active_run = RunId::new();
+        let start = Instant::now();
+        let record = |offset_secs: u64| CallRecord {
+            tool_name: "tool".to_string(),
+            input_hash: 0,
+            cost_cents: 10,
+            settled: true,
+            timestamp: start + Duration::from_secs(offset_secs),
+        };
+
+        {
+            let mut runs = tracker.runs.write().await;
+            runs.entry(stale_run.to_string())
+                .or_default()
+                .calls
+                .extend([record(0), record(5)]);
+            runs.entry(active_run.to_string())
+                .or_default()
+                .calls
+                .extend([record(0), record(25), record(30)]);
+        }
+
+        let reclaimed = tracker.compact_at(start + Duration::from_secs(40)).await;
+        assert_eq!(
+            reclaimed,
+            VelocityCompaction {
+                records_removed: 3,
+                runs_removed: 1,
+            }
+        );
+
+        let runs = tracker.runs.read().await;
+        assert!(!runs.contains_key(&stale_run.to_string()));
+        assert_eq!(runs[&active_run.to_string()].calls.len(), 2);
+        drop(runs);
+
+        // Nothing left to reclaim until the clock moves on
+        let again = tracker.compact_at(start + Duration::from_secs(40)).await;
+        assert_eq!(again, VelocityCompaction::default());
+    }
 }
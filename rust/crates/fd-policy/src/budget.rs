@@ -124,6 +124,38 @@ pub enum BudgetExceeded {
     Cost { used_cents: u64, limit_cents: u64 },
 }
 
+/// Machine-readable summary of which budget dimension was exceeded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetKillDetail {
+    /// `input_tokens`, `output_tokens`, `total_tokens`, `tool_calls`,
+    /// `wall_time_ms` or `cost_cents`
+    pub metric: String,
+    pub used: u64,
+    pub limit: u64,
+}
+
+impl BudgetExceeded {
+    /// The exceeded dimension with its usage and limit
+    pub fn detail(&self) -> BudgetKillDetail {
+        let (metric, used, limit) = match *self {
+            BudgetExceeded::InputTokens { used, limit } => ("input_tokens", used, limit),
+            BudgetExceeded::OutputTokens { used, limit } => ("output_tokens", used, limit),
+            BudgetExceeded::TotalTokens { used, limit } => ("total_tokens", used, limit),
+            BudgetExceeded::ToolCalls { used, limit } => ("tool_calls", used.into(), limit.into()),
+            BudgetExceeded::WallTime { used_ms, limit_ms } => ("wall_time_ms", used_ms, limit_ms),
+            BudgetExceeded::Cost {
+                used_cents,
+                limit_cents,
+            } => ("cost_cents", used_cents, limit_cents),
+        };
+        BudgetKillDetail {
+            metric: metric.to_string(),
+            used,
+            limit,
+        }
+    }
+}
+
 impl std::fmt::Display for BudgetExceeded {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
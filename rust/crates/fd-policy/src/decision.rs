@@ -1,5 +1,6 @@
 //! Policy decisions
 
+use crate::airlock::{AirlockResult, AirlockViolation};
 use fd_core::{PolicyDecisionId, PolicyRuleId};
 use serde::{Deserialize, Serialize};
 
@@ -40,6 +41,116 @@ pub enum PolicyDecisionKind {
     AllowWithWarning,
 }
 
+impl PolicyDecisionKind {
+    /// Rank used when merging decisions: higher is more restrictive
+    fn restrictiveness(self) -> u8 {
+        match self {
+            Self::Allow => 0,
+            Self::AllowWithWarning => 1,
+            Self::RequiresApproval => 2,
+            Self::Deny => 3,
+        }
+    }
+}
+
+/// Which check governed a combined decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionSource {
+    /// Kill switch or tool allowlist
+    Policy,
+    /// Airlock runtime inspection
+    Airlock,
+}
+
+/// Outcome of the policy checks and Airlock inspection for one tool call
+///
+/// Precedence: a policy deny, then an Airlock block, then a policy approval
+/// requirement. An allowed call with a logged (shadow or advisory) violation
+/// is `AllowWithWarning`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CombinedDecision {
+    /// Final outcome
+    pub kind: PolicyDecisionKind,
+    /// Explanation from the check that governed the outcome
+    pub reason: String,
+    /// The check that governed the outcome
+    pub source: DecisionSource,
+    /// Kill switch / allowlist decision
+    pub policy: PolicyDecision,
+    /// Airlock inspection result
+    pub airlock: AirlockResult,
+}
+
+impl CombinedDecision {
+    /// Combine a policy decision with an Airlock result
+    pub fn new(policy: PolicyDecision, airlock: AirlockResult) -> Self {
+        let airlock_reason = || {
+            airlock
+                .violation
+                .as_ref()
+                .map(|v| v.details.clone())
+                .unwrap_or_else(|| "Airlock security violation".to_string())
+        };
+
+        let (kind, source, reason) = if policy.is_denied() {
+            (
+                PolicyDecisionKind::Deny,
+                DecisionSource::Policy,
+                policy.reason.clone(),
+            )
+        } else if !airlock.allowed {
+            (
+                PolicyDecisionKind::Deny,
+                DecisionSource::Airlock,
+                airlock_reason(),
+            )
+        } else if policy.needs_approval() {
+            (
+                PolicyDecisionKind::RequiresApproval,
+                DecisionSource::Policy,
+                policy.reason.clone(),
+            )
+        } else if airlock.violation.is_some() {
+            (
+                PolicyDecisionKind::AllowWithWarning,
+                DecisionSource::Airlock,
+                airlock_reason(),
+            )
+        } else {
+            (policy.kind, DecisionSource::Policy, policy.reason.clone())
+        };
+
+        Self {
+            kind,
+            reason,
+            source,
+            policy,
+            airlock,
+        }
+    }
+
+    /// Any violation Airlock detected, whether or not it blocked the call
+    pub fn violation(&self) -> Option<&AirlockViolation> {
+        self.airlock.violation.as_ref()
+    }
+
+    pub fn is_allowed(&self) -> bool {
+        matches!(
+            self.kind,
+            PolicyDecisionKind::Allow | PolicyDecisionKind::AllowWithWarning
+        )
+    }
+
+    pub fn is_denied(&self) -> bool {
+        matches!(self.kind, PolicyDecisionKind::Deny)
+    }
+
+    pub fn needs_approval(&self) -> bool {
+        matches!(self.kind, PolicyDecisionKind::RequiresApproval)
+    }
+}
+
 impl PolicyDecision {
     pub fn allow(reason: impl Into<String>) -> Self {
         Self {
@@ -61,6 +172,14 @@ impl PolicyDecision {
         }
     }
 
+    /// A deny from the global kill switch, which nothing overrides
+    pub fn kill_switch_deny(reason: impl Into<String>) -> Self {
+        Self {
+            metadata: serde_json::json!({ "kill_switch": true }),
+            ..Self::deny(reason)
+        }
+    }
+
     pub fn requires_approval(reason: impl Into<String>) -> Self {
         Self {
             id: PolicyDecisionId::new(),
@@ -90,4 +209,65 @@ impl PolicyDecision {
     pub fn needs_approval(&self) -> bool {
         matches!(self.kind, PolicyDecisionKind::RequiresApproval)
     }
+
+    /// Whether this is a deny from the global kill switch
+    pub fn is_kill_switch_deny(&self) -> bool {
+        self.is_denied() && self.metadata["kill_switch"] == serde_json::Value::Bool(true)
+    }
+
+    /// Combine independent checks into the most restrictive decision
+    ///
+    /// Deny beats requires-approval, which beats allow-with-warning, which
+    /// beats allow. The governing decision is returned whole, so its reason
+    /// and rule are kept; on a tie the first one wins. With no checks the
+    /// result is an allow.
+    pub fn merge(decisions: &[PolicyDecision]) -> PolicyDecision {
+        decisions
+            .iter()
+            .rev()
+            .max_by_key(|decision| decision.kind.restrictiveness())
+            .cloned()
+            .unwrap_or_else(|| Self::allow("No policy checks applied"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_prefers_deny_and_keeps_its_reason() {
+        let merged = PolicyDecision::merge(&[
+            PolicyDecision::allow("Tool is allowlisted"),
+            PolicyDecision::requires_approval("Tool requires approval"),
+            PolicyDecision::deny("Budget exceeded"),
+        ]);
+
+        assert_eq!(merged.kind, PolicyDecisionKind::Deny);
+        assert_eq!(merged.reason, "Budget exceeded");
+    }
+
+    #[test]
+    fn test_merge_prefers_approval_over_allow() {
+        let approval = PolicyDecision::requires_approval("Tool requires approval");
+        let merged = PolicyDecision::merge(&[
+            PolicyDecision::allow("Tool is allowlisted"),
+            approval.clone(),
+        ]);
+
+        assert_eq!(merged.kind, PolicyDecisionKind::RequiresApproval);
+        assert_eq!(merged.reason, approval.reason);
+        assert_eq!(merged.id, approval.id);
+    }
+
+    #[test]
+    fn test_merge_breaks_ties_by_order_and_allows_when_empty() {
+        let merged = PolicyDecision::merge(&[
+            PolicyDecision::deny("Kill switch active"),
+            PolicyDecision::deny("Budget exceeded"),
+        ]);
+        assert_eq!(merged.reason, "Kill switch active");
+
+        assert!(PolicyDecision::merge(&[]).is_allowed());
+    }
 }
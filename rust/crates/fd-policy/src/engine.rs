@@ -1,15 +1,33 @@
 //! Policy engine implementation
 
-use crate::budget::{Budget, BudgetUsage};
-use crate::decision::PolicyDecision;
-use crate::rules::{ToolAllowlist, ToolAllowlistResult};
+use crate::airlock::{AirlockInspector, InspectionContext};
+use crate::budget::{Budget, BudgetExceeded, BudgetUsage};
+use crate::decision::{CombinedDecision, PolicyDecision};
+use crate::kill_switch::ToolKillSwitch;
+use crate::rules::{ToolAllowlist, ToolAllowlistResult, ToolRuleMatch};
+use serde::Serialize;
+use std::sync::Arc;
 use tracing::instrument;
 
+/// Breakdown of how a tool call would be evaluated (for policy debugging)
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyExplanation {
+    /// Tool that was explained
+    pub tool_name: String,
+    /// Every allowlist entry that matched, in precedence order
+    pub matches: Vec<ToolRuleMatch>,
+    /// The decision `evaluate_tool_call` would return
+    pub decision: PolicyDecision,
+    /// Budget applied when a run does not override it
+    pub effective_budget: Budget,
+}
+
 /// The policy engine evaluates actions against configured rules
 #[derive(Default)]
 pub struct PolicyEngine {
     tool_allowlist: ToolAllowlist,
     default_budget: Budget,
+    kill_switch: Arc<ToolKillSwitch>,
 }
 
 impl PolicyEngine {
@@ -17,12 +35,40 @@ impl PolicyEngine {
         Self {
             tool_allowlist,
             default_budget,
+            kill_switch: Arc::default(),
         }
     }
 
+    /// Use a shared kill switch (e.g. one refreshed from Redis)
+    pub fn with_kill_switch(mut self, kill_switch: Arc<ToolKillSwitch>) -> Self {
+        self.kill_switch = kill_switch;
+        self
+    }
+
+    /// Global kill switch consulted before the allowlist
+    pub fn kill_switch(&self) -> &Arc<ToolKillSwitch> {
+        &self.kill_switch
+    }
+
+    /// Engine seeded with [`ToolAllowlist::safe_defaults`] and the default budget
+    ///
+    /// A starting point for new deployments: common read-only tools are
+    /// allowed, common write tools need approval, and unknown tools are
+    /// still denied.
+    pub fn with_safe_defaults() -> Self {
+        Self::new(ToolAllowlist::safe_defaults(), Budget::default())
+    }
+
     /// Evaluate whether a tool call is allowed
     #[instrument(skip(self))]
     pub fn evaluate_tool_call(&self, tool_name: &str) -> PolicyDecision {
+        if self.kill_switch.is_killed(tool_name) {
+            return PolicyDecision::kill_switch_deny(format!(
+                "tool '{}' is disabled by the global kill switch",
+                tool_name
+            ));
+        }
+
         match self.tool_allowlist.check(tool_name) {
             ToolAllowlistResult::Allowed => {
                 PolicyDecision::allow(format!("tool '{}' is in allowlist", tool_name))
@@ -37,17 +83,58 @@ impl PolicyEngine {
         }
     }
 
+    /// Evaluate a tool call against policy and Airlock in one pass
+    ///
+    /// Airlock inspects the payload even when policy denies, so threats are
+    /// still detected. Calls that end up allowed are recorded for velocity
+    /// tracking.
+    #[instrument(skip(self, ctx, airlock), fields(tool = %ctx.tool_name))]
+    pub async fn evaluate_tool_call_full(
+        &self,
+        ctx: &InspectionContext,
+        airlock: &AirlockInspector,
+    ) -> CombinedDecision {
+        let policy = self.evaluate_tool_call(&ctx.tool_name);
+        let airlock_result = airlock.inspect(ctx).await;
+        let decision = CombinedDecision::new(policy, airlock_result);
+        if decision.is_allowed() {
+            airlock.record_call(ctx, None).await;
+        }
+        decision
+    }
+
+    /// Explain how a tool call would be evaluated
+    ///
+    /// Unlike `evaluate_tool_call`, reports every matching rule rather than
+    /// only the one that won.
+    #[instrument(skip(self))]
+    pub fn explain(&self, tool_name: &str) -> PolicyExplanation {
+        PolicyExplanation {
+            tool_name: tool_name.to_string(),
+            matches: self.tool_allowlist.matches(tool_name),
+            decision: self.evaluate_tool_call(tool_name),
+            effective_budget: self.default_budget.clone(),
+        }
+    }
+
     /// Check if budget allows continuing
     #[instrument(skip(self))]
     pub fn check_budget(&self, usage: &BudgetUsage, budget: Option<&Budget>) -> PolicyDecision {
-        let budget = budget.unwrap_or(&self.default_budget);
-
-        match usage.check_against(budget) {
+        match self.budget_exceeded(usage, budget) {
             Some(exceeded) => PolicyDecision::deny(format!("budget exceeded: {}", exceeded)),
             None => PolicyDecision::allow("within budget limits"),
         }
     }
 
+    /// The first limit `usage` exceeds, falling back to the default budget
+    pub fn budget_exceeded(
+        &self,
+        usage: &BudgetUsage,
+        budget: Option<&Budget>,
+    ) -> Option<BudgetExceeded> {
+        usage.check_against(budget.unwrap_or(&self.default_budget))
+    }
+
     /// Get the default budget
     pub fn default_budget(&self) -> &Budget {
         &self.default_budget
@@ -70,6 +157,19 @@ mod tests {
         assert!(decision.reason.contains("not in allowlist"));
     }
 
+    #[test]
+    fn test_with_safe_defaults() {
+        let engine = PolicyEngine::with_safe_defaults();
+
+        assert!(engine.evaluate_tool_call("read_file").is_allowed());
+        assert!(engine.evaluate_tool_call("list_directory").is_allowed());
+        assert!(engine.evaluate_tool_call("write_file").needs_approval());
+
+        let decision = engine.evaluate_tool_call("unknown_tool");
+        assert!(decision.is_denied());
+        assert!(decision.reason.contains("not in allowlist"));
+    }
+
     #[test]
     fn test_tool_allowlist_allow() {
         let allowlist = ToolAllowlist {
@@ -81,6 +181,24 @@ mod tests {
         assert!(decision.is_allowed());
     }
 
+    #[test]
+    fn test_kill_switch_denies_allowlisted_tool_until_revived() {
+        let allowlist = ToolAllowlist {
+            allowed_tools: vec!["read_file".to_string()],
+            ..Default::default()
+        };
+        let engine = PolicyEngine::new(allowlist, Budget::default());
+
+        engine.kill_switch().kill("read_file");
+        let decision = engine.evaluate_tool_call("read_file");
+        assert!(decision.is_denied());
+        assert!(decision.reason.contains("kill switch"));
+        assert!(decision.is_kill_switch_deny());
+
+        engine.kill_switch().revive("read_file");
+        assert!(engine.evaluate_tool_call("read_file").is_allowed());
+    }
+
     #[test]
     fn test_tool_allowlist_requires_approval() {
         let allowlist = ToolAllowlist {
@@ -134,6 +252,79 @@ mod tests {
         assert!(engine.evaluate_tool_call("unknown").is_denied());
     }
 
+    #[test]
+    fn test_explain_reports_all_matches_and_deny_wins() {
+        use crate::rules::ToolRuleList;
+
+        let allowlist = ToolAllowlist {
+            allowed_tools: vec!["dangerous_tool".to_string()],
+            approval_required: vec![],
+            denied_tools: vec!["dangerous_tool".to_string()],
+        };
+        let engine = PolicyEngine::new(allowlist, Budget::default());
+        let explanation = engine.explain("dangerous_tool");
+
+        let lists: Vec<_> = explanation.matches.iter().map(|m| m.list).collect();
+        assert_eq!(lists, vec![ToolRuleList::Denied, ToolRuleList::Allowed]);
+        assert!(explanation.decision.is_denied());
+        assert_eq!(explanation.effective_budget.max_input_tokens, Some(100_000));
+    }
+
+    #[tokio::test]
+    async fn test_full_evaluation_denies_allowlisted_tool_on_rce() {
+        use crate::airlock::{AirlockConfig, AirlockMode, ViolationType};
+        use crate::decision::{DecisionSource, PolicyDecisionKind};
+        use fd_core::RunId;
+
+        let allowlist = ToolAllowlist {
+            allowed_tools: vec!["write_file".to_string()],
+            ..Default::default()
+        };
+        let engine = PolicyEngine::new(allowlist, Budget::default());
+        let airlock = AirlockInspector::new(AirlockConfig {
+            mode: AirlockMode::Enforce,
+            ..Default::default()
+        });
+        let ctx = |content: &str| InspectionContext {
+            run_id: RunId::new(),
+            tool_name: "write_file".to_string(),
+            tool_input: serde_json::json!({ "content": content }),
+            estimated_cost_cents: None,
+        };
+
+        let decision = engine
+            .evaluate_tool_call_full(&ctx("result = eval(user_input)"), &airlock)
+            .await;
+        assert!(decision.policy.is_allowed());
+        assert!(decision.is_denied());
+        assert_eq!(decision.source, DecisionSource::Airlock);
+        let violation = decision.violation().expect("airlock violation is cited");
+        assert_eq!(violation.violation_type, ViolationType::RcePattern);
+        assert_eq!(decision.reason, violation.details);
+
+        let clean = engine
+            .evaluate_tool_call_full(&ctx("hello world"), &airlock)
+            .await;
+        assert_eq!(clean.kind, PolicyDecisionKind::Allow);
+        assert_eq!(clean.source, DecisionSource::Policy);
+
+        let unknown = InspectionContext {
+            tool_name: "unknown_tool".to_string(),
+            ..ctx("result = eval(user_input)")
+        };
+        let denied = engine.evaluate_tool_call_full(&unknown, &airlock).await;
+        assert!(denied.is_denied());
+        assert_eq!(denied.source, DecisionSource::Policy);
+    }
+
+    #[test]
+    fn test_explain_unknown_tool_has_no_matches() {
+        let engine = PolicyEngine::default();
+        let explanation = engine.explain("unknown_tool");
+        assert!(explanation.matches.is_empty());
+        assert!(explanation.decision.is_denied());
+    }
+
     // =============================================================================
     // Budget Tests
     // =============================================================================
@@ -0,0 +1,132 @@
+//! Global tool kill switch
+//!
+//! During an incident a compromised tool can be disabled for every tenant at
+//! once, regardless of allowlists. The authoritative set lives in shared
+//! storage (Redis in the gateway); this type is the local cache consulted on
+//! every tool call, refreshed once its TTL lapses.
+
+use std::collections::HashSet;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// How long a cached kill list is trusted before it should be refreshed
+pub const DEFAULT_KILL_SWITCH_TTL: Duration = Duration::from_secs(5);
+
+/// Locally cached set of globally disabled tools
+#[derive(Debug)]
+pub struct ToolKillSwitch {
+    ttl: Duration,
+    state: RwLock<KillSwitchState>,
+}
+
+#[derive(Debug, Default)]
+struct KillSwitchState {
+    tools: HashSet<String>,
+    refreshed_at: Option<Instant>,
+}
+
+impl Default for ToolKillSwitch {
+    fn default() -> Self {
+        Self::new(DEFAULT_KILL_SWITCH_TTL)
+    }
+}
+
+impl ToolKillSwitch {
+    /// Create an empty kill switch whose cache expires after `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            state: RwLock::new(KillSwitchState::default()),
+        }
+    }
+
+    /// Whether a tool is currently disabled
+    pub fn is_killed(&self, tool_name: &str) -> bool {
+        self.state
+            .read()
+            .map(|state| state.tools.contains(tool_name))
+            .unwrap_or(false)
+    }
+
+    /// Whether the cached set is older than the TTL (or was never loaded)
+    pub fn needs_refresh(&self) -> bool {
+        self.state
+            .read()
+            .map(|state| {
+                state
+                    .refreshed_at
+                    .map_or(true, |at| at.elapsed() >= self.ttl)
+            })
+            .unwrap_or(true)
+    }
+
+    /// Replace the cached set with the authoritative one
+    pub fn refresh(&self, tools: impl IntoIterator<Item = String>) {
+        if let Ok(mut state) = self.state.write() {
+            state.tools = tools.into_iter().collect();
+            state.refreshed_at = Some(Instant::now());
+        }
+    }
+
+    /// Disable a tool in the local cache
+    pub fn kill(&self, tool_name: &str) {
+        if let Ok(mut state) = self.state.write() {
+            state.tools.insert(tool_name.to_string());
+        }
+    }
+
+    /// Re-enable a tool in the local cache
+    pub fn revive(&self, tool_name: &str) {
+        if let Ok(mut state) = self.state.write() {
+            state.tools.remove(tool_name);
+        }
+    }
+
+    /// Currently disabled tools, sorted by name
+    pub fn killed_tools(&self) -> Vec<String> {
+        let mut tools: Vec<String> = self
+            .state
+            .read()
+            .map(|state| state.tools.iter().cloned().collect())
+            .unwrap_or_default();
+        tools.sort();
+        tools
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kill_and_revive() {
+        let switch = ToolKillSwitch::default();
+        assert!(!switch.is_killed("shell"));
+
+        switch.kill("shell");
+        assert!(switch.is_killed("shell"));
+        assert_eq!(switch.killed_tools(), vec!["shell".to_string()]);
+
+        switch.revive("shell");
+        assert!(!switch.is_killed("shell"));
+    }
+
+    #[test]
+    fn test_refresh_replaces_set_and_resets_ttl() {
+        let switch = ToolKillSwitch::new(Duration::from_secs(60));
+        assert!(switch.needs_refresh());
+
+        switch.kill("stale_tool");
+        switch.refresh(vec!["shell".to_string()]);
+        assert!(!switch.needs_refresh());
+        assert!(switch.is_killed("shell"));
+        assert!(!switch.is_killed("stale_tool"));
+    }
+
+    #[test]
+    fn test_zero_ttl_always_needs_refresh() {
+        let switch = ToolKillSwitch::new(Duration::ZERO);
+        switch.refresh(Vec::new());
+        assert!(switch.needs_refresh());
+    }
+}
@@ -4,19 +4,22 @@
 //! - Tool allowlists (deny-by-default)
 //! - Budget limits (tokens, tool calls, wall time)
 //! - Approval gates for sensitive actions
+//! - Global tool kill switch for incidents
 //! - **Airlock**: Runtime security inspection (Agent RASP)
 
 pub mod airlock;
 pub mod budget;
 pub mod decision;
 pub mod engine;
+pub mod kill_switch;
 pub mod rules;
 
-pub use decision::{PolicyDecision, PolicyDecisionKind};
-pub use engine::PolicyEngine;
+pub use decision::{CombinedDecision, DecisionSource, PolicyDecision, PolicyDecisionKind};
+pub use engine::{PolicyEngine, PolicyExplanation};
+pub use kill_switch::ToolKillSwitch;
 
 // Re-export Airlock types for convenience
 pub use airlock::{
-    AirlockConfig, AirlockInspector, AirlockMode, AirlockResult, AirlockViolation,
-    InspectionContext, RiskLevel, ViolationType,
+    AirlockConfig, AirlockInspector, AirlockMode, AirlockRegistry, AirlockResult, AirlockViolation,
+    InspectionContext, RiskLevel, TenantAirlockCache, ViolationType,
 };
@@ -15,7 +15,32 @@ pub struct ToolAllowlist {
     pub denied_tools: Vec<String>,
 }
 
+/// Read-only tools allowed by [`ToolAllowlist::safe_defaults`]
+pub const SAFE_READ_ONLY_TOOLS: &[&str] = &["read_file", "list_directory", "get_time", "search"];
+
+/// Write tools gated behind approval by [`ToolAllowlist::safe_defaults`]
+pub const APPROVAL_GATED_WRITE_TOOLS: &[&str] = &[
+    "write_file",
+    "delete_file",
+    "create_directory",
+    "run_command",
+];
+
 impl ToolAllowlist {
+    /// A starting-point allowlist for new deployments
+    ///
+    /// Allows a curated set of read-only tools and requires approval for
+    /// common write tools. Everything else stays denied by default, so
+    /// deployments should extend this with their own tools.
+    pub fn safe_defaults() -> Self {
+        let owned = |tools: &[&str]| tools.iter().map(|t| t.to_string()).collect();
+        Self {
+            allowed_tools: owned(SAFE_READ_ONLY_TOOLS),
+            approval_required: owned(APPROVAL_GATED_WRITE_TOOLS),
+            denied_tools: Vec::new(),
+        }
+    }
+
     /// Check if a tool is allowed
     pub fn check(&self, tool_name: &str) -> ToolAllowlistResult {
         // Explicit deny takes precedence
@@ -36,6 +61,44 @@ impl ToolAllowlist {
         // Deny by default
         ToolAllowlistResult::Denied
     }
+
+    /// List every rule that matches a tool, in precedence order
+    pub fn matches(&self, tool_name: &str) -> Vec<ToolRuleMatch> {
+        [
+            (ToolRuleList::Denied, &self.denied_tools),
+            (ToolRuleList::ApprovalRequired, &self.approval_required),
+            (ToolRuleList::Allowed, &self.allowed_tools),
+        ]
+        .into_iter()
+        .flat_map(|(list, patterns)| {
+            patterns
+                .iter()
+                .filter(|p| *p == tool_name)
+                .map(move |p| ToolRuleMatch {
+                    list,
+                    pattern: p.clone(),
+                })
+        })
+        .collect()
+    }
+}
+
+/// Which allowlist a rule belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolRuleList {
+    Denied,
+    ApprovalRequired,
+    Allowed,
+}
+
+/// A single allowlist entry that matched a tool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolRuleMatch {
+    /// List the matching entry belongs to
+    pub list: ToolRuleList,
+    /// The entry that matched
+    pub pattern: String,
 }
 
 /// Result of checking a tool against the allowlist
@@ -47,17 +110,4 @@ pub enum ToolAllowlistResult {
 }
 
 /// Risk classification for tools
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-#[derive(Default)]
-pub enum ToolRiskLevel {
-    /// Read-only operations
-    Low,
-    /// Mutations with limited blast radius
-    #[default]
-    Medium,
-    /// External communications, writes to important systems
-    High,
-    /// Payments, deployments, security-sensitive
-    Critical,
-}
+pub use fd_core::ToolRiskLevel;
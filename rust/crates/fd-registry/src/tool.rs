@@ -26,17 +26,4 @@ pub struct ToolVersion {
 }
 
 /// Risk classification for tools
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-#[derive(Default)]
-pub enum ToolRiskLevel {
-    /// Read-only operations
-    Low,
-    /// Mutations with limited blast radius
-    #[default]
-    Medium,
-    /// External communications, writes to important systems
-    High,
-    /// Payments, deployments, security-sensitive
-    Critical,
-}
+pub use fd_core::ToolRiskLevel;
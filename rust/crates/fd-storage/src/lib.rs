@@ -11,5 +11,5 @@ pub mod repos;
 
 pub use migrations::run_migrations;
 pub use pool::{create_pool, DbPool};
-pub use queue::{QueueClient, QueueMessage};
+pub use queue::{Delivery, KeyKind, KeyTtlConfig, QueueClient, QueueMessage};
 pub use repos::*;
@@ -26,6 +26,20 @@ pub struct Agent {
     pub status: AgentStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Caller-supplied stable ID, unique per project
+    pub external_id: Option<String>,
+    /// Maximum active (non-terminal) runs; None means unlimited
+    pub max_concurrent_runs: Option<i32>,
+}
+
+impl Agent {
+    /// Whether another run may start given the agent's current active runs
+    pub fn has_run_capacity(&self, active_runs: i64) -> bool {
+        match self.max_concurrent_runs {
+            Some(limit) => active_runs < i64::from(limit),
+            None => true,
+        }
+    }
 }
 
 /// Create agent request
@@ -36,6 +50,8 @@ pub struct CreateAgent {
     pub name: String,
     pub slug: String,
     pub description: Option<String>,
+    pub external_id: Option<String>,
+    pub max_concurrent_runs: Option<i32>,
 }
 
 /// Update agent request
@@ -64,6 +80,9 @@ pub struct AgentVersion {
     pub changelog: Option<String>,
     pub created_at: DateTime<Utc>,
     pub created_by: Option<String>,
+    /// Whether this version is the agent's production default
+    pub promoted: bool,
+    pub promoted_at: Option<DateTime<Utc>>,
 }
 
 /// Create agent version request
@@ -92,3 +111,36 @@ pub struct AgentWithVersion {
     pub agent: Agent,
     pub latest_version: Option<AgentVersion>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_capacity_rejects_at_limit_and_frees_after_completion() {
+        let agent = Agent {
+            id: "agt_01".to_string(),
+            project_id: "prj_01".to_string(),
+            name: "Support".to_string(),
+            slug: "support".to_string(),
+            description: None,
+            status: AgentStatus::Active,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            external_id: None,
+            max_concurrent_runs: Some(2),
+        };
+
+        assert!(agent.has_run_capacity(1));
+        assert!(!agent.has_run_capacity(2));
+
+        // One active run completes, freeing a slot
+        assert!(agent.has_run_capacity(2 - 1));
+
+        let unlimited = Agent {
+            max_concurrent_runs: None,
+            ..agent
+        };
+        assert!(unlimited.has_run_capacity(1_000));
+    }
+}
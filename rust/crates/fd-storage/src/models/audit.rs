@@ -29,6 +29,10 @@ pub struct AuditEvent {
     pub trace_id: Option<String>,
     pub span_id: Option<String>,
     pub occurred_at: DateTime<Utc>,
+    /// Syslog-style severity (info, notice, warning, alert)
+    pub severity: Option<String>,
+    /// Risk score (0-100) for security findings
+    pub risk_score: Option<i32>,
 }
 
 impl AuditEvent {
@@ -57,6 +61,8 @@ pub struct CreateAuditEvent {
     pub user_agent: Option<String>,
     pub trace_id: Option<String>,
     pub span_id: Option<String>,
+    pub severity: Option<String>,
+    pub risk_score: Option<i32>,
 }
 
 /// Actor types for audit events
@@ -75,19 +81,25 @@ pub mod action {
     pub const RUN_COMPLETED: &str = "run.completed";
     pub const RUN_FAILED: &str = "run.failed";
     pub const RUN_CANCELLED: &str = "run.cancelled";
+    pub const RUN_SUMMARY: &str = "run.summary";
+    pub const RUNS_ARCHIVED: &str = "runs.archived";
+    pub const RUNS_STUCK_DETECTED: &str = "runs.stuck_detected";
 
     // Step actions
     pub const STEP_CREATED: &str = "step.created";
     pub const STEP_STARTED: &str = "step.started";
     pub const STEP_COMPLETED: &str = "step.completed";
     pub const STEP_FAILED: &str = "step.failed";
+    pub const STEP_RETRIES_EXHAUSTED: &str = "step.retries_exhausted";
 
     // Policy actions
     pub const POLICY_ALLOWED: &str = "policy.allowed";
     pub const POLICY_DENIED: &str = "policy.denied";
     pub const POLICY_APPROVAL_REQUIRED: &str = "policy.approval_required";
+    pub const POLICY_BREAK_GLASS: &str = "policy.break_glass";
 
     // Approval actions
+    pub const APPROVAL_RECORDED: &str = "approval.recorded";
     pub const APPROVAL_APPROVED: &str = "approval.approved";
     pub const APPROVAL_REJECTED: &str = "approval.rejected";
     pub const APPROVAL_EXPIRED: &str = "approval.expired";
@@ -96,13 +108,19 @@ pub mod action {
     pub const AGENT_CREATED: &str = "agent.created";
     pub const AGENT_UPDATED: &str = "agent.updated";
     pub const AGENT_VERSION_CREATED: &str = "agent_version.created";
+    pub const AGENT_VERSION_PROMOTED: &str = "agent_version.promoted";
     pub const TOOL_CREATED: &str = "tool.created";
     pub const TOOL_UPDATED: &str = "tool.updated";
+    pub const TOOL_KILLED: &str = "tool.killed";
+    pub const TOOL_REVIVED: &str = "tool.revived";
 
     // Auth actions
     pub const API_KEY_CREATED: &str = "api_key.created";
     pub const API_KEY_REVOKED: &str = "api_key.revoked";
     pub const API_KEY_USED: &str = "api_key.used";
+
+    // Airlock actions
+    pub const AIRLOCK_VIOLATION_DETECTED: &str = "airlock.violation_detected";
 }
 
 /// Resource types
@@ -142,6 +160,8 @@ impl AuditEventBuilder {
                 user_agent: None,
                 trace_id: None,
                 span_id: None,
+                severity: None,
+                risk_score: None,
             },
         }
     }
@@ -188,6 +208,13 @@ impl AuditEventBuilder {
         self
     }
 
+    /// Attach a severity and risk score (for security findings)
+    pub fn severity(mut self, severity: impl Into<String>, risk_score: u8) -> Self {
+        self.event.severity = Some(severity.into());
+        self.event.risk_score = Some(i32::from(risk_score));
+        self
+    }
+
     pub fn build(self) -> CreateAuditEvent {
         self.event
     }
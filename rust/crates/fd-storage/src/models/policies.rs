@@ -1,7 +1,10 @@
 //! Policy entity models
 
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use sqlx::FromRow;
 
 /// Policy effect enum
@@ -94,6 +97,46 @@ pub enum ApprovalStatus {
     Expired,
 }
 
+/// Kind of action an approval request gates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalActionType {
+    /// A tool call held by a `require_approval` policy
+    ToolCall,
+    /// Letting a run continue past a budget limit
+    BudgetOverride,
+    /// Emergency access that bypasses normal policy
+    BreakGlass,
+    /// A workflow step gated on human review
+    WorkflowStep,
+}
+
+impl ApprovalActionType {
+    /// Every action type
+    pub const ALL: [ApprovalActionType; 4] = [
+        Self::ToolCall,
+        Self::BudgetOverride,
+        Self::BreakGlass,
+        Self::WorkflowStep,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ToolCall => "tool_call",
+            Self::BudgetOverride => "budget_override",
+            Self::BreakGlass => "break_glass",
+            Self::WorkflowStep => "workflow_step",
+        }
+    }
+}
+
+impl std::fmt::Display for ApprovalActionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Approval request entity
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct ApprovalRequest {
@@ -101,7 +144,7 @@ pub struct ApprovalRequest {
     pub run_id: String,
     pub step_id: String,
     pub policy_decision_id: String,
-    pub action_type: String,
+    pub action_type: ApprovalActionType,
     pub action_details: serde_json::Value,
     pub reason: String,
     pub status: ApprovalStatus,
@@ -110,6 +153,50 @@ pub struct ApprovalRequest {
     pub resolution_note: Option<String>,
     pub created_at: DateTime<Utc>,
     pub expires_at: Option<DateTime<Utc>>,
+    /// Number of distinct approvers needed before the step resumes
+    #[sqlx(try_from = "i32")]
+    pub required_approvals: u32,
+    /// Individual approvals recorded so far
+    #[sqlx(json)]
+    pub approvals: Vec<ApprovalVote>,
+}
+
+impl ApprovalRequest {
+    /// Number of distinct approvers who have approved so far
+    pub fn approval_count(&self) -> u32 {
+        self.approvals.len() as u32
+    }
+
+    /// Whether the given approver has already approved this request
+    pub fn has_approved(&self, approver: &str) -> bool {
+        self.approvals.iter().any(|v| v.approver == approver)
+    }
+
+    /// Record an approval from `approver`.
+    ///
+    /// Returns `false` (and records nothing) if this approver already approved.
+    pub fn record_approval(&mut self, approver: &str, at: DateTime<Utc>) -> bool {
+        if self.has_approved(approver) {
+            return false;
+        }
+        self.approvals.push(ApprovalVote {
+            approver: approver.to_string(),
+            approved_at: at,
+        });
+        true
+    }
+
+    /// Whether enough distinct approvers have approved to resume the step
+    pub fn quorum_reached(&self) -> bool {
+        self.approval_count() >= self.required_approvals.max(1)
+    }
+}
+
+/// A single approver's approval of an approval request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalVote {
+    pub approver: String,
+    pub approved_at: DateTime<Utc>,
 }
 
 /// Create approval request
@@ -119,10 +206,16 @@ pub struct CreateApprovalRequest {
     pub run_id: String,
     pub step_id: String,
     pub policy_decision_id: String,
-    pub action_type: String,
+    pub action_type: ApprovalActionType,
     pub action_details: serde_json::Value,
     pub reason: String,
     pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default = "default_required_approvals")]
+    pub required_approvals: u32,
+}
+
+fn default_required_approvals() -> u32 {
+    1
 }
 
 /// Resolve approval request
@@ -132,3 +225,218 @@ pub struct ResolveApproval {
     pub resolved_by: String,
     pub resolution_note: Option<String>,
 }
+
+/// Prefix identifying allow-once approval tokens
+const APPROVAL_TOKEN_PREFIX: &str = "fdat_";
+
+/// What an allow-once approval token authorizes
+///
+/// Signed into the token itself, so verifying needs only the key; the
+/// `token_id` is recorded on first use to make the token single-use.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalTokenClaims {
+    pub token_id: String,
+    pub approval_id: String,
+    pub run_id: String,
+    pub step_id: String,
+    pub tool_name: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Why an approval token was refused
+#[derive(Debug, thiserror::Error)]
+pub enum ApprovalTokenError {
+    #[error("approval token is malformed")]
+    Malformed,
+    #[error("approval token signature is invalid")]
+    InvalidSignature,
+    #[error("approval token has expired")]
+    Expired,
+    #[error("approval token does not authorize this call")]
+    Mismatch,
+    #[error("approval token has already been used")]
+    AlreadyUsed,
+    #[error("approval is not approved")]
+    NotApproved,
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl ApprovalTokenClaims {
+    /// Encode and sign as `fdat_<claims>.<hmac>`
+    pub fn sign(&self, key: &[u8]) -> String {
+        let payload = URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(self).expect("token claims always serialize"));
+        let signature = hex::encode(token_mac(key, &payload).finalize().into_bytes());
+        format!("{}{}.{}", APPROVAL_TOKEN_PREFIX, payload, signature)
+    }
+
+    /// Decode a token, checking its signature and expiry (not single use)
+    pub fn verify(key: &[u8], token: &str, now: DateTime<Utc>) -> Result<Self, ApprovalTokenError> {
+        let (payload, signature) = token
+            .strip_prefix(APPROVAL_TOKEN_PREFIX)
+            .and_then(|rest| rest.split_once('.'))
+            .ok_or(ApprovalTokenError::Malformed)?;
+        let signature = hex::decode(signature).map_err(|_| ApprovalTokenError::Malformed)?;
+        token_mac(key, payload)
+            .verify_slice(&signature)
+            .map_err(|_| ApprovalTokenError::InvalidSignature)?;
+
+        let claims: Self = URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or(ApprovalTokenError::Malformed)?;
+        if now >= claims.expires_at {
+            return Err(ApprovalTokenError::Expired);
+        }
+        Ok(claims)
+    }
+
+    /// Whether the token was issued for exactly this tool call
+    pub fn authorizes(
+        &self,
+        run_id: &str,
+        step_id: &str,
+        tool_name: &str,
+    ) -> Result<(), ApprovalTokenError> {
+        if self.run_id == run_id && self.step_id == step_id && self.tool_name == tool_name {
+            Ok(())
+        } else {
+            Err(ApprovalTokenError::Mismatch)
+        }
+    }
+}
+
+fn token_mac(key: &[u8], payload: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(APPROVAL_TOKEN_PREFIX.as_bytes());
+    mac.update(payload.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approval(required_approvals: u32) -> ApprovalRequest {
+        ApprovalRequest {
+            id: "apr_01".to_string(),
+            run_id: "run_01".to_string(),
+            step_id: "step_01".to_string(),
+            policy_decision_id: "pdec_01".to_string(),
+            action_type: ApprovalActionType::ToolCall,
+            action_details: serde_json::json!({}),
+            reason: "needs review".to_string(),
+            status: ApprovalStatus::Pending,
+            resolved_by: None,
+            resolved_at: None,
+            resolution_note: None,
+            created_at: Utc::now(),
+            expires_at: None,
+            required_approvals,
+            approvals: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_approval_action_types_round_trip() {
+        for action_type in ApprovalActionType::ALL {
+            let json = serde_json::to_value(action_type).unwrap();
+            assert_eq!(json, serde_json::json!(action_type.as_str()));
+            assert_eq!(
+                serde_json::from_value::<ApprovalActionType>(json).unwrap(),
+                action_type
+            );
+        }
+        assert_eq!(
+            ApprovalActionType::BudgetOverride.to_string(),
+            "budget_override"
+        );
+        assert!(serde_json::from_str::<ApprovalActionType>("\"ToolCall\"").is_err());
+    }
+
+    #[test]
+    fn test_record_approval_ignores_duplicate_approver() {
+        let mut req = approval(2);
+        assert!(req.record_approval("key_a", Utc::now()));
+        assert!(!req.record_approval("key_a", Utc::now()));
+        assert_eq!(req.approval_count(), 1);
+        assert!(!req.quorum_reached());
+
+        assert!(req.record_approval("key_b", Utc::now()));
+        assert!(req.quorum_reached());
+    }
+
+    #[test]
+    fn test_zero_required_approvals_still_needs_one() {
+        let mut req = approval(0);
+        assert!(!req.quorum_reached());
+        req.record_approval("key_a", Utc::now());
+        assert!(req.quorum_reached());
+    }
+
+    fn claims() -> ApprovalTokenClaims {
+        ApprovalTokenClaims {
+            token_id: "apt_01".to_string(),
+            approval_id: "apr_01".to_string(),
+            run_id: "run_01".to_string(),
+            step_id: "step_01".to_string(),
+            tool_name: "delete_file".to_string(),
+            expires_at: Utc::now() + chrono::Duration::minutes(5),
+        }
+    }
+
+    #[test]
+    fn test_approval_token_authorizes_only_the_bound_call() {
+        let issued = claims();
+        let token = issued.sign(b"secret");
+        let verified = ApprovalTokenClaims::verify(b"secret", &token, Utc::now()).unwrap();
+        assert_eq!(verified, issued);
+
+        assert!(verified
+            .authorizes("run_01", "step_01", "delete_file")
+            .is_ok());
+        assert!(matches!(
+            verified.authorizes("run_01", "step_01", "write_file"),
+            Err(ApprovalTokenError::Mismatch)
+        ));
+        assert!(matches!(
+            verified.authorizes("run_02", "step_01", "delete_file"),
+            Err(ApprovalTokenError::Mismatch)
+        ));
+    }
+
+    #[test]
+    fn test_approval_token_rejects_tampering_other_keys_and_expiry() {
+        let token = claims().sign(b"secret");
+        assert!(matches!(
+            ApprovalTokenClaims::verify(b"other", &token, Utc::now()),
+            Err(ApprovalTokenError::InvalidSignature)
+        ));
+
+        let mut forged = claims();
+        forged.tool_name = "shell".to_string();
+        let (_, signature) = token.split_once('.').unwrap();
+        let forged_token = forged.sign(b"other");
+        let (forged_payload, _) = forged_token.split_once('.').unwrap();
+        let spliced = format!("{}.{}", forged_payload, signature);
+        assert!(matches!(
+            ApprovalTokenClaims::verify(b"secret", &spliced, Utc::now()),
+            Err(ApprovalTokenError::InvalidSignature)
+        ));
+
+        assert!(matches!(
+            ApprovalTokenClaims::verify(b"secret", "not-a-token", Utc::now()),
+            Err(ApprovalTokenError::Malformed)
+        ));
+        assert!(matches!(
+            ApprovalTokenClaims::verify(
+                b"secret",
+                &token,
+                Utc::now() + chrono::Duration::minutes(6)
+            ),
+            Err(ApprovalTokenError::Expired)
+        ));
+    }
+}
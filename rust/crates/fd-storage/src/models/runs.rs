@@ -1,6 +1,7 @@
 //! Run and Step entity models
 
 use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -35,6 +36,88 @@ impl RunStatus {
                 | RunStatus::PolicyBlocked
         )
     }
+
+    /// Database name of the status
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Created => "created",
+            RunStatus::Queued => "queued",
+            RunStatus::Running => "running",
+            RunStatus::WaitingApproval => "waiting_approval",
+            RunStatus::Completed => "completed",
+            RunStatus::Failed => "failed",
+            RunStatus::Cancelled => "cancelled",
+            RunStatus::Timeout => "timeout",
+            RunStatus::BudgetKilled => "budget_killed",
+            RunStatus::PolicyBlocked => "policy_blocked",
+        }
+    }
+
+    /// Whether a run in this status is expected to make progress on its own
+    ///
+    /// `WaitingApproval` is excluded: it waits on a human (and expires on its own).
+    pub fn is_progressing(&self) -> bool {
+        matches!(
+            self,
+            RunStatus::Created | RunStatus::Queued | RunStatus::Running
+        )
+    }
+
+    /// Statuses in which a run can be considered stuck
+    pub fn progressing_statuses() -> Vec<RunStatus> {
+        vec![RunStatus::Created, RunStatus::Queued, RunStatus::Running]
+    }
+
+    const ALL: [RunStatus; 10] = [
+        RunStatus::Created,
+        RunStatus::Queued,
+        RunStatus::Running,
+        RunStatus::WaitingApproval,
+        RunStatus::Completed,
+        RunStatus::Failed,
+        RunStatus::Cancelled,
+        RunStatus::Timeout,
+        RunStatus::BudgetKilled,
+        RunStatus::PolicyBlocked,
+    ];
+
+    /// All terminal statuses
+    pub fn terminal_statuses() -> Vec<RunStatus> {
+        Self::ALL
+            .into_iter()
+            .filter(RunStatus::is_terminal)
+            .collect()
+    }
+
+    /// Statuses from which a run may move to `next`
+    pub fn allowed_from(next: RunStatus) -> Vec<RunStatus> {
+        Self::ALL
+            .into_iter()
+            .filter(|status| status.can_transition_to(next))
+            .collect()
+    }
+
+    /// Check whether moving from this status to `next` is a legal transition.
+    ///
+    /// Terminal statuses never transition. Active statuses may repeat (e.g. a
+    /// second approval while already waiting) but nothing returns to `Created`.
+    pub fn can_transition_to(&self, next: RunStatus) -> bool {
+        use RunStatus::*;
+
+        if self.is_terminal() {
+            return false;
+        }
+        if *self == next {
+            return true;
+        }
+
+        match self {
+            Created => matches!(next, Queued | Running | Failed | Cancelled | PolicyBlocked),
+            // Workers may report results for a queued run before it is marked running
+            Queued | Running | WaitingApproval => !matches!(next, Created),
+            _ => false,
+        }
+    }
 }
 
 /// Run entity
@@ -58,6 +141,87 @@ pub struct Run {
     pub error: Option<serde_json::Value>,
     pub trace_id: Option<String>,
     pub span_id: Option<String>,
+    /// Caller-supplied metadata propagated to step jobs and audit events
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+    /// Effective budget captured at creation (None for older runs)
+    #[serde(default)]
+    pub budget_snapshot: Option<serde_json::Value>,
+    /// Budget dimension exceeded, for budget-killed runs
+    #[serde(default)]
+    pub budget_kill_detail: Option<serde_json::Value>,
+}
+
+impl Run {
+    /// Whether this run can be archived under the given cutoff
+    ///
+    /// Only terminal runs that finished (or, lacking a completion time, were
+    /// created) before the cutoff are archived.
+    pub fn is_archivable(&self, cutoff: DateTime<Utc>) -> bool {
+        self.status.is_terminal() && self.completed_at.unwrap_or(self.created_at) < cutoff
+    }
+
+    /// Whether this run has gone idle for longer than `idle_threshold`
+    ///
+    /// `last_step_update` is the latest create/start/completion time across
+    /// the run's steps; runs without steps fall back to their own start (or
+    /// creation) time. Only progressing runs can be stuck.
+    pub fn is_stuck(
+        &self,
+        last_step_update: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+        idle_threshold: chrono::Duration,
+    ) -> bool {
+        let last_activity = last_step_update
+            .or(self.started_at)
+            .unwrap_or(self.created_at);
+        self.status.is_progressing() && now - last_activity > idle_threshold
+    }
+}
+
+/// Archived run tombstone with the run, step and evidence snapshots
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ArchivedRun {
+    pub id: String,
+    pub project_id: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub archived_at: DateTime<Utc>,
+    pub run: serde_json::Value,
+    pub steps: serde_json::Value,
+    /// Threats, approval requests, policy decisions and velocity events
+    /// recorded against the run, keyed by table name
+    pub evidence: serde_json::Value,
+}
+
+/// A run with everything recorded against it, for export and offline replay
+///
+/// Steps are in execution order; decisions and threats are oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunExportBundle {
+    pub run: Run,
+    pub steps: Vec<super::Step>,
+    pub policy_decisions: Vec<super::PolicyDecision>,
+    pub threats: Vec<super::Threat>,
+}
+
+/// A run export read from one snapshot, with its steps still to be read
+///
+/// Steps arrive in execution order as the stream is polled; decisions and
+/// threats are oldest first. Dropping the stream ends the snapshot.
+pub struct RunExportStream {
+    pub run: Run,
+    pub policy_decisions: Vec<super::PolicyDecision>,
+    pub threats: Vec<super::Threat>,
+    pub steps: BoxStream<'static, Result<super::Step, sqlx::Error>>,
+}
+
+/// A run with its steps in execution order, as compared by run diffs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunWithSteps {
+    pub run: Run,
+    pub steps: Vec<super::Step>,
 }
 
 /// Create run request
@@ -70,6 +234,11 @@ pub struct CreateRun {
     pub config: serde_json::Value,
     pub trace_id: Option<String>,
     pub span_id: Option<String>,
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+    /// Effective budget the run is created under
+    #[serde(default)]
+    pub budget_snapshot: Option<serde_json::Value>,
 }
 
 /// Update run request
@@ -85,6 +254,7 @@ pub struct UpdateRun {
     pub completed_at: Option<DateTime<Utc>>,
     pub output: Option<serde_json::Value>,
     pub error: Option<serde_json::Value>,
+    pub budget_kill_detail: Option<serde_json::Value>,
 }
 
 /// Run with aggregated stats
@@ -212,6 +382,8 @@ mod tests {
             config: serde_json::json!({}),
             trace_id: Some("trace_abc".to_string()),
             span_id: None,
+            metadata: serde_json::json!({}),
+            budget_snapshot: None,
         };
 
         let json = serde_json::to_string(&create).unwrap();
@@ -309,8 +481,142 @@ mod tests {
             config: serde_json::json!({}),
             trace_id: None,
             span_id: None,
+            metadata: serde_json::json!({}),
+            budget_snapshot: None,
         };
         let debug = format!("{:?}", create);
         assert!(debug.contains("run_debug"));
     }
+
+    // ==========================================================================
+    // STO-RUN-010: Status transitions
+    // ==========================================================================
+    #[test]
+    fn test_run_status_queued_to_running_allowed() {
+        assert!(RunStatus::Queued.can_transition_to(RunStatus::Running));
+        assert!(RunStatus::Created.can_transition_to(RunStatus::Queued));
+        assert!(RunStatus::Running.can_transition_to(RunStatus::WaitingApproval));
+        assert!(RunStatus::WaitingApproval.can_transition_to(RunStatus::Running));
+        assert!(RunStatus::Running.can_transition_to(RunStatus::BudgetKilled));
+        assert!(RunStatus::Running.can_transition_to(RunStatus::PolicyBlocked));
+        assert!(RunStatus::Queued.can_transition_to(RunStatus::Cancelled));
+    }
+
+    #[test]
+    fn test_run_status_terminal_to_active_rejected() {
+        let terminal = [
+            RunStatus::Completed,
+            RunStatus::Failed,
+            RunStatus::Cancelled,
+            RunStatus::Timeout,
+            RunStatus::BudgetKilled,
+            RunStatus::PolicyBlocked,
+        ];
+        for status in terminal {
+            assert!(
+                !status.can_transition_to(RunStatus::Running),
+                "{:?}",
+                status
+            );
+            assert!(!status.can_transition_to(status), "{:?}", status);
+        }
+    }
+
+    #[test]
+    fn test_run_status_allowed_from_matches_transitions() {
+        use RunStatus::*;
+
+        assert_eq!(
+            RunStatus::allowed_from(Running),
+            vec![Created, Queued, Running, WaitingApproval]
+        );
+        // Only a run that was never queued is still `Created`
+        assert_eq!(RunStatus::allowed_from(Created), vec![Created]);
+        assert!(!RunStatus::allowed_from(Completed).contains(&Created));
+    }
+
+    #[test]
+    fn test_run_status_never_returns_to_created() {
+        assert!(!RunStatus::Queued.can_transition_to(RunStatus::Created));
+        assert!(!RunStatus::Running.can_transition_to(RunStatus::Created));
+        assert!(!RunStatus::Created.can_transition_to(RunStatus::Completed));
+    }
+
+    fn run_with(status: RunStatus, completed_at: Option<DateTime<Utc>>) -> Run {
+        Run {
+            id: "run_01".to_string(),
+            project_id: "proj_01".to_string(),
+            agent_version_id: "agv_01".to_string(),
+            input: serde_json::json!({}),
+            config: serde_json::json!({}),
+            status,
+            status_reason: None,
+            input_tokens: 0,
+            output_tokens: 0,
+            tool_calls: 0,
+            cost_cents: 0,
+            created_at: Utc::now() - chrono::Duration::days(60),
+            started_at: None,
+            completed_at,
+            output: None,
+            error: None,
+            trace_id: None,
+            span_id: None,
+            metadata: serde_json::json!({}),
+            budget_snapshot: None,
+            budget_kill_detail: None,
+        }
+    }
+
+    #[test]
+    fn test_run_is_stuck_when_last_step_update_exceeds_threshold() {
+        let now = Utc::now();
+        let threshold = chrono::Duration::hours(1);
+        let running = run_with(RunStatus::Running, None);
+
+        assert!(running.is_stuck(Some(now - chrono::Duration::hours(2)), now, threshold));
+        // A run whose steps are still updating is not stuck
+        assert!(!running.is_stuck(Some(now - chrono::Duration::minutes(5)), now, threshold));
+        // Without steps, the run's own creation time is used
+        assert!(running.is_stuck(None, now, threshold));
+
+        // Runs waiting on a human or already finished are never stuck
+        let waiting = run_with(RunStatus::WaitingApproval, None);
+        assert!(!waiting.is_stuck(Some(now - chrono::Duration::hours(2)), now, threshold));
+        let done = run_with(RunStatus::Completed, Some(now - chrono::Duration::hours(2)));
+        assert!(!done.is_stuck(None, now, threshold));
+    }
+
+    #[test]
+    fn test_run_is_archivable_when_terminal_and_old() {
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+        let old = Some(Utc::now() - chrono::Duration::days(45));
+        let recent = Some(Utc::now() - chrono::Duration::days(1));
+
+        assert!(run_with(RunStatus::Completed, old).is_archivable(cutoff));
+        assert!(run_with(RunStatus::BudgetKilled, old).is_archivable(cutoff));
+        // Falls back to created_at when completion time is missing
+        assert!(run_with(RunStatus::Failed, None).is_archivable(cutoff));
+
+        // Recently finished runs stay hot
+        assert!(!run_with(RunStatus::Completed, recent).is_archivable(cutoff));
+        // Non-terminal runs are never archived, however old
+        assert!(!run_with(RunStatus::Running, None).is_archivable(cutoff));
+        assert!(!run_with(RunStatus::WaitingApproval, old).is_archivable(cutoff));
+    }
+
+    #[test]
+    fn test_terminal_statuses_match_database_names() {
+        let statuses = RunStatus::terminal_statuses();
+        assert_eq!(statuses.len(), 6);
+        assert!(!statuses.contains(&RunStatus::Running));
+
+        for status in statuses {
+            assert!(status.is_terminal());
+            assert_eq!(
+                serde_json::to_value(status).unwrap(),
+                serde_json::json!(status.as_str())
+            );
+        }
+    }
 }
@@ -26,15 +26,27 @@ pub enum StepStatus {
     Completed,
     Failed,
     Skipped,
+    /// Step was still outstanding when its run was force-terminated
+    Cancelled,
 }
 
 impl StepStatus {
     pub fn is_terminal(&self) -> bool {
         matches!(
             self,
-            StepStatus::Completed | StepStatus::Failed | StepStatus::Skipped
+            StepStatus::Completed
+                | StepStatus::Failed
+                | StepStatus::Skipped
+                | StepStatus::Cancelled
         )
     }
+
+    /// Check whether moving from this status to `next` is a legal transition.
+    ///
+    /// Terminal steps are final, and no step returns to `Pending`.
+    pub fn can_transition_to(&self, next: StepStatus) -> bool {
+        !self.is_terminal() && next != StepStatus::Pending
+    }
 }
 
 /// Step entity
@@ -373,4 +385,34 @@ mod tests {
         let debug = format!("{:?}", status);
         assert_eq!(debug, "Skipped");
     }
+
+    // ==========================================================================
+    // STO-STP-010: Status transitions
+    // ==========================================================================
+    #[test]
+    fn test_step_status_cancelled_is_terminal() {
+        assert!(StepStatus::Cancelled.is_terminal());
+        for status in [
+            StepStatus::Pending,
+            StepStatus::Running,
+            StepStatus::WaitingApproval,
+        ] {
+            assert!(status.can_transition_to(StepStatus::Cancelled));
+        }
+        assert_eq!(
+            serde_json::to_string(&StepStatus::Cancelled).unwrap(),
+            "\"cancelled\""
+        );
+    }
+
+    #[test]
+    fn test_step_status_transitions() {
+        assert!(StepStatus::Pending.can_transition_to(StepStatus::Running));
+        assert!(StepStatus::Running.can_transition_to(StepStatus::Completed));
+        assert!(StepStatus::Running.can_transition_to(StepStatus::WaitingApproval));
+        assert!(StepStatus::WaitingApproval.can_transition_to(StepStatus::Running));
+        assert!(!StepStatus::Running.can_transition_to(StepStatus::Pending));
+        assert!(!StepStatus::Completed.can_transition_to(StepStatus::Running));
+        assert!(!StepStatus::Failed.can_transition_to(StepStatus::Completed));
+    }
 }
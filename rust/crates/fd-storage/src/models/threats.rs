@@ -115,6 +115,14 @@ pub struct CreateVelocityEvent {
     pub cost_cents: i32,
 }
 
+/// Per-tenant Airlock configuration override
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TenantAirlockConfig {
+    pub tenant_id: String,
+    pub config: serde_json::Value,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3,6 +3,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 
 /// Tool status enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
@@ -14,15 +15,7 @@ pub enum ToolStatus {
     Disabled,
 }
 
-/// Tool risk level enum
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "tool_risk_level", rename_all = "snake_case")]
-#[serde(rename_all = "snake_case")]
-pub enum ToolRiskLevel {
-    Read,
-    Write,
-    Destructive,
-}
+pub use fd_core::ToolRiskLevel;
 
 /// Tool entity
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
@@ -37,6 +30,47 @@ pub struct Tool {
     pub risk_level: ToolRiskLevel,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Estimated cost of one call in cents, if registered with one
+    #[serde(default)]
+    pub estimated_cost_cents: Option<i32>,
+}
+
+/// Error resolving `${VAR}` references in a tool's MCP server URL
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum McpServerError {
+    #[error("Undefined variable '{0}' in mcp_server")]
+    UndefinedVariable(String),
+
+    #[error("Unterminated variable reference in mcp_server")]
+    Unterminated,
+}
+
+impl Tool {
+    /// Resolve `${VAR}` references in `mcp_server` against the configured
+    /// variable map, so one registration can target different hosts per
+    /// environment.
+    pub fn resolved_mcp_server(
+        &self,
+        vars: &HashMap<String, String>,
+    ) -> Result<String, McpServerError> {
+        let mut resolved = String::with_capacity(self.mcp_server.len());
+        let mut rest = self.mcp_server.as_str();
+
+        while let Some(start) = rest.find("${") {
+            resolved.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after.find('}').ok_or(McpServerError::Unterminated)?;
+            let name = &after[..end];
+            let value = vars
+                .get(name)
+                .ok_or_else(|| McpServerError::UndefinedVariable(name.to_string()))?;
+            resolved.push_str(value);
+            rest = &after[end + 1..];
+        }
+        resolved.push_str(rest);
+
+        Ok(resolved)
+    }
 }
 
 /// Create tool request
@@ -49,6 +83,7 @@ pub struct CreateTool {
     pub description: Option<String>,
     pub mcp_server: String,
     pub risk_level: ToolRiskLevel,
+    pub estimated_cost_cents: Option<i32>,
 }
 
 /// Update tool request
@@ -90,3 +125,66 @@ pub struct ToolWithVersion {
     pub tool: Tool,
     pub latest_version: Option<ToolVersion>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_with_server(mcp_server: &str) -> Tool {
+        Tool {
+            id: "tol_01".to_string(),
+            project_id: None,
+            name: "Test Tool".to_string(),
+            slug: "test-tool".to_string(),
+            description: None,
+            mcp_server: mcp_server.to_string(),
+            status: ToolStatus::Active,
+            risk_level: ToolRiskLevel::Read,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            estimated_cost_cents: None,
+        }
+    }
+
+    #[test]
+    fn test_resolved_mcp_server_interpolates_vars() {
+        let tool = tool_with_server("${MCP_HOST}/tool");
+        let vars = HashMap::from([(
+            "MCP_HOST".to_string(),
+            "https://mcp.staging.internal".to_string(),
+        )]);
+
+        assert_eq!(
+            tool.resolved_mcp_server(&vars).unwrap(),
+            "https://mcp.staging.internal/tool"
+        );
+    }
+
+    #[test]
+    fn test_resolved_mcp_server_errors_on_undefined_var() {
+        let tool = tool_with_server("${MCP_HOST}/tool");
+
+        assert_eq!(
+            tool.resolved_mcp_server(&HashMap::new()),
+            Err(McpServerError::UndefinedVariable("MCP_HOST".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_resolved_mcp_server_without_vars_is_unchanged() {
+        let tool = tool_with_server("http://localhost:3000");
+        assert_eq!(
+            tool.resolved_mcp_server(&HashMap::new()).unwrap(),
+            "http://localhost:3000"
+        );
+    }
+
+    #[test]
+    fn test_resolved_mcp_server_rejects_unterminated_reference() {
+        let tool = tool_with_server("${MCP_HOST/tool");
+        assert_eq!(
+            tool.resolved_mcp_server(&HashMap::new()),
+            Err(McpServerError::Unterminated)
+        );
+    }
+}
@@ -17,6 +17,32 @@ pub enum WorkflowStepType {
     Approval,
 }
 
+impl From<fd_dag::StepType> for WorkflowStepType {
+    fn from(step_type: fd_dag::StepType) -> Self {
+        match step_type {
+            fd_dag::StepType::Llm => Self::Llm,
+            fd_dag::StepType::Tool => Self::Tool,
+            fd_dag::StepType::Condition => Self::Condition,
+            fd_dag::StepType::Loop => Self::Loop,
+            fd_dag::StepType::Parallel => Self::Parallel,
+            fd_dag::StepType::Approval => Self::Approval,
+        }
+    }
+}
+
+impl From<WorkflowStepType> for fd_dag::StepType {
+    fn from(step_type: WorkflowStepType) -> Self {
+        match step_type {
+            WorkflowStepType::Llm => Self::Llm,
+            WorkflowStepType::Tool => Self::Tool,
+            WorkflowStepType::Condition => Self::Condition,
+            WorkflowStepType::Loop => Self::Loop,
+            WorkflowStepType::Parallel => Self::Parallel,
+            WorkflowStepType::Approval => Self::Approval,
+        }
+    }
+}
+
 /// Workflow status enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "workflow_status", rename_all = "snake_case")]
@@ -42,6 +68,8 @@ pub struct Workflow {
     pub on_error: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Caller-supplied stable ID, unique per project
+    pub external_id: Option<String>,
 }
 
 /// Create workflow request
@@ -55,6 +83,7 @@ pub struct CreateWorkflow {
     pub definition: serde_json::Value,
     pub max_iterations: i32,
     pub on_error: String,
+    pub external_id: Option<String>,
 }
 
 /// Update workflow request
@@ -122,6 +151,8 @@ pub enum WorkflowRunStatus {
     Created,
     Running,
     WaitingApproval,
+    /// Paused by an operator; no new steps are scheduled until resumed
+    Paused,
     Completed,
     Failed,
     Cancelled,
@@ -157,6 +188,10 @@ pub struct WorkflowRun {
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub trace_id: Option<String>,
+    /// Span ID of the run's root span; step spans are parented on it
+    pub span_id: Option<String>,
+    /// Last persisted DAG scheduler snapshot (opaque to storage)
+    pub scheduler_state: Option<serde_json::Value>,
 }
 
 /// Create workflow run request
@@ -167,6 +202,7 @@ pub struct CreateWorkflowRun {
     pub project_id: String,
     pub input: serde_json::Value,
     pub trace_id: Option<String>,
+    pub span_id: Option<String>,
 }
 
 /// Update workflow run request
@@ -185,6 +221,80 @@ pub struct UpdateWorkflowRun {
     pub completed_at: Option<DateTime<Utc>>,
 }
 
+/// Which step outputs a workflow run keeps in `step_results`
+///
+/// Read from the workflow definition's top-level `output_retention` field,
+/// e.g. `{"mode": "last_n", "count": 3}`. Pruned steps keep an entry
+/// recording their status, but not their output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum OutputRetention {
+    /// Keep every step output
+    #[default]
+    All,
+    /// Keep outputs of only the `count` most recently completed steps
+    LastN { count: usize },
+    /// Keep outputs of only terminal steps (those nothing depends on)
+    TerminalOnly,
+}
+
+impl OutputRetention {
+    /// Parse the retention policy from a workflow definition (defaults to `All`)
+    pub fn from_definition(definition: &serde_json::Value) -> Result<Self, serde_json::Error> {
+        match definition.get("output_retention") {
+            Some(value) => serde_json::from_value(value.clone()),
+            None => Ok(Self::All),
+        }
+    }
+
+    /// Prune outputs in `step_results` that this policy does not retain.
+    ///
+    /// `completed_order` lists completed step IDs, oldest first; only those
+    /// steps are candidates for pruning, so skip markers are left alone.
+    /// Returns the IDs of the steps whose outputs were pruned.
+    pub fn prune(
+        &self,
+        step_results: &mut serde_json::Value,
+        completed_order: &[String],
+        terminal_steps: &[String],
+    ) -> Vec<String> {
+        let mut pruned = Vec::new();
+        let Some(results) = step_results.as_object_mut() else {
+            return pruned;
+        };
+
+        let retained: Vec<&String> = match self {
+            Self::All => return pruned,
+            Self::LastN { count } => {
+                let mut recent = Vec::new();
+                for step_id in completed_order.iter().rev() {
+                    if recent.len() == *count {
+                        break;
+                    }
+                    if results.contains_key(step_id) && !recent.contains(&step_id) {
+                        recent.push(step_id);
+                    }
+                }
+                recent
+            }
+            Self::TerminalOnly => terminal_steps.iter().collect(),
+        };
+
+        for step_id in completed_order {
+            if retained.contains(&step_id) {
+                continue;
+            }
+            if let Some(entry) = results.get_mut(step_id) {
+                if entry.get("output_pruned").is_none() {
+                    *entry = serde_json::json!({ "status": "completed", "output_pruned": true });
+                    pruned.push(step_id.clone());
+                }
+            }
+        }
+        pruned
+    }
+}
+
 /// Workflow step execution record
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct WorkflowStepExecution {
@@ -255,3 +365,115 @@ pub struct UpdateWorkflowStepExecution {
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fd_dag::StepType;
+
+    /// Every DAG step type. The match fails to compile when a variant is
+    /// added, as a reminder to list it here and map it in both `From` impls.
+    fn all_step_types() -> Vec<StepType> {
+        let all = vec![
+            StepType::Llm,
+            StepType::Tool,
+            StepType::Condition,
+            StepType::Loop,
+            StepType::Parallel,
+            StepType::Approval,
+        ];
+        for step_type in &all {
+            match step_type {
+                StepType::Llm
+                | StepType::Tool
+                | StepType::Condition
+                | StepType::Loop
+                | StepType::Parallel
+                | StepType::Approval => {}
+            }
+        }
+        all
+    }
+
+    #[test]
+    fn test_step_type_round_trips_through_workflow_step_type() {
+        for step_type in all_step_types() {
+            let stored = WorkflowStepType::from(step_type);
+            assert_eq!(StepType::from(stored), step_type);
+            // Both enums share a wire format
+            assert_eq!(
+                serde_json::to_value(stored).unwrap(),
+                serde_json::to_value(step_type).unwrap()
+            );
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_output_retention_parses_from_definition() {
+        let definition = serde_json::json!({
+            "steps": [],
+            "output_retention": {"mode": "last_n", "count": 3}
+        });
+        assert_eq!(
+            OutputRetention::from_definition(&definition).unwrap(),
+            OutputRetention::LastN { count: 3 }
+        );
+        assert_eq!(
+            OutputRetention::from_definition(&serde_json::json!({"steps": []})).unwrap(),
+            OutputRetention::All
+        );
+        assert!(OutputRetention::from_definition(
+            &serde_json::json!({"output_retention": {"mode": "bogus"}})
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_retain_last_three_keeps_most_recent_outputs() {
+        let mut results = serde_json::json!({
+            "s1": {"v": 1},
+            "s2": {"v": 2},
+            "s3": {"v": 3},
+            "s4": {"v": 4},
+            "s5": {"v": 5},
+        });
+        let order = ids(&["s1", "s2", "s3", "s4", "s5"]);
+
+        let pruned = OutputRetention::LastN { count: 3 }.prune(&mut results, &order, &[]);
+
+        assert_eq!(pruned, ids(&["s1", "s2"]));
+        for old in ["s1", "s2"] {
+            assert_eq!(results[old]["output_pruned"], true);
+            assert_eq!(results[old]["status"], "completed");
+        }
+        assert_eq!(results["s3"], serde_json::json!({"v": 3}));
+        assert_eq!(results["s4"], serde_json::json!({"v": 4}));
+        assert_eq!(results["s5"], serde_json::json!({"v": 5}));
+
+        // Pruning again is a no-op
+        assert!(OutputRetention::LastN { count: 3 }
+            .prune(&mut results, &order, &[])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_terminal_only_keeps_exit_steps_and_skip_markers() {
+        let mut results = serde_json::json!({
+            "fetch": {"rows": 10},
+            "branch": {"skipped": true, "reason": "branch_not_taken"},
+            "report": {"summary": "ok"},
+        });
+        let order = ids(&["fetch", "report"]);
+
+        let pruned = OutputRetention::TerminalOnly.prune(&mut results, &order, &ids(&["report"]));
+
+        assert_eq!(pruned, ids(&["fetch"]));
+        assert_eq!(results["fetch"]["output_pruned"], true);
+        assert_eq!(results["branch"]["skipped"], true);
+        assert_eq!(results["report"]["summary"], "ok");
+    }
+}
@@ -6,6 +6,7 @@ use redis::aio::MultiplexedConnection;
 use redis::{AsyncCommands, RedisError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use tracing::{debug, instrument};
 
 /// Queue message wrapper
@@ -45,6 +46,205 @@ pub struct JobContext {
     pub project_id: String,
     pub trace_id: Option<String>,
     pub span_id: Option<String>,
+    /// Run metadata passed through for correlation
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+    /// Per-run secret for signing step result submissions (when enabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+    /// Allow-once token for a step resumed after approval; present it when
+    /// submitting the step's result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_token: Option<String>,
+}
+
+/// Dead-letter record for a step job that failed permanently
+///
+/// Captures the original job so the failure can be inspected and replayed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub job: StepJob,
+    pub error: serde_json::Value,
+    pub attempts: u32,
+    pub failed_at: i64,
+}
+
+impl DeadLetter {
+    pub fn new(message: &QueueMessage<StepJob>, error: serde_json::Value) -> Self {
+        Self {
+            job: message.payload.clone(),
+            error,
+            attempts: message.attempts,
+            failed_at: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// Kind of workflow progress event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowEventKind {
+    RunStarted,
+    RunPaused,
+    RunResumed,
+    RunCompleted,
+    RunFailed,
+    RunCancelled,
+    StepCompleted,
+    StepFailed,
+    StepSkipped,
+    StepWaitingApproval,
+}
+
+/// Lightweight workflow progress event for external consumers
+///
+/// Published to the `workflow-events` stream on each transition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowEvent {
+    pub run_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_id: Option<String>,
+    pub event: WorkflowEventKind,
+    pub timestamp: i64,
+}
+
+impl WorkflowEvent {
+    /// Create a run-level event
+    pub fn run(run_id: impl Into<String>, event: WorkflowEventKind) -> Self {
+        Self {
+            run_id: run_id.into(),
+            step_id: None,
+            event,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// Create a step-level event
+    pub fn step(
+        run_id: impl Into<String>,
+        step_id: impl Into<String>,
+        event: WorkflowEventKind,
+    ) -> Self {
+        Self {
+            step_id: Some(step_id.into()),
+            ..Self::run(run_id, event)
+        }
+    }
+}
+
+/// What a consumer should do with a delivered message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// First delivery (or an earlier attempt never finished): execute it
+    Process,
+    /// Already processed, redelivered because the ack was lost: ack and skip
+    Duplicate,
+}
+
+impl Delivery {
+    /// Decide from the processed-set lookup for the job's logical id
+    pub fn from_processed(was_processed: bool) -> Self {
+        if was_processed {
+            Delivery::Duplicate
+        } else {
+            Delivery::Process
+        }
+    }
+}
+
+/// Key of the processed-id set guarding a queue
+fn processed_key(prefix: &str, queue: &str) -> String {
+    format!("{}processed:{}", prefix, queue)
+}
+
+/// Key of the sorted set recording when each processed id was marked
+fn processed_times_key(prefix: &str, queue: &str) -> String {
+    format!("{}:at", processed_key(prefix, queue))
+}
+
+/// Kind of short-lived key the queue client writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    /// Guards a request against being applied twice
+    Idempotency,
+    /// Suppresses a message seen recently
+    Dedup,
+    /// Processed-id set used to detect redelivery
+    Processed,
+}
+
+impl KeyKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            KeyKind::Idempotency => "idempotency",
+            KeyKind::Dedup => "dedup",
+            KeyKind::Processed => "processed",
+        }
+    }
+}
+
+/// Key of a claimed idempotency or dedup marker
+fn claim_key(prefix: &str, kind: KeyKind, name: &str) -> String {
+    format!("{}{}:{}", prefix, kind.as_str(), name)
+}
+
+/// How long each kind of short-lived key lives in Redis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyTtlConfig {
+    pub idempotency: Duration,
+    pub dedup: Duration,
+    pub processed: Duration,
+}
+
+impl Default for KeyTtlConfig {
+    fn default() -> Self {
+        Self {
+            idempotency: Duration::from_secs(24 * 60 * 60),
+            dedup: Duration::from_secs(60 * 60),
+            processed: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl KeyTtlConfig {
+    /// Read overrides (in seconds) through `lookup`, keeping defaults for
+    /// unset or unparsable values
+    ///
+    /// Variables: `IDEMPOTENCY_KEY_TTL_SECS`, `DEDUP_KEY_TTL_SECS`,
+    /// `PROCESSED_KEY_TTL_SECS`.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let secs = |name: &str, default: Duration| {
+            lookup(name)
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|secs| *secs > 0)
+                .map_or(default, Duration::from_secs)
+        };
+        let defaults = Self::default();
+        Self {
+            idempotency: secs("IDEMPOTENCY_KEY_TTL_SECS", defaults.idempotency),
+            dedup: secs("DEDUP_KEY_TTL_SECS", defaults.dedup),
+            processed: secs("PROCESSED_KEY_TTL_SECS", defaults.processed),
+        }
+    }
+
+    /// TTL for a kind of key
+    pub fn ttl(&self, kind: KeyKind) -> Duration {
+        match kind {
+            KeyKind::Idempotency => self.idempotency,
+            KeyKind::Dedup => self.dedup,
+            KeyKind::Processed => self.processed,
+        }
+    }
+
+    /// Whether a key written at `set_at` has expired by `now`
+    pub fn is_expired(&self, kind: KeyKind, set_at: Instant, now: Instant) -> bool {
+        now.saturating_duration_since(set_at) >= self.ttl(kind)
+    }
+
+    /// Latest write time (Unix ms) that has expired by `now_ms`
+    pub fn expiry_cutoff_ms(&self, kind: KeyKind, now_ms: i64) -> i64 {
+        now_ms.saturating_sub(self.ttl(kind).as_millis() as i64)
+    }
 }
 
 /// Redis queue client
@@ -55,6 +255,7 @@ pub struct JobContext {
 pub struct QueueClient {
     conn: MultiplexedConnection,
     prefix: String,
+    ttls: KeyTtlConfig,
 }
 
 impl QueueClient {
@@ -65,9 +266,21 @@ impl QueueClient {
         Ok(Self {
             conn,
             prefix: prefix.to_string(),
+            ttls: KeyTtlConfig::default(),
         })
     }
 
+    /// Use custom TTLs for idempotency, dedup and processed keys
+    pub fn with_key_ttls(mut self, ttls: KeyTtlConfig) -> Self {
+        self.ttls = ttls;
+        self
+    }
+
+    /// TTLs applied to short-lived keys
+    pub fn key_ttls(&self) -> &KeyTtlConfig {
+        &self.ttls
+    }
+
     /// Get a clone of the connection for concurrent operations
     fn conn(&self) -> MultiplexedConnection {
         self.conn.clone()
@@ -138,6 +351,17 @@ impl QueueClient {
         Ok(id)
     }
 
+    /// Route a permanently failed step job to the dead-letter queue
+    #[instrument(skip(self, job, error), fields(run_id = %job.payload.run_id, step_id = %job.payload.step_id))]
+    pub async fn enqueue_dlq(
+        &self,
+        job: &QueueMessage<StepJob>,
+        error: serde_json::Value,
+    ) -> Result<String, RedisError> {
+        let letter = QueueMessage::new(job.id.clone(), DeadLetter::new(job, error));
+        self.enqueue(queues::DLQ, &letter).await
+    }
+
     /// Dequeue messages (read from consumer group)
     #[instrument(skip(self))]
     pub async fn dequeue<T: for<'de> Deserialize<'de>>(
@@ -237,6 +461,33 @@ impl QueueClient {
         Ok(claimed)
     }
 
+    /// Read messages added after `after_id` without joining a consumer group
+    ///
+    /// For observers tailing a stream (every reader sees every message).
+    /// Never blocks, so the shared multiplexed connection is not held; poll it.
+    #[instrument(skip(self))]
+    pub async fn read_after<T: for<'de> Deserialize<'de>>(
+        &self,
+        queue: &str,
+        after_id: &str,
+        count: usize,
+    ) -> Result<Vec<(String, QueueMessage<T>)>, RedisError> {
+        let key = self.stream_key(queue);
+        let mut conn = self.conn();
+
+        // XREAD COUNT count STREAMS key id (Nil when nothing is newer)
+        let result: redis::Value = redis::cmd("XREAD")
+            .arg("COUNT")
+            .arg(count)
+            .arg("STREAMS")
+            .arg(&key)
+            .arg(after_id)
+            .query_async(&mut conn)
+            .await?;
+
+        self.parse_stream_response(result)
+    }
+
     /// Get queue length (approximate)
     #[instrument(skip(self))]
     pub async fn len(&self, queue: &str) -> Result<usize, RedisError> {
@@ -268,6 +519,154 @@ impl QueueClient {
         Ok(0)
     }
 
+    /// Record a job's logical id as processed
+    ///
+    /// Call after the job's side effects are durable and before acking, so a
+    /// crash in between leads to a skipped redelivery rather than a repeat.
+    /// The processed TTL applies to the whole set and is refreshed on every mark;
+    /// [`compact_processed`](Self::compact_processed) expires individual ids.
+    #[instrument(skip(self))]
+    pub async fn mark_processed(&self, queue: &str, job_id: &str) -> Result<(), RedisError> {
+        let key = processed_key(&self.prefix, queue);
+        let times_key = processed_times_key(&self.prefix, queue);
+        let ttl_secs = self.ttls.processed.as_secs().max(1) as i64;
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let mut conn = self.conn();
+        let _: () = redis::pipe()
+            .atomic()
+            .sadd(&key, job_id)
+            .ignore()
+            .zadd(&times_key, job_id, now_ms)
+            .ignore()
+            .expire(&key, ttl_secs)
+            .ignore()
+            .expire(&times_key, ttl_secs)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Remove processed ids marked longer ago than the processed TTL
+    ///
+    /// A busy queue keeps refreshing the set's TTL, so without this the set
+    /// only grows. Returns the number of ids removed.
+    #[instrument(skip(self))]
+    pub async fn compact_processed(&self, queue: &str, now_ms: i64) -> Result<usize, RedisError> {
+        let key = processed_key(&self.prefix, queue);
+        let times_key = processed_times_key(&self.prefix, queue);
+        let cutoff = self.ttls.expiry_cutoff_ms(KeyKind::Processed, now_ms);
+        let mut conn = self.conn();
+
+        let expired: Vec<String> = conn.zrangebyscore(&times_key, "-inf", cutoff).await?;
+        if expired.is_empty() {
+            return Ok(0);
+        }
+        let _: () = redis::pipe()
+            .atomic()
+            .srem(&key, &expired)
+            .ignore()
+            .zrem(&times_key, &expired)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        debug!(queue = %queue, removed = expired.len(), "Compacted processed set");
+        Ok(expired.len())
+    }
+
+    /// Whether a job's logical id has been marked processed
+    #[instrument(skip(self))]
+    pub async fn was_processed(&self, queue: &str, job_id: &str) -> Result<bool, RedisError> {
+        let key = processed_key(&self.prefix, queue);
+        let mut conn = self.conn();
+        conn.sismember(&key, job_id).await
+    }
+
+    /// Check a delivered message against the processed set
+    ///
+    /// Duplicates are acknowledged here so the caller only has to skip them.
+    #[instrument(skip(self))]
+    pub async fn accept_delivery(
+        &self,
+        queue: &str,
+        stream_id: &str,
+        job_id: &str,
+    ) -> Result<Delivery, RedisError> {
+        let delivery = Delivery::from_processed(self.was_processed(queue, job_id).await?);
+        if delivery == Delivery::Duplicate {
+            debug!(queue = %queue, job_id = %job_id, "Skipping already processed job");
+            self.ack(queue, stream_id).await?;
+        }
+        Ok(delivery)
+    }
+
+    /// Claim an idempotency key; false if it was already claimed and has not expired
+    #[instrument(skip(self))]
+    pub async fn claim_idempotency_key(&self, key: &str) -> Result<bool, RedisError> {
+        self.claim(KeyKind::Idempotency, key).await
+    }
+
+    /// Claim a dedup key; false if the same key was seen within the dedup TTL
+    #[instrument(skip(self))]
+    pub async fn claim_dedup_key(&self, key: &str) -> Result<bool, RedisError> {
+        self.claim(KeyKind::Dedup, key).await
+    }
+
+    /// SET NX with the kind's TTL
+    async fn claim(&self, kind: KeyKind, name: &str) -> Result<bool, RedisError> {
+        let key = claim_key(&self.prefix, kind, name);
+        let ttl_ms = self.ttls.ttl(kind).as_millis().max(1) as u64;
+        let mut conn = self.conn();
+        let result: Option<String> = redis::cmd("SET")
+            .arg(&key)
+            .arg(1)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl_ms)
+            .query_async(&mut conn)
+            .await?;
+        Ok(result.is_some())
+    }
+
+    /// Get the full set key with prefix
+    fn set_key(&self, name: &str) -> String {
+        format!("{}set:{}", self.prefix, name)
+    }
+
+    /// List the members of a set
+    #[instrument(skip(self))]
+    pub async fn set_members(&self, name: &str) -> Result<Vec<String>, RedisError> {
+        let key = self.set_key(name);
+        let mut conn = self.conn();
+        conn.smembers(&key).await
+    }
+
+    /// Add a member to a set; returns whether it was newly added
+    #[instrument(skip(self))]
+    pub async fn set_add(&self, name: &str, member: &str) -> Result<bool, RedisError> {
+        let key = self.set_key(name);
+        let mut conn = self.conn();
+        let added: i64 = conn.sadd(&key, member).await?;
+        Ok(added > 0)
+    }
+
+    /// Delete a set and all its members
+    #[instrument(skip(self))]
+    pub async fn set_delete(&self, name: &str) -> Result<(), RedisError> {
+        let key = self.set_key(name);
+        let mut conn = self.conn();
+        conn.del(&key).await
+    }
+
+    /// Remove a member from a set; returns whether it was present
+    #[instrument(skip(self))]
+    pub async fn set_remove(&self, name: &str, member: &str) -> Result<bool, RedisError> {
+        let key = self.set_key(name);
+        let mut conn = self.conn();
+        let removed: i64 = conn.srem(&key, member).await?;
+        Ok(removed > 0)
+    }
+
     /// Parse XREADGROUP response
     fn parse_stream_response<T: for<'de> Deserialize<'de>>(
         &self,
@@ -377,6 +776,19 @@ impl QueueClient {
 pub mod queues {
     pub const STEPS: &str = "steps";
     pub const DLQ: &str = "dlq";
+    /// Workflow progress events; consumers attach their own group
+    pub const WORKFLOW_EVENTS: &str = "workflow-events";
+}
+
+/// Redis set names
+pub mod sets {
+    /// Tools disabled for every tenant by the global kill switch
+    pub const KILLED_TOOLS: &str = "killed-tools";
+
+    /// Step execution IDs of a workflow run that made it onto the steps queue
+    pub fn enqueued_steps(run_id: &str) -> String {
+        format!("enqueued-steps:{}", run_id)
+    }
 }
 
 #[cfg(test)]
@@ -463,6 +875,9 @@ mod tests {
                 project_id: "prj_1".to_string(),
                 trace_id: Some("trace_abc".to_string()),
                 span_id: None,
+                metadata: serde_json::json!({}),
+                signing_secret: None,
+                approval_token: None,
             },
         };
 
@@ -506,6 +921,9 @@ mod tests {
                 project_id: "prj_rt".to_string(),
                 trace_id: Some("trace_rt".to_string()),
                 span_id: Some("span_rt".to_string()),
+                metadata: serde_json::json!({}),
+                signing_secret: None,
+                approval_token: None,
             },
         };
 
@@ -526,6 +944,9 @@ mod tests {
             project_id: "prj_full".to_string(),
             trace_id: Some("trace_full".to_string()),
             span_id: Some("span_full".to_string()),
+            metadata: serde_json::json!({}),
+            signing_secret: None,
+            approval_token: None,
         };
 
         let json = serde_json::to_string(&ctx).unwrap();
@@ -541,6 +962,9 @@ mod tests {
             project_id: "prj_min".to_string(),
             trace_id: None,
             span_id: None,
+            metadata: serde_json::json!({}),
+            signing_secret: None,
+            approval_token: None,
         };
 
         let json = serde_json::to_string(&ctx).unwrap();
@@ -580,6 +1004,9 @@ mod tests {
                 project_id: "prj_c".to_string(),
                 trace_id: None,
                 span_id: None,
+                metadata: serde_json::json!({}),
+                signing_secret: None,
+                approval_token: None,
             },
         };
 
@@ -622,6 +1049,9 @@ mod tests {
                 project_id: "p".to_string(),
                 trace_id: None,
                 span_id: None,
+                metadata: serde_json::json!({}),
+                signing_secret: None,
+                approval_token: None,
             },
         };
         let cloned = job.clone();
@@ -635,8 +1065,119 @@ mod tests {
             project_id: "prj_dbg".to_string(),
             trace_id: Some("trace".to_string()),
             span_id: None,
+            metadata: serde_json::json!({}),
+            signing_secret: None,
+            approval_token: None,
         };
         let debug = format!("{:?}", ctx);
         assert!(debug.contains("ten_dbg"));
     }
+
+    #[test]
+    fn test_dead_letter_captures_job_and_error() {
+        let job = StepJob {
+            run_id: "run_123".to_string(),
+            step_id: "stp_456".to_string(),
+            step_type: "tool".to_string(),
+            input: serde_json::json!({"tool": "http_get"}),
+            context: JobContext {
+                tenant_id: "tenant_1".to_string(),
+                project_id: "proj_1".to_string(),
+                trace_id: None,
+                span_id: None,
+                metadata: serde_json::json!({}),
+                signing_secret: None,
+                approval_token: None,
+            },
+        };
+        let mut message = QueueMessage::new("stp_456", job);
+        message.attempts = 3;
+
+        let letter = DeadLetter::new(&message, serde_json::json!({"message": "timeout"}));
+        assert_eq!(letter.attempts, 3);
+
+        let json = serde_json::to_value(&letter).unwrap();
+        assert_eq!(json["job"]["run_id"], "run_123");
+        assert_eq!(json["job"]["step_id"], "stp_456");
+        assert_eq!(json["error"]["message"], "timeout");
+    }
+
+    #[test]
+    fn test_workflow_event_serialization() {
+        let event = WorkflowEvent::step("wfr_01", "fetch", WorkflowEventKind::StepCompleted);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["run_id"], "wfr_01");
+        assert_eq!(json["step_id"], "fetch");
+        assert_eq!(json["event"], "step_completed");
+        assert!(json["timestamp"].as_i64().unwrap() > 0);
+
+        let run_event = WorkflowEvent::run("wfr_01", WorkflowEventKind::RunCompleted);
+        let json = serde_json::to_value(&run_event).unwrap();
+        assert!(json.get("step_id").is_none());
+        assert_eq!(json["event"], "run_completed");
+    }
+
+    #[test]
+    fn test_processed_job_is_skipped_on_redelivery() {
+        assert_eq!(Delivery::from_processed(false), Delivery::Process);
+        assert_eq!(Delivery::from_processed(true), Delivery::Duplicate);
+        assert_eq!(
+            processed_key("fd:queue:", queues::STEPS),
+            "fd:queue:processed:steps"
+        );
+    }
+
+    #[test]
+    fn test_processed_compaction_cutoff_keeps_fresh_ids() {
+        let ttls = KeyTtlConfig::from_lookup(|name| {
+            (name == "PROCESSED_KEY_TTL_SECS").then(|| "60".to_string())
+        });
+        let now_ms = 1_700_000_000_000;
+        let cutoff = ttls.expiry_cutoff_ms(KeyKind::Processed, now_ms);
+
+        // Ids marked at or before the cutoff are removed, later ones kept
+        assert_eq!(cutoff, now_ms - 60_000);
+        assert_eq!(ttls.expiry_cutoff_ms(KeyKind::Processed, 0), -60_000);
+        assert_eq!(
+            processed_times_key("fd:queue:", queues::STEPS),
+            "fd:queue:processed:steps:at"
+        );
+    }
+
+    #[test]
+    fn test_idempotency_key_expires_after_configured_ttl() {
+        let ttls = KeyTtlConfig::from_lookup(|name| {
+            (name == "IDEMPOTENCY_KEY_TTL_SECS").then(|| "2".to_string())
+        });
+        assert_eq!(ttls.idempotency, Duration::from_secs(2));
+        assert_eq!(ttls.dedup, KeyTtlConfig::default().dedup);
+
+        let set_at = Instant::now();
+        assert!(!ttls.is_expired(
+            KeyKind::Idempotency,
+            set_at,
+            set_at + Duration::from_secs(1)
+        ));
+        assert!(ttls.is_expired(
+            KeyKind::Idempotency,
+            set_at,
+            set_at + Duration::from_secs(2)
+        ));
+        assert!(!ttls.is_expired(KeyKind::Dedup, set_at, set_at + Duration::from_secs(2)));
+
+        assert_eq!(
+            claim_key("fd:queue:", KeyKind::Idempotency, "run_01"),
+            "fd:queue:idempotency:run_01"
+        );
+    }
+
+    #[test]
+    fn test_key_ttls_ignore_invalid_overrides() {
+        let ttls = KeyTtlConfig::from_lookup(|name| match name {
+            "DEDUP_KEY_TTL_SECS" => Some("0".to_string()),
+            "PROCESSED_KEY_TTL_SECS" => Some("soon".to_string()),
+            _ => None,
+        });
+        assert_eq!(ttls, KeyTtlConfig::default());
+    }
 }
@@ -22,8 +22,8 @@ impl AgentsRepo {
     pub async fn create(&self, agent: CreateAgent) -> Result<Agent, sqlx::Error> {
         sqlx::query_as::<_, Agent>(
             r#"
-            INSERT INTO agents (id, project_id, name, slug, description)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO agents (id, project_id, name, slug, description, external_id, max_concurrent_runs)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING *
             "#,
         )
@@ -32,10 +32,68 @@ impl AgentsRepo {
         .bind(&agent.name)
         .bind(&agent.slug)
         .bind(&agent.description)
+        .bind(&agent.external_id)
+        .bind(agent.max_concurrent_runs)
         .fetch_one(&self.pool)
         .await
     }
 
+    /// Create an agent, or return the existing one with the same external ID
+    ///
+    /// Returns the agent and whether it was newly created. Without an
+    /// external ID this always creates.
+    #[instrument(skip(self, agent), fields(agent_id = %agent.id))]
+    pub async fn create_or_get(&self, agent: CreateAgent) -> Result<(Agent, bool), sqlx::Error> {
+        let Some(external_id) = agent.external_id.clone() else {
+            return Ok((self.create(agent).await?, true));
+        };
+
+        let created = sqlx::query_as::<_, Agent>(
+            r#"
+            INSERT INTO agents (id, project_id, name, slug, description, external_id, max_concurrent_runs)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (project_id, external_id) WHERE external_id IS NOT NULL DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(&agent.id)
+        .bind(&agent.project_id)
+        .bind(&agent.name)
+        .bind(&agent.slug)
+        .bind(&agent.description)
+        .bind(&external_id)
+        .bind(agent.max_concurrent_runs)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match created {
+            Some(agent) => Ok((agent, true)),
+            None => {
+                let existing = self
+                    .find_by_external_id(&agent.project_id, &external_id)
+                    .await?
+                    .ok_or(sqlx::Error::RowNotFound)?;
+                Ok((existing, false))
+            }
+        }
+    }
+
+    /// Get an agent by its external ID within a project
+    #[instrument(skip(self))]
+    pub async fn find_by_external_id(
+        &self,
+        project_id: &str,
+        external_id: &str,
+    ) -> Result<Option<Agent>, sqlx::Error> {
+        sqlx::query_as::<_, Agent>(
+            "SELECT * FROM agents WHERE project_id = $1 AND external_id = $2",
+        )
+        .bind(project_id)
+        .bind(external_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
     /// Get an agent by ID
     #[instrument(skip(self))]
     pub async fn get(&self, id: &str) -> Result<Option<Agent>, sqlx::Error> {
@@ -59,13 +117,14 @@ impl AgentsRepo {
             .await
     }
 
-    /// Find an agent by slug (global lookup without project_id)
-    /// Used when caller provides a slug instead of an ID
+    /// List agents with a slug across all projects (global lookup without project_id)
+    /// Used when caller provides a slug instead of an ID; slugs are only
+    /// unique per project, so this may return several agents
     #[instrument(skip(self))]
-    pub async fn find_by_slug(&self, slug: &str) -> Result<Option<Agent>, sqlx::Error> {
-        sqlx::query_as::<_, Agent>("SELECT * FROM agents WHERE slug = $1")
+    pub async fn list_by_slug(&self, slug: &str) -> Result<Vec<Agent>, sqlx::Error> {
+        sqlx::query_as::<_, Agent>("SELECT * FROM agents WHERE slug = $1 ORDER BY created_at ASC")
             .bind(slug)
-            .fetch_optional(&self.pool)
+            .fetch_all(&self.pool)
             .await
     }
 
@@ -204,7 +263,7 @@ impl AgentsRepo {
             .await
     }
 
-    /// Get the latest version of an agent
+    /// Get the most recently created version of an agent
     #[instrument(skip(self))]
     pub async fn get_latest_version(
         &self,
@@ -223,6 +282,91 @@ impl AgentsRepo {
         .await
     }
 
+    /// Get the promoted (production) version of an agent
+    #[instrument(skip(self))]
+    pub async fn get_promoted_version(
+        &self,
+        agent_id: &str,
+    ) -> Result<Option<AgentVersion>, sqlx::Error> {
+        sqlx::query_as::<_, AgentVersion>(
+            "SELECT * FROM agent_versions WHERE agent_id = $1 AND promoted",
+        )
+        .bind(agent_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Get the version a run uses when none is pinned
+    ///
+    /// The promoted version if there is one, otherwise the most recently
+    /// created.
+    #[instrument(skip(self))]
+    pub async fn get_default_version(
+        &self,
+        agent_id: &str,
+    ) -> Result<Option<AgentVersion>, sqlx::Error> {
+        sqlx::query_as::<_, AgentVersion>(
+            r#"
+            SELECT * FROM agent_versions
+            WHERE agent_id = $1
+            ORDER BY promoted DESC, created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(agent_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Promote a version to be the agent's production default
+    ///
+    /// Demotes any previously promoted version in the same transaction.
+    /// Returns None if the version does not belong to the agent.
+    #[instrument(skip(self))]
+    pub async fn promote_version(
+        &self,
+        agent_id: &str,
+        version_id: &str,
+    ) -> Result<Option<AgentVersion>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE agent_versions
+            SET promoted = FALSE, promoted_at = NULL
+            WHERE agent_id = $1 AND promoted AND id <> $2
+            "#,
+        )
+        .bind(agent_id)
+        .bind(version_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let promoted = sqlx::query_as::<_, AgentVersion>(
+            r#"
+            UPDATE agent_versions
+            SET promoted = TRUE, promoted_at = COALESCE(promoted_at, NOW())
+            WHERE id = $1 AND agent_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(version_id)
+        .bind(agent_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match promoted {
+            Some(version) => {
+                tx.commit().await?;
+                Ok(Some(version))
+            }
+            None => {
+                tx.rollback().await?;
+                Ok(None)
+            }
+        }
+    }
+
     /// List all versions of an agent
     #[instrument(skip(self))]
     pub async fn list_versions(&self, agent_id: &str) -> Result<Vec<AgentVersion>, sqlx::Error> {
@@ -238,3 +382,110 @@ impl AgentsRepo {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seeded by `20241223000002_seed_dev_data.sql`
+    const SEED_PROJECT: &str = "prj_01JFVX0000000000000000001";
+
+    fn new_version(agent_id: &str, version: &str) -> CreateAgentVersion {
+        CreateAgentVersion {
+            id: format!("agv_{}", ulid::Ulid::new()),
+            agent_id: agent_id.to_string(),
+            version: version.to_string(),
+            system_prompt: "You are helpful".to_string(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            model_params: serde_json::json!({}),
+            allowed_tools: vec![],
+            tool_configs: serde_json::json!({}),
+            max_tokens: None,
+            max_tool_calls: None,
+            max_wall_time_secs: None,
+            max_cost_cents: None,
+            changelog: None,
+            created_by: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_default_version_is_promoted_over_newer() {
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let agents = AgentsRepo::new(pool);
+
+        let slug = format!("default-version-{}", ulid::Ulid::new()).to_lowercase();
+        let agent = agents
+            .create(CreateAgent {
+                id: format!("agt_{}", ulid::Ulid::new()),
+                project_id: SEED_PROJECT.to_string(),
+                name: slug.clone(),
+                slug,
+                description: None,
+                external_id: None,
+                max_concurrent_runs: None,
+            })
+            .await
+            .unwrap();
+        let old = agents
+            .create_version(new_version(&agent.id, "1.0.0"))
+            .await
+            .unwrap();
+        let new = agents
+            .create_version(new_version(&agent.id, "1.1.0"))
+            .await
+            .unwrap();
+
+        // Without a promoted version the latest one is the default
+        assert_eq!(
+            agents
+                .get_default_version(&agent.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .id,
+            new.id
+        );
+
+        // A promoted version wins over newer ones
+        agents.promote_version(&agent.id, &old.id).await.unwrap();
+        assert_eq!(
+            agents
+                .get_default_version(&agent.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .id,
+            old.id
+        );
+        agents
+            .create_version(new_version(&agent.id, "1.2.0"))
+            .await
+            .unwrap();
+        assert_eq!(
+            agents
+                .get_default_version(&agent.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .id,
+            old.id
+        );
+
+        // Promoting another version demotes the previous one
+        agents.promote_version(&agent.id, &new.id).await.unwrap();
+        let promoted = agents
+            .get_default_version(&agent.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(promoted.id, new.id);
+        assert!(promoted.promoted);
+        let old = agents.get_version(&old.id).await.unwrap().unwrap();
+        assert!(!old.promoted);
+    }
+}
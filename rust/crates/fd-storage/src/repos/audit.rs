@@ -2,6 +2,7 @@
 
 use crate::models::{AuditEvent, CreateAuditEvent};
 use crate::DbPool;
+use sqlx::PgExecutor;
 use tracing::instrument;
 
 /// Repository for audit event operations
@@ -18,35 +19,7 @@ impl AuditRepo {
     /// Create an audit event
     #[instrument(skip(self, event), fields(event_id = %event.id))]
     pub async fn create(&self, event: CreateAuditEvent) -> Result<AuditEvent, sqlx::Error> {
-        sqlx::query_as::<_, AuditEvent>(
-            r#"
-            INSERT INTO audit_events (
-                id, actor_type, actor_id, action, resource_type, resource_id,
-                details, tenant_id, workspace_id, project_id, run_id,
-                request_id, ip_address, user_agent, trace_id, span_id
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13::inet, $14, $15, $16)
-            RETURNING *
-            "#,
-        )
-        .bind(&event.id)
-        .bind(&event.actor_type)
-        .bind(&event.actor_id)
-        .bind(&event.action)
-        .bind(&event.resource_type)
-        .bind(&event.resource_id)
-        .bind(&event.details)
-        .bind(&event.tenant_id)
-        .bind(&event.workspace_id)
-        .bind(&event.project_id)
-        .bind(&event.run_id)
-        .bind(&event.request_id)
-        .bind(&event.ip_address)
-        .bind(&event.user_agent)
-        .bind(&event.trace_id)
-        .bind(&event.span_id)
-        .fetch_one(&self.pool)
-        .await
+        insert_audit_event(&self.pool, &event).await
     }
 
     /// List audit events for a run
@@ -131,3 +104,42 @@ impl AuditRepo {
         .await
     }
 }
+
+/// Insert an audit event, possibly inside a caller's transaction
+pub(crate) async fn insert_audit_event<'e, E: PgExecutor<'e>>(
+    executor: E,
+    event: &CreateAuditEvent,
+) -> Result<AuditEvent, sqlx::Error> {
+    sqlx::query_as::<_, AuditEvent>(
+            r#"
+            INSERT INTO audit_events (
+                id, actor_type, actor_id, action, resource_type, resource_id,
+                details, tenant_id, workspace_id, project_id, run_id,
+                request_id, ip_address, user_agent, trace_id, span_id,
+                severity, risk_score
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13::inet, $14, $15, $16, $17, $18)
+            RETURNING *
+            "#,
+        )
+        .bind(&event.id)
+        .bind(&event.actor_type)
+        .bind(&event.actor_id)
+        .bind(&event.action)
+        .bind(&event.resource_type)
+        .bind(&event.resource_id)
+        .bind(&event.details)
+        .bind(&event.tenant_id)
+        .bind(&event.workspace_id)
+        .bind(&event.project_id)
+        .bind(&event.run_id)
+        .bind(&event.request_id)
+        .bind(&event.ip_address)
+        .bind(&event.user_agent)
+        .bind(&event.trace_id)
+        .bind(&event.span_id)
+        .bind(&event.severity)
+        .bind(event.risk_score)
+        .fetch_one(executor)
+        .await
+}
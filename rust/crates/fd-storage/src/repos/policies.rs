@@ -1,11 +1,14 @@
 //! Policies repository
 
 use crate::models::{
-    ApprovalRequest, CreateApprovalRequest, CreatePolicyDecision, CreatePolicyRule, PolicyDecision,
-    PolicyEffect, PolicyRule, ResolveApproval, UpdatePolicyRule,
+    ApprovalActionType, ApprovalRequest, ApprovalStatus, ApprovalTokenClaims, ApprovalTokenError,
+    ApprovalVote, CreateApprovalRequest, CreateAuditEvent, CreatePolicyDecision, CreatePolicyRule,
+    PolicyDecision, PolicyEffect, PolicyRule, ResolveApproval, UpdatePolicyRule,
 };
+use crate::repos::audit::insert_audit_event;
 use crate::DbPool;
 use chrono::Utc;
+use sqlx::PgExecutor;
 use tracing::instrument;
 
 /// Repository for policy operations
@@ -164,24 +167,24 @@ impl PoliciesRepo {
         &self,
         decision: CreatePolicyDecision,
     ) -> Result<PolicyDecision, sqlx::Error> {
-        sqlx::query_as::<_, PolicyDecision>(
-            r#"
-            INSERT INTO policy_decisions (id, run_id, step_id, action_type, action_details, decision, matched_rule_id, reason, evaluation_time_ms)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            RETURNING *
-            "#,
-        )
-        .bind(&decision.id)
-        .bind(&decision.run_id)
-        .bind(&decision.step_id)
-        .bind(&decision.action_type)
-        .bind(&decision.action_details)
-        .bind(decision.decision)
-        .bind(&decision.matched_rule_id)
-        .bind(&decision.reason)
-        .bind(decision.evaluation_time_ms)
-        .fetch_one(&self.pool)
-        .await
+        insert_decision(&self.pool, &decision).await
+    }
+
+    /// Record a policy decision and its audit event in one transaction
+    ///
+    /// A failed audit insert rolls the decision back, so every stored
+    /// decision has its audit record.
+    #[instrument(skip(self, decision, audit), fields(decision_id = %decision.id))]
+    pub async fn create_decision_with_audit(
+        &self,
+        decision: CreatePolicyDecision,
+        audit: &CreateAuditEvent,
+    ) -> Result<PolicyDecision, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let created = insert_decision(&mut *tx, &decision).await?;
+        insert_audit_event(&mut *tx, audit).await?;
+        tx.commit().await?;
+        Ok(created)
     }
 
     /// List decisions for a run
@@ -235,8 +238,8 @@ impl PoliciesRepo {
     ) -> Result<ApprovalRequest, sqlx::Error> {
         sqlx::query_as::<_, ApprovalRequest>(
             r#"
-            INSERT INTO approval_requests (id, run_id, step_id, policy_decision_id, action_type, action_details, reason, expires_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            INSERT INTO approval_requests (id, run_id, step_id, policy_decision_id, action_type, action_details, reason, expires_at, required_approvals)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             RETURNING *
             "#,
         )
@@ -244,10 +247,11 @@ impl PoliciesRepo {
         .bind(&approval.run_id)
         .bind(&approval.step_id)
         .bind(&approval.policy_decision_id)
-        .bind(&approval.action_type)
+        .bind(approval.action_type)
         .bind(&approval.action_details)
         .bind(&approval.reason)
         .bind(approval.expires_at)
+        .bind(approval.required_approvals as i32)
         .fetch_one(&self.pool)
         .await
     }
@@ -261,7 +265,10 @@ impl PoliciesRepo {
             .await
     }
 
-    /// Resolve an approval request
+    /// Resolve a pending approval request
+    ///
+    /// Returns `None` if the request does not exist or was already resolved,
+    /// so only one caller can resolve it.
     #[instrument(skip(self, resolution), fields(approval_id = %id))]
     pub async fn resolve_approval(
         &self,
@@ -272,7 +279,7 @@ impl PoliciesRepo {
             r#"
             UPDATE approval_requests
             SET status = $2, resolved_by = $3, resolved_at = $4, resolution_note = $5
-            WHERE id = $1
+            WHERE id = $1 AND status = 'pending'
             RETURNING *
             "#,
         )
@@ -285,6 +292,124 @@ impl PoliciesRepo {
         .await
     }
 
+    /// Issue an allow-once token for an approved request's tool call
+    ///
+    /// The token is signed with `key`, bound to the approval's run and step
+    /// and to `tool_name`, and expires after `ttl`.
+    #[instrument(skip(self, key))]
+    pub async fn issue_approval_token(
+        &self,
+        approval_id: &str,
+        tool_name: &str,
+        key: &[u8],
+        ttl: chrono::Duration,
+    ) -> Result<String, ApprovalTokenError> {
+        let approval = self
+            .get_approval(approval_id)
+            .await?
+            .filter(|approval| approval.status == ApprovalStatus::Approved)
+            .ok_or(ApprovalTokenError::NotApproved)?;
+
+        let claims = ApprovalTokenClaims {
+            token_id: format!("apt_{}", ulid::Ulid::new()),
+            approval_id: approval.id,
+            run_id: approval.run_id,
+            step_id: approval.step_id,
+            tool_name: tool_name.to_string(),
+            expires_at: Utc::now() + ttl,
+        };
+        Ok(claims.sign(key))
+    }
+
+    /// Verify an approval token for a tool call and spend it
+    ///
+    /// Fails if the token is forged, expired, bound to a different call, or
+    /// was already used.
+    #[instrument(skip(self, key, token))]
+    pub async fn verify_approval_token(
+        &self,
+        key: &[u8],
+        token: &str,
+        run_id: &str,
+        step_id: &str,
+        tool_name: &str,
+    ) -> Result<ApprovalTokenClaims, ApprovalTokenError> {
+        let claims = ApprovalTokenClaims::verify(key, token, Utc::now())?;
+        claims.authorizes(run_id, step_id, tool_name)?;
+
+        let spent = sqlx::query(
+            r#"
+            INSERT INTO approval_token_uses (token_id, approval_id)
+            VALUES ($1, $2)
+            ON CONFLICT (token_id) DO NOTHING
+            "#,
+        )
+        .bind(&claims.token_id)
+        .bind(&claims.approval_id)
+        .execute(&self.pool)
+        .await?;
+
+        if spent.rows_affected() == 0 {
+            return Err(ApprovalTokenError::AlreadyUsed);
+        }
+        Ok(claims)
+    }
+
+    /// Approved request for a step whose allow-once token is still unspent
+    ///
+    /// A step resumed from such an approval must present its token when its
+    /// result is submitted.
+    #[instrument(skip(self))]
+    pub async fn unspent_approval_for_step(
+        &self,
+        step_id: &str,
+    ) -> Result<Option<ApprovalRequest>, sqlx::Error> {
+        sqlx::query_as::<_, ApprovalRequest>(
+            r#"
+            SELECT a.* FROM approval_requests a
+            WHERE a.step_id = $1 AND a.status = 'approved'
+              AND NOT EXISTS (
+                  SELECT 1 FROM approval_token_uses u WHERE u.approval_id = a.id
+              )
+            ORDER BY a.resolved_at DESC NULLS LAST
+            LIMIT 1
+            "#,
+        )
+        .bind(step_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Append an approver's vote to a still-pending request
+    ///
+    /// The append happens in one statement, so concurrent voters never
+    /// overwrite each other. Returns `None` when the request is no longer
+    /// pending or `vote.approver` has already voted on it.
+    #[instrument(skip(self, vote), fields(approval_id = %id, approver = %vote.approver))]
+    pub async fn record_approval_vote(
+        &self,
+        id: &str,
+        vote: &ApprovalVote,
+    ) -> Result<Option<ApprovalRequest>, sqlx::Error> {
+        sqlx::query_as::<_, ApprovalRequest>(
+            r#"
+            UPDATE approval_requests
+            SET approvals = approvals || $2
+            WHERE id = $1 AND status = 'pending'
+              AND NOT EXISTS (
+                  SELECT 1 FROM jsonb_array_elements(approvals) AS v
+                  WHERE v->>'approver' = $3
+              )
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(sqlx::types::Json([vote]))
+        .bind(&vote.approver)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
     /// List pending approvals for a run
     #[instrument(skip(self))]
     pub async fn list_pending_approvals(
@@ -303,20 +428,34 @@ impl PoliciesRepo {
         .await
     }
 
-    /// Get pending approvals globally (for admin view)
+    /// Count a run's pending approvals
+    #[instrument(skip(self))]
+    pub async fn count_pending_approvals(&self, run_id: &str) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM approval_requests WHERE run_id = $1 AND status = 'pending'",
+        )
+        .bind(run_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Get pending approvals globally (for admin view), optionally of one action type
     #[instrument(skip(self))]
     pub async fn list_all_pending_approvals(
         &self,
+        action_type: Option<ApprovalActionType>,
         limit: i64,
     ) -> Result<Vec<ApprovalRequest>, sqlx::Error> {
         sqlx::query_as::<_, ApprovalRequest>(
             r#"
             SELECT * FROM approval_requests
             WHERE status = 'pending'
+              AND ($1::text IS NULL OR action_type = $1)
             ORDER BY created_at ASC
-            LIMIT $1
+            LIMIT $2
             "#,
         )
+        .bind(action_type)
         .bind(limit)
         .fetch_all(&self.pool)
         .await
@@ -337,3 +476,272 @@ impl PoliciesRepo {
         Ok(result.rows_affected())
     }
 }
+
+/// Insert a policy decision on any executor (pool or transaction)
+async fn insert_decision<'e, E: PgExecutor<'e>>(
+    executor: E,
+    decision: &CreatePolicyDecision,
+) -> Result<PolicyDecision, sqlx::Error> {
+    sqlx::query_as::<_, PolicyDecision>(
+        r#"
+        INSERT INTO policy_decisions (id, run_id, step_id, action_type, action_details, decision, matched_rule_id, reason, evaluation_time_ms)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(&decision.id)
+    .bind(&decision.run_id)
+    .bind(&decision.step_id)
+    .bind(&decision.action_type)
+    .bind(&decision.action_details)
+    .bind(decision.decision)
+    .bind(&decision.matched_rule_id)
+    .bind(&decision.reason)
+    .bind(decision.evaluation_time_ms)
+    .fetch_one(executor)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateRun, CreateStep, StepType};
+    use crate::repos::{RunsRepo, StepsRepo};
+
+    /// Seeded by `20241223000002_seed_dev_data.sql`
+    const SEED_PROJECT: &str = "prj_01JFVX0000000000000000001";
+    const SEED_AGENT_VERSION: &str = "agv_01JFVX0000000000000000001";
+
+    /// Connect to the test database and seed a run with one tool step
+    ///
+    /// Returns the repo and the IDs of the run, step and policy decision.
+    async fn seed_tool_step() -> (PoliciesRepo, String, String, String) {
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let policies = PoliciesRepo::new(pool.clone());
+
+        let run_id = format!("run_{}", ulid::Ulid::new());
+        RunsRepo::new(pool.clone())
+            .create(CreateRun {
+                id: run_id.clone(),
+                project_id: SEED_PROJECT.to_string(),
+                agent_version_id: SEED_AGENT_VERSION.to_string(),
+                input: serde_json::json!({}),
+                config: serde_json::json!({}),
+                trace_id: None,
+                span_id: None,
+                metadata: serde_json::json!({}),
+                budget_snapshot: None,
+            })
+            .await
+            .unwrap();
+        let step_id = format!("stp_{}", ulid::Ulid::new());
+        StepsRepo::new(pool.clone())
+            .create(CreateStep {
+                id: step_id.clone(),
+                run_id: run_id.clone(),
+                parent_step_id: None,
+                step_number: 1,
+                step_type: StepType::Tool,
+                input: serde_json::json!({}),
+                tool_name: Some("deploy".to_string()),
+                tool_version: None,
+                model: None,
+                span_id: None,
+            })
+            .await
+            .unwrap();
+        let decision_id = format!("pdc_{}", ulid::Ulid::new());
+        policies
+            .create_decision(CreatePolicyDecision {
+                id: decision_id.clone(),
+                run_id: Some(run_id.clone()),
+                step_id: Some(step_id.clone()),
+                action_type: "tool_call".to_string(),
+                action_details: serde_json::json!({}),
+                decision: PolicyEffect::RequireApproval,
+                matched_rule_id: None,
+                reason: "needs review".to_string(),
+                evaluation_time_ms: None,
+            })
+            .await
+            .unwrap();
+
+        (policies, run_id, step_id, decision_id)
+    }
+
+    fn approval_request(
+        run_id: &str,
+        step_id: &str,
+        decision_id: &str,
+        action_type: ApprovalActionType,
+        required_approvals: u32,
+    ) -> CreateApprovalRequest {
+        CreateApprovalRequest {
+            id: format!("apr_{}", ulid::Ulid::new()),
+            run_id: run_id.to_string(),
+            step_id: step_id.to_string(),
+            policy_decision_id: decision_id.to_string(),
+            action_type,
+            action_details: serde_json::json!({}),
+            reason: "needs review".to_string(),
+            expires_at: None,
+            required_approvals,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_pending_approvals_filter_by_action_type() {
+        let (policies, run_id, step_id, decision_id) = seed_tool_step().await;
+
+        let mut created = Vec::new();
+        for action_type in [ApprovalActionType::ToolCall, ApprovalActionType::BreakGlass] {
+            let approval = policies
+                .create_approval(approval_request(
+                    &run_id,
+                    &step_id,
+                    &decision_id,
+                    action_type,
+                    1,
+                ))
+                .await
+                .unwrap();
+            assert_eq!(approval.action_type, action_type);
+            created.push(approval.id);
+        }
+
+        let listed = |filter| {
+            let policies = &policies;
+            let run_id = &run_id;
+            async move {
+                policies
+                    .list_all_pending_approvals(filter, 10_000)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .filter(|a| &a.run_id == run_id)
+                    .map(|a| a.id)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(listed(None).await, created);
+        assert_eq!(
+            listed(Some(ApprovalActionType::BreakGlass)).await,
+            vec![created[1].clone()]
+        );
+        assert!(listed(Some(ApprovalActionType::BudgetOverride))
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_approved_step_awaits_its_token_until_spent() {
+        let (policies, run_id, step_id, decision_id) = seed_tool_step().await;
+        let approval = policies
+            .create_approval(approval_request(
+                &run_id,
+                &step_id,
+                &decision_id,
+                ApprovalActionType::ToolCall,
+                1,
+            ))
+            .await
+            .unwrap();
+        assert!(policies
+            .unspent_approval_for_step(&step_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        policies
+            .resolve_approval(
+                &approval.id,
+                ResolveApproval {
+                    status: ApprovalStatus::Approved,
+                    resolved_by: "key_reviewer".to_string(),
+                    resolution_note: None,
+                },
+            )
+            .await
+            .unwrap();
+        let awaiting = policies.unspent_approval_for_step(&step_id).await.unwrap();
+        assert_eq!(awaiting.map(|a| a.id), Some(approval.id.clone()));
+
+        let key = b"token-key";
+        let token = policies
+            .issue_approval_token(&approval.id, "deploy", key, chrono::Duration::minutes(5))
+            .await
+            .unwrap();
+        policies
+            .verify_approval_token(key, &token, &run_id, &step_id, "deploy")
+            .await
+            .unwrap();
+        assert!(policies
+            .unspent_approval_for_step(&step_id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_concurrent_votes_are_all_kept_and_resolve_once() {
+        let (policies, run_id, step_id, decision_id) = seed_tool_step().await;
+        let approval = policies
+            .create_approval(approval_request(
+                &run_id,
+                &step_id,
+                &decision_id,
+                ApprovalActionType::ToolCall,
+                2,
+            ))
+            .await
+            .unwrap();
+        let vote = |approver: &str| ApprovalVote {
+            approver: approver.to_string(),
+            approved_at: Utc::now(),
+        };
+
+        let (alice, bob) = (vote("key_alice"), vote("key_bob"));
+        let (alice, bob) = tokio::join!(
+            policies.record_approval_vote(&approval.id, &alice),
+            policies.record_approval_vote(&approval.id, &bob)
+        );
+        assert!(alice.unwrap().is_some() && bob.unwrap().is_some());
+        let stored = policies.get_approval(&approval.id).await.unwrap().unwrap();
+        assert_eq!(stored.approval_count(), 2);
+        assert!(stored.quorum_reached());
+
+        // A repeat vote from the same approver is refused
+        assert!(policies
+            .record_approval_vote(&approval.id, &vote("key_alice"))
+            .await
+            .unwrap()
+            .is_none());
+
+        let resolution = || ResolveApproval {
+            status: ApprovalStatus::Approved,
+            resolved_by: "key_bob".to_string(),
+            resolution_note: None,
+        };
+        assert!(policies
+            .resolve_approval(&approval.id, resolution())
+            .await
+            .unwrap()
+            .is_some());
+        assert!(policies
+            .resolve_approval(&approval.id, resolution())
+            .await
+            .unwrap()
+            .is_none());
+        assert!(policies
+            .record_approval_vote(&approval.id, &vote("key_carol"))
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1,9 +1,23 @@
 //! Runs repository
 
-use crate::models::{CreateRun, Run, RunStatus, UpdateRun};
+use crate::models::{
+    Agent, ArchivedRun, CreateAuditEvent, CreateRun, PolicyDecision, Run, RunExportStream,
+    RunStatus, RunWithSteps, Step, Threat, UpdateRun,
+};
+use crate::repos::audit::insert_audit_event;
 use crate::DbPool;
-use sqlx::Row;
-use tracing::instrument;
+use futures_util::StreamExt;
+use sqlx::{PgExecutor, Row};
+use std::time::Duration;
+use tokio::sync::mpsc::error::SendTimeoutError;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{instrument, warn};
+
+/// Step rows read ahead of a slow export reader
+const EXPORT_STEP_BUFFER: usize = 32;
+
+/// How long an export waits for its reader to make room for the next step
+const EXPORT_SEND_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Repository for run operations
 #[derive(Clone)]
@@ -19,22 +33,58 @@ impl RunsRepo {
     /// Create a new run
     #[instrument(skip(self, run), fields(run_id = %run.id))]
     pub async fn create(&self, run: CreateRun) -> Result<Run, sqlx::Error> {
-        sqlx::query_as::<_, Run>(
-            r#"
-            INSERT INTO runs (id, project_id, agent_version_id, input, config, trace_id, span_id)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
-            RETURNING *
-            "#,
-        )
-        .bind(&run.id)
-        .bind(&run.project_id)
-        .bind(&run.agent_version_id)
-        .bind(&run.input)
-        .bind(&run.config)
-        .bind(&run.trace_id)
-        .bind(&run.span_id)
-        .fetch_one(&self.pool)
-        .await
+        insert_run(&self.pool, &run).await
+    }
+
+    /// Create a run unless the agent is already at its concurrency limit
+    ///
+    /// The agent row is locked while its active (non-terminal) runs are
+    /// counted, so concurrent creates cannot both slip under the cap.
+    /// The run's audit event is written in the same transaction as the run.
+    /// Returns `None` when the agent has no capacity left.
+    #[instrument(skip(self, run, agent, audit), fields(run_id = %run.id, agent_id = %agent.id))]
+    pub async fn create_within_agent_limit(
+        &self,
+        run: CreateRun,
+        agent: &Agent,
+        audit: &CreateAuditEvent,
+    ) -> Result<Option<Run>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        if agent.max_concurrent_runs.is_some() {
+            let terminal: Vec<&str> = RunStatus::terminal_statuses()
+                .iter()
+                .map(RunStatus::as_str)
+                .collect();
+
+            sqlx::query("SELECT id FROM agents WHERE id = $1 FOR UPDATE")
+                .bind(&agent.id)
+                .execute(&mut *tx)
+                .await?;
+
+            let active: i64 = sqlx::query_scalar(
+                r#"
+                SELECT COUNT(*) FROM runs r
+                JOIN agent_versions av ON av.id = r.agent_version_id
+                WHERE av.agent_id = $1
+                  AND NOT (r.status::text = ANY($2))
+                "#,
+            )
+            .bind(&agent.id)
+            .bind(&terminal)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if !agent.has_run_capacity(active) {
+                tx.rollback().await?;
+                return Ok(None);
+            }
+        }
+
+        let created = insert_run(&mut *tx, &run).await?;
+        insert_audit_event(&mut *tx, audit).await?;
+        tx.commit().await?;
+        Ok(Some(created))
     }
 
     /// Get a run by ID
@@ -46,99 +96,332 @@ impl RunsRepo {
             .await
     }
 
-    /// Update a run
-    #[instrument(skip(self, update), fields(run_id = %id))]
-    pub async fn update(&self, id: &str, update: UpdateRun) -> Result<Option<Run>, sqlx::Error> {
-        // Build dynamic update query
-        let mut set_clauses = Vec::new();
-        let mut param_idx = 2; // $1 is the id
+    /// Get an archived run by ID
+    #[instrument(skip(self))]
+    pub async fn get_archived(&self, id: &str) -> Result<Option<ArchivedRun>, sqlx::Error> {
+        sqlx::query_as::<_, ArchivedRun>("SELECT * FROM archived_runs WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
 
-        if update.status.is_some() {
-            set_clauses.push(format!("status = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.status_reason.is_some() {
-            set_clauses.push(format!("status_reason = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.input_tokens.is_some() {
-            set_clauses.push(format!("input_tokens = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.output_tokens.is_some() {
-            set_clauses.push(format!("output_tokens = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.tool_calls.is_some() {
-            set_clauses.push(format!("tool_calls = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.cost_cents.is_some() {
-            set_clauses.push(format!("cost_cents = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.started_at.is_some() {
-            set_clauses.push(format!("started_at = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.completed_at.is_some() {
-            set_clauses.push(format!("completed_at = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.output.is_some() {
-            set_clauses.push(format!("output = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.error.is_some() {
-            set_clauses.push(format!("error = ${}", param_idx));
-        }
+    /// Load a run with its policy decisions and threats, streaming its steps
+    ///
+    /// Everything is read in one repeatable-read transaction so the export
+    /// is a consistent snapshot even while the run is still executing. The
+    /// transaction stays open while steps are streamed and ends once the
+    /// stream is exhausted, fails or is dropped. Values are returned as
+    /// stored; callers are responsible for redaction.
+    ///
+    /// A reader that leaves the step buffer full for 30 seconds is
+    /// considered stalled: the transaction is rolled back, returning its
+    /// connection to the pool, and the stream ends with a timeout error
+    /// after the steps already buffered.
+    #[instrument(skip(self))]
+    pub async fn export_stream(
+        &self,
+        run_id: &str,
+    ) -> Result<Option<RunExportStream>, sqlx::Error> {
+        self.export_stream_within(run_id, EXPORT_SEND_TIMEOUT).await
+    }
 
-        if set_clauses.is_empty() {
-            return self.get(id).await;
-        }
+    async fn export_stream_within(
+        &self,
+        run_id: &str,
+        send_timeout: Duration,
+    ) -> Result<Option<RunExportStream>, sqlx::Error> {
+        let (head_tx, head_rx) = oneshot::channel();
+        let (step_tx, step_rx) = mpsc::channel(EXPORT_STEP_BUFFER);
+        let pool = self.pool.clone();
+        let run_id = run_id.to_string();
 
-        let query = format!(
-            "UPDATE runs SET {} WHERE id = $1 RETURNING *",
-            set_clauses.join(", ")
-        );
+        // The step cursor borrows the transaction, so both live in a task
+        // that outlives this call and feeds rows through a bounded channel
+        tokio::spawn(async move {
+            let head = async {
+                let mut tx = pool.begin().await?;
+                sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+                    .execute(&mut *tx)
+                    .await?;
 
-        let mut q = sqlx::query_as::<_, Run>(&query).bind(id);
+                let Some(run) = sqlx::query_as::<_, Run>("SELECT * FROM runs WHERE id = $1")
+                    .bind(&run_id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                else {
+                    return Ok(None);
+                };
 
-        if let Some(status) = &update.status {
-            q = q.bind(status);
-        }
-        if let Some(reason) = &update.status_reason {
-            q = q.bind(reason);
-        }
-        if let Some(tokens) = &update.input_tokens {
-            q = q.bind(tokens);
-        }
-        if let Some(tokens) = &update.output_tokens {
-            q = q.bind(tokens);
-        }
-        if let Some(calls) = &update.tool_calls {
-            q = q.bind(calls);
-        }
-        if let Some(cost) = &update.cost_cents {
-            q = q.bind(cost);
-        }
-        if let Some(started) = &update.started_at {
-            q = q.bind(started);
-        }
-        if let Some(completed) = &update.completed_at {
-            q = q.bind(completed);
-        }
-        if let Some(output) = &update.output {
-            q = q.bind(output);
-        }
-        if let Some(error) = &update.error {
-            q = q.bind(error);
-        }
+                let policy_decisions = sqlx::query_as::<_, PolicyDecision>(
+                    "SELECT * FROM policy_decisions WHERE run_id = $1 ORDER BY evaluated_at ASC",
+                )
+                .bind(&run_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+                let threats = sqlx::query_as::<_, Threat>(
+                    "SELECT * FROM threats WHERE run_id = $1 ORDER BY created_at ASC",
+                )
+                .bind(&run_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+                Ok(Some((tx, run, policy_decisions, threats)))
+            }
+            .await;
+
+            let mut tx = match head {
+                Ok(Some((tx, run, policy_decisions, threats))) => {
+                    let steps = futures_util::stream::unfold(step_rx, |mut rx| async move {
+                        rx.recv().await.map(|step| (step, rx))
+                    })
+                    .boxed();
+                    let export = RunExportStream {
+                        run,
+                        policy_decisions,
+                        threats,
+                        steps,
+                    };
+                    if head_tx.send(Ok(Some(export))).is_err() {
+                        return;
+                    }
+                    tx
+                }
+                Ok(None) => {
+                    let _ = head_tx.send(Ok(None));
+                    return;
+                }
+                Err(e) => {
+                    let _ = head_tx.send(Err(e));
+                    return;
+                }
+            };
+
+            let mut steps = sqlx::query_as::<_, Step>(
+                "SELECT * FROM steps WHERE run_id = $1 ORDER BY step_number ASC, created_at ASC",
+            )
+            .bind(&run_id)
+            .fetch(&mut *tx);
+            let mut stalled = false;
+            while let Some(step) = steps.next().await {
+                let failed = step.is_err();
+                match step_tx.send_timeout(step, send_timeout).await {
+                    Ok(()) if !failed => {}
+                    // A closed channel means the reader went away
+                    Ok(()) | Err(SendTimeoutError::Closed(_)) => return,
+                    Err(SendTimeoutError::Timeout(_)) => {
+                        stalled = true;
+                        break;
+                    }
+                }
+            }
+            if !stalled {
+                return;
+            }
+
+            // The reader stalled: release the connection before telling it
+            drop(steps);
+            drop(tx);
+            warn!(run_id = %run_id, "Run export reader stalled, aborting export");
+            let _ = step_tx
+                .send(Err(sqlx::Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "export reader stalled",
+                ))))
+                .await;
+        });
+
+        head_rx.await.map_err(|_| sqlx::Error::WorkerCrashed)?
+    }
+
+    /// Load two runs with their steps for comparison
+    ///
+    /// Both are read from the same snapshot, so a run still executing can't
+    /// be seen at two different points. Either side is `None` if missing.
+    #[instrument(skip(self))]
+    pub async fn load_for_diff(
+        &self,
+        a: &str,
+        b: &str,
+    ) -> Result<(Option<RunWithSteps>, Option<RunWithSteps>), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+
+        let a = load_with_steps(&mut tx, a).await?;
+        let b = load_with_steps(&mut tx, b).await?;
+
+        tx.commit().await?;
+        Ok((a, b))
+    }
+
+    /// Move terminal runs that finished before `cutoff` into `archived_runs`
+    ///
+    /// Each run is snapshotted together with its steps and its security
+    /// evidence (threats, approvals, policy decisions and velocity events),
+    /// then deleted from the hot tables. Audit events and policy decisions
+    /// stay behind with their `run_id` intact; the archived row remains as a
+    /// tombstone for lookups. Returns the number of runs archived.
+    #[instrument(skip(self))]
+    pub async fn archive_older_than(
+        &self,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize, sqlx::Error> {
+        let terminal: Vec<&str> = RunStatus::terminal_statuses()
+            .iter()
+            .map(RunStatus::as_str)
+            .collect();
 
-        q.fetch_optional(&self.pool).await
+        let result = sqlx::query(
+            r#"
+            WITH candidates AS (
+                SELECT * FROM runs
+                WHERE status::text = ANY($1)
+                  AND COALESCE(completed_at, created_at) < $2
+                FOR UPDATE SKIP LOCKED
+            ),
+            archived AS (
+                INSERT INTO archived_runs
+                    (id, project_id, status, created_at, completed_at, run, steps, evidence)
+                SELECT
+                    c.id,
+                    c.project_id,
+                    c.status::text,
+                    c.created_at,
+                    c.completed_at,
+                    to_jsonb(c),
+                    COALESCE(
+                        (SELECT jsonb_agg(to_jsonb(s) ORDER BY s.step_number)
+                         FROM steps s WHERE s.run_id = c.id),
+                        '[]'::jsonb
+                    ),
+                    jsonb_build_object(
+                        'threats', COALESCE(
+                            (SELECT jsonb_agg(to_jsonb(t) ORDER BY t.created_at)
+                             FROM threats t WHERE t.run_id = c.id),
+                            '[]'::jsonb
+                        ),
+                        'approval_requests', COALESCE(
+                            (SELECT jsonb_agg(to_jsonb(a) ORDER BY a.created_at)
+                             FROM approval_requests a WHERE a.run_id = c.id),
+                            '[]'::jsonb
+                        ),
+                        'policy_decisions', COALESCE(
+                            (SELECT jsonb_agg(to_jsonb(d) ORDER BY d.evaluated_at)
+                             FROM policy_decisions d WHERE d.run_id = c.id),
+                            '[]'::jsonb
+                        ),
+                        'velocity_events', COALESCE(
+                            (SELECT jsonb_agg(to_jsonb(v) ORDER BY v.id)
+                             FROM velocity_events v WHERE v.run_id = c.id),
+                            '[]'::jsonb
+                        )
+                    )
+                FROM candidates c
+                ON CONFLICT (id) DO NOTHING
+                RETURNING id
+            )
+            DELETE FROM runs WHERE id IN (SELECT id FROM archived)
+            "#,
+        )
+        .bind(&terminal)
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() as usize)
+    }
+
+    /// Find progressing runs with no step activity since `now - idle_threshold`
+    ///
+    /// Activity is the latest create/start/completion time across the run's
+    /// steps, falling back to the run's own start or creation time (see
+    /// [`Run::is_stuck`]).
+    #[instrument(skip(self))]
+    pub async fn find_stuck(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        idle_threshold: chrono::Duration,
+    ) -> Result<Vec<Run>, sqlx::Error> {
+        let progressing: Vec<&str> = RunStatus::progressing_statuses()
+            .iter()
+            .map(RunStatus::as_str)
+            .collect();
+
+        sqlx::query_as::<_, Run>(
+            r#"
+            SELECT r.* FROM runs r
+            LEFT JOIN LATERAL (
+                SELECT MAX(GREATEST(s.created_at, s.started_at, s.completed_at)) AS last_update
+                FROM steps s
+                WHERE s.run_id = r.id
+            ) activity ON TRUE
+            WHERE r.status::text = ANY($1)
+              AND COALESCE(activity.last_update, r.started_at, r.created_at) < $2
+            ORDER BY r.created_at ASC
+            "#,
+        )
+        .bind(&progressing)
+        .bind(now - idle_threshold)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Count runs that are currently in progress (not terminal)
+    #[instrument(skip(self))]
+    pub async fn count_in_progress(&self) -> Result<i64, sqlx::Error> {
+        let terminal: Vec<&str> = RunStatus::terminal_statuses()
+            .iter()
+            .map(RunStatus::as_str)
+            .collect();
+
+        let row =
+            sqlx::query("SELECT COUNT(*) as count FROM runs WHERE NOT (status::text = ANY($1))")
+                .bind(&terminal)
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(row.get("count"))
+    }
+
+    /// Update a run
+    ///
+    /// A status change only applies when the run's current status may move
+    /// to it; `None` means the run does not exist or cannot make the
+    /// transition.
+    #[instrument(skip(self, update), fields(run_id = %id))]
+    pub async fn update(&self, id: &str, update: UpdateRun) -> Result<Option<Run>, sqlx::Error> {
+        update_run(&self.pool, id, &update).await
+    }
+
+    /// Update a run and record its audit event in one transaction
+    ///
+    /// Either both the state change and the audit event are committed or
+    /// neither is: a failed audit insert rolls the update back. Returns
+    /// `None` (writing no audit event) when the run does not exist or cannot
+    /// make the requested status transition.
+    #[instrument(skip(self, update, audit), fields(run_id = %id, audit_action = %audit.action))]
+    pub async fn update_with_audit(
+        &self,
+        id: &str,
+        update: UpdateRun,
+        audit: &CreateAuditEvent,
+    ) -> Result<Option<Run>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let Some(run) = update_run(&mut *tx, id, &update).await? else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+        insert_audit_event(&mut *tx, audit).await?;
+        tx.commit().await?;
+        Ok(Some(run))
     }
 
     /// Update run status
+    ///
+    /// Only applies when the run's current status may move to `status`, so a
+    /// concurrent terminal update is never overwritten. Returns `None` when
+    /// the run does not exist or cannot make the transition.
     #[instrument(skip(self))]
     pub async fn update_status(
         &self,
@@ -146,17 +429,45 @@ impl RunsRepo {
         status: RunStatus,
         reason: Option<&str>,
     ) -> Result<Option<Run>, sqlx::Error> {
+        let allowed_from: Vec<&str> = RunStatus::allowed_from(status)
+            .iter()
+            .map(RunStatus::as_str)
+            .collect();
         sqlx::query_as::<_, Run>(
             r#"
             UPDATE runs
             SET status = $2, status_reason = $3
-            WHERE id = $1
+            WHERE id = $1 AND status::text = ANY($4)
             RETURNING *
             "#,
         )
         .bind(id)
         .bind(status)
         .bind(reason)
+        .bind(&allowed_from)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Record when a run started executing
+    ///
+    /// Keeps an existing `started_at`, so only the first call takes effect.
+    #[instrument(skip(self))]
+    pub async fn mark_started(
+        &self,
+        id: &str,
+        started_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<Run>, sqlx::Error> {
+        sqlx::query_as::<_, Run>(
+            r#"
+            UPDATE runs
+            SET started_at = COALESCE(started_at, $2)
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(started_at)
         .fetch_optional(&self.pool)
         .await
     }
@@ -225,24 +536,15 @@ impl RunsRepo {
         tool_calls: i32,
         cost_cents: i32,
     ) -> Result<(), sqlx::Error> {
-        sqlx::query(
-            r#"
-            UPDATE runs
-            SET input_tokens = input_tokens + $2,
-                output_tokens = output_tokens + $3,
-                tool_calls = tool_calls + $4,
-                cost_cents = cost_cents + $5
-            WHERE id = $1
-            "#,
+        increment_run_usage(
+            &self.pool,
+            id,
+            input_tokens,
+            output_tokens,
+            tool_calls,
+            cost_cents,
         )
-        .bind(id)
-        .bind(input_tokens)
-        .bind(output_tokens)
-        .bind(tool_calls)
-        .bind(cost_cents)
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+        .await
     }
 
     /// Get agent run statistics
@@ -289,6 +591,235 @@ impl RunsRepo {
             last_run_at: last_run_at.map(|t| t.to_rfc3339()),
         })
     }
+
+    /// Average token usage of an agent's completed runs
+    ///
+    /// Returns `(input_tokens, output_tokens)`, or `None` if the agent has
+    /// no completed runs.
+    #[instrument(skip(self))]
+    pub async fn average_token_usage(
+        &self,
+        agent_id: &str,
+    ) -> Result<Option<(u64, u64)>, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                AVG(r.input_tokens)::DOUBLE PRECISION as avg_input_tokens,
+                AVG(r.output_tokens)::DOUBLE PRECISION as avg_output_tokens
+            FROM runs r
+            JOIN agent_versions av ON r.agent_version_id = av.id
+            WHERE av.agent_id = $1 AND r.status = 'completed'
+            "#,
+        )
+        .bind(agent_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let input: Option<f64> = row.get("avg_input_tokens");
+        let output: Option<f64> = row.get("avg_output_tokens");
+        Ok(input
+            .zip(output)
+            .map(|(input, output)| (input.round() as u64, output.round() as u64)))
+    }
+}
+
+/// Load a run and its steps in execution order
+async fn load_with_steps(
+    conn: &mut sqlx::PgConnection,
+    run_id: &str,
+) -> Result<Option<RunWithSteps>, sqlx::Error> {
+    let Some(run) = sqlx::query_as::<_, Run>("SELECT * FROM runs WHERE id = $1")
+        .bind(run_id)
+        .fetch_optional(&mut *conn)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let steps = sqlx::query_as::<_, Step>(
+        "SELECT * FROM steps WHERE run_id = $1 ORDER BY step_number ASC, created_at ASC",
+    )
+    .bind(run_id)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(Some(RunWithSteps { run, steps }))
+}
+
+/// Insert a run on any executor (pool or transaction)
+async fn insert_run<'e, E: PgExecutor<'e>>(
+    executor: E,
+    run: &CreateRun,
+) -> Result<Run, sqlx::Error> {
+    sqlx::query_as::<_, Run>(
+        r#"
+        INSERT INTO runs (id, project_id, agent_version_id, input, config, trace_id, span_id, metadata, budget_snapshot)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(&run.id)
+    .bind(&run.project_id)
+    .bind(&run.agent_version_id)
+    .bind(&run.input)
+    .bind(&run.config)
+    .bind(&run.trace_id)
+    .bind(&run.span_id)
+    .bind(&run.metadata)
+    .bind(&run.budget_snapshot)
+    .fetch_one(executor)
+    .await
+}
+
+/// Apply a run update on any executor (pool or transaction)
+async fn update_run<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: &str,
+    update: &UpdateRun,
+) -> Result<Option<Run>, sqlx::Error> {
+    // Build dynamic update query
+    let mut set_clauses = Vec::new();
+    let mut param_idx = 2; // $1 is the id
+
+    if update.status.is_some() {
+        set_clauses.push(format!("status = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.status_reason.is_some() {
+        set_clauses.push(format!("status_reason = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.input_tokens.is_some() {
+        set_clauses.push(format!("input_tokens = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.output_tokens.is_some() {
+        set_clauses.push(format!("output_tokens = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.tool_calls.is_some() {
+        set_clauses.push(format!("tool_calls = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.cost_cents.is_some() {
+        set_clauses.push(format!("cost_cents = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.started_at.is_some() {
+        set_clauses.push(format!("started_at = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.completed_at.is_some() {
+        set_clauses.push(format!("completed_at = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.output.is_some() {
+        set_clauses.push(format!("output = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.error.is_some() {
+        set_clauses.push(format!("error = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.budget_kill_detail.is_some() {
+        set_clauses.push(format!("budget_kill_detail = ${}", param_idx));
+        param_idx += 1;
+    }
+
+    if set_clauses.is_empty() {
+        return sqlx::query_as::<_, Run>("SELECT * FROM runs WHERE id = $1")
+            .bind(id)
+            .fetch_optional(executor)
+            .await;
+    }
+
+    // A status change only applies from a status that may make it
+    let allowed_from: Option<Vec<&str>> = update.status.map(|status| {
+        RunStatus::allowed_from(status)
+            .iter()
+            .map(RunStatus::as_str)
+            .collect()
+    });
+    let guard = match allowed_from {
+        Some(_) => format!(" AND status::text = ANY(${})", param_idx),
+        None => String::new(),
+    };
+
+    let query = format!(
+        "UPDATE runs SET {} WHERE id = $1{} RETURNING *",
+        set_clauses.join(", "),
+        guard
+    );
+
+    let mut q = sqlx::query_as::<_, Run>(&query).bind(id);
+
+    if let Some(status) = &update.status {
+        q = q.bind(status);
+    }
+    if let Some(reason) = &update.status_reason {
+        q = q.bind(reason);
+    }
+    if let Some(tokens) = &update.input_tokens {
+        q = q.bind(tokens);
+    }
+    if let Some(tokens) = &update.output_tokens {
+        q = q.bind(tokens);
+    }
+    if let Some(calls) = &update.tool_calls {
+        q = q.bind(calls);
+    }
+    if let Some(cost) = &update.cost_cents {
+        q = q.bind(cost);
+    }
+    if let Some(started) = &update.started_at {
+        q = q.bind(started);
+    }
+    if let Some(completed) = &update.completed_at {
+        q = q.bind(completed);
+    }
+    if let Some(output) = &update.output {
+        q = q.bind(output);
+    }
+    if let Some(error) = &update.error {
+        q = q.bind(error);
+    }
+    if let Some(detail) = &update.budget_kill_detail {
+        q = q.bind(detail);
+    }
+    if let Some(allowed_from) = &allowed_from {
+        q = q.bind(allowed_from);
+    }
+
+    q.fetch_optional(executor).await
+}
+
+/// Increment run usage counters on any executor (pool or transaction)
+pub(crate) async fn increment_run_usage<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: &str,
+    input_tokens: i32,
+    output_tokens: i32,
+    tool_calls: i32,
+    cost_cents: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE runs
+        SET input_tokens = input_tokens + $2,
+            output_tokens = output_tokens + $3,
+            tool_calls = tool_calls + $4,
+            cost_cents = cost_cents + $5
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(input_tokens)
+    .bind(output_tokens)
+    .bind(tool_calls)
+    .bind(cost_cents)
+    .execute(executor)
+    .await?;
+    Ok(())
 }
 
 /// Agent run statistics
@@ -302,3 +833,470 @@ pub struct AgentStats {
     pub total_cost_cents: i64,
     pub last_run_at: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        ApprovalActionType, CreateApprovalRequest, CreatePolicyDecision, CreateStep, CreateThreat,
+        PolicyEffect, StepType,
+    };
+    use crate::repos::audit::AuditRepo;
+    use crate::repos::{PoliciesRepo, StepsRepo, ThreatsRepo};
+    use chrono::SubsecRound;
+
+    /// Seeded by `20241223000002_seed_dev_data.sql`
+    const SEED_PROJECT: &str = "prj_01JFVX0000000000000000001";
+    const SEED_AGENT_VERSION: &str = "agv_01JFVX0000000000000000001";
+
+    fn audit_event(id: &str, run_id: &str) -> CreateAuditEvent {
+        CreateAuditEvent {
+            id: id.to_string(),
+            actor_type: "system".to_string(),
+            actor_id: None,
+            action: "run.cancelled".to_string(),
+            resource_type: "run".to_string(),
+            resource_id: Some(run_id.to_string()),
+            details: serde_json::json!({}),
+            tenant_id: None,
+            workspace_id: None,
+            project_id: Some(SEED_PROJECT.to_string()),
+            run_id: Some(run_id.to_string()),
+            request_id: None,
+            ip_address: None,
+            user_agent: None,
+            trace_id: None,
+            span_id: None,
+            severity: None,
+            risk_score: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_archive_keeps_security_evidence_and_audit_linkage() {
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let runs = RunsRepo::new(pool.clone());
+        let policies = PoliciesRepo::new(pool.clone());
+
+        let run_id = format!("run_{}", ulid::Ulid::new());
+        runs.create(CreateRun {
+            id: run_id.clone(),
+            project_id: SEED_PROJECT.to_string(),
+            agent_version_id: SEED_AGENT_VERSION.to_string(),
+            input: serde_json::json!({}),
+            config: serde_json::json!({}),
+            trace_id: None,
+            span_id: None,
+            metadata: serde_json::json!({}),
+            budget_snapshot: None,
+        })
+        .await
+        .unwrap();
+        let step_id = format!("stp_{}", ulid::Ulid::new());
+        StepsRepo::new(pool.clone())
+            .create(CreateStep {
+                id: step_id.clone(),
+                run_id: run_id.clone(),
+                parent_step_id: None,
+                step_number: 1,
+                step_type: StepType::Tool,
+                input: serde_json::json!({}),
+                tool_name: Some("deploy".to_string()),
+                tool_version: None,
+                model: None,
+                span_id: None,
+            })
+            .await
+            .unwrap();
+
+        ThreatsRepo::new(pool.clone())
+            .create(CreateThreat {
+                id: format!("thr_{}", ulid::Ulid::new()),
+                run_id: run_id.clone(),
+                step_id: Some(step_id.clone()),
+                tool_name: "deploy".to_string(),
+                risk_score: 90,
+                risk_level: "critical".to_string(),
+                violation_type: "rce_pattern".to_string(),
+                violation_details: None,
+                blocked_payload: None,
+                trigger_pattern: Some("python_eval".to_string()),
+                action: "blocked".to_string(),
+                shadow_mode: false,
+                project_id: Some(SEED_PROJECT.to_string()),
+                tenant_id: None,
+            })
+            .await
+            .unwrap();
+        let decision_id = format!("pdc_{}", ulid::Ulid::new());
+        policies
+            .create_decision(CreatePolicyDecision {
+                id: decision_id.clone(),
+                run_id: Some(run_id.clone()),
+                step_id: Some(step_id.clone()),
+                action_type: "tool_call".to_string(),
+                action_details: serde_json::json!({}),
+                decision: PolicyEffect::RequireApproval,
+                matched_rule_id: None,
+                reason: "needs review".to_string(),
+                evaluation_time_ms: None,
+            })
+            .await
+            .unwrap();
+        policies
+            .create_approval(CreateApprovalRequest {
+                id: format!("apr_{}", ulid::Ulid::new()),
+                run_id: run_id.clone(),
+                step_id: step_id.clone(),
+                policy_decision_id: decision_id.clone(),
+                action_type: ApprovalActionType::ToolCall,
+                action_details: serde_json::json!({}),
+                reason: "needs review".to_string(),
+                expires_at: None,
+                required_approvals: 1,
+            })
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO velocity_events (run_id, tool_name, tool_input_hash) VALUES ($1, 'deploy', 'h')",
+        )
+        .bind(&run_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let audit_id = format!("aud_{}", ulid::Ulid::new());
+        AuditRepo::new(pool.clone())
+            .create(audit_event(&audit_id, &run_id))
+            .await
+            .unwrap();
+
+        let finished = chrono::Utc::now() - chrono::Duration::days(30);
+        runs.update_status(&run_id, RunStatus::Running, None)
+            .await
+            .unwrap();
+        runs.update(
+            &run_id,
+            UpdateRun {
+                status: Some(RunStatus::Completed),
+                completed_at: Some(finished),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(
+            runs.archive_older_than(finished + chrono::Duration::minutes(1))
+                .await
+                .unwrap()
+                >= 1
+        );
+        assert!(runs.get(&run_id).await.unwrap().is_none());
+
+        let archived = runs.get_archived(&run_id).await.unwrap().unwrap();
+        for table in [
+            "threats",
+            "approval_requests",
+            "policy_decisions",
+            "velocity_events",
+        ] {
+            let rows = archived.evidence[table].as_array().unwrap();
+            assert_eq!(rows.len(), 1, "{} snapshot", table);
+            assert_eq!(rows[0]["run_id"], run_id.as_str());
+        }
+        assert_eq!(
+            archived.evidence["threats"][0]["trigger_pattern"],
+            "python_eval"
+        );
+
+        // The audit trail still points at the archived run
+        for (table, id) in [
+            ("audit_events", &audit_id),
+            ("policy_decisions", &decision_id),
+        ] {
+            let linked: Option<String> =
+                sqlx::query_scalar(&format!("SELECT run_id FROM {} WHERE id = $1", table))
+                    .bind(id)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(linked.as_deref(), Some(run_id.as_str()), "{}", table);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_export_stream_reads_steps_in_execution_order() {
+        use futures_util::TryStreamExt;
+
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let runs = RunsRepo::new(pool.clone());
+
+        let run_id = format!("run_{}", ulid::Ulid::new());
+        runs.create(CreateRun {
+            id: run_id.clone(),
+            project_id: SEED_PROJECT.to_string(),
+            agent_version_id: SEED_AGENT_VERSION.to_string(),
+            input: serde_json::json!({}),
+            config: serde_json::json!({}),
+            trace_id: None,
+            span_id: None,
+            metadata: serde_json::json!({}),
+            budget_snapshot: None,
+        })
+        .await
+        .unwrap();
+        for step_number in [3, 1, 2] {
+            StepsRepo::new(pool.clone())
+                .create(CreateStep {
+                    id: format!("stp_{}", ulid::Ulid::new()),
+                    run_id: run_id.clone(),
+                    parent_step_id: None,
+                    step_number,
+                    step_type: StepType::Tool,
+                    input: serde_json::json!({}),
+                    tool_name: Some("deploy".to_string()),
+                    tool_version: None,
+                    model: None,
+                    span_id: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let export = runs.export_stream(&run_id).await.unwrap().unwrap();
+        assert_eq!(export.run.id, run_id);
+        let steps: Vec<Step> = export.steps.try_collect().await.unwrap();
+        let numbers: Vec<i32> = steps.iter().map(|s| s.step_number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+
+        assert!(runs.export_stream("run_missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_export_stream_gives_up_on_stalled_reader() {
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let runs = RunsRepo::new(pool.clone());
+
+        let run_id = format!("run_{}", ulid::Ulid::new());
+        runs.create(CreateRun {
+            id: run_id.clone(),
+            project_id: SEED_PROJECT.to_string(),
+            agent_version_id: SEED_AGENT_VERSION.to_string(),
+            input: serde_json::json!({}),
+            config: serde_json::json!({}),
+            trace_id: None,
+            span_id: None,
+            metadata: serde_json::json!({}),
+            budget_snapshot: None,
+        })
+        .await
+        .unwrap();
+        let total = EXPORT_STEP_BUFFER + 8;
+        for step_number in 1..=total as i32 {
+            StepsRepo::new(pool.clone())
+                .create(CreateStep {
+                    id: format!("stp_{}", ulid::Ulid::new()),
+                    run_id: run_id.clone(),
+                    parent_step_id: None,
+                    step_number,
+                    step_type: StepType::Tool,
+                    input: serde_json::json!({}),
+                    tool_name: Some("deploy".to_string()),
+                    tool_version: None,
+                    model: None,
+                    span_id: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let mut export = runs
+            .export_stream_within(&run_id, Duration::from_millis(50))
+            .await
+            .unwrap()
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        // The connection is back in the pool while the reader is still stalled
+        assert_eq!(pool.size() as usize, pool.num_idle());
+
+        // The buffered steps are still delivered, then the export fails
+        let mut delivered = 0;
+        let error = loop {
+            match export.steps.next().await {
+                Some(Ok(_)) => delivered += 1,
+                Some(Err(e)) => break e,
+                None => panic!("stalled export ended without an error"),
+            }
+        };
+        assert_eq!(delivered, EXPORT_STEP_BUFFER);
+        assert!(
+            matches!(error, sqlx::Error::Io(ref e) if e.kind() == std::io::ErrorKind::TimedOut)
+        );
+        assert!(export.steps.next().await.is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_status_updates_only_apply_legal_transitions() {
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let runs = RunsRepo::new(pool.clone());
+
+        let run_id = format!("run_{}", ulid::Ulid::new());
+        runs.create(CreateRun {
+            id: run_id.clone(),
+            project_id: SEED_PROJECT.to_string(),
+            agent_version_id: SEED_AGENT_VERSION.to_string(),
+            input: serde_json::json!({}),
+            config: serde_json::json!({}),
+            trace_id: None,
+            span_id: None,
+            metadata: serde_json::json!({}),
+            budget_snapshot: None,
+        })
+        .await
+        .unwrap();
+
+        let queued = runs
+            .update_status(&run_id, RunStatus::Queued, None)
+            .await
+            .unwrap();
+        assert_eq!(queued.unwrap().status, RunStatus::Queued);
+        let cancelled = runs
+            .update(
+                &run_id,
+                UpdateRun {
+                    status: Some(RunStatus::Cancelled),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(cancelled.unwrap().status, RunStatus::Cancelled);
+
+        // Terminal runs stay terminal
+        assert!(runs
+            .update_status(&run_id, RunStatus::Running, None)
+            .await
+            .unwrap()
+            .is_none());
+        let completed = UpdateRun {
+            status: Some(RunStatus::Completed),
+            status_reason: Some("late result".to_string()),
+            ..Default::default()
+        };
+        assert!(runs.update(&run_id, completed).await.unwrap().is_none());
+        let run = runs.get(&run_id).await.unwrap().unwrap();
+        assert_eq!(run.status, RunStatus::Cancelled);
+        assert_eq!(run.status_reason, None);
+
+        // Updates that leave the status alone are not guarded
+        let noted = UpdateRun {
+            status_reason: Some("note".to_string()),
+            ..Default::default()
+        };
+        assert!(runs.update(&run_id, noted).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_mark_started_keeps_first_start() {
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let runs = RunsRepo::new(pool.clone());
+
+        let run_id = format!("run_{}", ulid::Ulid::new());
+        runs.create(CreateRun {
+            id: run_id.clone(),
+            project_id: SEED_PROJECT.to_string(),
+            agent_version_id: SEED_AGENT_VERSION.to_string(),
+            input: serde_json::json!({}),
+            config: serde_json::json!({}),
+            trace_id: None,
+            span_id: None,
+            metadata: serde_json::json!({}),
+            budget_snapshot: None,
+        })
+        .await
+        .unwrap();
+
+        let first = chrono::Utc::now().trunc_subsecs(0);
+        let run = runs.mark_started(&run_id, first).await.unwrap().unwrap();
+        assert_eq!(run.started_at, Some(first));
+
+        let run = runs
+            .mark_started(&run_id, first + chrono::Duration::minutes(5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.started_at, Some(first));
+        assert!(runs
+            .mark_started("run_missing", first)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_failed_audit_insert_rolls_back_run_update() {
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let runs = RunsRepo::new(pool.clone());
+
+        let run_id = format!("run_{}", ulid::Ulid::new());
+        runs.create(CreateRun {
+            id: run_id.clone(),
+            project_id: SEED_PROJECT.to_string(),
+            agent_version_id: SEED_AGENT_VERSION.to_string(),
+            input: serde_json::json!({}),
+            config: serde_json::json!({}),
+            trace_id: None,
+            span_id: None,
+            metadata: serde_json::json!({}),
+            budget_snapshot: None,
+        })
+        .await
+        .unwrap();
+
+        // An audit event with this ID already exists, so the second insert fails
+        let audit_id = format!("aud_{}", ulid::Ulid::new());
+        AuditRepo::new(pool.clone())
+            .create(audit_event(&audit_id, &run_id))
+            .await
+            .unwrap();
+
+        let result = runs
+            .update_with_audit(
+                &run_id,
+                UpdateRun {
+                    status: Some(RunStatus::Cancelled),
+                    ..Default::default()
+                },
+                &audit_event(&audit_id, &run_id),
+            )
+            .await;
+        assert!(result.is_err());
+
+        let run = runs.get(&run_id).await.unwrap().unwrap();
+        assert_eq!(run.status, RunStatus::Created);
+    }
+}
@@ -1,8 +1,12 @@
 //! Steps repository
 
-use crate::models::{CreateArtifact, CreateStep, Step, StepArtifact, StepStatus, UpdateStep};
+use crate::models::{
+    CreateArtifact, CreateAuditEvent, CreateStep, Step, StepArtifact, StepStatus, UpdateStep,
+};
+use crate::repos::audit::insert_audit_event;
+use crate::repos::runs::increment_run_usage;
 use crate::DbPool;
-use sqlx::Row;
+use sqlx::{PgExecutor, Row};
 use tracing::instrument;
 
 /// Repository for step operations
@@ -52,71 +56,89 @@ impl StepsRepo {
     /// Update a step
     #[instrument(skip(self, update), fields(step_id = %id))]
     pub async fn update(&self, id: &str, update: UpdateStep) -> Result<Option<Step>, sqlx::Error> {
-        let mut set_clauses = Vec::new();
-        let mut param_idx = 2;
-
-        if update.status.is_some() {
-            set_clauses.push(format!("status = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.output.is_some() {
-            set_clauses.push(format!("output = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.error.is_some() {
-            set_clauses.push(format!("error = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.input_tokens.is_some() {
-            set_clauses.push(format!("input_tokens = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.output_tokens.is_some() {
-            set_clauses.push(format!("output_tokens = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.started_at.is_some() {
-            set_clauses.push(format!("started_at = ${}", param_idx));
-            param_idx += 1;
-        }
-        if update.completed_at.is_some() {
-            set_clauses.push(format!("completed_at = ${}", param_idx));
-        }
-
-        if set_clauses.is_empty() {
+        if is_empty_update(&update) {
             return self.get(id).await;
         }
+        update_step(&self.pool, id, None, &update).await
+    }
 
-        let query = format!(
-            "UPDATE steps SET {} WHERE id = $1 RETURNING *",
-            set_clauses.join(", ")
-        );
+    /// Update a step and record its audit event in one transaction
+    ///
+    /// A failed audit insert rolls the update back. Returns `None` (writing
+    /// no audit event) when the step does not exist.
+    #[instrument(skip(self, update, audit), fields(step_id = %id, audit_action = %audit.action))]
+    pub async fn update_with_audit(
+        &self,
+        id: &str,
+        update: UpdateStep,
+        audit: &CreateAuditEvent,
+    ) -> Result<Option<Step>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let step = if is_empty_update(&update) {
+            sqlx::query_as::<_, Step>("SELECT * FROM steps WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?
+        } else {
+            update_step(&mut *tx, id, None, &update).await?
+        };
+        let Some(step) = step else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+        insert_audit_event(&mut *tx, audit).await?;
+        tx.commit().await?;
+        Ok(Some(step))
+    }
 
-        let mut q = sqlx::query_as::<_, Step>(&query).bind(id);
+    /// Apply a batch of step updates for a run in a single transaction
+    ///
+    /// Updates are applied in the given order. A step that does not exist or
+    /// belongs to another run yields `None` at its position without aborting
+    /// the rest of the batch. Each updated step's audit event and the
+    /// aggregate run usage are written in the same transaction, so step
+    /// state, audit trail and usage are committed together.
+    #[instrument(skip(self, updates), fields(run_id = %run_id, batch_size = updates.len()))]
+    pub async fn update_batch(
+        &self,
+        run_id: &str,
+        updates: &[(String, UpdateStep, CreateAuditEvent)],
+        input_tokens: i32,
+        output_tokens: i32,
+        tool_calls: i32,
+        cost_cents: i32,
+    ) -> Result<Vec<Option<Step>>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(updates.len());
 
-        if let Some(status) = &update.status {
-            q = q.bind(status);
-        }
-        if let Some(output) = &update.output {
-            q = q.bind(output);
-        }
-        if let Some(error) = &update.error {
-            q = q.bind(error);
-        }
-        if let Some(tokens) = &update.input_tokens {
-            q = q.bind(tokens);
-        }
-        if let Some(tokens) = &update.output_tokens {
-            q = q.bind(tokens);
-        }
-        if let Some(started) = &update.started_at {
-            q = q.bind(started);
-        }
-        if let Some(completed) = &update.completed_at {
-            q = q.bind(completed);
+        for (id, update, audit) in updates {
+            let step = if is_empty_update(update) {
+                sqlx::query_as::<_, Step>("SELECT * FROM steps WHERE id = $1 AND run_id = $2")
+                    .bind(id)
+                    .bind(run_id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+            } else {
+                update_step(&mut *tx, id, Some(run_id), update).await?
+            };
+            if step.is_some() {
+                insert_audit_event(&mut *tx, audit).await?;
+            }
+            results.push(step);
         }
 
-        q.fetch_optional(&self.pool).await
+        increment_run_usage(
+            &mut *tx,
+            run_id,
+            input_tokens,
+            output_tokens,
+            tool_calls,
+            cost_cents,
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(results)
     }
 
     /// Update step status
@@ -137,6 +159,25 @@ impl StepsRepo {
         .await
     }
 
+    /// Cancel every non-terminal step of a run in one statement.
+    ///
+    /// Returns the number of steps cancelled.
+    #[instrument(skip(self))]
+    pub async fn cancel_pending_for_run(&self, run_id: &str) -> Result<usize, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE steps
+            SET status = 'cancelled', completed_at = COALESCE(completed_at, NOW())
+            WHERE run_id = $1 AND status IN ('pending', 'running', 'waiting_approval')
+            "#,
+        )
+        .bind(run_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() as usize)
+    }
+
     /// List steps for a run
     #[instrument(skip(self))]
     pub async fn list_by_run(&self, run_id: &str) -> Result<Vec<Step>, sqlx::Error> {
@@ -249,3 +290,207 @@ impl StepsRepo {
         .await
     }
 }
+
+fn is_empty_update(update: &UpdateStep) -> bool {
+    update.status.is_none()
+        && update.output.is_none()
+        && update.error.is_none()
+        && update.input_tokens.is_none()
+        && update.output_tokens.is_none()
+        && update.started_at.is_none()
+        && update.completed_at.is_none()
+}
+
+/// Run a dynamic step update on any executor (pool or transaction).
+///
+/// When `run_id` is set, the update only matches a step belonging to that run.
+async fn update_step<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: &str,
+    run_id: Option<&str>,
+    update: &UpdateStep,
+) -> Result<Option<Step>, sqlx::Error> {
+    let mut set_clauses = Vec::new();
+    let mut param_idx = 2;
+
+    if update.status.is_some() {
+        set_clauses.push(format!("status = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.output.is_some() {
+        set_clauses.push(format!("output = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.error.is_some() {
+        set_clauses.push(format!("error = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.input_tokens.is_some() {
+        set_clauses.push(format!("input_tokens = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.output_tokens.is_some() {
+        set_clauses.push(format!("output_tokens = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.started_at.is_some() {
+        set_clauses.push(format!("started_at = ${}", param_idx));
+        param_idx += 1;
+    }
+    if update.completed_at.is_some() {
+        set_clauses.push(format!("completed_at = ${}", param_idx));
+        param_idx += 1;
+    }
+
+    let run_filter = if run_id.is_some() {
+        format!(" AND run_id = ${}", param_idx)
+    } else {
+        String::new()
+    };
+
+    let query = format!(
+        "UPDATE steps SET {} WHERE id = $1{} RETURNING *",
+        set_clauses.join(", "),
+        run_filter
+    );
+
+    let mut q = sqlx::query_as::<_, Step>(&query).bind(id);
+
+    if let Some(status) = &update.status {
+        q = q.bind(status);
+    }
+    if let Some(output) = &update.output {
+        q = q.bind(output);
+    }
+    if let Some(error) = &update.error {
+        q = q.bind(error);
+    }
+    if let Some(tokens) = &update.input_tokens {
+        q = q.bind(tokens);
+    }
+    if let Some(tokens) = &update.output_tokens {
+        q = q.bind(tokens);
+    }
+    if let Some(started) = &update.started_at {
+        q = q.bind(started);
+    }
+    if let Some(completed) = &update.completed_at {
+        q = q.bind(completed);
+    }
+    if let Some(run_id) = run_id {
+        q = q.bind(run_id);
+    }
+
+    q.fetch_optional(executor).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateRun, StepType};
+    use crate::repos::audit::AuditRepo;
+    use crate::repos::RunsRepo;
+
+    /// Seeded by `20241223000002_seed_dev_data.sql`
+    const SEED_PROJECT: &str = "prj_01JFVX0000000000000000001";
+    const SEED_AGENT_VERSION: &str = "agv_01JFVX0000000000000000001";
+
+    fn step_audit(step_id: &str, run_id: &str) -> CreateAuditEvent {
+        CreateAuditEvent {
+            id: format!("aud_{}", ulid::Ulid::new()),
+            actor_type: "system".to_string(),
+            actor_id: None,
+            action: "step.completed".to_string(),
+            resource_type: "step".to_string(),
+            resource_id: Some(step_id.to_string()),
+            details: serde_json::json!({"batch": true}),
+            tenant_id: None,
+            workspace_id: None,
+            project_id: Some(SEED_PROJECT.to_string()),
+            run_id: Some(run_id.to_string()),
+            request_id: None,
+            ip_address: None,
+            user_agent: None,
+            trace_id: None,
+            span_id: None,
+            severity: None,
+            risk_score: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_batch_update_writes_audits_for_updated_steps_only() {
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let steps = StepsRepo::new(pool.clone());
+
+        let run_id = format!("run_{}", ulid::Ulid::new());
+        RunsRepo::new(pool.clone())
+            .create(CreateRun {
+                id: run_id.clone(),
+                project_id: SEED_PROJECT.to_string(),
+                agent_version_id: SEED_AGENT_VERSION.to_string(),
+                input: serde_json::json!({}),
+                config: serde_json::json!({}),
+                trace_id: None,
+                span_id: None,
+                metadata: serde_json::json!({}),
+                budget_snapshot: None,
+            })
+            .await
+            .unwrap();
+        let step_id = format!("stp_{}", ulid::Ulid::new());
+        steps
+            .create(CreateStep {
+                id: step_id.clone(),
+                run_id: run_id.clone(),
+                parent_step_id: None,
+                step_number: 1,
+                step_type: StepType::Tool,
+                input: serde_json::json!({}),
+                tool_name: Some("search".to_string()),
+                tool_version: None,
+                model: None,
+                span_id: None,
+            })
+            .await
+            .unwrap();
+
+        let completed = UpdateStep {
+            status: Some(StepStatus::Completed),
+            ..Default::default()
+        };
+        let missing_id = format!("stp_{}", ulid::Ulid::new());
+        let results = steps
+            .update_batch(
+                &run_id,
+                &[
+                    (
+                        step_id.clone(),
+                        completed.clone(),
+                        step_audit(&step_id, &run_id),
+                    ),
+                    (
+                        missing_id.clone(),
+                        completed,
+                        step_audit(&missing_id, &run_id),
+                    ),
+                ],
+                0,
+                0,
+                1,
+                0,
+            )
+            .await
+            .unwrap();
+        assert!(results[0].is_some());
+        assert!(results[1].is_none());
+
+        let events = AuditRepo::new(pool).list_by_run(&run_id).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].resource_id.as_deref(), Some(step_id.as_str()));
+    }
+}
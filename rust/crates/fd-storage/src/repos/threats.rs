@@ -1,6 +1,8 @@
 //! Threats repository for Airlock security events
 
-use crate::models::threats::{CreateThreat, CreateVelocityEvent, Threat, VelocityEvent};
+use crate::models::threats::{
+    CreateThreat, CreateVelocityEvent, TenantAirlockConfig, Threat, VelocityEvent,
+};
 use crate::DbPool;
 use tracing::instrument;
 
@@ -326,4 +328,40 @@ impl ThreatsRepo {
 
         Ok(result.rows_affected())
     }
+
+    /// Get a tenant's Airlock configuration override
+    #[instrument(skip(self))]
+    pub async fn get_tenant_airlock_config(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Option<TenantAirlockConfig>, sqlx::Error> {
+        sqlx::query_as::<_, TenantAirlockConfig>(
+            "SELECT * FROM tenant_airlock_configs WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Create or replace a tenant's Airlock configuration override
+    #[instrument(skip(self, config))]
+    pub async fn upsert_tenant_airlock_config(
+        &self,
+        tenant_id: &str,
+        config: &serde_json::Value,
+    ) -> Result<TenantAirlockConfig, sqlx::Error> {
+        sqlx::query_as::<_, TenantAirlockConfig>(
+            r#"
+            INSERT INTO tenant_airlock_configs (tenant_id, config)
+            VALUES ($1, $2)
+            ON CONFLICT (tenant_id)
+            DO UPDATE SET config = EXCLUDED.config, updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(config)
+        .fetch_one(&self.pool)
+        .await
+    }
 }
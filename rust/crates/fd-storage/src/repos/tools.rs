@@ -20,8 +20,11 @@ impl ToolsRepo {
     pub async fn create(&self, tool: CreateTool) -> Result<Tool, sqlx::Error> {
         sqlx::query_as::<_, Tool>(
             r#"
-            INSERT INTO tools (id, project_id, name, slug, description, mcp_server, risk_level)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO tools (
+                id, project_id, name, slug, description, mcp_server, risk_level,
+                estimated_cost_cents
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             RETURNING *
             "#,
         )
@@ -32,6 +35,7 @@ impl ToolsRepo {
         .bind(&tool.description)
         .bind(&tool.mcp_server)
         .bind(tool.risk_level)
+        .bind(tool.estimated_cost_cents)
         .fetch_one(&self.pool)
         .await
     }
@@ -45,13 +49,25 @@ impl ToolsRepo {
             .await
     }
 
-    /// Get a tool by slug
+    /// Get a tool by slug within a project, falling back to a global tool
     #[instrument(skip(self))]
-    pub async fn get_by_slug(&self, slug: &str) -> Result<Option<Tool>, sqlx::Error> {
-        sqlx::query_as::<_, Tool>("SELECT * FROM tools WHERE slug = $1")
-            .bind(slug)
-            .fetch_optional(&self.pool)
-            .await
+    pub async fn get_by_slug(
+        &self,
+        project_id: &str,
+        slug: &str,
+    ) -> Result<Option<Tool>, sqlx::Error> {
+        sqlx::query_as::<_, Tool>(
+            r#"
+            SELECT * FROM tools
+            WHERE slug = $2 AND (project_id = $1 OR project_id IS NULL)
+            ORDER BY project_id NULLS LAST
+            LIMIT 1
+            "#,
+        )
+        .bind(project_id)
+        .bind(slug)
+        .fetch_optional(&self.pool)
+        .await
     }
 
     /// Update a tool
@@ -9,6 +9,14 @@ use crate::models::{
     WorkflowStatus, WorkflowStepExecution, WorkflowStepExecutionStatus,
 };
 
+/// Unique index on a workflow's `(project_id, name, version)`
+pub const NAME_VERSION_INDEX: &str = "idx_workflows_project_name_version";
+
+/// Whether `e` rejected a workflow whose name and version already exist in its project
+pub fn is_name_version_conflict(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::Database(db) if db.constraint() == Some(NAME_VERSION_INDEX))
+}
+
 /// Repository for workflow operations
 #[derive(Clone)]
 pub struct WorkflowsRepo {
@@ -24,12 +32,16 @@ impl WorkflowsRepo {
     // Workflow CRUD
     // =========================================================================
 
+    /// Insert a workflow
+    ///
+    /// Fails with an error matching [`is_name_version_conflict`] when the
+    /// project already has a workflow with the same name and version.
     pub async fn create(&self, workflow: CreateWorkflow) -> Result<Workflow, sqlx::Error> {
         let now = Utc::now();
         sqlx::query_as::<_, Workflow>(
             r#"
-            INSERT INTO workflows (id, project_id, name, description, version, status, definition, max_iterations, on_error, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            INSERT INTO workflows (id, project_id, name, description, version, status, definition, max_iterations, on_error, created_at, updated_at, external_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             RETURNING *
             "#,
         )
@@ -44,10 +56,74 @@ impl WorkflowsRepo {
         .bind(&workflow.on_error)
         .bind(now)
         .bind(now)
+        .bind(&workflow.external_id)
         .fetch_one(&self.pool)
         .await
     }
 
+    /// Create a workflow, or return the existing one with the same external ID
+    ///
+    /// Returns the workflow and whether it was newly created. Without an
+    /// external ID this always creates.
+    pub async fn create_or_get(
+        &self,
+        workflow: CreateWorkflow,
+    ) -> Result<(Workflow, bool), sqlx::Error> {
+        let Some(external_id) = workflow.external_id.clone() else {
+            return Ok((self.create(workflow).await?, true));
+        };
+
+        let now = Utc::now();
+        let created = sqlx::query_as::<_, Workflow>(
+            r#"
+            INSERT INTO workflows (id, project_id, name, description, version, status, definition, max_iterations, on_error, created_at, updated_at, external_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (project_id, external_id) WHERE external_id IS NOT NULL DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(&workflow.id)
+        .bind(&workflow.project_id)
+        .bind(&workflow.name)
+        .bind(&workflow.description)
+        .bind(&workflow.version)
+        .bind(WorkflowStatus::Active)
+        .bind(&workflow.definition)
+        .bind(workflow.max_iterations)
+        .bind(&workflow.on_error)
+        .bind(now)
+        .bind(now)
+        .bind(&external_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match created {
+            Some(workflow) => Ok((workflow, true)),
+            None => {
+                let existing = self
+                    .find_by_external_id(&workflow.project_id, &external_id)
+                    .await?
+                    .ok_or(sqlx::Error::RowNotFound)?;
+                Ok((existing, false))
+            }
+        }
+    }
+
+    /// Get a workflow by its external ID within a project
+    pub async fn find_by_external_id(
+        &self,
+        project_id: &str,
+        external_id: &str,
+    ) -> Result<Option<Workflow>, sqlx::Error> {
+        sqlx::query_as::<_, Workflow>(
+            "SELECT * FROM workflows WHERE project_id = $1 AND external_id = $2",
+        )
+        .bind(project_id)
+        .bind(external_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
     pub async fn get(&self, id: &str) -> Result<Option<Workflow>, sqlx::Error> {
         sqlx::query_as::<_, Workflow>("SELECT * FROM workflows WHERE id = $1")
             .bind(id)
@@ -131,8 +207,8 @@ impl WorkflowsRepo {
         let now = Utc::now();
         sqlx::query_as::<_, WorkflowRun>(
             r#"
-            INSERT INTO workflow_runs (id, workflow_id, project_id, status, input, context, step_results, input_tokens, output_tokens, tool_calls, cost_cents, created_at, trace_id)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, 0, 0, 0, 0, $8, $9)
+            INSERT INTO workflow_runs (id, workflow_id, project_id, status, input, context, step_results, input_tokens, output_tokens, tool_calls, cost_cents, created_at, trace_id, span_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, 0, 0, 0, 0, $8, $9, $10)
             RETURNING *
             "#,
         )
@@ -145,6 +221,7 @@ impl WorkflowsRepo {
         .bind(serde_json::json!({}))
         .bind(now)
         .bind(&run.trace_id)
+        .bind(&run.span_id)
         .fetch_one(&self.pool)
         .await
     }
@@ -177,6 +254,23 @@ impl WorkflowsRepo {
         .await
     }
 
+    /// Count runs of a workflow that have not reached a terminal status
+    pub async fn count_active_runs_by_workflow(
+        &self,
+        workflow_id: &str,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM workflow_runs
+            WHERE workflow_id = $1
+              AND status NOT IN ('completed', 'failed', 'cancelled')
+            "#,
+        )
+        .bind(workflow_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
     pub async fn list_runs_by_project(
         &self,
         project_id: &str,
@@ -198,6 +292,19 @@ impl WorkflowsRepo {
         .await
     }
 
+    /// IDs of runs that are scheduling steps (running or waiting for approval)
+    pub async fn list_active_run_ids(&self) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT id FROM workflow_runs
+            WHERE status IN ('running', 'waiting_approval')
+            ORDER BY created_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
     pub async fn update_run(
         &self,
         id: &str,
@@ -252,6 +359,19 @@ impl WorkflowsRepo {
         .await
     }
 
+    pub async fn save_scheduler_state(
+        &self,
+        id: &str,
+        scheduler_state: &serde_json::Value,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE workflow_runs SET scheduler_state = $1 WHERE id = $2")
+            .bind(scheduler_state)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     pub async fn update_run_step_results(
         &self,
         id: &str,
@@ -266,7 +386,7 @@ impl WorkflowsRepo {
             RETURNING *
             "#,
         )
-        .bind(format!("{{{}}}", step_id))
+        .bind([step_id])
         .bind(&result)
         .bind(id)
         .fetch_optional(&self.pool)
@@ -425,3 +545,109 @@ impl WorkflowsRepo {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WorkflowStepType;
+
+    /// Seeded by `20241223000002_seed_dev_data.sql`
+    const SEED_PROJECT: &str = "prj_01JFVX0000000000000000001";
+
+    fn new_workflow(name: &str, version: &str) -> CreateWorkflow {
+        CreateWorkflow {
+            id: format!("wf_{}", ulid::Ulid::new()),
+            project_id: SEED_PROJECT.to_string(),
+            name: name.to_string(),
+            description: None,
+            version: version.to_string(),
+            definition: serde_json::json!({"steps": []}),
+            max_iterations: 10,
+            on_error: "fail".to_string(),
+            external_id: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_duplicate_name_and_version_conflicts() {
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let workflows = WorkflowsRepo::new(pool);
+
+        let name = format!("unique-version-{}", ulid::Ulid::new());
+        workflows
+            .create(new_workflow(&name, "1.0.0"))
+            .await
+            .unwrap();
+
+        let duplicate = workflows
+            .create(new_workflow(&name, "1.0.0"))
+            .await
+            .unwrap_err();
+        assert!(is_name_version_conflict(&duplicate), "{}", duplicate);
+
+        workflows
+            .create(new_workflow(&name, "1.1.0"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database at DATABASE_URL"]
+    async fn test_latest_step_execution_is_highest_attempt() {
+        let pool = crate::create_pool(&std::env::var("DATABASE_URL").unwrap(), 2, 1)
+            .await
+            .unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+        let workflows = WorkflowsRepo::new(pool);
+
+        let name = format!("latest-attempt-{}", ulid::Ulid::new());
+        let workflow = workflows
+            .create(new_workflow(&name, "1.0.0"))
+            .await
+            .unwrap();
+        let run = workflows
+            .create_run(CreateWorkflowRun {
+                id: format!("wfr_{}", ulid::Ulid::new()),
+                workflow_id: workflow.id,
+                project_id: SEED_PROJECT.to_string(),
+                input: serde_json::json!({}),
+                trace_id: None,
+                span_id: None,
+            })
+            .await
+            .unwrap();
+
+        for attempt in [1, 3, 2] {
+            workflows
+                .create_step_execution(CreateWorkflowStepExecution {
+                    id: format!("wfse_{}", ulid::Ulid::new()),
+                    workflow_run_id: run.id.clone(),
+                    step_id: "fetch".to_string(),
+                    step_type: WorkflowStepType::Tool,
+                    input: serde_json::json!({"attempt": attempt}),
+                    attempt,
+                    span_id: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let latest = workflows
+            .get_latest_step_execution(&run.id, "fetch")
+            .await
+            .unwrap()
+            .expect("step has executions");
+        assert_eq!(latest.attempt, 3);
+        assert_eq!(latest.input["attempt"], 3);
+
+        let unknown = workflows
+            .get_latest_step_execution(&run.id, "missing")
+            .await
+            .unwrap();
+        assert!(unknown.is_none());
+    }
+}
@@ -8,19 +8,35 @@ use axum::{
 use chrono::Utc;
 use fd_storage::{
     models::{
-        action, actor, resource, ApprovalStatus, AuditEventBuilder, ResolveApproval, RunStatus,
-        StepStatus, UpdateStep,
+        action, actor, resource, ApprovalActionType, ApprovalRequest, ApprovalStatus,
+        ApprovalTokenError, AuditEventBuilder, ResolveApproval, RunStatus, StepStatus, UpdateStep,
     },
-    queue::{JobContext, StepJob},
+    queue::StepJob,
     QueueMessage,
 };
 use serde::{Deserialize, Serialize};
 use tracing::{info, instrument, warn};
 
-use crate::handlers::ApiError;
+use crate::handlers::runs::{run_job_context, spawn_run_summary};
+use crate::handlers::{applied_transition, ensure_run_transition, ApiError};
 use crate::middleware::AuthContext;
 use crate::state::AppState;
 
+/// How long an allow-once approval token stays valid
+const APPROVAL_TOKEN_TTL: chrono::Duration = chrono::Duration::minutes(15);
+
+/// Header carrying an allow-once approval token on result submission
+pub const APPROVAL_TOKEN_HEADER: &str = "x-fd-approval-token";
+
+/// Map an approval token failure to an API error
+pub(crate) fn approval_token_error(error: ApprovalTokenError) -> ApiError {
+    match error {
+        ApprovalTokenError::Database(e) => e.into(),
+        ApprovalTokenError::NotApproved => ApiError::conflict(error.to_string()),
+        _ => ApiError::forbidden(error.to_string()),
+    }
+}
+
 // =============================================================================
 // Request/Response DTOs
 // =============================================================================
@@ -29,6 +45,9 @@ use crate::state::AppState;
 pub struct ListApprovalsQuery {
     #[serde(default = "default_limit")]
     pub limit: i64,
+    /// Only return approvals gating this kind of action
+    #[serde(default)]
+    pub action_type: Option<ApprovalActionType>,
 }
 
 fn default_limit() -> i64 {
@@ -40,7 +59,7 @@ pub struct ApprovalResponse {
     pub id: String,
     pub run_id: String,
     pub step_id: String,
-    pub action_type: String,
+    pub action_type: ApprovalActionType,
     pub action_details: serde_json::Value,
     pub reason: String,
     pub status: String,
@@ -49,6 +68,8 @@ pub struct ApprovalResponse {
     pub resolved_by: Option<String>,
     pub resolved_at: Option<String>,
     pub resolution_note: Option<String>,
+    pub approval_count: u32,
+    pub required_approvals: u32,
 }
 
 #[derive(Debug, Deserialize)]
@@ -61,8 +82,46 @@ pub struct ResolveApprovalRequest {
 // Helpers
 // =============================================================================
 
-fn approval_to_response(approval: fd_storage::models::ApprovalRequest) -> ApprovalResponse {
+/// Outcome of a single approver's decision on a pending approval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ApprovalOutcome {
+    /// Approval recorded, but the quorum is not yet reached
+    Pending,
+    /// Quorum reached; the step can resume
+    Approved,
+    /// Any rejection fails the approval
+    Rejected,
+}
+
+/// Apply one approver's decision to a pending approval.
+///
+/// Approvals are counted per distinct approver; a second approval from the
+/// same approver is rejected rather than counted twice.
+pub(crate) fn tally_approval(
+    approval: &mut ApprovalRequest,
+    approver: &str,
+    approved: bool,
+) -> Result<ApprovalOutcome, ApiError> {
+    if !approved {
+        return Ok(ApprovalOutcome::Rejected);
+    }
+    if !approval.record_approval(approver, Utc::now()) {
+        return Err(ApiError::conflict(format!(
+            "Approver {} has already approved this request",
+            approver
+        )));
+    }
+    if approval.quorum_reached() {
+        Ok(ApprovalOutcome::Approved)
+    } else {
+        Ok(ApprovalOutcome::Pending)
+    }
+}
+
+fn approval_to_response(approval: ApprovalRequest) -> ApprovalResponse {
     ApprovalResponse {
+        approval_count: approval.approval_count(),
+        required_approvals: approval.required_approvals,
         id: approval.id,
         run_id: approval.run_id,
         step_id: approval.step_id,
@@ -94,7 +153,7 @@ pub async fn list_pending_approvals(
     let repos = state.repos();
     let all_pending = repos
         .policies()
-        .list_all_pending_approvals(query.limit)
+        .list_all_pending_approvals(query.action_type, query.limit)
         .await?;
 
     let now = Utc::now();
@@ -124,14 +183,17 @@ pub async fn list_pending_approvals(
                     info!(approval_id = %approval.id, "Auto-expired stale approval");
 
                     // Also fail the associated run
-                    let _ = repos
+                    if let Ok(Some(run)) = repos
                         .runs()
                         .update_status(
                             &approval.run_id,
                             RunStatus::Failed,
                             Some("Approval expired"),
                         )
-                        .await;
+                        .await
+                    {
+                        spawn_run_summary(repos, &run);
+                    }
                 }
                 // Don't include expired approvals in the response
                 continue;
@@ -154,7 +216,7 @@ pub async fn resolve_approval(
     let repos = state.repos();
 
     // Get the approval
-    let approval = repos
+    let mut approval = repos
         .policies()
         .get_approval(&approval_id)
         .await?
@@ -206,6 +268,61 @@ pub async fn resolve_approval(
         )));
     }
 
+    let next_run_status = if request.approved {
+        RunStatus::Running
+    } else {
+        RunStatus::Failed
+    };
+    ensure_run_transition(run.status, next_run_status)?;
+
+    let mut outcome = tally_approval(&mut approval, &auth.api_key_id, request.approved)?;
+    if request.approved {
+        // Other approvers may be voting concurrently: append just this vote
+        // and let the stored votes decide the quorum
+        let vote = approval
+            .approvals
+            .last()
+            .cloned()
+            .expect("tally records the approval");
+        approval = repos
+            .policies()
+            .record_approval_vote(&approval_id, &vote)
+            .await?
+            .ok_or_else(|| {
+                ApiError::conflict("Approval is no longer pending or already has this vote")
+            })?;
+        outcome = if approval.quorum_reached() {
+            ApprovalOutcome::Approved
+        } else {
+            ApprovalOutcome::Pending
+        };
+    }
+
+    if outcome == ApprovalOutcome::Pending {
+        // Quorum not reached yet: record the individual approval and keep waiting
+        let audit_event = AuditEventBuilder::new(action::APPROVAL_RECORDED, resource::APPROVAL)
+            .actor(actor::API_KEY, Some(auth.api_key_id.clone()))
+            .resource_id(&approval_id)
+            .tenant(auth.tenant_id.clone())
+            .run(&approval.run_id)
+            .details(serde_json::json!({
+                "step_id": approval.step_id,
+                "approval_count": approval.approval_count(),
+                "required_approvals": approval.required_approvals,
+                "note": request.note,
+            }))
+            .build();
+        repos.spawn_audit(audit_event);
+
+        info!(
+            approval_id = %approval_id,
+            approval_count = approval.approval_count(),
+            required_approvals = approval.required_approvals,
+            "Recorded approval; waiting for quorum"
+        );
+        return Ok(Json(approval_to_response(approval)));
+    }
+
     // Resolve the approval
     let status = if request.approved {
         ApprovalStatus::Approved
@@ -223,7 +340,7 @@ pub async fn resolve_approval(
         .policies()
         .resolve_approval(&approval_id, resolution)
         .await?
-        .ok_or_else(|| ApiError::internal("Failed to resolve approval"))?;
+        .ok_or_else(|| ApiError::conflict("Approval is no longer pending"))?;
 
     // Audit log the approval decision
     let audit_action = if request.approved {
@@ -253,13 +370,6 @@ pub async fn resolve_approval(
             .await?
             .ok_or_else(|| ApiError::internal("Step not found for approved request"))?;
 
-        // Get the run details for context
-        let run = repos
-            .runs()
-            .get(&approval.run_id)
-            .await?
-            .ok_or_else(|| ApiError::internal("Run not found for approved request"))?;
-
         // Mark step as running (will be re-processed)
         repos
             .steps()
@@ -273,24 +383,36 @@ pub async fn resolve_approval(
             .await?;
 
         // Update run status back to running
-        repos
+        let resumed = repos
             .runs()
-            .update_status(&approval.run_id, RunStatus::Running, None)
+            .update_status(&approval.run_id, next_run_status, None)
             .await?;
-
-        // Re-enqueue the step for processing
+        applied_transition(resumed, &approval.run_id, next_run_status)?;
+
+        // Re-enqueue the step for processing, with a token authorizing
+        // exactly this tool call once
+        let mut context = run_job_context(&run, &auth.tenant_id);
+        if let Some(tool_name) = step.tool_name.as_deref() {
+            context.approval_token = Some(
+                repos
+                    .policies()
+                    .issue_approval_token(
+                        &approval_id,
+                        tool_name,
+                        state.approval_token_key(),
+                        APPROVAL_TOKEN_TTL,
+                    )
+                    .await
+                    .map_err(approval_token_error)?,
+            );
+        }
         let step_type = format!("{:?}", step.step_type).to_lowercase();
         let job = StepJob {
             run_id: approval.run_id.clone(),
             step_id: approval.step_id.clone(),
             step_type,
             input: step.input,
-            context: JobContext {
-                tenant_id: auth.tenant_id.clone(),
-                project_id: run.project_id,
-                trace_id: run.trace_id,
-                span_id: run.span_id,
-            },
+            context,
         };
 
         let message = QueueMessage::new(&approval.step_id, job);
@@ -333,14 +455,12 @@ pub async fn resolve_approval(
             .await?;
 
         // Mark run as failed
-        repos
+        let failed = repos
             .runs()
-            .update_status(
-                &approval.run_id,
-                RunStatus::Failed,
-                Some("Approval rejected"),
-            )
+            .update_status(&approval.run_id, next_run_status, Some("Approval rejected"))
             .await?;
+        let failed = applied_transition(failed, &approval.run_id, next_run_status)?;
+        spawn_run_summary(repos, &failed);
     }
 
     Ok(Json(approval_to_response(updated)))
@@ -38,6 +38,10 @@ pub struct ComponentStatus {
     pub database: ComponentHealth,
     /// Redis health
     pub redis: ComponentHealth,
+    /// OTLP exporter reachability, present when an endpoint is configured;
+    /// reported only, it does not affect overall readiness
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<ComponentHealth>,
 }
 
 /// Health status of an individual component
@@ -73,7 +77,8 @@ pub async fn health_check() -> Json<HealthResponse> {
 }
 
 /// Readiness probe - checks if the service can handle requests
-/// Verifies database and Redis connectivity
+/// Verifies database and Redis connectivity, and reports whether the OTLP
+/// exporter is reachable
 #[utoipa::path(
     get,
     path = "/ready",
@@ -83,6 +88,7 @@ pub async fn health_check() -> Json<HealthResponse> {
         (status = 503, description = "Service is not ready", body = ReadinessResponse)
     )
 )]
+#[allow(clippy::result_large_err)]
 pub async fn readiness_check(
     State(state): State<AppState>,
 ) -> Result<Json<ReadinessResponse>, (StatusCode, Json<ReadinessResponse>)> {
@@ -117,6 +123,11 @@ pub async fn readiness_check(
         error: redis_health.err(),
     };
 
+    let telemetry_status = match state.otel_endpoint.as_deref() {
+        Some(endpoint) => Some(check_telemetry(endpoint).await),
+        None => None,
+    };
+
     let all_healthy = db_status.status == "healthy" && redis_status.status == "healthy";
 
     let response = ReadinessResponse {
@@ -125,6 +136,7 @@ pub async fn readiness_check(
         components: ComponentStatus {
             database: db_status,
             redis: redis_status,
+            telemetry: telemetry_status,
         },
     };
 
@@ -168,3 +180,25 @@ async fn check_redis(state: &AppState) -> Result<(), String> {
         }
     }
 }
+
+/// Probe the OTLP exporter endpoint
+pub(crate) async fn check_telemetry(endpoint: &str) -> ComponentHealth {
+    let start = std::time::Instant::now();
+    let reachable = fd_otel::check_exporter_health(endpoint).await;
+    let latency_ms = start.elapsed().as_millis() as u64;
+
+    if reachable {
+        ComponentHealth {
+            status: "healthy",
+            latency_ms: Some(latency_ms),
+            error: None,
+        }
+    } else {
+        warn!(endpoint, "Telemetry health check failed");
+        ComponentHealth {
+            status: "unhealthy",
+            latency_ms: Some(latency_ms),
+            error: Some(format!("OTLP exporter unreachable at {}", endpoint)),
+        }
+    }
+}
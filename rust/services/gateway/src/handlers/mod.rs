@@ -8,6 +8,7 @@ pub mod policies;
 pub mod registry;
 pub mod runs;
 pub mod security;
+pub mod workflow_stream;
 pub mod workflows;
 
 #[cfg(test)]
@@ -22,11 +23,78 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use fd_storage::models::{Run, RunStatus, StepStatus};
 use serde::de::DeserializeOwned;
 use serde_json::json;
 use validator::Validate;
 
+/// Reject an illegal run status transition with `409 Conflict`
+pub(crate) fn ensure_run_transition(current: RunStatus, next: RunStatus) -> Result<(), ApiError> {
+    if current.can_transition_to(next) {
+        Ok(())
+    } else {
+        Err(ApiError::conflict(format!(
+            "Run cannot transition from {:?} to {:?}",
+            current, next
+        )))
+    }
+}
+
+/// Require a guarded run status update to have applied
+///
+/// The storage layer only writes a status the run may move to, so an
+/// update that matched no row raced with another transition (or targeted a
+/// missing run); either way it is answered with `409 Conflict`.
+pub(crate) fn applied_transition(
+    updated: Option<Run>,
+    run_id: &str,
+    next: RunStatus,
+) -> Result<Run, ApiError> {
+    updated.ok_or_else(|| {
+        ApiError::conflict(format!(
+            "Run '{}' can no longer transition to {:?}",
+            run_id, next
+        ))
+    })
+}
+
+/// Settle a guarded run status update made after a step result committed
+///
+/// The step's writes are already in, so a run that concurrently moved on
+/// (e.g. was cancelled) is not an error: the transition is dropped and the
+/// caller skips its follow-ups.
+pub(crate) fn settled_transition(
+    updated: Option<Run>,
+    run_id: &str,
+    next: RunStatus,
+) -> Option<Run> {
+    if updated.is_none() {
+        tracing::info!(
+            run_id,
+            ?next,
+            "Run moved on concurrently; transition dropped"
+        );
+    }
+    updated
+}
+
+/// Reject an illegal step status transition with `409 Conflict`
+pub(crate) fn ensure_step_transition(
+    current: StepStatus,
+    next: StepStatus,
+) -> Result<(), ApiError> {
+    if current.can_transition_to(next) {
+        Ok(())
+    } else {
+        Err(ApiError::conflict(format!(
+            "Step cannot transition from {:?} to {:?}",
+            current, next
+        )))
+    }
+}
+
 /// Standard API error response
+#[derive(Debug)]
 pub struct ApiError {
     pub status: StatusCode,
     pub code: &'static str,
@@ -58,6 +126,14 @@ impl ApiError {
         }
     }
 
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            code: "UNAUTHORIZED",
+            message: message.into(),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn forbidden(message: impl Into<String>) -> Self {
         Self {
@@ -67,6 +143,15 @@ impl ApiError {
         }
     }
 
+    /// Return when a request conflicts with the resource's current state
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::CONFLICT,
+            code: "CONFLICT",
+            message: message.into(),
+        }
+    }
+
     /// Return when a tool call is blocked by policy
     #[allow(dead_code)]
     pub fn policy_blocked(reason: impl Into<String>) -> Self {
@@ -86,6 +171,27 @@ impl ApiError {
         }
     }
 
+    /// Return when an agent already has its maximum number of active runs
+    pub fn agent_concurrency_exceeded(agent_id: &str, limit: i32) -> Self {
+        Self {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            code: "AGENT_CONCURRENCY_EXCEEDED",
+            message: format!(
+                "Agent {} has reached its limit of {} concurrent runs",
+                agent_id, limit
+            ),
+        }
+    }
+
+    /// Return when a request body exceeds the configured limit
+    pub fn payload_too_large(limit_bytes: usize) -> Self {
+        Self {
+            status: StatusCode::PAYLOAD_TOO_LARGE,
+            code: "PAYLOAD_TOO_LARGE",
+            message: format!("Request body exceeds the {} byte limit", limit_bytes),
+        }
+    }
+
     /// Return when request validation fails
     pub fn validation_error(errors: validator::ValidationErrors) -> Self {
         // Collect all error messages into a readable format
@@ -112,13 +218,14 @@ impl ApiError {
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let body = json!({
-            "error": {
-                "code": self.code,
-                "message": self.message
-            }
+        let mut error = json!({
+            "code": self.code,
+            "message": self.message
         });
-        (self.status, Json(body)).into_response()
+        if let Some(request_id) = crate::middleware::current_request_id() {
+            error["request_id"] = json!(request_id);
+        }
+        (self.status, Json(json!({ "error": error }))).into_response()
     }
 }
 
@@ -178,6 +285,64 @@ impl From<redis::RedisError> for ApiError {
     }
 }
 
+/// Validate a caller-supplied external ID (1-255 chars of `[A-Za-z0-9._:-]`)
+pub(crate) fn validate_external_id(external_id: &str) -> Result<(), ApiError> {
+    let valid_chars = external_id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | ':' | '-'));
+
+    if external_id.is_empty() || external_id.len() > 255 || !valid_chars {
+        return Err(ApiError::bad_request(
+            "external_id must be 1-255 characters of letters, digits, '.', '_', ':' or '-'",
+        ));
+    }
+
+    Ok(())
+}
+
+/// How a client referenced a registry entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EntityRef<'a> {
+    Id(&'a str),
+    Slug(&'a str),
+}
+
+impl<'a> EntityRef<'a> {
+    /// IDs carry their type prefix (`agt_...`); anything else is a slug
+    pub(crate) fn parse(value: &'a str, id_prefix: &str) -> Self {
+        match value.strip_prefix(id_prefix) {
+            Some(rest) if rest.starts_with('_') => Self::Id(value),
+            _ => Self::Slug(value),
+        }
+    }
+}
+
+/// Pick the single entity matching a slug
+///
+/// Slugs are only unique per project, so several matches mean the caller
+/// must use the ID (or a key scoped to one project) instead.
+pub(crate) fn single_slug_match<T>(
+    resource: &str,
+    slug: &str,
+    mut matches: Vec<T>,
+) -> Result<T, ApiError> {
+    match matches.len() {
+        0 => Err(ApiError {
+            status: StatusCode::NOT_FOUND,
+            code: "NOT_FOUND",
+            message: format!("{} with slug '{}' not found", resource, slug),
+        }),
+        1 => Ok(matches.remove(0)),
+        n => Err(ApiError::bad_request(format!(
+            "{} slug '{}' is ambiguous ({} matches across projects); use the {} ID",
+            resource,
+            slug,
+            n,
+            resource.to_lowercase()
+        ))),
+    }
+}
+
 // =============================================================================
 // Validated Extractors
 // =============================================================================
@@ -217,6 +382,27 @@ where
     }
 }
 
+/// Deserialize and validate a JSON body that was read as raw bytes
+///
+/// For handlers that need the exact body (e.g. to verify a signature) but
+/// still want `ValidatedJson` semantics.
+pub(crate) fn parse_validated_json<T>(body: &[u8]) -> Result<T, ApiError>
+where
+    T: DeserializeOwned + Validate,
+{
+    let value: T = serde_json::from_slice(body).map_err(|e| {
+        tracing::debug!(error = %e, "JSON parsing error");
+        ApiError::bad_request(format!("Invalid JSON: {}", e))
+    })?;
+
+    value.validate().map_err(|e| {
+        tracing::debug!(errors = ?e, "Validation error");
+        ApiError::validation_error(e)
+    })?;
+
+    Ok(value)
+}
+
 /// Query extractor that validates the parameters using the `validator` crate.
 ///
 /// Usage:
@@ -2,24 +2,21 @@
 //!
 //! Manages the execution of workflow steps using the DAG scheduler.
 //! Handles step completion callbacks and triggers dependent steps.
-//!
-//! Note: This module is implemented but not yet wired into handlers.
-//! It will be integrated in a future phase.
-
-#![allow(dead_code)]
 
 use fd_dag::{
     DagScheduler, SchedulerState, StepCompletionResult, StepDefinition,
-    StepStatus as DagStepStatus, StepType as DagStepType, WorkflowDag,
+    StepStatus as DagStepStatus, WorkflowDag,
 };
 use fd_storage::models::{
-    CreateWorkflowStepExecution, UpdateWorkflowRun, UpdateWorkflowStepExecution, WorkflowRunStatus,
-    WorkflowStepExecutionStatus, WorkflowStepType,
+    CreateWorkflowStepExecution, OutputRetention, UpdateWorkflowRun, UpdateWorkflowStepExecution,
+    WorkflowRunStatus, WorkflowStepExecution, WorkflowStepExecutionStatus, WorkflowStepType,
+};
+use fd_storage::queue::{
+    sets, JobContext, QueueMessage, StepJob, WorkflowEvent, WorkflowEventKind,
 };
-use fd_storage::queue::{JobContext, QueueMessage, StepJob};
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard, RwLock};
 use tracing::{debug, error, info, instrument, warn};
 use ulid::Ulid;
 
@@ -29,7 +26,314 @@ use crate::state::{AppState, Repos};
 /// In-memory cache of active workflow schedulers
 type SchedulerCache = Arc<RwLock<HashMap<String, DagScheduler>>>;
 
+/// Per-run locks serializing workflow scheduler transitions
+///
+/// Every transition restores the run's scheduler snapshot, mutates it and
+/// saves it back, so two transitions of the same run must not interleave or
+/// the later save drops the earlier one's changes. The locks are held by
+/// this gateway instance only; runs are expected to be driven by one gateway.
+#[derive(Clone, Default)]
+pub struct WorkflowRunLocks {
+    runs: Arc<Mutex<HashMap<String, Weak<AsyncMutex<()>>>>>,
+}
+
+impl WorkflowRunLocks {
+    /// Wait for exclusive access to a run's scheduler
+    pub async fn lock(&self, run_id: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut runs = self.runs.lock().expect("run lock registry poisoned");
+            runs.retain(|_, lock| lock.strong_count() > 0);
+            match runs.get(run_id).and_then(Weak::upgrade) {
+                Some(lock) => lock,
+                None => {
+                    let lock = Arc::new(AsyncMutex::new(()));
+                    runs.insert(run_id.to_string(), Arc::downgrade(&lock));
+                    lock
+                }
+            }
+        };
+        lock.lock_owned().await
+    }
+}
+
+/// Structural overview of a workflow run's DAG (for run detail views)
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct DagTopology {
+    /// Steps with no dependencies
+    pub entry_points: Vec<String>,
+    /// Steps no other step depends on
+    pub exit_points: Vec<String>,
+    /// Steps grouped into layers that can run in parallel
+    pub execution_layers: Vec<Vec<String>>,
+    /// Layers still expected to run, with decided-against branches removed
+    pub projected_layers: Vec<Vec<String>>,
+}
+
+/// A dependency holding back a pending workflow step
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct BlockingDependency {
+    pub step_id: String,
+    pub status: String,
+}
+
+/// Why a workflow step was skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipCause {
+    /// The step's condition (or the branch it sits on) evaluated false
+    ConditionFalse,
+    /// A step it depends on failed under the `continue` error policy
+    UpstreamFailed,
+}
+
+/// Output recorded for a skipped step
+///
+/// Stored as `{"skipped": true, "cause": ..., "detail": ...}` in both the
+/// step execution and the run's step results.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SkipOutput {
+    pub skipped: bool,
+    pub cause: SkipCause,
+    pub detail: String,
+}
+
+impl SkipOutput {
+    pub fn new(cause: SkipCause, detail: impl Into<String>) -> Self {
+        Self {
+            skipped: true,
+            cause,
+            detail: detail.into(),
+        }
+    }
+
+    /// Parse a stored skip marker, if `output` is one
+    pub fn from_output(output: &serde_json::Value) -> Option<Self> {
+        serde_json::from_value(output.clone())
+            .ok()
+            .filter(|skip: &Self| skip.skipped)
+    }
+
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("skip output always serializes")
+    }
+}
+
+/// Rebuild scheduler state from step execution records
+///
+/// Fallback for runs without a persisted snapshot. Only step statuses and
+/// outputs are recovered; the iteration count starts over.
+pub(crate) fn state_from_executions(
+    executions: Vec<WorkflowStepExecution>,
+    on_error: String,
+    max_iterations: u32,
+    paused: bool,
+) -> SchedulerState {
+    let mut step_status = HashMap::new();
+    let mut step_outputs = HashMap::new();
+
+    for exec in executions {
+        let status = match exec.status {
+            // An execution exists once the step was released to the queue
+            WorkflowStepExecutionStatus::Pending => DagStepStatus::Running,
+            WorkflowStepExecutionStatus::Running => DagStepStatus::Running,
+            WorkflowStepExecutionStatus::WaitingApproval => DagStepStatus::WaitingApproval,
+            WorkflowStepExecutionStatus::Completed => DagStepStatus::Completed,
+            WorkflowStepExecutionStatus::Failed => DagStepStatus::Failed,
+            WorkflowStepExecutionStatus::Skipped => DagStepStatus::Skipped,
+            WorkflowStepExecutionStatus::Retrying => DagStepStatus::Running,
+        };
+        step_status.insert(exec.step_id.clone(), status);
+        if let Some(output) = exec.output {
+            step_outputs.insert(exec.step_id, output);
+        }
+    }
+
+    SchedulerState {
+        step_status,
+        step_outputs,
+        on_error,
+        max_iterations,
+        iteration_count: 0,
+        loop_iterations: HashMap::new(),
+        paused,
+    }
+}
+
+/// Released steps with no live queue entry
+///
+/// A step is live when one of its executions has been picked up by a worker,
+/// or is still pending and its ID is in the run's enqueued set. Anything else
+/// was lost between persisting the scheduler and publishing the job, e.g. by
+/// a gateway crash, and has to be enqueued again.
+pub(crate) fn steps_missing_from_queue(
+    released: &[String],
+    executions: &[WorkflowStepExecution],
+    enqueued: &HashSet<String>,
+) -> Vec<String> {
+    let mut missing: Vec<String> = released
+        .iter()
+        .filter(|step_id| {
+            !executions.iter().any(|exec| {
+                &exec.step_id == *step_id
+                    && match exec.status {
+                        WorkflowStepExecutionStatus::Pending => enqueued.contains(&exec.id),
+                        WorkflowStepExecutionStatus::Running
+                        | WorkflowStepExecutionStatus::WaitingApproval
+                        | WorkflowStepExecutionStatus::Retrying => true,
+                        WorkflowStepExecutionStatus::Completed
+                        | WorkflowStepExecutionStatus::Failed
+                        | WorkflowStepExecutionStatus::Skipped => false,
+                    }
+            })
+        })
+        .cloned()
+        .collect();
+    missing.sort();
+    missing
+}
+
+/// Status of a run once resumed: still waiting if any step awaits approval
+pub(crate) fn resumed_run_status(scheduler: &DagScheduler) -> WorkflowRunStatus {
+    if scheduler
+        .all_step_status()
+        .values()
+        .any(|status| *status == DagStepStatus::WaitingApproval)
+    {
+        WorkflowRunStatus::WaitingApproval
+    } else {
+        WorkflowRunStatus::Running
+    }
+}
+
+/// Mark steps released by a transition as running
+///
+/// A released step stays pending in the scheduler until its result comes
+/// back, so without this the next transition would release it again.
+fn mark_released(scheduler: &mut DagScheduler, step_ids: &[String]) -> Result<(), ApiError> {
+    for step_id in step_ids {
+        scheduler
+            .mark_running(step_id)
+            .map_err(|e| ApiError::internal(format!("DAG error: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// Message of a step error reported as JSON
+///
+/// Workers report `{"message": ...}`; anything else is used verbatim.
+pub(crate) fn error_message(error: &serde_json::Value) -> String {
+    match error.get("message").and_then(|m| m.as_str()) {
+        Some(message) => message.to_string(),
+        None => match error.as_str() {
+            Some(message) => message.to_string(),
+            None => error.to_string(),
+        },
+    }
+}
+
+/// Events to publish after a step transition
+///
+/// Always includes the step event, then a skip event for each step skipped
+/// as a side effect, followed by a run-level event when the transition
+/// finished or failed the workflow.
+pub(crate) fn transition_events(
+    run_id: &str,
+    step_id: &str,
+    step_event: WorkflowEventKind,
+    result: &StepCompletionResult,
+) -> Vec<WorkflowEvent> {
+    let mut events = vec![WorkflowEvent::step(run_id, step_id, step_event)];
+    events.extend(
+        result
+            .skipped_steps
+            .iter()
+            .map(|id| WorkflowEvent::step(run_id, id, WorkflowEventKind::StepSkipped)),
+    );
+
+    if result.workflow_failed {
+        events.push(WorkflowEvent::run(run_id, WorkflowEventKind::RunFailed));
+    } else if result.workflow_complete {
+        events.push(WorkflowEvent::run(run_id, WorkflowEventKind::RunCompleted));
+    }
+
+    events
+}
+
+/// Completed step IDs ordered by completion time, oldest first
+pub(crate) fn completed_step_order(executions: &[WorkflowStepExecution]) -> Vec<String> {
+    let mut completed: Vec<_> = executions
+        .iter()
+        .filter(|e| e.status == WorkflowStepExecutionStatus::Completed)
+        .filter_map(|e| e.completed_at.map(|at| (at, &e.step_id)))
+        .collect();
+    completed.sort_by_key(|(at, _)| *at);
+    completed
+        .into_iter()
+        .map(|(_, step_id)| step_id.clone())
+        .collect()
+}
+
+/// Prune a run's `step_results` according to its workflow's `output_retention`
+///
+/// Pruned entries are rewritten one at a time, so concurrent step results
+/// recorded for other steps are not overwritten.
+pub(crate) async fn apply_output_retention(
+    repos: &Repos,
+    run_id: &str,
+    workflow_id: &str,
+) -> Result<(), ApiError> {
+    let Some(workflow) = repos.workflows().get(workflow_id).await? else {
+        return Ok(());
+    };
+    let retention = match OutputRetention::from_definition(&workflow.definition) {
+        Ok(OutputRetention::All) => return Ok(()),
+        Ok(retention) => retention,
+        Err(e) => {
+            warn!(run_id, workflow_id, error = %e, "Ignoring invalid output_retention");
+            return Ok(());
+        }
+    };
+    let Some(run) = repos.workflows().get_run(run_id).await? else {
+        return Ok(());
+    };
+
+    let executions = repos
+        .workflows()
+        .list_step_executions_by_run(run_id)
+        .await?;
+    let order = completed_step_order(&executions);
+    let terminal_steps = match retention {
+        OutputRetention::TerminalOnly => workflow
+            .definition
+            .get("steps")
+            .and_then(|steps| serde_json::from_value::<Vec<StepDefinition>>(steps.clone()).ok())
+            .and_then(|steps| WorkflowDag::build(steps).ok())
+            .map(|dag| dag.exit_points())
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+
+    let mut step_results = run.step_results;
+    let pruned = retention.prune(&mut step_results, &order, &terminal_steps);
+    for step_id in &pruned {
+        repos
+            .workflows()
+            .update_run_step_results(run_id, step_id, step_results[step_id.as_str()].clone())
+            .await?;
+    }
+    if !pruned.is_empty() {
+        debug!(
+            run_id,
+            pruned = pruned.len(),
+            "Pruned retained step outputs"
+        );
+    }
+    Ok(())
+}
+
 /// Workflow orchestrator that manages DAG execution
+///
+/// Callers hold the run's [`WorkflowRunLocks`] guard across a transition.
 #[derive(Clone)]
 pub struct WorkflowOrchestrator {
     state: AppState,
@@ -50,14 +354,13 @@ impl WorkflowOrchestrator {
     }
 
     /// Start a workflow run
-    #[instrument(skip(self, input))]
+    #[instrument(skip(self))]
     pub async fn start_workflow(
         &self,
         run_id: &str,
         workflow_id: &str,
         project_id: &str,
         tenant_id: &str,
-        input: serde_json::Value,
     ) -> Result<Vec<String>, ApiError> {
         // Get workflow definition
         let workflow = self
@@ -86,16 +389,33 @@ impl WorkflowOrchestrator {
         }
 
         // Store scheduler
+        let mut scheduler = scheduler;
+        mark_released(&mut scheduler, &initial_steps)?;
         {
             let mut cache = self.schedulers.write().await;
             cache.insert(run_id.to_string(), scheduler);
         }
+        self.persist_scheduler_state(run_id).await?;
 
         // Create step executions and enqueue jobs for initial steps
+        let run = self
+            .repos()
+            .workflows()
+            .get_run(run_id)
+            .await?
+            .ok_or_else(|| ApiError::not_found("WorkflowRun", run_id))?;
+        let run_span = fd_otel::parent_context(run.trace_id.as_deref(), run.span_id.as_deref());
         for step_id in &initial_steps {
             if let Some(step) = steps.iter().find(|s| &s.id == step_id) {
-                self.create_and_enqueue_step(run_id, step, project_id, tenant_id, &input)
-                    .await?;
+                self.create_and_enqueue_step(
+                    run_id,
+                    step,
+                    project_id,
+                    tenant_id,
+                    1,
+                    run_span.as_ref(),
+                )
+                .await?;
             }
         }
 
@@ -105,6 +425,12 @@ impl WorkflowOrchestrator {
             .update_run_status(run_id, WorkflowRunStatus::Running)
             .await?;
 
+        self.publish_events(vec![WorkflowEvent::run(
+            run_id,
+            WorkflowEventKind::RunStarted,
+        )])
+        .await;
+
         info!(
             run_id,
             workflow_id,
@@ -129,6 +455,36 @@ impl WorkflowOrchestrator {
         // Ensure scheduler is available (restore from DB if needed)
         self.get_or_restore_scheduler(run_id).await?;
 
+        // Apply the step's output projection before the output is stored
+        let (projection, is_tool) = {
+            let cache = self.schedulers.read().await;
+            let step = cache
+                .get(run_id)
+                .and_then(|scheduler| scheduler.dag().get_step(step_id));
+            (
+                step.map(|step| (step.project_output(&output), step.keep_raw_output())),
+                step.is_some_and(|step| {
+                    WorkflowStepType::from(step.step_type) == WorkflowStepType::Tool
+                }),
+            )
+        };
+        let (output, raw_output) = match projection {
+            Some((Ok(projected), keep_raw)) => (projected, keep_raw.then_some(output)),
+            Some((Err(e), _)) => {
+                warn!(run_id, step_id, error = %e, "Output projection failed");
+                return self
+                    .fail_step(
+                        run_id,
+                        step_id,
+                        execution_id,
+                        serde_json::json!({ "message": e.to_string() }),
+                        None,
+                    )
+                    .await;
+            }
+            None => (output, None),
+        };
+
         // Get scheduler
         let result = {
             let mut cache = self.schedulers.write().await;
@@ -136,10 +492,13 @@ impl WorkflowOrchestrator {
                 .get_mut(run_id)
                 .ok_or_else(|| ApiError::internal("Scheduler not found after restore"))?;
 
-            scheduler
+            let result = scheduler
                 .complete_step(step_id, output.clone())
-                .map_err(|e| ApiError::internal(format!("DAG error: {}", e)))?
+                .map_err(|e| ApiError::internal(format!("DAG error: {}", e)))?;
+            mark_released(scheduler, &result.ready_steps)?;
+            result
         };
+        self.persist_scheduler_state(run_id).await?;
 
         // Update step execution in DB
         self.repos()
@@ -148,7 +507,8 @@ impl WorkflowOrchestrator {
                 execution_id,
                 UpdateWorkflowStepExecution {
                     status: Some(WorkflowStepExecutionStatus::Completed),
-                    output: Some(output.clone()),
+                    // The execution record keeps the raw output when requested
+                    output: Some(raw_output.unwrap_or_else(|| output.clone())),
                     input_tokens,
                     output_tokens,
                     completed_at: Some(chrono::Utc::now()),
@@ -158,16 +518,35 @@ impl WorkflowOrchestrator {
             .await?;
 
         // Update run step results
-        self.repos()
+        let updated_run = self
+            .repos()
             .workflows()
             .update_run_step_results(run_id, step_id, output.clone())
             .await?;
 
-        // Update run usage
-        if let (Some(in_tok), Some(out_tok)) = (input_tokens, output_tokens) {
+        // Record steps on the untaken side of a conditional branch
+        self.record_skipped(
+            run_id,
+            &result.skipped_steps,
+            &SkipOutput::new(SkipCause::ConditionFalse, "branch_not_taken"),
+        )
+        .await?;
+        if let Some(run) = updated_run {
+            apply_output_retention(self.repos(), run_id, &run.workflow_id).await?;
+        }
+
+        // Update run usage; each completed tool step counts as one tool call
+        let tool_calls = i32::from(is_tool);
+        if (input_tokens.is_some() && output_tokens.is_some()) || tool_calls > 0 {
             self.repos()
                 .workflows()
-                .increment_run_usage(run_id, in_tok, out_tok, 0, 0)
+                .increment_run_usage(
+                    run_id,
+                    input_tokens.unwrap_or(0),
+                    output_tokens.unwrap_or(0),
+                    tool_calls,
+                    0,
+                )
                 .await?;
         }
 
@@ -175,7 +554,8 @@ impl WorkflowOrchestrator {
         if result.workflow_complete {
             self.complete_workflow(run_id, Some(output)).await?;
         } else if result.workflow_failed {
-            self.fail_workflow(run_id, result.error.as_deref().unwrap_or("Unknown error"))
+            let message = result.error.as_deref().unwrap_or("Unknown error");
+            self.fail_workflow(run_id, serde_json::json!({ "message": message }))
                 .await?;
         } else {
             // Enqueue ready steps
@@ -183,10 +563,19 @@ impl WorkflowOrchestrator {
                 .await?;
         }
 
+        self.publish_events(transition_events(
+            run_id,
+            step_id,
+            WorkflowEventKind::StepCompleted,
+            &result,
+        ))
+        .await;
+
         info!(
             run_id,
             step_id,
             ready_steps = ?result.ready_steps,
+            skipped_steps = ?result.skipped_steps,
             workflow_complete = result.workflow_complete,
             "Step completed"
         );
@@ -195,14 +584,21 @@ impl WorkflowOrchestrator {
     }
 
     /// Handle step failure
-    #[instrument(skip(self))]
+    ///
+    /// `error` is recorded on the execution. When the failure fails the
+    /// workflow, `run_error` is recorded on the run, defaulting to the
+    /// scheduler's failure message.
+    #[instrument(skip(self, error, run_error))]
     pub async fn fail_step(
         &self,
         run_id: &str,
         step_id: &str,
         execution_id: &str,
-        error: &str,
+        error: serde_json::Value,
+        run_error: Option<serde_json::Value>,
     ) -> Result<StepCompletionResult, ApiError> {
+        let message = error_message(&error);
+
         // Ensure scheduler is available (restore from DB if needed)
         self.get_or_restore_scheduler(run_id).await?;
 
@@ -213,10 +609,13 @@ impl WorkflowOrchestrator {
                 .get_mut(run_id)
                 .ok_or_else(|| ApiError::internal("Scheduler not found after restore"))?;
 
-            scheduler
-                .fail_step(step_id, error)
-                .map_err(|e| ApiError::internal(format!("DAG error: {}", e)))?
+            let result = scheduler
+                .fail_step(step_id, &message)
+                .map_err(|e| ApiError::internal(format!("DAG error: {}", e)))?;
+            mark_released(scheduler, &result.ready_steps)?;
+            result
         };
+        self.persist_scheduler_state(run_id).await?;
 
         // Update step execution in DB
         self.repos()
@@ -225,16 +624,30 @@ impl WorkflowOrchestrator {
                 execution_id,
                 UpdateWorkflowStepExecution {
                     status: Some(WorkflowStepExecutionStatus::Failed),
-                    error: Some(serde_json::json!({ "message": error })),
+                    error: Some(error),
                     completed_at: Some(chrono::Utc::now()),
                     ..Default::default()
                 },
             )
             .await?;
 
+        // Record dependents skipped under the continue policy
+        self.record_skipped(
+            run_id,
+            &result.skipped_steps,
+            &SkipOutput::new(
+                SkipCause::UpstreamFailed,
+                format!("Step '{}' failed", step_id),
+            ),
+        )
+        .await?;
+
         // Handle workflow failure or continuation
         if result.workflow_failed {
-            self.fail_workflow(run_id, error).await?;
+            let run_error = run_error.unwrap_or_else(
+                || serde_json::json!({ "message": result.error.as_deref().unwrap_or(&message) }),
+            );
+            self.fail_workflow(run_id, run_error).await?;
         } else if result.workflow_complete {
             // Workflow complete with some failures (continue policy)
             self.complete_workflow(run_id, None).await?;
@@ -244,10 +657,18 @@ impl WorkflowOrchestrator {
                 .await?;
         }
 
+        self.publish_events(transition_events(
+            run_id,
+            step_id,
+            WorkflowEventKind::StepFailed,
+            &result,
+        ))
+        .await;
+
         warn!(
             run_id,
             step_id,
-            error,
+            error = %message,
             workflow_failed = result.workflow_failed,
             "Step failed"
         );
@@ -255,6 +676,52 @@ impl WorkflowOrchestrator {
         Ok(result)
     }
 
+    /// Enqueue another attempt of a failed step
+    ///
+    /// The step stays running in the scheduler, so its dependents keep
+    /// waiting. Returns the new execution's ID.
+    #[instrument(skip(self))]
+    pub async fn retry_step(
+        &self,
+        run_id: &str,
+        step_id: &str,
+        attempt: i32,
+    ) -> Result<String, ApiError> {
+        let run = self
+            .repos()
+            .workflows()
+            .get_run(run_id)
+            .await?
+            .ok_or_else(|| ApiError::not_found("WorkflowRun", run_id))?;
+        let workflow = self
+            .repos()
+            .workflows()
+            .get(&run.workflow_id)
+            .await?
+            .ok_or_else(|| ApiError::internal("Workflow not found for run"))?;
+
+        let steps = self.parse_workflow_steps(&workflow.definition)?;
+        let step = steps
+            .iter()
+            .find(|s| s.id == step_id)
+            .ok_or_else(|| ApiError::not_found("WorkflowStep", step_id))?;
+        let run_span = fd_otel::parent_context(run.trace_id.as_deref(), run.span_id.as_deref());
+
+        let execution_id = self
+            .create_and_enqueue_step(
+                run_id,
+                step,
+                &run.project_id,
+                &run.project_id, // tenant_id same as project_id for now
+                attempt,
+                run_span.as_ref(),
+            )
+            .await?;
+
+        info!(run_id, step_id, attempt, "Retrying failed step");
+        Ok(execution_id)
+    }
+
     /// Skip a step (e.g., condition not met)
     #[instrument(skip(self))]
     pub async fn skip_step(
@@ -262,7 +729,8 @@ impl WorkflowOrchestrator {
         run_id: &str,
         step_id: &str,
         execution_id: &str,
-        reason: &str,
+        cause: SkipCause,
+        detail: &str,
     ) -> Result<StepCompletionResult, ApiError> {
         // Ensure scheduler is available (restore from DB if needed)
         self.get_or_restore_scheduler(run_id).await?;
@@ -273,37 +741,73 @@ impl WorkflowOrchestrator {
                 .get_mut(run_id)
                 .ok_or_else(|| ApiError::internal("Scheduler not found after restore"))?;
 
-            scheduler
-                .skip_step(step_id)
-                .map_err(|e| ApiError::internal(format!("DAG error: {}", e)))?
+            let result = scheduler
+                .mark_skipped_with_cascade(step_id)
+                .map_err(|e| ApiError::internal(format!("DAG error: {}", e)))?;
+            mark_released(scheduler, &result.ready_steps)?;
+            result
         };
+        self.persist_scheduler_state(run_id).await?;
 
         // Update step execution in DB
+        let skip = SkipOutput::new(cause, detail);
         self.repos()
             .workflows()
             .update_step_execution(
                 execution_id,
                 UpdateWorkflowStepExecution {
                     status: Some(WorkflowStepExecutionStatus::Skipped),
-                    output: Some(serde_json::json!({ "skipped": true, "reason": reason })),
+                    output: Some(skip.to_value()),
                     completed_at: Some(chrono::Utc::now()),
                     ..Default::default()
                 },
             )
             .await?;
+        self.record_skipped(run_id, &[step_id.to_string()], &skip)
+            .await?;
+        self.record_skipped(run_id, &result.skipped_steps, &skip)
+            .await?;
 
         if result.workflow_complete {
             self.complete_workflow(run_id, None).await?;
+        } else if result.workflow_failed {
+            let message = result.error.as_deref().unwrap_or("Unknown error");
+            self.fail_workflow(run_id, serde_json::json!({ "message": message }))
+                .await?;
         } else {
             self.enqueue_ready_steps(run_id, &result.ready_steps)
                 .await?;
         }
 
-        debug!(run_id, step_id, reason, "Step skipped");
+        self.publish_events(transition_events(
+            run_id,
+            step_id,
+            WorkflowEventKind::StepSkipped,
+            &result,
+        ))
+        .await;
+
+        debug!(run_id, step_id, ?cause, detail, "Step skipped");
 
         Ok(result)
     }
 
+    /// Record skip markers in the run's step results
+    async fn record_skipped(
+        &self,
+        run_id: &str,
+        step_ids: &[String],
+        skip: &SkipOutput,
+    ) -> Result<(), ApiError> {
+        for skipped_id in step_ids {
+            self.repos()
+                .workflows()
+                .update_run_step_results(run_id, skipped_id, skip.to_value())
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Mark step as waiting for approval
     pub async fn mark_waiting_approval(
         &self,
@@ -314,7 +818,7 @@ impl WorkflowOrchestrator {
         // Ensure scheduler is available (restore from DB if needed)
         self.get_or_restore_scheduler(run_id).await?;
 
-        {
+        let paused = {
             let mut cache = self.schedulers.write().await;
             let scheduler = cache
                 .get_mut(run_id)
@@ -323,7 +827,9 @@ impl WorkflowOrchestrator {
             scheduler
                 .mark_waiting_approval(step_id)
                 .map_err(|e| ApiError::internal(format!("DAG error: {}", e)))?;
-        }
+            scheduler.is_paused()
+        };
+        self.persist_scheduler_state(run_id).await?;
 
         // Update step execution
         self.repos()
@@ -337,26 +843,172 @@ impl WorkflowOrchestrator {
             )
             .await?;
 
-        // Update run status
+        // A paused run stays paused; resuming it picks up the waiting step
         self.repos()
             .workflows()
             .update_run(
                 run_id,
                 UpdateWorkflowRun {
-                    status: Some(WorkflowRunStatus::WaitingApproval),
+                    status: (!paused).then_some(WorkflowRunStatus::WaitingApproval),
                     current_step_id: Some(step_id.to_string()),
                     ..Default::default()
                 },
             )
             .await?;
 
+        self.publish_events(vec![WorkflowEvent::step(
+            run_id,
+            step_id,
+            WorkflowEventKind::StepWaitingApproval,
+        )])
+        .await;
+
         info!(run_id, step_id, "Step waiting for approval");
 
         Ok(())
     }
 
-    /// Get execution layers for a workflow run (for visualization)
-    pub async fn get_execution_layers(&self, run_id: &str) -> Result<Vec<Vec<String>>, ApiError> {
+    /// Pause a workflow run
+    ///
+    /// In-flight steps may still finish, but their dependents are held until
+    /// the run is resumed.
+    #[instrument(skip(self))]
+    pub async fn pause_run(&self, run_id: &str) -> Result<(), ApiError> {
+        self.get_or_restore_scheduler(run_id).await?;
+
+        {
+            let mut cache = self.schedulers.write().await;
+            let scheduler = cache
+                .get_mut(run_id)
+                .ok_or_else(|| ApiError::internal("Scheduler not found after restore"))?;
+            scheduler.pause();
+        }
+        self.persist_scheduler_state(run_id).await?;
+
+        self.repos()
+            .workflows()
+            .update_run_status(run_id, WorkflowRunStatus::Paused)
+            .await?;
+
+        self.publish_events(vec![WorkflowEvent::run(
+            run_id,
+            WorkflowEventKind::RunPaused,
+        )])
+        .await;
+
+        info!(run_id, "Workflow run paused");
+        Ok(())
+    }
+
+    /// Resume a paused workflow run and enqueue the steps that became ready
+    ///
+    /// Returns the step IDs that were enqueued.
+    #[instrument(skip(self))]
+    pub async fn resume_run(&self, run_id: &str) -> Result<Vec<String>, ApiError> {
+        self.get_or_restore_scheduler(run_id).await?;
+
+        // Skip steps that still have a live execution, e.g. enqueued before
+        // the pause; finished ones don't count, loop bodies run again
+        let executions = self
+            .repos()
+            .workflows()
+            .list_step_executions_by_run(run_id)
+            .await?;
+        let enqueued: HashSet<String> = self
+            .state
+            .queue
+            .set_members(&sets::enqueued_steps(run_id))
+            .await?
+            .into_iter()
+            .collect();
+        let (ready, status) = {
+            let mut cache = self.schedulers.write().await;
+            let scheduler = cache
+                .get_mut(run_id)
+                .ok_or_else(|| ApiError::internal("Scheduler not found after restore"))?;
+            let ready = steps_missing_from_queue(&scheduler.resume(), &executions, &enqueued);
+            mark_released(scheduler, &ready)?;
+            (ready, resumed_run_status(scheduler))
+        };
+        self.persist_scheduler_state(run_id).await?;
+
+        self.repos()
+            .workflows()
+            .update_run_status(run_id, status)
+            .await?;
+
+        self.publish_events(vec![WorkflowEvent::run(
+            run_id,
+            WorkflowEventKind::RunResumed,
+        )])
+        .await;
+
+        self.enqueue_ready_steps(run_id, &ready).await?;
+
+        info!(run_id, resumed_steps = ?ready, "Workflow run resumed");
+        Ok(ready)
+    }
+
+    /// Get entry/exit points and execution layers for a workflow run
+    pub async fn get_dag_topology(&self, run_id: &str) -> Result<DagTopology, ApiError> {
+        // Ensure scheduler is available (restore from DB if needed)
+        self.get_or_restore_scheduler(run_id).await?;
+
+        let cache = self.schedulers.read().await;
+        let scheduler = cache
+            .get(run_id)
+            .ok_or_else(|| ApiError::internal("Scheduler not found after restore"))?;
+
+        let dag = scheduler.dag();
+        let mut entry_points = dag.entry_points().to_vec();
+        entry_points.sort();
+
+        Ok(DagTopology {
+            entry_points,
+            exit_points: dag.exit_points(),
+            execution_layers: scheduler.execution_layers(),
+            projected_layers: scheduler.projected_layers(),
+        })
+    }
+
+    /// Pending steps of a workflow run and the dependencies they wait on
+    pub async fn blocked_steps(
+        &self,
+        run_id: &str,
+    ) -> Result<BTreeMap<String, Vec<BlockingDependency>>, ApiError> {
+        // Ensure scheduler is available (restore from DB if needed)
+        self.get_or_restore_scheduler(run_id).await?;
+
+        let cache = self.schedulers.read().await;
+        let scheduler = cache
+            .get(run_id)
+            .ok_or_else(|| ApiError::internal("Scheduler not found after restore"))?;
+
+        Ok(scheduler
+            .all_step_status()
+            .iter()
+            .filter(|(_, status)| **status == DagStepStatus::Pending)
+            .filter_map(|(step_id, _)| {
+                let blocking: Vec<BlockingDependency> = scheduler
+                    .blocking_dependencies(step_id)
+                    .into_iter()
+                    .map(|(dep, status)| BlockingDependency {
+                        step_id: dep,
+                        status: format!("{:?}", status).to_lowercase(),
+                    })
+                    .collect();
+                (!blocking.is_empty()).then(|| (step_id.clone(), blocking))
+            })
+            .collect())
+    }
+
+    /// Check whether step `to` is downstream of step `from` in a workflow run
+    pub async fn is_step_reachable(
+        &self,
+        run_id: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<bool, ApiError> {
         // Ensure scheduler is available (restore from DB if needed)
         self.get_or_restore_scheduler(run_id).await?;
 
@@ -365,18 +1017,112 @@ impl WorkflowOrchestrator {
             .get(run_id)
             .ok_or_else(|| ApiError::internal("Scheduler not found after restore"))?;
 
-        Ok(scheduler.execution_layers())
+        let dag = scheduler.dag();
+        for step_id in [from, to] {
+            if dag.get_step(step_id).is_none() {
+                return Err(ApiError::not_found("WorkflowStep", step_id));
+            }
+        }
+        Ok(dag.is_reachable(from, to))
     }
 
     /// Clean up scheduler for completed run
     pub async fn cleanup(&self, run_id: &str) {
-        let mut cache = self.schedulers.write().await;
-        cache.remove(run_id);
+        {
+            let mut cache = self.schedulers.write().await;
+            cache.remove(run_id);
+        }
+        if let Err(e) = self
+            .state
+            .queue
+            .set_delete(&sets::enqueued_steps(run_id))
+            .await
+        {
+            warn!(run_id, error = %e, "Failed to delete enqueued step set");
+        }
         debug!(run_id, "Cleaned up scheduler");
     }
 
+    /// Persist the cached scheduler's snapshot for a run
+    async fn persist_scheduler_state(&self, run_id: &str) -> Result<(), ApiError> {
+        let snapshot = {
+            let cache = self.schedulers.read().await;
+            match cache.get(run_id) {
+                Some(scheduler) => scheduler.snapshot(),
+                None => return Ok(()),
+            }
+        };
+        let snapshot = serde_json::to_value(snapshot)
+            .map_err(|e| ApiError::internal(format!("Failed to serialize scheduler: {}", e)))?;
+
+        self.repos()
+            .workflows()
+            .save_scheduler_state(run_id, &snapshot)
+            .await?;
+        Ok(())
+    }
+
+    /// Enqueue the released steps of a run that never reached the queue
+    ///
+    /// Steps released just before a gateway crash may have been recorded in
+    /// the scheduler without their job being published. Restoring a scheduler
+    /// never enqueues anything itself; this is the explicit recovery step,
+    /// run once per active run at startup. Returns the step IDs enqueued.
+    #[instrument(skip(self))]
+    pub async fn recover_run(&self, run_id: &str) -> Result<Vec<String>, ApiError> {
+        self.get_or_restore_scheduler(run_id).await?;
+
+        // Ready steps count too: older snapshots left released steps pending
+        let candidates = {
+            let cache = self.schedulers.read().await;
+            let scheduler = cache
+                .get(run_id)
+                .ok_or_else(|| ApiError::internal("Scheduler not found after restore"))?;
+            let mut candidates: Vec<String> = scheduler
+                .all_step_status()
+                .iter()
+                .filter(|(_, status)| **status == DagStepStatus::Running)
+                .map(|(step_id, _)| step_id.clone())
+                .collect();
+            if !scheduler.is_paused() {
+                candidates.extend(scheduler.get_ready_steps());
+            }
+            candidates
+        };
+
+        let executions = self
+            .repos()
+            .workflows()
+            .list_step_executions_by_run(run_id)
+            .await?;
+        let enqueued: HashSet<String> = self
+            .state
+            .queue
+            .set_members(&sets::enqueued_steps(run_id))
+            .await?
+            .into_iter()
+            .collect();
+        let missing = steps_missing_from_queue(&candidates, &executions, &enqueued);
+        if missing.is_empty() {
+            return Ok(missing);
+        }
+
+        {
+            let mut cache = self.schedulers.write().await;
+            let scheduler = cache
+                .get_mut(run_id)
+                .ok_or_else(|| ApiError::internal("Scheduler not found after restore"))?;
+            mark_released(scheduler, &missing)?;
+        }
+        self.persist_scheduler_state(run_id).await?;
+
+        warn!(run_id, steps = ?missing, "Re-enqueueing released steps lost before restart");
+        self.enqueue_ready_steps(run_id, &missing).await?;
+        Ok(missing)
+    }
+
     /// Get or restore scheduler for a workflow run
-    /// This enables surviving gateway restarts by reconstructing scheduler from DB
+    /// This enables surviving gateway restarts by reconstructing scheduler from DB.
     async fn get_or_restore_scheduler(&self, run_id: &str) -> Result<(), ApiError> {
         // Check if already in cache
         {
@@ -415,45 +1161,20 @@ impl WorkflowOrchestrator {
         let dag = WorkflowDag::build(steps)
             .map_err(|e| ApiError::bad_request(format!("Invalid workflow DAG: {}", e)))?;
 
-        // Get step executions to restore state
-        let executions = self
-            .repos()
-            .workflows()
-            .list_step_executions_by_run(run_id)
-            .await?;
-
-        // Build scheduler state from executions
-        let mut step_status = std::collections::HashMap::new();
-        let mut step_outputs = std::collections::HashMap::new();
-
-        // Initialize all steps as pending
-        for step_id in dag.step_ids() {
-            step_status.insert(step_id.clone(), DagStepStatus::Pending);
-        }
-
-        // Update from executions
-        for exec in executions {
-            let status = match exec.status {
-                WorkflowStepExecutionStatus::Pending => DagStepStatus::Pending,
-                WorkflowStepExecutionStatus::Running => DagStepStatus::Running,
-                WorkflowStepExecutionStatus::WaitingApproval => DagStepStatus::WaitingApproval,
-                WorkflowStepExecutionStatus::Completed => DagStepStatus::Completed,
-                WorkflowStepExecutionStatus::Failed => DagStepStatus::Failed,
-                WorkflowStepExecutionStatus::Skipped => DagStepStatus::Skipped,
-                WorkflowStepExecutionStatus::Retrying => DagStepStatus::Running,
-            };
-            step_status.insert(exec.step_id.clone(), status);
-            if let Some(output) = exec.output {
-                step_outputs.insert(exec.step_id, output);
-            }
-        }
-
-        let state = SchedulerState {
-            step_status,
-            step_outputs,
-            on_error: workflow.on_error,
-            max_iterations: workflow.max_iterations as u32,
-            iteration_count: 0,
+        let state = match run.scheduler_state {
+            Some(snapshot) => serde_json::from_value(snapshot).map_err(|e| {
+                ApiError::internal(format!("Corrupt scheduler state for run: {}", e))
+            })?,
+            // Runs started before snapshots were persisted
+            None => state_from_executions(
+                self.repos()
+                    .workflows()
+                    .list_step_executions_by_run(run_id)
+                    .await?,
+                workflow.on_error,
+                workflow.max_iterations as u32,
+                run.status == WorkflowRunStatus::Paused,
+            ),
         };
 
         let scheduler = DagScheduler::from_dag_with_state(dag, state);
@@ -494,10 +1215,16 @@ impl WorkflowOrchestrator {
         step: &StepDefinition,
         project_id: &str,
         tenant_id: &str,
-        _input: &serde_json::Value,
+        attempt: i32,
+        run_span: Option<&fd_otel::SpanContext>,
     ) -> Result<String, ApiError> {
         let execution_id = format!("wfse_{}", Ulid::new());
-        let step_type = convert_step_type(&step.step_type);
+        let step_type = WorkflowStepType::from(step.step_type);
+
+        // Give the step its own span under the run's; workers parent their
+        // processing on it
+        let span = fd_otel::step_span(run_id, &step.id, &step.step_type.to_string(), run_span);
+        let (trace_id, span_id) = fd_otel::span_ids(&span).unzip();
 
         // Create step execution
         let create = CreateWorkflowStepExecution {
@@ -506,8 +1233,8 @@ impl WorkflowOrchestrator {
             step_id: step.id.clone(),
             step_type,
             input: step.config.clone(),
-            attempt: 1,
-            span_id: None,
+            attempt,
+            span_id: span_id.clone(),
         };
 
         self.repos()
@@ -524,19 +1251,42 @@ impl WorkflowOrchestrator {
             context: JobContext {
                 tenant_id: tenant_id.to_string(),
                 project_id: project_id.to_string(),
-                trace_id: None,
-                span_id: None,
+                trace_id,
+                span_id,
+                metadata: serde_json::json!({}),
+                signing_secret: None,
+                approval_token: None,
             },
         };
 
         let message = QueueMessage::new(&execution_id, job);
         self.state.enqueue_step(&message).await?;
+        self.state
+            .queue
+            .set_add(&sets::enqueued_steps(run_id), &execution_id)
+            .await?;
 
         debug!(run_id, step_id = %step.id, execution_id, "Created and enqueued step");
 
         Ok(execution_id)
     }
 
+    /// Publish workflow progress events
+    ///
+    /// Failures are logged and never abort the transition that produced them.
+    async fn publish_events(&self, events: Vec<WorkflowEvent>) {
+        for event in events {
+            if let Err(e) = self.state.publish_workflow_event(&event).await {
+                warn!(
+                    run_id = %event.run_id,
+                    event = ?event.event,
+                    error = %e,
+                    "Failed to publish workflow event"
+                );
+            }
+        }
+    }
+
     /// Enqueue ready steps
     async fn enqueue_ready_steps(&self, run_id: &str, step_ids: &[String]) -> Result<(), ApiError> {
         // Get run info
@@ -556,6 +1306,7 @@ impl WorkflowOrchestrator {
             .ok_or_else(|| ApiError::internal("Workflow not found for run"))?;
 
         let steps = self.parse_workflow_steps(&workflow.definition)?;
+        let run_span = fd_otel::parent_context(run.trace_id.as_deref(), run.span_id.as_deref());
 
         for step_id in step_ids {
             if let Some(step) = steps.iter().find(|s| &s.id == step_id) {
@@ -564,7 +1315,8 @@ impl WorkflowOrchestrator {
                     step,
                     &run.project_id,
                     &run.project_id, // tenant_id same as project_id for now
-                    &run.input,
+                    1,
+                    run_span.as_ref(),
                 )
                 .await?;
             }
@@ -600,14 +1352,14 @@ impl WorkflowOrchestrator {
     }
 
     /// Fail workflow run
-    async fn fail_workflow(&self, run_id: &str, error: &str) -> Result<(), ApiError> {
+    async fn fail_workflow(&self, run_id: &str, error: serde_json::Value) -> Result<(), ApiError> {
         self.repos()
             .workflows()
             .update_run(
                 run_id,
                 UpdateWorkflowRun {
                     status: Some(WorkflowRunStatus::Failed),
-                    error: Some(serde_json::json!({ "message": error })),
+                    error: Some(error.clone()),
                     completed_at: Some(chrono::Utc::now()),
                     ..Default::default()
                 },
@@ -617,19 +1369,30 @@ impl WorkflowOrchestrator {
         // Cleanup scheduler
         self.cleanup(run_id).await;
 
-        error!(run_id, error, "Workflow failed");
+        error!(run_id, %error, "Workflow failed");
         Ok(())
     }
 }
 
-/// Convert fd-dag StepType to fd-storage WorkflowStepType
-fn convert_step_type(step_type: &DagStepType) -> WorkflowStepType {
-    match step_type {
-        DagStepType::Llm => WorkflowStepType::Llm,
-        DagStepType::Tool => WorkflowStepType::Tool,
-        DagStepType::Condition => WorkflowStepType::Condition,
-        DagStepType::Loop => WorkflowStepType::Loop,
-        DagStepType::Parallel => WorkflowStepType::Parallel,
-        DagStepType::Approval => WorkflowStepType::Approval,
+/// Recover every active workflow run after a gateway restart
+///
+/// See [`WorkflowOrchestrator::recover_run`]. A run that fails to recover is
+/// logged and skipped.
+pub async fn recover_workflow_runs(state: AppState) {
+    let run_ids = match state.repos().workflows().list_active_run_ids().await {
+        Ok(run_ids) => run_ids,
+        Err(e) => {
+            warn!(error = %e, "Failed to list active workflow runs for recovery");
+            return;
+        }
+    };
+
+    for run_id in &run_ids {
+        let _guard = state.workflow_run_locks.lock(run_id).await;
+        let orchestrator = WorkflowOrchestrator::new(state.clone());
+        if let Err(e) = orchestrator.recover_run(run_id).await {
+            warn!(run_id = %run_id, error = %e.message, "Failed to recover workflow run");
+        }
     }
+    info!(runs = run_ids.len(), "Workflow run recovery completed");
 }
@@ -6,9 +6,12 @@ use axum::{
     response::IntoResponse,
     Extension, Json,
 };
-use fd_storage::models::{CreatePolicyRule, PolicyEffect, UpdatePolicyRule};
+use fd_storage::models::{
+    action, actor, resource, AuditEventBuilder, CreatePolicyRule, PolicyEffect, UpdatePolicyRule,
+};
+use fd_storage::queue::sets;
 use serde::{Deserialize, Serialize};
-use tracing::instrument;
+use tracing::{instrument, warn};
 use ulid::Ulid;
 
 use crate::handlers::ApiError;
@@ -69,6 +72,11 @@ fn default_limit() -> i64 {
     50
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ExplainPolicyQuery {
+    pub tool: String,
+}
+
 // =============================================================================
 // Helpers
 // =============================================================================
@@ -237,3 +245,92 @@ pub async fn delete_policy(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Explain how the policy engine evaluates a tool (admin debugging aid)
+#[instrument(skip(state, _auth))]
+pub async fn explain_policy(
+    State(state): State<AppState>,
+    Extension(_auth): Extension<AuthContext>,
+    Query(query): Query<ExplainPolicyQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    if query.tool.trim().is_empty() {
+        return Err(ApiError::bad_request("tool must not be empty"));
+    }
+
+    Ok(Json(state.policy_engine.explain(&query.tool)))
+}
+
+/// Tools currently disabled by the global kill switch
+#[derive(Debug, Serialize)]
+pub struct KillSwitchResponse {
+    pub killed_tools: Vec<String>,
+}
+
+/// List tools disabled by the global kill switch (admin only)
+#[instrument(skip(state, _auth))]
+pub async fn list_killed_tools(
+    State(state): State<AppState>,
+    Extension(_auth): Extension<AuthContext>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut killed_tools = state.queue.set_members(sets::KILLED_TOOLS).await?;
+    killed_tools.sort();
+    state
+        .policy_engine
+        .kill_switch()
+        .refresh(killed_tools.iter().cloned());
+
+    Ok(Json(KillSwitchResponse { killed_tools }))
+}
+
+/// Disable a tool for every tenant, regardless of allowlists (admin only)
+#[instrument(skip(state, auth))]
+pub async fn kill_tool(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(tool_name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if tool_name.trim().is_empty() {
+        return Err(ApiError::bad_request("tool must not be empty"));
+    }
+
+    state.queue.set_add(sets::KILLED_TOOLS, &tool_name).await?;
+    // Take effect on this gateway immediately; others pick it up on refresh
+    state.policy_engine.kill_switch().kill(&tool_name);
+
+    let audit_event = AuditEventBuilder::new(action::TOOL_KILLED, resource::TOOL)
+        .actor(actor::API_KEY, Some(auth.api_key_id.clone()))
+        .tenant(auth.tenant_id.clone())
+        .details(serde_json::json!({ "tool_name": tool_name }))
+        .build();
+    state.repos().spawn_audit(audit_event);
+
+    warn!(tool_name = %tool_name, "Tool disabled by global kill switch");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Re-enable a tool disabled by the global kill switch (admin only)
+#[instrument(skip(state, auth))]
+pub async fn revive_tool(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(tool_name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !state
+        .queue
+        .set_remove(sets::KILLED_TOOLS, &tool_name)
+        .await?
+    {
+        return Err(ApiError::not_found("Killed tool", &tool_name));
+    }
+    state.policy_engine.kill_switch().revive(&tool_name);
+
+    let audit_event = AuditEventBuilder::new(action::TOOL_REVIVED, resource::TOOL)
+        .actor(actor::API_KEY, Some(auth.api_key_id.clone()))
+        .tenant(auth.tenant_id.clone())
+        .details(serde_json::json!({ "tool_name": tool_name }))
+        .build();
+    state.repos().spawn_audit(audit_event);
+
+    Ok(StatusCode::NO_CONTENT)
+}
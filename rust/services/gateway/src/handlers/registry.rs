@@ -7,13 +7,15 @@ use axum::{
     Extension, Json,
 };
 use fd_storage::models::{
-    AgentStatus, CreateAgent, CreateAgentVersion, CreateTool, CreateToolVersion, ToolRiskLevel,
+    action, actor, resource, AgentStatus, AuditEventBuilder, CreateAgent, CreateAgentVersion,
+    CreateTool, CreateToolVersion, ToolRiskLevel,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tracing::instrument;
 use ulid::Ulid;
 
-use crate::handlers::ApiError;
+use crate::handlers::{validate_external_id, ApiError};
 use crate::middleware::AuthContext;
 use crate::state::AppState;
 
@@ -27,6 +29,10 @@ pub struct CreateAgentRequest {
     pub name: String,
     pub slug: String,
     pub description: Option<String>,
+    /// Caller-supplied stable ID; reusing it returns the existing agent
+    pub external_id: Option<String>,
+    /// Maximum active runs for this agent; omitted means unlimited
+    pub max_concurrent_runs: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,6 +62,9 @@ pub struct AgentResponse {
     pub description: Option<String>,
     pub status: String,
     pub created_at: String,
+    pub external_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_runs: Option<i32>,
     pub latest_version: Option<AgentVersionResponse>,
 }
 
@@ -66,6 +75,20 @@ pub struct AgentVersionResponse {
     pub model: String,
     pub allowed_tools: Vec<String>,
     pub created_at: String,
+    /// Whether this is the production default for new runs
+    pub promoted: bool,
+}
+
+/// A tool an agent version can call under the current policy
+#[derive(Debug, Serialize)]
+pub struct EffectiveToolResponse {
+    pub tool_name: String,
+    /// `allow` or `requires_approval`
+    pub decision: fd_policy::PolicyDecisionKind,
+    pub reason: String,
+    /// Registered risk level; absent for tools not in the registry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk_level: Option<ToolRiskLevel>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -95,6 +118,9 @@ pub struct CreateToolRequest {
     pub risk_level: String,
     pub input_schema: serde_json::Value,
     pub output_schema: Option<serde_json::Value>,
+    /// Estimated cost of one call in cents, used when a tool check omits one
+    #[serde(default)]
+    pub estimated_cost_cents: Option<u32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -107,6 +133,7 @@ pub struct ToolResponse {
     pub mcp_server: String,
     pub status: String,
     pub risk_level: String,
+    pub estimated_cost_cents: Option<i32>,
     pub created_at: String,
 }
 
@@ -135,28 +162,43 @@ fn agent_to_response(
         description: agent.description,
         status: format!("{:?}", agent.status).to_lowercase(),
         created_at: agent.created_at.to_rfc3339(),
-        latest_version: latest_version.map(|v| AgentVersionResponse {
-            id: v.id,
-            version: v.version,
-            model: v.model,
-            allowed_tools: v.allowed_tools,
-            created_at: v.created_at.to_rfc3339(),
-        }),
+        external_id: agent.external_id,
+        max_concurrent_runs: agent.max_concurrent_runs,
+        latest_version: latest_version.map(version_to_response),
     }
 }
 
-fn tool_to_response(tool: fd_storage::models::Tool) -> ToolResponse {
-    ToolResponse {
+fn version_to_response(version: fd_storage::models::AgentVersion) -> AgentVersionResponse {
+    AgentVersionResponse {
+        id: version.id,
+        version: version.version,
+        model: version.model,
+        allowed_tools: version.allowed_tools,
+        created_at: version.created_at.to_rfc3339(),
+        promoted: version.promoted,
+    }
+}
+
+fn tool_to_response(
+    tool: fd_storage::models::Tool,
+    mcp_server_vars: &HashMap<String, String>,
+) -> Result<ToolResponse, ApiError> {
+    let mcp_server = tool
+        .resolved_mcp_server(mcp_server_vars)
+        .map_err(|e| ApiError::internal(format!("Tool {}: {}", tool.id, e)))?;
+
+    Ok(ToolResponse {
         id: tool.id,
         project_id: tool.project_id,
         name: tool.name,
         slug: tool.slug,
         description: tool.description,
-        mcp_server: tool.mcp_server,
+        mcp_server,
         status: format!("{:?}", tool.status).to_lowercase(),
-        risk_level: format!("{:?}", tool.risk_level).to_lowercase(),
+        risk_level: tool.risk_level.to_string(),
+        estimated_cost_cents: tool.estimated_cost_cents,
         created_at: tool.created_at.to_rfc3339(),
-    }
+    })
 }
 
 // =============================================================================
@@ -198,6 +240,15 @@ pub async fn create_agent(
     Extension(_auth): Extension<AuthContext>,
     Json(request): Json<CreateAgentRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
+    if let Some(external_id) = &request.external_id {
+        validate_external_id(external_id)?;
+    }
+    if request.max_concurrent_runs.is_some_and(|limit| limit < 1) {
+        return Err(ApiError::bad_request(
+            "max_concurrent_runs must be at least 1",
+        ));
+    }
+
     let agent_id = format!("agt_{}", Ulid::new());
 
     let create = CreateAgent {
@@ -206,11 +257,19 @@ pub async fn create_agent(
         name: request.name,
         slug: request.slug,
         description: request.description,
+        external_id: request.external_id,
+        max_concurrent_runs: request.max_concurrent_runs,
     };
 
-    let agent = state.repos().agents().create(create).await?;
+    // Re-provisioning with a known external ID returns the existing agent
+    let (agent, created) = state.repos().agents().create_or_get(create).await?;
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
 
-    Ok((StatusCode::CREATED, Json(agent_to_response(agent, None))))
+    Ok((status, Json(agent_to_response(agent, None))))
 }
 
 /// Get an agent by ID
@@ -251,16 +310,8 @@ pub async fn list_agent_versions(
 
     let versions = repos.agents().list_versions(&agent_id).await?;
 
-    let responses: Vec<AgentVersionResponse> = versions
-        .into_iter()
-        .map(|v| AgentVersionResponse {
-            id: v.id,
-            version: v.version,
-            model: v.model,
-            allowed_tools: v.allowed_tools,
-            created_at: v.created_at.to_rfc3339(),
-        })
-        .collect();
+    let responses: Vec<AgentVersionResponse> =
+        versions.into_iter().map(version_to_response).collect();
 
     Ok(Json(responses))
 }
@@ -311,15 +362,46 @@ pub async fn create_agent_version(
 
     let version = repos.agents().create_version(create).await?;
 
-    let response = AgentVersionResponse {
-        id: version.id,
-        version: version.version,
-        model: version.model,
-        allowed_tools: version.allowed_tools,
-        created_at: version.created_at.to_rfc3339(),
-    };
+    Ok((StatusCode::CREATED, Json(version_to_response(version))))
+}
+
+/// Promote an agent version to be the production default
+///
+/// Runs created without an explicit version resolve to the promoted one.
+#[instrument(skip(state, auth))]
+pub async fn promote_agent_version(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path((agent_id, version_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let repos = state.repos();
 
-    Ok((StatusCode::CREATED, Json(response)))
+    let agent = repos
+        .agents()
+        .get(&agent_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Agent", &agent_id))?;
+
+    let version = repos
+        .agents()
+        .promote_version(&agent_id, &version_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("AgentVersion", &version_id))?;
+
+    repos.spawn_audit(
+        AuditEventBuilder::new(action::AGENT_VERSION_PROMOTED, resource::AGENT_VERSION)
+            .actor(actor::API_KEY, Some(auth.api_key_id.clone()))
+            .resource_id(&version.id)
+            .tenant(&auth.tenant_id)
+            .project(&agent.project_id)
+            .details(serde_json::json!({
+                "agent_id": agent_id,
+                "version": version.version,
+            }))
+            .build(),
+    );
+
+    Ok(Json(version_to_response(version)))
 }
 
 // =============================================================================
@@ -360,6 +442,67 @@ pub async fn get_agent_stats(
     Ok(Json(stats))
 }
 
+/// An agent version's effective tool surface under the current policy
+///
+/// Tools the policy denies (including kill-switched ones) are left out; the
+/// rest keep their `allowed_tools` order, without duplicates.
+pub(crate) fn effective_tools(
+    engine: &fd_policy::PolicyEngine,
+    allowed_tools: &[String],
+) -> Vec<(String, fd_policy::PolicyDecision)> {
+    let mut seen = std::collections::HashSet::new();
+    allowed_tools
+        .iter()
+        .filter(|tool| seen.insert(tool.as_str()))
+        .map(|tool| (tool.clone(), engine.evaluate_tool_call(tool)))
+        .filter(|(_, decision)| !decision.is_denied())
+        .collect()
+}
+
+/// List the tools an agent version may call, with their policy decision
+#[instrument(skip(state, auth))]
+pub async fn list_agent_version_tools(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path((agent_id, version_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let repos = state.repos();
+
+    let agent = repos
+        .agents()
+        .get(&agent_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Agent", &agent_id))?;
+    if !auth.can_access_project(&agent.project_id) {
+        return Err(ApiError::forbidden("Access denied to this agent"));
+    }
+
+    let version = repos
+        .agents()
+        .get_version(&version_id)
+        .await?
+        .filter(|version| version.agent_id == agent.id)
+        .ok_or_else(|| ApiError::not_found("AgentVersion", &version_id))?;
+
+    state.refresh_kill_switch().await;
+    let mut tools = Vec::new();
+    for (tool_name, decision) in effective_tools(&state.policy_engine, &version.allowed_tools) {
+        let risk_level = repos
+            .tools()
+            .get_by_slug(&agent.project_id, &tool_name)
+            .await?
+            .map(|tool| tool.risk_level);
+        tools.push(EffectiveToolResponse {
+            tool_name,
+            decision: decision.kind,
+            reason: decision.reason,
+            risk_level,
+        });
+    }
+
+    Ok(Json(serde_json::json!({ "tools": tools })))
+}
+
 // =============================================================================
 // Tool Handlers
 // =============================================================================
@@ -378,7 +521,7 @@ pub async fn get_tool(
         .await?
         .ok_or_else(|| ApiError::not_found("Tool", &tool_id))?;
 
-    Ok(Json(tool_to_response(tool)))
+    Ok(Json(tool_to_response(tool, &state.mcp_server_vars)?))
 }
 
 /// List tools
@@ -394,7 +537,10 @@ pub async fn list_tools(
         .list(query.project_id.as_deref(), None, query.limit, query.offset)
         .await?;
 
-    let responses: Vec<ToolResponse> = tools.into_iter().map(tool_to_response).collect();
+    let responses = tools
+        .into_iter()
+        .map(|tool| tool_to_response(tool, &state.mcp_server_vars))
+        .collect::<Result<Vec<ToolResponse>, _>>()?;
 
     Ok(Json(responses))
 }
@@ -408,12 +554,19 @@ pub async fn create_tool(
 ) -> Result<impl IntoResponse, ApiError> {
     let repos = state.repos();
 
-    let risk_level = match request.risk_level.as_str() {
-        "read" => ToolRiskLevel::Read,
-        "write" => ToolRiskLevel::Write,
-        "destructive" => ToolRiskLevel::Destructive,
-        _ => return Err(ApiError::bad_request("Invalid risk_level")),
-    };
+    let risk_level: ToolRiskLevel =
+        request
+            .risk_level
+            .parse()
+            .map_err(|e: fd_core::UnknownRiskLevel| {
+                ApiError::bad_request(format!("Invalid risk_level: {}", e))
+            })?;
+
+    let estimated_cost_cents = request
+        .estimated_cost_cents
+        .map(i32::try_from)
+        .transpose()
+        .map_err(|_| ApiError::bad_request("estimated_cost_cents is too large"))?;
 
     let tool_id = format!("tol_{}", Ulid::new());
     let version_id = format!("tlv_{}", Ulid::new());
@@ -426,6 +579,7 @@ pub async fn create_tool(
         description: request.description,
         mcp_server: request.mcp_server,
         risk_level,
+        estimated_cost_cents,
     };
 
     let tool = repos.tools().create(create_tool).await?;
@@ -442,7 +596,10 @@ pub async fn create_tool(
 
     repos.tools().create_version(create_version).await?;
 
-    Ok((StatusCode::CREATED, Json(tool_to_response(tool))))
+    Ok((
+        StatusCode::CREATED,
+        Json(tool_to_response(tool, &state.mcp_server_vars)?),
+    ))
 }
 
 // =============================================================================
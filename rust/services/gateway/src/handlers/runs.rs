@@ -1,31 +1,41 @@
 //! Run management handlers
 
 use axum::{
+    body::Bytes,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     Extension, Json,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use fd_otel::genai::pricing;
-use fd_policy::budget::BudgetUsage;
+use fd_policy::budget::{Budget, BudgetExceeded, BudgetUsage};
+use fd_policy::{AirlockInspector, AirlockMode};
 use fd_storage::{
     models::{
-        action, actor, resource, AuditEventBuilder, CreateRun, CreateStep, RunStatus, StepStatus,
-        StepType, UpdateRun, UpdateStep,
+        action, actor, resource, AuditEventBuilder, CreateAuditEvent, CreateRun, CreateStep,
+        RunExportStream, RunStatus, RunWithSteps, StepStatus, StepType, UpdateRun, UpdateStep,
     },
     queue::{JobContext, StepJob},
     QueueMessage,
 };
+use futures_util::{Stream, StreamExt, TryStreamExt};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
-use tracing::{info, instrument, warn};
+use sha2::Sha256;
+use std::sync::Arc;
+use tracing::{error, info, instrument, warn};
 use ulid::Ulid;
 use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
-use crate::handlers::{ApiError, ValidatedJson, ValidatedQuery};
+use crate::handlers::approvals::{approval_token_error, APPROVAL_TOKEN_HEADER};
+use crate::handlers::{
+    applied_transition, ensure_run_transition, ensure_step_transition, parse_validated_json,
+    settled_transition, single_slug_match, ApiError, EntityRef, ValidatedJson, ValidatedQuery,
+};
 use crate::middleware::AuthContext;
-use crate::state::AppState;
+use crate::state::{AppState, Repos};
 
 // =============================================================================
 // Request/Response DTOs
@@ -47,6 +57,9 @@ pub struct CreateRunRequest {
     /// Optional run configuration overrides
     #[serde(default)]
     pub config: serde_json::Value,
+    /// Optional metadata (e.g. request_id) propagated to step jobs and audit events
+    #[serde(default)]
+    pub metadata: serde_json::Value,
 }
 
 /// Agent run response
@@ -80,6 +93,15 @@ pub struct RunResponse {
     pub started_at: Option<String>,
     /// When execution completed
     pub completed_at: Option<String>,
+    /// Caller-supplied metadata
+    pub metadata: serde_json::Value,
+    /// Effective budget captured when the run was created (null for older runs)
+    pub budget_snapshot: Option<serde_json::Value>,
+    /// Budget dimension a budget-killed run exceeded: `{metric, used, limit}`
+    pub budget_kill_detail: Option<serde_json::Value>,
+    /// When the run was moved to cold storage (archived runs only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<String>,
 }
 
 /// Query parameters for listing runs
@@ -159,6 +181,60 @@ pub struct SubmitStepResultRequest {
     pub input_tokens: Option<i32>,
     #[validate(range(min = 0, message = "output_tokens must be non-negative"))]
     pub output_tokens: Option<i32>,
+    /// Declared media type of a tool step's output (e.g. `application/json`)
+    pub content_type: Option<String>,
+}
+
+/// A single step result within a batch submission
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct BatchStepResultItem {
+    #[validate(length(min = 1, max = 255, message = "step_id must be 1-255 characters"))]
+    pub step_id: String,
+    #[validate(custom(function = "validate_step_status"))]
+    pub status: String,
+    pub output: Option<serde_json::Value>,
+    pub error: Option<serde_json::Value>,
+    #[validate(range(min = 0, message = "input_tokens must be non-negative"))]
+    pub input_tokens: Option<i32>,
+    #[validate(range(min = 0, message = "output_tokens must be non-negative"))]
+    pub output_tokens: Option<i32>,
+    /// Declared media type of a tool step's output (e.g. `application/json`)
+    pub content_type: Option<String>,
+    /// Allow-once token for a step resumed from an approval
+    pub approval_token: Option<String>,
+}
+
+/// Batch of step results submitted by a worker in one call
+#[derive(Debug, Deserialize, Validate)]
+pub struct SubmitStepResultsBatchRequest {
+    #[validate(
+        length(min = 1, max = 100, message = "results must contain 1-100 items"),
+        nested
+    )]
+    pub results: Vec<BatchStepResultItem>,
+}
+
+/// Outcome for one item of a batch submission, in request order
+#[derive(Debug, Serialize)]
+pub struct BatchStepResultEntry {
+    /// Position of the item in the submitted batch
+    pub index: usize,
+    pub step_id: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<StepResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmitStepResultsBatchResponse {
+    pub run_id: String,
+    /// Run status after the whole batch was applied
+    pub run_status: String,
+    /// Index of the first item that could not be applied, if any
+    pub first_failed_index: Option<usize>,
+    pub results: Vec<BatchStepResultEntry>,
 }
 
 /// Custom validator for step status
@@ -177,7 +253,7 @@ fn validate_step_status(status: &str) -> Result<(), validator::ValidationError>
 // Helpers
 // =============================================================================
 
-fn run_to_response(run: fd_storage::models::Run) -> RunResponse {
+pub(crate) fn run_to_response(run: fd_storage::models::Run) -> RunResponse {
     RunResponse {
         id: run.id,
         project_id: run.project_id,
@@ -192,7 +268,106 @@ fn run_to_response(run: fd_storage::models::Run) -> RunResponse {
         created_at: run.created_at.to_rfc3339(),
         started_at: run.started_at.map(|t| t.to_rfc3339()),
         completed_at: run.completed_at.map(|t| t.to_rfc3339()),
+        metadata: run.metadata,
+        budget_snapshot: run.budget_snapshot,
+        budget_kill_detail: run.budget_kill_detail,
+        archived_at: None,
+    }
+}
+
+/// Run update that kills a run for exceeding `exceeded`
+pub(crate) fn budget_kill_update(exceeded: &BudgetExceeded) -> UpdateRun {
+    UpdateRun {
+        status: Some(RunStatus::BudgetKilled),
+        status_reason: Some(format!("budget exceeded: {}", exceeded)),
+        budget_kill_detail: Some(
+            serde_json::to_value(exceeded.detail()).expect("budget detail always serializes"),
+        ),
+        completed_at: Some(Utc::now()),
+        ..Default::default()
+    }
+}
+
+/// Audit event summarizing a run that reached a terminal status
+///
+/// `step_counts` are the run's steps by status. Duration runs from start
+/// (or creation, for runs that never started) to completion.
+pub(crate) fn run_summary_audit(
+    run: &fd_storage::models::Run,
+    step_counts: &std::collections::HashMap<String, i64>,
+) -> CreateAuditEvent {
+    let started_at = run.started_at.unwrap_or(run.created_at);
+    let completed_at = run.completed_at.unwrap_or_else(Utc::now);
+    let details = serde_json::json!({
+        "outcome": run.status.as_str(),
+        "status_reason": run.status_reason,
+        "input_tokens": run.input_tokens,
+        "output_tokens": run.output_tokens,
+        "total_tokens": i64::from(run.input_tokens) + i64::from(run.output_tokens),
+        "cost_cents": run.cost_cents,
+        "tool_calls": run.tool_calls,
+        "step_count": step_counts.values().sum::<i64>(),
+        "steps_by_status": step_counts,
+        "duration_ms": completed_at
+            .signed_duration_since(started_at)
+            .num_milliseconds()
+            .max(0),
+    });
+
+    AuditEventBuilder::new(action::RUN_SUMMARY, resource::RUN)
+        .actor(actor::SYSTEM, None)
+        .resource_id(&run.id)
+        .run(&run.id)
+        .project(&run.project_id)
+        .details(with_run_metadata(details, &run.metadata))
+        .build()
+}
+
+/// Emit `run.summary` for a run that just reached a terminal status
+///
+/// Steps are counted in the background so the caller doesn't wait on it.
+pub(crate) fn spawn_run_summary(repos: &Repos, run: &fd_storage::models::Run) {
+    if !run.status.is_terminal() {
+        return;
     }
+    let repos = repos.clone();
+    let run = run.clone();
+    tokio::spawn(async move {
+        match repos.steps().count_by_status(&run.id).await {
+            Ok(counts) => repos.spawn_audit(run_summary_audit(&run, &counts)),
+            Err(e) => warn!(run_id = %run.id, error = %e, "Failed to summarize run"),
+        }
+    });
+}
+
+/// Serialize the budget a run is created under
+pub(crate) fn budget_snapshot(budget: &Budget) -> serde_json::Value {
+    serde_json::to_value(budget).expect("budget always serializes")
+}
+
+/// Budget captured when the run was created
+///
+/// None for runs predating snapshots (or with an unreadable snapshot), in
+/// which case the engine default applies.
+pub(crate) fn run_budget(run: &fd_storage::models::Run) -> Option<Budget> {
+    let snapshot = run.budget_snapshot.as_ref()?;
+    serde_json::from_value(snapshot.clone())
+        .map_err(|e| warn!(run_id = %run.id, error = %e, "Ignoring unreadable budget snapshot"))
+        .ok()
+}
+
+/// Rebuild a run response from an archive tombstone
+pub(crate) fn archived_run_to_response(
+    archived: fd_storage::models::ArchivedRun,
+) -> Result<RunResponse, ApiError> {
+    let run: fd_storage::models::Run = serde_json::from_value(archived.run).map_err(|e| {
+        ApiError::internal(format!("Corrupt archive for run {}: {}", archived.id, e))
+    })?;
+
+    Ok(RunResponse {
+        archived_at: Some(archived.archived_at.to_rfc3339()),
+        ..run_to_response(run)
+    })
 }
 
 fn step_to_response(step: fd_storage::models::Step) -> StepResponse {
@@ -214,10 +389,256 @@ fn step_to_response(step: fd_storage::models::Step) -> StepResponse {
     }
 }
 
+fn parse_step_result_status(status: &str) -> Result<StepStatus, ApiError> {
+    match status {
+        "completed" => Ok(StepStatus::Completed),
+        "failed" => Ok(StepStatus::Failed),
+        "waiting_approval" => Ok(StepStatus::WaitingApproval),
+        _ => Err(ApiError::bad_request("Invalid status")),
+    }
+}
+
+/// Cancel the outstanding steps of a force-terminated run
+async fn cancel_outstanding_steps(repos: &Repos, run_id: &str) -> Result<usize, ApiError> {
+    let cancelled = repos.steps().cancel_pending_for_run(run_id).await?;
+    if cancelled > 0 {
+        info!(run_id = %run_id, cancelled, "Cancelled outstanding steps");
+    }
+    Ok(cancelled)
+}
+
+/// Validate run metadata, treating an omitted value as empty
+pub(crate) fn normalize_run_metadata(
+    metadata: serde_json::Value,
+) -> Result<serde_json::Value, ApiError> {
+    match metadata {
+        serde_json::Value::Null => Ok(serde_json::json!({})),
+        serde_json::Value::Object(_) => Ok(metadata),
+        _ => Err(ApiError::bad_request("metadata must be a JSON object")),
+    }
+}
+
+/// Add the run's metadata to audit details, when it has any
+pub(crate) fn with_run_metadata(
+    mut details: serde_json::Value,
+    metadata: &serde_json::Value,
+) -> serde_json::Value {
+    let has_metadata = metadata.as_object().is_some_and(|m| !m.is_empty());
+    if let (true, Some(obj)) = (has_metadata, details.as_object_mut()) {
+        obj.insert("run_metadata".to_string(), metadata.clone());
+    }
+    details
+}
+
+/// Queue job context for a run's steps
+pub(crate) fn run_job_context(run: &fd_storage::models::Run, tenant_id: &str) -> JobContext {
+    JobContext {
+        tenant_id: tenant_id.to_string(),
+        project_id: run.project_id.clone(),
+        trace_id: run.trace_id.clone(),
+        span_id: run.span_id.clone(),
+        metadata: run.metadata.clone(),
+        signing_secret: None,
+        approval_token: None,
+    }
+}
+
+/// Rebuild the queue job for a step from its stored input
+pub(crate) fn step_job(
+    step: &fd_storage::models::Step,
+    tenant_id: &str,
+    project_id: &str,
+    metadata: &serde_json::Value,
+) -> StepJob {
+    StepJob {
+        run_id: step.run_id.clone(),
+        step_id: step.id.clone(),
+        step_type: format!("{:?}", step.step_type).to_lowercase(),
+        input: step.input.clone(),
+        context: JobContext {
+            tenant_id: tenant_id.to_string(),
+            project_id: project_id.to_string(),
+            trace_id: None,
+            span_id: step.span_id.clone(),
+            metadata: metadata.clone(),
+            signing_secret: None,
+            approval_token: None,
+        },
+    }
+}
+
+/// Rebuild the queue job for a step so it can be dead-lettered
+pub(crate) fn dead_letter_job(
+    step: &fd_storage::models::Step,
+    tenant_id: &str,
+    project_id: &str,
+    metadata: &serde_json::Value,
+) -> QueueMessage<StepJob> {
+    let job = step_job(step, tenant_id, project_id, metadata);
+    let mut message = QueueMessage::new(&step.id, job);
+    message.attempts = 1;
+    message
+}
+
+/// Record a permanently failed step in the DLQ.
+///
+/// Queue errors are logged rather than failing the result submission.
+async fn dead_letter_step(
+    state: &AppState,
+    step: &fd_storage::models::Step,
+    tenant_id: &str,
+    run: &fd_storage::models::Run,
+) {
+    let job = dead_letter_job(step, tenant_id, &run.project_id, &run.metadata);
+    let error = step.error.clone().unwrap_or(serde_json::Value::Null);
+
+    if let Err(e) = state.queue.enqueue_dlq(&job, error).await {
+        warn!(step_id = %step.id, error = %e, "Failed to enqueue step into DLQ");
+    }
+}
+
+/// Pick the step status that drives the run transition after a batch.
+///
+/// A failure anywhere in the batch wins, then a pending approval; otherwise
+/// a completed step lets the run complete once nothing else is pending.
+pub(crate) fn batch_decisive_status(statuses: &[StepStatus]) -> Option<StepStatus> {
+    [
+        StepStatus::Failed,
+        StepStatus::WaitingApproval,
+        StepStatus::Completed,
+    ]
+    .into_iter()
+    .find(|candidate| statuses.contains(candidate))
+}
+
+/// Longest preview kept in a truncated step output marker
+const OUTPUT_PREVIEW_BYTES: usize = 1024;
+
+/// Cap a step output at `max_bytes` of serialized JSON.
+///
+/// Oversized outputs are replaced with a `_truncated` marker carrying the
+/// original size and a short preview. A cap of zero disables truncation.
+pub(crate) fn truncate_step_output(
+    output: serde_json::Value,
+    max_bytes: usize,
+) -> serde_json::Value {
+    if max_bytes == 0 {
+        return output;
+    }
+
+    let serialized = output.to_string();
+    if serialized.len() <= max_bytes {
+        return output;
+    }
+
+    let mut preview_len = OUTPUT_PREVIEW_BYTES.min(max_bytes);
+    while !serialized.is_char_boundary(preview_len) {
+        preview_len -= 1;
+    }
+
+    serde_json::json!({
+        "_truncated": true,
+        "bytes": serialized.len(),
+        "preview": &serialized[..preview_len],
+    })
+}
+
+/// Scope required to run in a looser airlock mode than the gateway default
+pub(crate) const AIRLOCK_OVERRIDE_SCOPE: &str = "airlock:override";
+
+/// Read the airlock mode override from a run's config, if any.
+fn run_airlock_mode(config: &serde_json::Value) -> Result<Option<AirlockMode>, ApiError> {
+    match config.get("airlock_mode") {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|_| ApiError::bad_request("airlock_mode must be \"shadow\" or \"enforce\"")),
+    }
+}
+
+/// The tenant inspector adjusted for the run's airlock mode override, if any
+fn run_airlock(
+    airlock: &Arc<AirlockInspector>,
+    run: &fd_storage::models::Run,
+) -> Arc<AirlockInspector> {
+    match run_airlock_mode(&run.config).ok().flatten() {
+        Some(mode) => airlock.for_mode(mode),
+        None => Arc::clone(airlock),
+    }
+}
+
+/// Validate a requested per-run airlock mode against the caller's scopes.
+///
+/// Tightening to `enforce` is always allowed; loosening an enforcing gateway
+/// to `shadow` requires the `airlock:override` scope.
+pub(crate) fn resolve_run_airlock_mode(
+    config: &serde_json::Value,
+    global: AirlockMode,
+    auth: &AuthContext,
+) -> Result<Option<AirlockMode>, ApiError> {
+    let mode = run_airlock_mode(config)?;
+
+    if mode == Some(AirlockMode::Shadow)
+        && global == AirlockMode::Enforce
+        && !auth.has_scope(AIRLOCK_OVERRIDE_SCOPE)
+    {
+        return Err(ApiError::forbidden(format!(
+            "Running in shadow airlock mode requires the '{}' scope",
+            AIRLOCK_OVERRIDE_SCOPE
+        )));
+    }
+
+    Ok(mode)
+}
+
 // =============================================================================
 // Handlers
 // =============================================================================
 
+/// Look up the agent (by ID or slug) and the version a run would use
+///
+/// Uses the requested version if given, otherwise the promoted/latest default.
+async fn resolve_agent_version(
+    repos: &Repos,
+    auth: &AuthContext,
+    agent_ref: &str,
+    version_id: Option<&str>,
+) -> Result<(fd_storage::models::Agent, fd_storage::models::AgentVersion), ApiError> {
+    // Agents are referenced by ID (`agt_...`) or by slug
+    let agent = match EntityRef::parse(agent_ref, fd_core::AgentId::PREFIX) {
+        EntityRef::Id(id) => repos
+            .agents()
+            .get(id)
+            .await?
+            .ok_or_else(|| ApiError::not_found("Agent", id))?,
+        EntityRef::Slug(slug) => {
+            let visible = repos
+                .agents()
+                .list_by_slug(slug)
+                .await?
+                .into_iter()
+                .filter(|agent| auth.can_access_project(&agent.project_id))
+                .collect();
+            single_slug_match("Agent", slug, visible)?
+        }
+    };
+
+    let agent_version = match version_id {
+        Some(version_id) => repos
+            .agents()
+            .get_version(version_id)
+            .await?
+            .ok_or_else(|| ApiError::not_found("AgentVersion", version_id))?,
+        None => repos
+            .agents()
+            .get_default_version(&agent.id)
+            .await?
+            .ok_or_else(|| ApiError::bad_request("Agent has no versions"))?,
+    };
+
+    Ok((agent, agent_version))
+}
+
 /// Create a new run
 #[utoipa::path(
     post,
@@ -228,6 +649,7 @@ fn step_to_response(step: fd_storage::models::Step) -> StepResponse {
         (status = 201, description = "Run created and queued", body = RunResponse),
         (status = 400, description = "Invalid request"),
         (status = 404, description = "Agent not found"),
+        (status = 429, description = "Agent concurrency limit reached"),
     )
 )]
 #[instrument(skip(state, auth), fields(run_id, agent_id = %request.agent_id))]
@@ -238,32 +660,13 @@ pub async fn create_run(
 ) -> Result<impl IntoResponse, ApiError> {
     let repos = state.repos();
 
-    // Get the agent by ID, falling back to slug lookup
-    let agent = match repos.agents().get(&request.agent_id).await? {
-        Some(agent) => agent,
-        None => {
-            // Try looking up by slug if not found by ID
-            repos
-                .agents()
-                .find_by_slug(&request.agent_id)
-                .await?
-                .ok_or_else(|| ApiError::not_found("Agent", &request.agent_id))?
-        }
-    };
-
-    // Get agent version (latest or specific)
-    let agent_version = match &request.agent_version {
-        Some(version_id) => repos
-            .agents()
-            .get_version(version_id)
-            .await?
-            .ok_or_else(|| ApiError::not_found("AgentVersion", version_id))?,
-        None => repos
-            .agents()
-            .get_latest_version(&agent.id)
-            .await?
-            .ok_or_else(|| ApiError::bad_request("Agent has no versions"))?,
-    };
+    let (agent, agent_version) = resolve_agent_version(
+        repos,
+        &auth,
+        &request.agent_id,
+        request.agent_version.as_deref(),
+    )
+    .await?;
 
     // Check initial budget (ensure we're starting with empty budget)
     let initial_usage = BudgetUsage::default();
@@ -273,6 +676,12 @@ pub async fn create_run(
         return Err(ApiError::budget_exceeded(&budget_decision.reason));
     }
 
+    // Per-run airlock mode override (loosening requires airlock:override)
+    let airlock = state.airlock_for_tenant(&auth.tenant_id).await?;
+    resolve_run_airlock_mode(&request.config, airlock.config().mode, &auth)?;
+
+    let metadata = normalize_run_metadata(request.metadata)?;
+
     // Create the run
     let run_id = format!("run_{}", Ulid::new());
     tracing::Span::current().record("run_id", &run_id);
@@ -285,24 +694,39 @@ pub async fn create_run(
         config: request.config,
         trace_id: None,
         span_id: None,
+        metadata,
+        budget_snapshot: Some(budget_snapshot(state.policy_engine.default_budget())),
     };
 
-    let run = repos.runs().create(create_run).await?;
-
-    // Audit: Run created
+    // Audit: Run created, committed together with the run
     let audit_event = AuditEventBuilder::new(action::RUN_CREATED, resource::RUN)
         .actor(actor::API_KEY, Some(auth.api_key_id.clone()))
         .resource_id(&run_id)
         .tenant(auth.tenant_id.clone())
         .project(&agent.project_id)
         .run(&run_id)
-        .details(serde_json::json!({
-            "agent_id": request.agent_id,
-            "agent_version_id": agent_version.id,
-        }))
+        .details(with_run_metadata(
+            serde_json::json!({
+                "agent_id": request.agent_id,
+                "agent_version_id": agent_version.id,
+            }),
+            &create_run.metadata,
+        ))
         .build();
-    // Spawn audit write in background to reduce latency
-    repos.spawn_audit(audit_event);
+
+    // Create atomically against the agent's concurrency limit
+    let run = match repos
+        .runs()
+        .create_within_agent_limit(create_run, &agent, &audit_event)
+        .await?
+    {
+        Some(run) => run,
+        None => {
+            let limit = agent.max_concurrent_runs.unwrap_or_default();
+            warn!(agent_id = %agent.id, limit, "Agent concurrency limit reached");
+            return Err(ApiError::agent_concurrency_exceeded(&agent.id, limit));
+        }
+    };
 
     // Create the initial LLM step
     let step_id = format!("stp_{}", Ulid::new());
@@ -323,10 +747,11 @@ pub async fn create_run(
     repos.steps().create(create_step).await?;
 
     // Update run status to queued
-    repos
+    let queued = repos
         .runs()
         .update_status(&run_id, RunStatus::Queued, None)
         .await?;
+    applied_transition(queued, &run_id, RunStatus::Queued)?;
 
     // Enqueue the step for processing
     // Merge user input (task, etc.) with agent version settings
@@ -351,12 +776,7 @@ pub async fn create_run(
         step_id: step_id.clone(),
         step_type: "llm".to_string(),
         input: job_input,
-        context: JobContext {
-            tenant_id: auth.tenant_id,
-            project_id: agent.project_id,
-            trace_id: None,
-            span_id: None,
-        },
+        context: run_job_context(&run, &auth.tenant_id),
     };
 
     let message = QueueMessage::new(&step_id, job);
@@ -384,122 +804,410 @@ pub async fn get_run(
     Extension(auth): Extension<AuthContext>,
     Path(run_id): Path<String>,
 ) -> Result<impl IntoResponse, ApiError> {
-    let run = state
-        .repos()
-        .runs()
-        .get(&run_id)
-        .await?
-        .ok_or_else(|| ApiError::not_found("Run", &run_id))?;
+    let runs = state.repos().runs();
+
+    // Archived runs are no longer in the hot table; serve them from the tombstone
+    let response = match runs.get(&run_id).await? {
+        Some(run) => run_to_response(run),
+        None => runs
+            .get_archived(&run_id)
+            .await?
+            .map(archived_run_to_response)
+            .transpose()?
+            .ok_or_else(|| ApiError::not_found("Run", &run_id))?,
+    };
 
     // SECURITY: Verify tenant owns this run's project
     // The run belongs to a project, and the project must belong to the authenticated tenant
-    if !auth.can_access_project(&run.project_id) {
+    if !auth.can_access_project(&response.project_id) {
         warn!(
             run_id = %run_id,
-            run_project = %run.project_id,
+            run_project = %response.project_id,
             auth_tenant = %auth.tenant_id,
             "Unauthorized access attempt to run from different tenant"
         );
         return Err(ApiError::forbidden("Access denied to this run"));
     }
 
-    Ok(Json(run_to_response(run)))
+    Ok(Json(response))
 }
 
-/// List runs
+/// Request to estimate a run's cost without creating it
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct EstimateRunRequest {
+    /// Same body as run creation
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub run: CreateRunRequest,
+    /// Expected input tokens (defaults to the agent's historical average)
+    #[serde(default)]
+    pub expected_input_tokens: Option<u64>,
+    /// Expected output tokens (defaults to the agent's historical average)
+    #[serde(default)]
+    pub expected_output_tokens: Option<u64>,
+}
+
+/// Estimated cost of a run
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RunEstimateResponse {
+    /// Agent version the run would use
+    pub agent_version_id: String,
+    /// Model the estimate is priced for
+    pub model: String,
+    /// Input tokens the estimate assumes
+    pub input_tokens: u64,
+    /// Output tokens the estimate assumes
+    pub output_tokens: u64,
+    /// Estimated cost in cents
+    pub cost_cents: u64,
+    /// `expected` when the caller supplied both token counts, otherwise
+    /// `historical_average`
+    #[schema(example = "expected")]
+    pub basis: String,
+}
+
+/// Price a run from expected token counts, filling gaps from history
+pub(crate) fn estimate_run_cost(
+    agent_version: &fd_storage::models::AgentVersion,
+    expected: (Option<u64>, Option<u64>),
+    historical: Option<(u64, u64)>,
+) -> Result<RunEstimateResponse, ApiError> {
+    let (input_tokens, output_tokens, basis) = match (expected, historical) {
+        ((Some(input), Some(output)), _) => (input, output, "expected"),
+        ((input, output), Some((avg_input, avg_output))) => (
+            input.unwrap_or(avg_input),
+            output.unwrap_or(avg_output),
+            "historical_average",
+        ),
+        _ => {
+            return Err(ApiError::bad_request(
+                "expected_input_tokens and expected_output_tokens are required: \
+                 agent has no completed runs to average",
+            ))
+        }
+    };
+
+    Ok(RunEstimateResponse {
+        agent_version_id: agent_version.id.clone(),
+        model: agent_version.model.clone(),
+        input_tokens,
+        output_tokens,
+        cost_cents: pricing::calculate_cost_cents(
+            &agent_version.model,
+            input_tokens,
+            output_tokens,
+        ),
+        basis: basis.to_string(),
+    })
+}
+
+/// Estimate a run's cost from the agent's model pricing, without creating it
 #[utoipa::path(
-    get,
-    path = "/v1/runs",
+    post,
+    path = "/v1/runs:estimate",
     tag = "runs",
-    params(ListRunsQuery),
+    request_body = EstimateRunRequest,
     responses(
-        (status = 200, description = "List of runs", body = ListRunsResponse),
-        (status = 400, description = "Invalid query parameters"),
+        (status = 200, description = "Estimated run cost", body = RunEstimateResponse),
+        (status = 400, description = "Invalid request or no usage history"),
+        (status = 404, description = "Agent not found"),
     )
 )]
-#[instrument(skip(state, _auth))]
-pub async fn list_runs(
+#[instrument(skip(state, auth), fields(agent_id = %request.run.agent_id))]
+pub async fn estimate_run(
     State(state): State<AppState>,
-    Extension(_auth): Extension<AuthContext>,
-    ValidatedQuery(query): ValidatedQuery<ListRunsQuery>,
+    Extension(auth): Extension<AuthContext>,
+    ValidatedJson(request): ValidatedJson<EstimateRunRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
-    let project_id = query
-        .project_id
-        .as_ref()
-        .ok_or_else(|| ApiError::bad_request("project_id is required"))?;
-
     let repos = state.repos();
-    let runs = repos
-        .runs()
-        .list_by_project(project_id, query.limit, query.offset)
-        .await?;
-    let total = repos.runs().count_by_project(project_id).await?;
 
-    let runs: Vec<RunResponse> = runs.into_iter().map(run_to_response).collect();
+    let (agent, agent_version) = resolve_agent_version(
+        repos,
+        &auth,
+        &request.run.agent_id,
+        request.run.agent_version.as_deref(),
+    )
+    .await?;
+
+    let expected = (
+        request.expected_input_tokens,
+        request.expected_output_tokens,
+    );
+    let historical = match expected {
+        (Some(_), Some(_)) => None,
+        _ => repos.runs().average_token_usage(&agent.id).await?,
+    };
 
-    Ok(Json(ListRunsResponse { runs, total }))
+    Ok(Json(estimate_run_cost(
+        &agent_version,
+        expected,
+        historical,
+    )?))
 }
 
-/// Cancel a run
+/// Request to archive old terminal runs
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ArchiveRunsRequest {
+    /// Archive terminal runs that finished more than this many days ago
+    #[validate(range(min = 1, max = 3650, message = "older_than_days must be 1-3650"))]
+    #[schema(example = 90)]
+    pub older_than_days: i64,
+}
+
+/// Result of a run archival pass
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ArchiveRunsResponse {
+    /// Number of runs moved to cold storage
+    pub archived: usize,
+    /// Runs that finished before this time were eligible
+    pub cutoff: String,
+}
+
+/// Archive terminal runs older than a cutoff (admin only)
 #[utoipa::path(
     post,
-    path = "/v1/runs/{run_id}/cancel",
+    path = "/v1/runs:archive",
     tag = "runs",
-    params(("run_id" = String, Path, description = "Run ID to cancel")),
+    request_body = ArchiveRunsRequest,
     responses(
-        (status = 200, description = "Run cancelled", body = RunResponse),
-        (status = 400, description = "Run already in terminal state"),
-        (status = 404, description = "Run not found"),
+        (status = 200, description = "Runs archived", body = ArchiveRunsResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 403, description = "Admin scope required"),
     )
 )]
-#[instrument(skip(state, auth), fields(run_id = %run_id))]
-pub async fn cancel_run(
+#[instrument(skip(state, auth))]
+pub async fn archive_runs(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthContext>,
-    Path(run_id): Path<String>,
+    ValidatedJson(request): ValidatedJson<ArchiveRunsRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
     let repos = state.repos();
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(request.older_than_days);
 
-    let run = repos
-        .runs()
-        .get(&run_id)
-        .await?
-        .ok_or_else(|| ApiError::not_found("Run", &run_id))?;
+    let archived = repos.runs().archive_older_than(cutoff).await?;
 
-    // SECURITY: Verify tenant owns this run's project
-    if !auth.can_access_project(&run.project_id) {
-        warn!(
-            run_id = %run_id,
-            run_project = %run.project_id,
-            auth_tenant = %auth.tenant_id,
-            "Unauthorized cancel attempt for run from different tenant"
-        );
-        return Err(ApiError::forbidden("Access denied to cancel this run"));
-    }
+    let audit_event = AuditEventBuilder::new(action::RUNS_ARCHIVED, resource::RUN)
+        .actor(actor::API_KEY, Some(auth.api_key_id.clone()))
+        .tenant(auth.tenant_id.clone())
+        .details(serde_json::json!({
+            "archived": archived,
+            "cutoff": cutoff.to_rfc3339(),
+        }))
+        .build();
+    repos.spawn_audit(audit_event);
 
-    if run.status.is_terminal() {
-        return Err(ApiError::bad_request(format!(
-            "Run is already in terminal state: {:?}",
-            run.status
-        )));
-    }
+    info!(archived, cutoff = %cutoff, "Archived terminal runs");
 
-    let updated = repos
-        .runs()
-        .update(
-            &run_id,
-            UpdateRun {
-                status: Some(RunStatus::Cancelled),
-                status_reason: Some("Cancelled by user".to_string()),
-                completed_at: Some(Utc::now()),
-                ..Default::default()
-            },
-        )
-        .await?
-        .ok_or_else(|| ApiError::internal("Failed to update run"))?;
+    Ok(Json(ArchiveRunsResponse {
+        archived,
+        cutoff: cutoff.to_rfc3339(),
+    }))
+}
 
-    // Audit: Run cancelled
-    let audit_event = AuditEventBuilder::new(action::RUN_CANCELLED, resource::RUN)
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct SweepStuckRunsRequest {
+    /// Runs with no step activity for this many minutes are considered stuck
+    #[validate(range(min = 1, max = 10080, message = "idle_minutes must be 1-10080"))]
+    #[schema(example = 60)]
+    pub idle_minutes: i64,
+    /// Mark stuck runs as failed instead of only reporting them
+    #[serde(default)]
+    pub fail: bool,
+}
+
+/// A run with no recent step activity
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StuckRun {
+    pub run_id: String,
+    pub project_id: String,
+    pub status: String,
+    pub created_at: String,
+    pub started_at: Option<String>,
+}
+
+/// Result of a stuck-run sweep
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SweepStuckRunsResponse {
+    /// Runs currently in a non-terminal status
+    pub in_progress: i64,
+    /// Runs idle beyond the threshold
+    pub stuck: Vec<StuckRun>,
+    /// Number of stuck runs marked as failed
+    pub failed: usize,
+    /// Runs without step activity since this time were considered stuck
+    pub cutoff: String,
+}
+
+/// Find runs stuck without step activity, optionally failing them (admin only)
+#[utoipa::path(
+    post,
+    path = "/v1/runs:sweep-stuck",
+    tag = "runs",
+    request_body = SweepStuckRunsRequest,
+    responses(
+        (status = 200, description = "Stuck runs found", body = SweepStuckRunsResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 403, description = "Admin scope required"),
+    )
+)]
+#[instrument(skip(state, auth))]
+pub async fn sweep_stuck_runs(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    ValidatedJson(request): ValidatedJson<SweepStuckRunsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let repos = state.repos();
+    let now = Utc::now();
+    let idle_threshold = chrono::Duration::minutes(request.idle_minutes);
+
+    let in_progress = repos.runs().count_in_progress().await?;
+    let stuck_runs = repos.runs().find_stuck(now, idle_threshold).await?;
+
+    let mut failed = 0;
+    if request.fail {
+        let reason = format!(
+            "Run stuck: no step activity for {} minutes",
+            request.idle_minutes
+        );
+        for run in &stuck_runs {
+            if !run.status.can_transition_to(RunStatus::Failed) {
+                continue;
+            }
+            let swept = repos
+                .runs()
+                .update(
+                    &run.id,
+                    UpdateRun {
+                        status: Some(RunStatus::Failed),
+                        status_reason: Some(reason.clone()),
+                        completed_at: Some(now),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            cancel_outstanding_steps(repos, &run.id).await?;
+            if let Some(swept) = &swept {
+                spawn_run_summary(repos, swept);
+            }
+            failed += 1;
+        }
+    }
+
+    if !stuck_runs.is_empty() {
+        let audit_event = AuditEventBuilder::new(action::RUNS_STUCK_DETECTED, resource::RUN)
+            .actor(actor::API_KEY, Some(auth.api_key_id.clone()))
+            .tenant(auth.tenant_id.clone())
+            .details(serde_json::json!({
+                "run_ids": stuck_runs.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(),
+                "idle_minutes": request.idle_minutes,
+                "failed": failed,
+            }))
+            .build();
+        repos.spawn_audit(audit_event);
+
+        warn!(
+            stuck = stuck_runs.len(),
+            failed,
+            idle_minutes = request.idle_minutes,
+            "Found stuck runs"
+        );
+    }
+
+    let stuck = stuck_runs
+        .into_iter()
+        .map(|run| StuckRun {
+            run_id: run.id,
+            project_id: run.project_id,
+            status: run.status.as_str().to_string(),
+            created_at: run.created_at.to_rfc3339(),
+            started_at: run.started_at.map(|t| t.to_rfc3339()),
+        })
+        .collect();
+
+    Ok(Json(SweepStuckRunsResponse {
+        in_progress,
+        stuck,
+        failed,
+        cutoff: (now - idle_threshold).to_rfc3339(),
+    }))
+}
+
+/// List runs
+#[utoipa::path(
+    get,
+    path = "/v1/runs",
+    tag = "runs",
+    params(ListRunsQuery),
+    responses(
+        (status = 200, description = "List of runs", body = ListRunsResponse),
+        (status = 400, description = "Invalid query parameters"),
+    )
+)]
+#[instrument(skip(state, _auth))]
+pub async fn list_runs(
+    State(state): State<AppState>,
+    Extension(_auth): Extension<AuthContext>,
+    ValidatedQuery(query): ValidatedQuery<ListRunsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let project_id = query
+        .project_id
+        .as_ref()
+        .ok_or_else(|| ApiError::bad_request("project_id is required"))?;
+
+    let repos = state.repos();
+    let runs = repos
+        .runs()
+        .list_by_project(project_id, query.limit, query.offset)
+        .await?;
+    let total = repos.runs().count_by_project(project_id).await?;
+
+    let runs: Vec<RunResponse> = runs.into_iter().map(run_to_response).collect();
+
+    Ok(Json(ListRunsResponse { runs, total }))
+}
+
+/// Cancel a run
+#[utoipa::path(
+    post,
+    path = "/v1/runs/{run_id}/cancel",
+    tag = "runs",
+    params(("run_id" = String, Path, description = "Run ID to cancel")),
+    responses(
+        (status = 200, description = "Run cancelled", body = RunResponse),
+        (status = 400, description = "Run already in terminal state"),
+        (status = 404, description = "Run not found"),
+    )
+)]
+#[instrument(skip(state, auth), fields(run_id = %run_id))]
+pub async fn cancel_run(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(run_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let repos = state.repos();
+
+    let run = repos
+        .runs()
+        .get(&run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Run", &run_id))?;
+
+    // SECURITY: Verify tenant owns this run's project
+    if !auth.can_access_project(&run.project_id) {
+        warn!(
+            run_id = %run_id,
+            run_project = %run.project_id,
+            auth_tenant = %auth.tenant_id,
+            "Unauthorized cancel attempt for run from different tenant"
+        );
+        return Err(ApiError::forbidden("Access denied to cancel this run"));
+    }
+
+    ensure_run_transition(run.status, RunStatus::Cancelled)?;
+
+    // Audit: Run cancelled, committed together with the status change
+    let steps_cancelled = cancel_outstanding_steps(repos, &run_id).await?;
+    let audit_event = AuditEventBuilder::new(action::RUN_CANCELLED, resource::RUN)
         .actor(actor::API_KEY, Some(auth.api_key_id.clone()))
         .resource_id(&run_id)
         .tenant(auth.tenant_id)
@@ -507,9 +1215,25 @@ pub async fn cancel_run(
         .run(&run_id)
         .details(serde_json::json!({
             "previous_status": format!("{:?}", run.status),
+            "steps_cancelled": steps_cancelled,
         }))
         .build();
-    repos.spawn_audit(audit_event);
+    let updated = repos
+        .runs()
+        .update_with_audit(
+            &run_id,
+            UpdateRun {
+                status: Some(RunStatus::Cancelled),
+                status_reason: Some("Cancelled by user".to_string()),
+                completed_at: Some(Utc::now()),
+                ..Default::default()
+            },
+            &audit_event,
+        )
+        .await?;
+    let updated = applied_transition(updated, &run_id, RunStatus::Cancelled)?;
+    state.tenant_airlocks.clear_run(&run_id).await;
+    spawn_run_summary(repos, &updated);
 
     info!(run_id = %run_id, "Run cancelled by user");
 
@@ -560,14 +1284,644 @@ pub async fn list_steps(
     Ok(Json(steps))
 }
 
+/// Scope required to read reconstructed step jobs
+pub(crate) const DEBUG_SCOPE: &str = "debug";
+
+/// Rebuild a step's queue job for local replay, with secrets redacted
+pub(crate) fn redacted_step_job(
+    step: &fd_storage::models::Step,
+    tenant_id: &str,
+    project_id: &str,
+    metadata: &serde_json::Value,
+) -> StepJob {
+    let mut job = step_job(step, tenant_id, project_id, metadata);
+    job.input = fd_audit::redact_json(&job.input);
+    job
+}
+
+/// Get the job payload that was (or would be) enqueued for a step
+///
+/// Debugging aid for reproducing a failing step locally. Requires the
+/// `debug` scope.
+#[instrument(skip(state, auth))]
+pub async fn get_step_job(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path((run_id, step_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !auth.has_scope(DEBUG_SCOPE) {
+        return Err(ApiError::forbidden(format!(
+            "Reading step jobs requires the '{}' scope",
+            DEBUG_SCOPE
+        )));
+    }
+
+    let repos = state.repos();
+
+    let run = repos
+        .runs()
+        .get(&run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Run", &run_id))?;
+
+    // SECURITY: Verify tenant owns this run's project
+    if !auth.can_access_project(&run.project_id) {
+        return Err(ApiError::forbidden("Access denied to this run"));
+    }
+
+    let step = repos
+        .steps()
+        .get(&step_id)
+        .await?
+        .filter(|step| step.run_id == run_id)
+        .ok_or_else(|| ApiError::not_found("Step", &step_id))?;
+
+    Ok(Json(redacted_step_job(
+        &step,
+        &auth.tenant_id,
+        &run.project_id,
+        &run.metadata,
+    )))
+}
+
+/// Version of the run export format, bumped on incompatible changes
+pub(crate) const RUN_EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Portable run bundle for support and local replay
+///
+/// The buffered form of the document [`export_chunks`] streams.
+#[cfg(test)]
+#[derive(Debug, Serialize)]
+pub struct RunExport {
+    pub format_version: u32,
+    pub exported_at: String,
+    #[serde(flatten)]
+    pub bundle: fd_storage::models::RunExportBundle,
+}
+
+/// Redact secrets from every payload in an export bundle
+#[cfg(test)]
+pub(crate) fn redact_export_bundle(
+    mut bundle: fd_storage::models::RunExportBundle,
+) -> fd_storage::models::RunExportBundle {
+    redact_export_run(&mut bundle.run);
+    bundle.steps.iter_mut().for_each(redact_export_step);
+    bundle.threats.iter_mut().for_each(redact_export_threat);
+    bundle
+}
+
+fn redact_opt(value: &mut Option<serde_json::Value>) {
+    if let Some(v) = value.as_mut() {
+        *v = fd_audit::redact_json(v);
+    }
+}
+
+fn redact_export_run(run: &mut fd_storage::models::Run) {
+    run.input = fd_audit::redact_json(&run.input);
+    run.config = fd_audit::redact_json(&run.config);
+    run.metadata = fd_audit::redact_json(&run.metadata);
+    redact_opt(&mut run.output);
+    redact_opt(&mut run.error);
+}
+
+fn redact_export_step(step: &mut fd_storage::models::Step) {
+    step.input = fd_audit::redact_json(&step.input);
+    redact_opt(&mut step.output);
+    redact_opt(&mut step.error);
+}
+
+fn redact_export_threat(threat: &mut fd_storage::models::Threat) {
+    redact_opt(&mut threat.blocked_payload);
+}
+
+/// Serialize a run export as a stream of JSON chunks
+///
+/// Produces the same document as serializing a redacted [`RunExport`], but
+/// steps are redacted and encoded one chunk at a time as they are read, so
+/// the full document is never held in memory. Steps keep the order of the
+/// stream. The first error ends the stream, leaving the document unclosed.
+pub(crate) fn export_chunks(
+    exported_at: String,
+    export: RunExportStream,
+) -> impl Stream<Item = Result<Vec<u8>, axum::BoxError>> + Send {
+    let RunExportStream {
+        mut run,
+        policy_decisions,
+        mut threats,
+        steps,
+    } = export;
+
+    let head = futures_util::stream::once(async move {
+        redact_export_run(&mut run);
+        let mut chunk = serde_json::to_vec(&serde_json::json!({
+            "format_version": RUN_EXPORT_FORMAT_VERSION,
+            "exported_at": exported_at,
+        }))?;
+        chunk.pop(); // reopen the object
+        chunk.extend_from_slice(b",\"run\":");
+        serde_json::to_writer(&mut chunk, &run)?;
+        chunk.extend_from_slice(b",\"steps\":[");
+        Ok(chunk)
+    });
+
+    let steps = steps.enumerate().map(|(i, step)| {
+        let mut step = step?;
+        redact_export_step(&mut step);
+        let mut chunk = if i == 0 { Vec::new() } else { b",".to_vec() };
+        serde_json::to_writer(&mut chunk, &step)?;
+        Ok(chunk)
+    });
+
+    let tail = futures_util::stream::once(async move {
+        threats.iter_mut().for_each(redact_export_threat);
+        let mut chunk = b"],\"policy_decisions\":".to_vec();
+        serde_json::to_writer(&mut chunk, &policy_decisions)?;
+        chunk.extend_from_slice(b",\"threats\":");
+        serde_json::to_writer(&mut chunk, &threats)?;
+        chunk.push(b'}');
+        Ok(chunk)
+    });
+
+    head.chain(steps)
+        .chain(tail)
+        .scan(false, |failed, chunk: Result<Vec<u8>, axum::BoxError>| {
+            let done = std::mem::replace(failed, chunk.is_err());
+            std::future::ready((!done).then_some(chunk))
+        })
+}
+
+/// Build the export response body, reading up to the first step up front
+///
+/// A step query that fails outright is answered with an error status. Once
+/// the body has started, a later failure aborts the response instead of
+/// closing it, so clients never receive a truncated document as complete.
+pub(crate) async fn export_body(
+    run_id: &str,
+    chunks: impl Stream<Item = Result<Vec<u8>, axum::BoxError>> + Send + 'static,
+) -> Result<axum::body::Body, ApiError> {
+    let mut chunks = Box::pin(chunks);
+    let mut started = Vec::new();
+    // The head chunk, then the first step (or the tail of an empty run)
+    while started.len() < 2 {
+        match chunks.next().await {
+            Some(Ok(chunk)) => started.push(Ok(chunk)),
+            Some(Err(e)) => return Err(ApiError::internal(format!("Failed to export run: {}", e))),
+            None => break,
+        }
+    }
+
+    let run_id = run_id.to_string();
+    let rest = chunks.inspect_err(move |e| {
+        error!(run_id = %run_id, error = %e, "Run export failed mid-stream");
+    });
+    Ok(axum::body::Body::from_stream(
+        futures_util::stream::iter(started).chain(rest),
+    ))
+}
+
+/// Export a run with its steps, policy decisions and Airlock findings
+///
+/// Payloads are redacted before they leave the gateway. Steps are streamed
+/// from the database into the response, so exports of any size are sent
+/// without buffering. A client that stops reading for 30 seconds has its
+/// export aborted so the database connection is released. Requires the
+/// `debug` scope.
+#[instrument(skip(state, auth))]
+pub async fn export_run(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(run_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !auth.has_scope(DEBUG_SCOPE) {
+        return Err(ApiError::forbidden(format!(
+            "Exporting runs requires the '{}' scope",
+            DEBUG_SCOPE
+        )));
+    }
+
+    let export = state
+        .repos()
+        .runs()
+        .export_stream(&run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Run", &run_id))?;
+
+    // SECURITY: Verify tenant owns this run's project
+    if !auth.can_access_project(&export.run.project_id) {
+        return Err(ApiError::forbidden("Access denied to this run"));
+    }
+
+    let chunks = export_chunks(Utc::now().to_rfc3339(), export);
+    let body = export_body(&run_id, chunks).await?;
+
+    Ok((
+        [(axum::http::header::CONTENT_TYPE, "application/json")],
+        body,
+    ))
+}
+
+/// Query parameters for comparing two runs
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+pub struct RunDiffQuery {
+    /// Baseline run ID
+    #[validate(length(min = 1, message = "a must not be empty"))]
+    pub a: String,
+    /// Run compared against the baseline
+    #[validate(length(min = 1, message = "b must not be empty"))]
+    pub b: String,
+}
+
+/// How one position in two runs' step sequences compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StepDiffStatus {
+    /// Both runs executed the same step with the same result
+    Matching,
+    /// Both runs have a step here but it differs
+    Diverged,
+    /// Only the baseline run got this far
+    OnlyInA,
+    /// Only the compared run got this far
+    OnlyInB,
+}
+
+/// One position in two runs' step sequences
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StepDiff {
+    /// 1-based position in execution order
+    pub position: usize,
+    pub status: StepDiffStatus,
+    /// Fields that differ between the two steps
+    #[schema(example = json!(["output"]))]
+    pub differences: Vec<String>,
+    /// The baseline run's step, if it has one here
+    pub a: Option<StepResponse>,
+    /// The compared run's step, if it has one here
+    pub b: Option<StepResponse>,
+}
+
+/// One run's outcome and usage in a diff
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RunDiffSide {
+    pub run_id: String,
+    pub agent_version_id: String,
+    #[schema(example = "completed")]
+    pub status: String,
+    pub input_tokens: i32,
+    pub output_tokens: i32,
+    pub tool_calls: i32,
+    pub cost_cents: i32,
+    pub step_count: usize,
+    pub output: Option<serde_json::Value>,
+}
+
+/// Structured comparison of two runs
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RunDiffResponse {
+    pub a: RunDiffSide,
+    pub b: RunDiffSide,
+    /// Whether both runs ended in the same status
+    pub status_matches: bool,
+    /// Whether both runs produced the same final output
+    pub output_matches: bool,
+    /// Position of the first step that is not matching, if any
+    pub first_divergence: Option<usize>,
+    /// Step-by-step comparison in execution order
+    pub steps: Vec<StepDiff>,
+}
+
+/// Names of the fields that differ between two steps
+///
+/// Token counts are reported on the steps but not compared: they vary
+/// between otherwise identical model calls.
+fn step_differences(a: &fd_storage::models::Step, b: &fd_storage::models::Step) -> Vec<String> {
+    [
+        ("step_type", a.step_type == b.step_type),
+        ("tool_name", a.tool_name == b.tool_name),
+        ("model", a.model == b.model),
+        ("input", a.input == b.input),
+        ("output", a.output == b.output),
+        ("status", a.status == b.status),
+        ("error", a.error == b.error),
+    ]
+    .into_iter()
+    .filter(|(_, same)| !same)
+    .map(|(field, _)| field.to_string())
+    .collect()
+}
+
+fn run_diff_side(loaded: &RunWithSteps) -> RunDiffSide {
+    let run = &loaded.run;
+    RunDiffSide {
+        run_id: run.id.clone(),
+        agent_version_id: run.agent_version_id.clone(),
+        status: run.status.as_str().to_string(),
+        input_tokens: run.input_tokens,
+        output_tokens: run.output_tokens,
+        tool_calls: run.tool_calls,
+        cost_cents: run.cost_cents,
+        step_count: loaded.steps.len(),
+        output: run.output.clone(),
+    }
+}
+
+/// Compare two runs step by step, aligned on execution order
+pub(crate) fn compare_runs(a: RunWithSteps, b: RunWithSteps) -> RunDiffResponse {
+    let side_a = run_diff_side(&a);
+    let side_b = run_diff_side(&b);
+
+    let mut steps_a = a.steps.into_iter();
+    let mut steps_b = b.steps.into_iter();
+    let mut steps = Vec::new();
+    for position in 1.. {
+        let (status, differences, a, b) = match (steps_a.next(), steps_b.next()) {
+            (None, None) => break,
+            (Some(a), Some(b)) => {
+                let differences = step_differences(&a, &b);
+                let status = if differences.is_empty() {
+                    StepDiffStatus::Matching
+                } else {
+                    StepDiffStatus::Diverged
+                };
+                (status, differences, Some(a), Some(b))
+            }
+            (Some(a), None) => (StepDiffStatus::OnlyInA, Vec::new(), Some(a), None),
+            (None, Some(b)) => (StepDiffStatus::OnlyInB, Vec::new(), None, Some(b)),
+        };
+        steps.push(StepDiff {
+            position,
+            status,
+            differences,
+            a: a.map(step_to_response),
+            b: b.map(step_to_response),
+        });
+    }
+
+    RunDiffResponse {
+        status_matches: side_a.status == side_b.status,
+        output_matches: side_a.output == side_b.output,
+        first_divergence: steps
+            .iter()
+            .find(|step| step.status != StepDiffStatus::Matching)
+            .map(|step| step.position),
+        steps,
+        a: side_a,
+        b: side_b,
+    }
+}
+
+/// Compare two runs of the same agent
+///
+/// Aligns the runs' steps by execution order and reports which fields
+/// differ at each position, along with both runs' final status, output
+/// and token usage.
+#[utoipa::path(
+    get,
+    path = "/v1/runs/diff",
+    tag = "runs",
+    params(RunDiffQuery),
+    responses(
+        (status = 200, description = "Comparison of the two runs", body = RunDiffResponse),
+        (status = 400, description = "Runs belong to different agents"),
+        (status = 404, description = "Run not found"),
+    )
+)]
+#[instrument(skip(state, auth))]
+pub async fn diff_runs(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    ValidatedQuery(query): ValidatedQuery<RunDiffQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let repos = state.repos();
+    let (a, b) = repos.runs().load_for_diff(&query.a, &query.b).await?;
+    let a = a.ok_or_else(|| ApiError::not_found("Run", &query.a))?;
+    let b = b.ok_or_else(|| ApiError::not_found("Run", &query.b))?;
+
+    // SECURITY: Verify tenant owns both runs' projects
+    if !auth.can_access_project(&a.run.project_id) || !auth.can_access_project(&b.run.project_id) {
+        return Err(ApiError::forbidden("Access denied to this run"));
+    }
+
+    if a.run.agent_version_id != b.run.agent_version_id {
+        let mut agent_ids = Vec::with_capacity(2);
+        for version_id in [&a.run.agent_version_id, &b.run.agent_version_id] {
+            let version = repos
+                .agents()
+                .get_version(version_id)
+                .await?
+                .ok_or_else(|| ApiError::not_found("AgentVersion", version_id))?;
+            agent_ids.push(version.agent_id);
+        }
+        if agent_ids[0] != agent_ids[1] {
+            return Err(ApiError::bad_request(format!(
+                "Runs '{}' and '{}' belong to different agents",
+                query.a, query.b
+            )));
+        }
+    }
+
+    Ok(Json(compare_runs(a, b)))
+}
+
+/// Tool calls a step result adds to its run's usage
+///
+/// Each completed tool step counts once toward `max_tool_calls`.
+pub(crate) fn tool_call_delta(step: &fd_storage::models::Step, status: StepStatus) -> i32 {
+    i32::from(step.step_type == StepType::Tool && status == StepStatus::Completed)
+}
+
+/// Wall time a run has used against its budget, as of `now`
+///
+/// Measured from when execution started, so time spent queued doesn't
+/// count. Runs that never recorded a start fall back to their creation time.
+pub(crate) fn budget_wall_time_ms(run: &fd_storage::models::Run, now: DateTime<Utc>) -> u64 {
+    now.signed_duration_since(run.started_at.unwrap_or(run.created_at))
+        .num_milliseconds()
+        .max(0) as u64
+}
+
+/// Mark `run` started if this is the first step reported for it
+///
+/// Workers pull steps straight from the queue, so the first result the
+/// gateway sees is the earliest sign that the run is executing.
+async fn ensure_run_started(
+    repos: &Repos,
+    run: fd_storage::models::Run,
+) -> Result<fd_storage::models::Run, ApiError> {
+    if run.started_at.is_some() {
+        return Ok(run);
+    }
+    let started = repos.runs().mark_started(&run.id, Utc::now()).await?;
+    Ok(started.unwrap_or(run))
+}
+
+/// Audit event for a step result reported by a worker
+///
+/// `usage` is (input tokens, output tokens, cost in cents).
+pub(crate) fn step_result_audit(
+    run: &fd_storage::models::Run,
+    step: &fd_storage::models::Step,
+    status: StepStatus,
+    usage: (i32, i32, u64),
+    batch: bool,
+) -> CreateAuditEvent {
+    let audit_action = match status {
+        StepStatus::Completed => action::STEP_COMPLETED,
+        StepStatus::Failed => action::STEP_FAILED,
+        _ => action::STEP_STARTED, // For WaitingApproval, use a neutral action
+    };
+    let (input_tokens, output_tokens, cost_cents) = usage;
+    let mut details = serde_json::json!({
+        "step_type": format!("{:?}", step.step_type),
+        "tool_name": step.tool_name,
+        "model": step.model,
+        "input_tokens": input_tokens,
+        "output_tokens": output_tokens,
+        "cost_cents": cost_cents,
+    });
+    if batch {
+        details["batch"] = serde_json::json!(true);
+    }
+
+    AuditEventBuilder::new(audit_action, resource::STEP)
+        .actor(actor::SYSTEM, None)
+        .resource_id(&step.id)
+        .run(&run.id)
+        .project(&run.project_id)
+        .details(with_run_metadata(details, &run.metadata))
+        .build()
+}
+
+/// Header carrying a worker's signature over a step result body
+pub(crate) const STEP_SIGNATURE_HEADER: &str = "x-fd-signature";
+
+/// Route a step result signature is bound to, relative to `/v1/runs/`
+///
+/// `{run_id}/steps/{step_id}` for a single result, `{run_id}/steps:batch`
+/// for a batch.
+pub(crate) fn step_result_signed_path(run_id: &str, step_id: Option<&str>) -> String {
+    match step_id {
+        Some(step_id) => format!("{}/steps/{}", run_id, step_id),
+        None => format!("{}/steps:batch", run_id),
+    }
+}
+
+/// Route a workflow step execution result signature is bound to, relative
+/// to `/v1/workflow-runs/`
+pub(crate) fn execution_result_signed_path(run_id: &str, execution_id: &str) -> String {
+    format!("{}/executions/{}", run_id, execution_id)
+}
+
+/// Verify a step result body against its `X-FD-Signature` header
+///
+/// The signature is HMAC-SHA256 keyed with the run's signing secret over
+/// `signed_path`, a newline and the raw body, hex-encoded with an optional
+/// `sha256=` prefix. Covering the path keeps a captured body from being
+/// replayed against another step of the run.
+pub(crate) fn verify_step_result_signature(
+    secret: &str,
+    signed_path: &str,
+    body: &[u8],
+    signature: Option<&str>,
+) -> Result<(), ApiError> {
+    let signature = signature
+        .map(str::trim)
+        .ok_or_else(|| ApiError::unauthorized("Missing X-FD-Signature header"))?;
+    let provided = hex::decode(signature.strip_prefix("sha256=").unwrap_or(signature))
+        .map_err(|_| ApiError::unauthorized("Malformed X-FD-Signature header"))?;
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(signed_path.as_bytes());
+    mac.update(b"\n");
+    mac.update(body);
+    // verify_slice compares in constant time
+    mac.verify_slice(&provided)
+        .map_err(|_| ApiError::unauthorized("Invalid step result signature"))
+}
+
+/// Require and spend the allow-once token of a step resumed from approval
+///
+/// Approving a tool call re-enqueues its step with a token, and the step's
+/// result is only accepted with that token. A token presented for any other
+/// step must still verify.
+async fn spend_approval_token(
+    state: &AppState,
+    step: &fd_storage::models::Step,
+    token: Option<&str>,
+) -> Result<(), ApiError> {
+    let policies = state.repos().policies();
+    let Some(token) = token else {
+        if step.tool_name.is_some()
+            && policies
+                .unspent_approval_for_step(&step.id)
+                .await?
+                .is_some()
+        {
+            warn!(step_id = %step.id, "Rejected result without its approval token");
+            return Err(ApiError::forbidden(
+                "Step was resumed from an approval and requires its approval token",
+            ));
+        }
+        return Ok(());
+    };
+
+    policies
+        .verify_approval_token(
+            state.approval_token_key(),
+            token,
+            &step.run_id,
+            &step.id,
+            step.tool_name.as_deref().unwrap_or_default(),
+        )
+        .await
+        .map(|_| ())
+        .map_err(approval_token_error)
+        .inspect_err(|e| warn!(error = %e.message, "Rejected approval token"))
+}
+
+/// Reject a step result submission without a valid signature over `signed_path`
+///
+/// Only enforced when step result signing is enabled.
+pub(crate) fn check_step_result_signature(
+    state: &AppState,
+    headers: &HeaderMap,
+    run_id: &str,
+    signed_path: &str,
+    body: &[u8],
+) -> Result<(), ApiError> {
+    let Some(secret) = state.run_signing_secret(run_id) else {
+        return Ok(());
+    };
+    let signature = headers
+        .get(STEP_SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok());
+    verify_step_result_signature(&secret, signed_path, body, signature)
+        .inspect_err(|e| warn!(error = %e.message, "Rejected step result submission"))
+}
+
 /// Submit step result (from worker)
-#[instrument(skip(state, _auth), fields(run_id = %run_id, step_id = %step_id))]
+///
+/// When step result signing is enabled the body must carry a valid
+/// `X-FD-Signature` for the run and step, otherwise the submission is
+/// rejected.
+#[instrument(skip(state, auth, headers, body), fields(run_id = %run_id, step_id = %step_id))]
 pub async fn submit_step_result(
     State(state): State<AppState>,
-    Extension(_auth): Extension<AuthContext>,
+    Extension(auth): Extension<AuthContext>,
     Path((run_id, step_id)): Path<(String, String)>,
-    ValidatedJson(request): ValidatedJson<SubmitStepResultRequest>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> Result<impl IntoResponse, ApiError> {
+    check_step_result_signature(
+        &state,
+        &headers,
+        &run_id,
+        &step_result_signed_path(&run_id, Some(&step_id)),
+        &body,
+    )?;
+    let request: SubmitStepResultRequest = parse_validated_json(&body)?;
+
     let repos = state.repos();
 
     let run = repos
@@ -586,16 +1940,34 @@ pub async fn submit_step_result(
         return Err(ApiError::bad_request("Step does not belong to this run"));
     }
 
-    let status = match request.status.as_str() {
-        "completed" => StepStatus::Completed,
-        "failed" => StepStatus::Failed,
-        "waiting_approval" => StepStatus::WaitingApproval,
-        _ => return Err(ApiError::bad_request("Invalid status")),
-    };
+    let status = parse_step_result_status(&request.status)?;
+    ensure_step_transition(step.status, status)?;
+
+    // Results are only accepted while the run is still active
+    ensure_run_transition(run.status, RunStatus::Running)?;
+
+    check_output_content_type(
+        &state,
+        &run,
+        &auth.tenant_id,
+        &step,
+        request.content_type.as_deref(),
+    )
+    .await?;
+
+    // Spent last, so a submission rejected above keeps its token
+    let token = headers
+        .get(APPROVAL_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok());
+    spend_approval_token(&state, &step, token).await?;
+    let run = ensure_run_started(repos, run).await?;
 
     let update = UpdateStep {
         status: Some(status),
-        output: request.output.clone(),
+        output: request
+            .output
+            .clone()
+            .map(|output| truncate_step_output(output, state.max_output_bytes)),
         error: request.error.clone(),
         input_tokens: request.input_tokens,
         output_tokens: request.output_tokens,
@@ -603,61 +1975,55 @@ pub async fn submit_step_result(
         ..Default::default()
     };
 
-    let updated_step = repos
-        .steps()
-        .update(&step_id, update)
-        .await?
-        .ok_or_else(|| ApiError::internal("Failed to update step"))?;
-
-    // Update token usage and calculate cost
+    // Calculate cost based on model (from step)
     let (new_input_tokens, new_output_tokens, step_cost_cents) =
         match (request.input_tokens, request.output_tokens) {
             (Some(in_tokens), Some(out_tokens)) => {
-                // Calculate cost based on model (from step)
                 let model = step.model.as_deref().unwrap_or("gpt-4o");
                 let cost =
                     pricing::calculate_cost_cents(model, in_tokens as u64, out_tokens as u64);
-
-                // Update run with tokens and cost
-                repos
-                    .runs()
-                    .increment_usage(&run_id, in_tokens, out_tokens, 0, cost as i32)
-                    .await?;
                 (in_tokens, out_tokens, cost)
             }
             _ => (0, 0, 0),
         };
 
-    // Audit: Step completed/failed
-    let audit_action = match status {
-        StepStatus::Completed => action::STEP_COMPLETED,
-        StepStatus::Failed => action::STEP_FAILED,
-        _ => action::STEP_STARTED, // For WaitingApproval, use a neutral action
-    };
-    let audit_event = AuditEventBuilder::new(audit_action, resource::STEP)
-        .actor(actor::SYSTEM, None)
-        .resource_id(&step_id)
-        .run(&run_id)
-        .project(&run.project_id)
-        .details(serde_json::json!({
-            "step_type": format!("{:?}", step.step_type),
-            "tool_name": step.tool_name,
-            "model": step.model,
-            "input_tokens": new_input_tokens,
-            "output_tokens": new_output_tokens,
-            "cost_cents": step_cost_cents,
-        }))
-        .build();
-    repos.spawn_audit(audit_event);
+    // Audit: Step completed/failed, committed together with the step update
+    let audit_event = step_result_audit(
+        &run,
+        &step,
+        status,
+        (new_input_tokens, new_output_tokens, step_cost_cents),
+        false,
+    );
+    let updated_step = repos
+        .steps()
+        .update_with_audit(&step_id, update, &audit_event)
+        .await?
+        .ok_or_else(|| ApiError::internal("Failed to update step"))?;
+
+    if status == StepStatus::Failed {
+        dead_letter_step(&state, &updated_step, &auth.tenant_id, &run).await;
+    }
+
+    // Update run with tokens, tool calls and cost
+    let tool_calls = tool_call_delta(&step, status);
+    if (request.input_tokens.is_some() && request.output_tokens.is_some()) || tool_calls > 0 {
+        repos
+            .runs()
+            .increment_usage(
+                &run_id,
+                new_input_tokens,
+                new_output_tokens,
+                tool_calls,
+                step_cost_cents as i32,
+            )
+            .await?;
+    }
 
     // Check budget after step completion
     let updated_run = repos.runs().get(&run_id).await?.unwrap();
 
-    // Calculate wall time from run creation to now
-    let wall_time_ms = Utc::now()
-        .signed_duration_since(updated_run.created_at)
-        .num_milliseconds()
-        .max(0) as u64;
+    let wall_time_ms = budget_wall_time_ms(&updated_run, Utc::now());
 
     let usage = BudgetUsage {
         input_tokens: updated_run.input_tokens as u64,
@@ -667,12 +2033,14 @@ pub async fn submit_step_result(
         cost_cents: updated_run.cost_cents as u64,
     };
 
-    let budget_decision = state.policy_engine.check_budget(&usage, None);
+    let budget = run_budget(&updated_run);
+    let exceeded = state.policy_engine.budget_exceeded(&usage, budget.as_ref());
 
-    if budget_decision.is_denied() {
+    if let Some(exceeded) = exceeded {
+        let kill = budget_kill_update(&exceeded);
         warn!(
             run_id = %run_id,
-            reason = %budget_decision.reason,
+            reason = ?kill.status_reason,
             "Budget exceeded, killing run"
         );
 
@@ -683,101 +2051,431 @@ pub async fn submit_step_result(
             .run(&run_id)
             .project(&run.project_id)
             .details(serde_json::json!({
-                "reason": budget_decision.reason,
+                "reason": kill.status_reason,
+                "detail": kill.budget_kill_detail,
                 "usage": usage,
             }))
             .build();
+
+        let killed = repos.runs().update(&run_id, kill).await?;
+        if let Some(killed) = settled_transition(killed, &run_id, RunStatus::BudgetKilled) {
+            repos.spawn_audit(audit_event);
+            spawn_run_summary(repos, &killed);
+            cancel_outstanding_steps(repos, &run_id).await?;
+        }
+
+        // Return the step result, but the run is now killed
+        return Ok(Json(step_to_response(updated_step)));
+    }
+
+    // Check if run is complete
+    let pending_steps = repos.steps().get_pending_steps(&run_id).await?;
+
+    if pending_steps.is_empty() && status == StepStatus::Completed {
+        let completed = repos
+            .runs()
+            .update(
+                &run_id,
+                UpdateRun {
+                    status: Some(RunStatus::Completed),
+                    completed_at: Some(Utc::now()),
+                    output: updated_step.output.clone(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let Some(completed) = settled_transition(completed, &run_id, RunStatus::Completed) else {
+            return Ok(Json(step_to_response(updated_step)));
+        };
+        spawn_run_summary(repos, &completed);
+
+        // Audit: Run completed
+        let audit_event = AuditEventBuilder::new(action::RUN_COMPLETED, resource::RUN)
+            .actor(actor::SYSTEM, None)
+            .resource_id(&run_id)
+            .run(&run_id)
+            .project(&run.project_id)
+            .details(serde_json::json!({
+                "input_tokens": updated_run.input_tokens,
+                "output_tokens": updated_run.output_tokens,
+                "tool_calls": updated_run.tool_calls,
+                "cost_cents": updated_run.cost_cents,
+            }))
+            .build();
         repos.spawn_audit(audit_event);
 
-        repos
+        info!(run_id = %run_id, "Run completed successfully");
+    } else if status == StepStatus::Failed {
+        let failed = repos
             .runs()
             .update(
                 &run_id,
                 UpdateRun {
-                    status: Some(RunStatus::BudgetKilled),
-                    status_reason: Some(budget_decision.reason.clone()),
+                    status: Some(RunStatus::Failed),
+                    status_reason: Some("Step failed".to_string()),
                     completed_at: Some(Utc::now()),
+                    error: updated_step.error.clone(),
                     ..Default::default()
                 },
             )
             .await?;
+        let Some(failed) = settled_transition(failed, &run_id, RunStatus::Failed) else {
+            return Ok(Json(step_to_response(updated_step)));
+        };
+        spawn_run_summary(repos, &failed);
+
+        // Audit: Run failed
+        let audit_event = AuditEventBuilder::new(action::RUN_FAILED, resource::RUN)
+            .actor(actor::SYSTEM, None)
+            .resource_id(&run_id)
+            .run(&run_id)
+            .project(&run.project_id)
+            .details(serde_json::json!({
+                "step_id": step_id,
+                "error": updated_step.error,
+            }))
+            .build();
+        repos.spawn_audit(audit_event);
+
+        warn!(run_id = %run_id, step_id = %step_id, "Run failed due to step failure");
+    } else if status == StepStatus::WaitingApproval {
+        let waiting = repos
+            .runs()
+            .update_status(&run_id, RunStatus::WaitingApproval, None)
+            .await?;
+        if settled_transition(waiting, &run_id, RunStatus::WaitingApproval).is_some() {
+            info!(run_id = %run_id, step_id = %step_id, "Run waiting for approval");
+        }
+    }
+
+    Ok(Json(step_to_response(updated_step)))
+}
+
+/// Submit a batch of step results (from worker)
+///
+/// All valid items are applied in a single transaction together with the
+/// aggregate usage increment. Items that cannot be applied (unknown step,
+/// step from another run) are reported by index without failing the batch.
+/// Budget and run status are evaluated once, after the whole batch.
+///
+/// Signed like a single result when step result signing is enabled.
+#[instrument(skip(state, auth, headers, body), fields(run_id = %run_id, batch_size))]
+pub async fn submit_step_results_batch(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(run_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse, ApiError> {
+    check_step_result_signature(
+        &state,
+        &headers,
+        &run_id,
+        &step_result_signed_path(&run_id, None),
+        &body,
+    )?;
+    let request: SubmitStepResultsBatchRequest = parse_validated_json(&body)?;
+    tracing::Span::current().record("batch_size", request.results.len());
+    let repos = state.repos();
+
+    let run = repos
+        .runs()
+        .get(&run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Run", &run_id))?;
+
+    // Results are only accepted while the run is still active
+    ensure_run_transition(run.status, RunStatus::Running)?;
+    let run = ensure_run_started(repos, run).await?;
+
+    let mut entries: Vec<BatchStepResultEntry> = Vec::with_capacity(request.results.len());
+    // (index into entries, status)
+    let mut applied: Vec<(usize, StepStatus)> = Vec::new();
+    let mut updates = Vec::new();
+    let (mut total_input, mut total_output, mut total_tool_calls, mut total_cost) =
+        (0i32, 0i32, 0i32, 0u64);
+
+    for (index, item) in request.results.into_iter().enumerate() {
+        let rejection = match repos.steps().get(&item.step_id).await? {
+            None => Err(format!("Step with id '{}' not found", item.step_id)),
+            Some(step) if step.run_id != run_id => {
+                Err("Step does not belong to this run".to_string())
+            }
+            Some(step) => parse_step_result_status(&item.status)
+                .and_then(|status| {
+                    ensure_step_transition(step.status, status)?;
+                    Ok((step, status))
+                })
+                .map_err(|e| e.message),
+        };
+
+        let (step, status) = match rejection {
+            Ok(found) => found,
+            Err(message) => {
+                entries.push(BatchStepResultEntry {
+                    index,
+                    step_id: item.step_id,
+                    success: false,
+                    step: None,
+                    error: Some(message),
+                });
+                continue;
+            }
+        };
+
+        let checked = match check_output_content_type(
+            &state,
+            &run,
+            &auth.tenant_id,
+            &step,
+            item.content_type.as_deref(),
+        )
+        .await
+        {
+            Ok(()) => spend_approval_token(&state, &step, item.approval_token.as_deref()).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = checked {
+            entries.push(BatchStepResultEntry {
+                index,
+                step_id: item.step_id,
+                success: false,
+                step: None,
+                error: Some(e.message),
+            });
+            continue;
+        }
+
+        let (in_tokens, out_tokens, cost) = match (item.input_tokens, item.output_tokens) {
+            (Some(in_tokens), Some(out_tokens)) => {
+                let model = step.model.as_deref().unwrap_or("gpt-4o");
+                let cost =
+                    pricing::calculate_cost_cents(model, in_tokens as u64, out_tokens as u64);
+                (in_tokens, out_tokens, cost)
+            }
+            _ => (0, 0, 0),
+        };
+        total_input += in_tokens;
+        total_output += out_tokens;
+        total_tool_calls += tool_call_delta(&step, status);
+        total_cost += cost;
+
+        updates.push((
+            item.step_id.clone(),
+            UpdateStep {
+                status: Some(status),
+                output: item
+                    .output
+                    .map(|output| truncate_step_output(output, state.max_output_bytes)),
+                error: item.error,
+                input_tokens: item.input_tokens,
+                output_tokens: item.output_tokens,
+                completed_at: Some(Utc::now()),
+                ..Default::default()
+            },
+            // Audit: Step completed/failed, committed with the update
+            step_result_audit(&run, &step, status, (in_tokens, out_tokens, cost), true),
+        ));
+        applied.push((entries.len(), status));
+        entries.push(BatchStepResultEntry {
+            index,
+            step_id: item.step_id,
+            success: true,
+            step: None,
+            error: None,
+        });
+    }
+
+    let updated_steps = repos
+        .steps()
+        .update_batch(
+            &run_id,
+            &updates,
+            total_input,
+            total_output,
+            total_tool_calls,
+            total_cost as i32,
+        )
+        .await?;
+
+    let mut applied_statuses = Vec::new();
+    let mut first_failed_step: Option<fd_storage::models::Step> = None;
+    let mut last_completed_step: Option<fd_storage::models::Step> = None;
+
+    for ((entry_idx, status), updated) in applied.into_iter().zip(updated_steps) {
+        let entry = &mut entries[entry_idx];
+        let Some(updated) = updated else {
+            entry.success = false;
+            entry.error = Some("Step could not be updated".to_string());
+            continue;
+        };
 
-        // Return the step result, but the run is now killed
-        return Ok(Json(step_to_response(updated_step)));
+        applied_statuses.push(status);
+        if status == StepStatus::Failed {
+            dead_letter_step(&state, &updated, &auth.tenant_id, &run).await;
+        }
+
+        match status {
+            StepStatus::Failed if first_failed_step.is_none() => {
+                first_failed_step = Some(updated.clone())
+            }
+            StepStatus::Completed => last_completed_step = Some(updated.clone()),
+            _ => {}
+        }
+
+        entry.step = Some(step_to_response(updated));
     }
 
-    // Check if run is complete
-    let pending_steps = repos.steps().get_pending_steps(&run_id).await?;
+    let first_failed_index = entries.iter().find(|e| !e.success).map(|e| e.index);
 
-    if pending_steps.is_empty() && status == StepStatus::Completed {
-        repos
-            .runs()
-            .update(
-                &run_id,
-                UpdateRun {
-                    status: Some(RunStatus::Completed),
-                    completed_at: Some(Utc::now()),
-                    output: updated_step.output.clone(),
-                    ..Default::default()
-                },
-            )
-            .await?;
+    // Check budget once, after the aggregate increment
+    let updated_run = repos
+        .runs()
+        .get(&run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Run", &run_id))?;
 
-        // Audit: Run completed
-        let audit_event = AuditEventBuilder::new(action::RUN_COMPLETED, resource::RUN)
-            .actor(actor::SYSTEM, None)
-            .resource_id(&run_id)
-            .run(&run_id)
-            .project(&run.project_id)
-            .details(serde_json::json!({
-                "input_tokens": updated_run.input_tokens,
-                "output_tokens": updated_run.output_tokens,
-                "tool_calls": updated_run.tool_calls,
-                "cost_cents": updated_run.cost_cents,
-            }))
-            .build();
-        repos.spawn_audit(audit_event);
+    let wall_time_ms = budget_wall_time_ms(&updated_run, Utc::now());
 
-        info!(run_id = %run_id, "Run completed successfully");
-    } else if status == StepStatus::Failed {
-        repos
-            .runs()
-            .update(
-                &run_id,
-                UpdateRun {
-                    status: Some(RunStatus::Failed),
-                    status_reason: Some("Step failed".to_string()),
-                    completed_at: Some(Utc::now()),
-                    error: updated_step.error.clone(),
-                    ..Default::default()
-                },
-            )
-            .await?;
+    let usage = BudgetUsage {
+        input_tokens: updated_run.input_tokens as u64,
+        output_tokens: updated_run.output_tokens as u64,
+        tool_calls: updated_run.tool_calls as u32,
+        wall_time_ms,
+        cost_cents: updated_run.cost_cents as u64,
+    };
 
-        // Audit: Run failed
-        let audit_event = AuditEventBuilder::new(action::RUN_FAILED, resource::RUN)
+    let budget = run_budget(&updated_run);
+    let exceeded = state.policy_engine.budget_exceeded(&usage, budget.as_ref());
+
+    let mut run_status = updated_run.status;
+
+    if let Some(exceeded) = exceeded {
+        let kill = budget_kill_update(&exceeded);
+        warn!(
+            run_id = %run_id,
+            reason = ?kill.status_reason,
+            "Budget exceeded after batch, killing run"
+        );
+
+        let audit_event = AuditEventBuilder::new("budget.exceeded", resource::RUN)
             .actor(actor::SYSTEM, None)
             .resource_id(&run_id)
             .run(&run_id)
             .project(&run.project_id)
             .details(serde_json::json!({
-                "step_id": step_id,
-                "error": updated_step.error,
+                "reason": kill.status_reason,
+                "detail": kill.budget_kill_detail,
+                "usage": usage,
             }))
             .build();
-        repos.spawn_audit(audit_event);
-
-        warn!(run_id = %run_id, step_id = %step_id, "Run failed due to step failure");
-    } else if status == StepStatus::WaitingApproval {
-        repos
-            .runs()
-            .update_status(&run_id, RunStatus::WaitingApproval, None)
-            .await?;
 
-        info!(run_id = %run_id, step_id = %step_id, "Run waiting for approval");
+        let killed = repos.runs().update(&run_id, kill).await?;
+        if let Some(killed) = settled_transition(killed, &run_id, RunStatus::BudgetKilled) {
+            repos.spawn_audit(audit_event);
+            spawn_run_summary(repos, &killed);
+            cancel_outstanding_steps(repos, &run_id).await?;
+            run_status = RunStatus::BudgetKilled;
+        }
+    } else {
+        match batch_decisive_status(&applied_statuses) {
+            Some(StepStatus::Failed) => {
+                let failed = first_failed_step.as_ref();
+                let failed_run = repos
+                    .runs()
+                    .update(
+                        &run_id,
+                        UpdateRun {
+                            status: Some(RunStatus::Failed),
+                            status_reason: Some("Step failed".to_string()),
+                            completed_at: Some(Utc::now()),
+                            error: failed.and_then(|s| s.error.clone()),
+                            ..Default::default()
+                        },
+                    )
+                    .await?;
+                if let Some(failed_run) = settled_transition(failed_run, &run_id, RunStatus::Failed)
+                {
+                    spawn_run_summary(repos, &failed_run);
+
+                    // Audit: Run failed
+                    let audit_event = AuditEventBuilder::new(action::RUN_FAILED, resource::RUN)
+                        .actor(actor::SYSTEM, None)
+                        .resource_id(&run_id)
+                        .run(&run_id)
+                        .project(&run.project_id)
+                        .details(serde_json::json!({
+                            "step_id": failed.map(|s| s.id.clone()),
+                            "error": failed.and_then(|s| s.error.clone()),
+                        }))
+                        .build();
+                    repos.spawn_audit(audit_event);
+
+                    run_status = RunStatus::Failed;
+                    warn!(run_id = %run_id, "Run failed due to step failure in batch");
+                }
+            }
+            Some(StepStatus::WaitingApproval) => {
+                let waiting = repos
+                    .runs()
+                    .update_status(&run_id, RunStatus::WaitingApproval, None)
+                    .await?;
+                if settled_transition(waiting, &run_id, RunStatus::WaitingApproval).is_some() {
+                    run_status = RunStatus::WaitingApproval;
+                    info!(run_id = %run_id, "Run waiting for approval");
+                }
+            }
+            Some(StepStatus::Completed) => {
+                let pending_steps = repos.steps().get_pending_steps(&run_id).await?;
+                if pending_steps.is_empty() {
+                    let completed = repos
+                        .runs()
+                        .update(
+                            &run_id,
+                            UpdateRun {
+                                status: Some(RunStatus::Completed),
+                                completed_at: Some(Utc::now()),
+                                output: last_completed_step.and_then(|s| s.output),
+                                ..Default::default()
+                            },
+                        )
+                        .await?;
+                    if let Some(completed) =
+                        settled_transition(completed, &run_id, RunStatus::Completed)
+                    {
+                        spawn_run_summary(repos, &completed);
+
+                        // Audit: Run completed
+                        let audit_event =
+                            AuditEventBuilder::new(action::RUN_COMPLETED, resource::RUN)
+                                .actor(actor::SYSTEM, None)
+                                .resource_id(&run_id)
+                                .run(&run_id)
+                                .project(&run.project_id)
+                                .details(serde_json::json!({
+                                    "input_tokens": updated_run.input_tokens,
+                                    "output_tokens": updated_run.output_tokens,
+                                    "tool_calls": updated_run.tool_calls,
+                                    "cost_cents": updated_run.cost_cents,
+                                }))
+                                .build();
+                        repos.spawn_audit(audit_event);
+
+                        run_status = RunStatus::Completed;
+                        info!(run_id = %run_id, "Run completed successfully");
+                    }
+                }
+            }
+            _ => {}
+        }
     }
 
-    Ok(Json(step_to_response(updated_step)))
+    Ok(Json(SubmitStepResultsBatchResponse {
+        run_id,
+        run_status: format!("{:?}", run_status).to_lowercase(),
+        first_failed_index,
+        results: entries,
+    }))
 }
 
 // =============================================================================
@@ -797,6 +2495,10 @@ pub struct CheckToolRequest {
     /// Estimated cost in cents for this tool call (for velocity tracking)
     #[serde(default)]
     pub estimated_cost_cents: Option<u64>,
+
+    /// Justification for overriding a policy deny (requires `policy:break_glass`)
+    #[serde(default)]
+    pub break_glass_reason: Option<String>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -829,6 +2531,227 @@ pub struct CheckToolResponse {
     /// Whether Airlock is in shadow mode (log-only)
     #[serde(default)]
     pub shadow_mode: bool,
+    /// Whether a policy deny was overridden via break-glass
+    #[serde(default)]
+    pub break_glass: bool,
+}
+
+/// Deny reason once a run has too many approvals waiting on reviewers
+pub(crate) const APPROVAL_BACKLOG_EXCEEDED: &str = "approval backlog exceeded";
+
+/// Deny an approval-gated call when the run's approval backlog is full
+///
+/// `pending` is the run's count of pending approvals; with no `cap`, or
+/// with room left under it, the decision is returned unchanged.
+pub(crate) fn cap_pending_approvals(
+    decision: fd_policy::PolicyDecision,
+    pending: i64,
+    cap: Option<i64>,
+) -> fd_policy::PolicyDecision {
+    match cap {
+        Some(cap) if decision.needs_approval() && pending >= cap => fd_policy::PolicyDecision {
+            kind: fd_policy::PolicyDecisionKind::Deny,
+            reason: APPROVAL_BACKLOG_EXCEEDED.to_string(),
+            ..decision
+        },
+        _ => decision,
+    }
+}
+
+/// Scope allowing a policy deny to be overridden, with a stated reason
+pub(crate) const BREAK_GLASS_SCOPE: &str = "policy:break_glass";
+
+/// Whether a check-tool request overrides a policy deny via break-glass
+///
+/// Only a hard policy deny is overridden; global kill-switch denies and
+/// Airlock blocks still apply. Requesting break-glass without the scope, or
+/// without a reason, is rejected rather than ignored.
+pub(crate) fn break_glass_override(
+    auth: &AuthContext,
+    reason: Option<&str>,
+    decision: &fd_policy::PolicyDecision,
+) -> Result<bool, ApiError> {
+    let Some(reason) = reason else {
+        return Ok(false);
+    };
+    if !auth.has_scope(BREAK_GLASS_SCOPE) {
+        return Err(ApiError::forbidden(format!(
+            "Break-glass requires the '{}' scope",
+            BREAK_GLASS_SCOPE
+        )));
+    }
+    if reason.trim().is_empty() {
+        return Err(ApiError::bad_request(
+            "break_glass_reason must not be empty",
+        ));
+    }
+    Ok(decision.is_denied() && !decision.is_kill_switch_deny())
+}
+
+/// High-severity audit event for a break-glass override
+pub(crate) fn break_glass_audit(
+    run: &fd_storage::models::Run,
+    auth: &AuthContext,
+    tool_name: &str,
+    reason: &str,
+    decision: &fd_policy::PolicyDecision,
+    risk_score: u8,
+) -> CreateAuditEvent {
+    AuditEventBuilder::new(action::POLICY_BREAK_GLASS, resource::RUN)
+        .actor(actor::API_KEY, Some(auth.api_key_id.clone()))
+        .resource_id(&run.id)
+        .run(&run.id)
+        .project(&run.project_id)
+        .tenant(&auth.tenant_id)
+        .severity("alert", risk_score)
+        .details(with_run_metadata(
+            serde_json::json!({
+                "tool_name": tool_name,
+                "break_glass_reason": reason,
+                "overridden_decision_id": decision.id.to_string(),
+                "overridden_reason": decision.reason,
+            }),
+            &run.metadata,
+        ))
+        .build()
+}
+
+/// Audit event for an Airlock finding, carrying its severity and risk score
+pub(crate) fn airlock_violation_audit(
+    run: &fd_storage::models::Run,
+    tenant_id: &str,
+    tool_name: &str,
+    result: &fd_policy::AirlockResult,
+    violation: &fd_policy::AirlockViolation,
+) -> CreateAuditEvent {
+    AuditEventBuilder::new(action::AIRLOCK_VIOLATION_DETECTED, resource::RUN)
+        .actor(actor::SYSTEM, None)
+        .resource_id(&run.id)
+        .run(&run.id)
+        .project(&run.project_id)
+        .tenant(tenant_id)
+        .severity(violation.risk_level.audit_severity(), violation.risk_score)
+        .details(with_run_metadata(
+            serde_json::json!({
+                "tool_name": tool_name,
+                "violation_type": format!("{:?}", violation.violation_type),
+                "risk_score": violation.risk_score,
+                "risk_level": violation.risk_level.as_str(),
+                "trigger": violation.trigger,
+                "shadow_mode": result.shadow_mode,
+                "blocked": !result.allowed,
+            }),
+            &run.metadata,
+        ))
+        .build()
+}
+
+/// Check a tool step's declared output content type against the tenant's Airlock config
+///
+/// Violations are audited; in enforce mode the result is rejected.
+async fn check_output_content_type(
+    state: &AppState,
+    run: &fd_storage::models::Run,
+    tenant_id: &str,
+    step: &fd_storage::models::Step,
+    content_type: Option<&str>,
+) -> Result<(), ApiError> {
+    let Some(tool_name) = step.tool_name.as_deref() else {
+        return Ok(());
+    };
+
+    let airlock = run_airlock(&state.airlock_for_tenant(tenant_id).await?, run);
+    let result = airlock.inspect_output(&run.id, tool_name, content_type);
+
+    let Some(violation) = &result.violation else {
+        return Ok(());
+    };
+    state.repos().spawn_audit(airlock_violation_audit(
+        run, tenant_id, tool_name, &result, violation,
+    ));
+
+    if result.allowed {
+        Ok(())
+    } else {
+        Err(ApiError::policy_blocked(violation.details.clone()))
+    }
+}
+
+/// Look up a registered tool by ID, visible to the run's project
+async fn registered_tool(
+    repos: &Repos,
+    project_id: &str,
+    id: &str,
+) -> Result<fd_storage::models::Tool, ApiError> {
+    repos
+        .tools()
+        .get(id)
+        .await?
+        .filter(|tool| {
+            tool.project_id
+                .as_deref()
+                .map_or(true, |owner| owner == project_id)
+        })
+        .ok_or_else(|| ApiError::not_found("Tool", id))
+}
+
+/// Resolve a tool reference to the name policies are written against
+///
+/// Tool IDs (`tol_...`) are looked up in the registry and must belong to the
+/// run's project or be global; anything else is taken as the tool slug.
+async fn resolve_tool_name(
+    repos: &Repos,
+    project_id: &str,
+    tool_ref: &str,
+) -> Result<String, ApiError> {
+    match EntityRef::parse(tool_ref, fd_core::ToolId::PREFIX) {
+        EntityRef::Id(id) => Ok(registered_tool(repos, project_id, id).await?.slug),
+        EntityRef::Slug(slug) => Ok(slug.to_string()),
+    }
+}
+
+/// Resolve a tool reference to its policy name and registry entry
+///
+/// Like [`resolve_tool_name`], but slugs are also looked up so the caller
+/// can use the registered tool's settings. Unregistered slugs resolve to
+/// no entry.
+async fn resolve_tool(
+    repos: &Repos,
+    project_id: &str,
+    tool_ref: &str,
+) -> Result<(String, Option<fd_storage::models::Tool>), ApiError> {
+    match EntityRef::parse(tool_ref, fd_core::ToolId::PREFIX) {
+        EntityRef::Id(id) => {
+            let tool = registered_tool(repos, project_id, id).await?;
+            Ok((tool.slug.clone(), Some(tool)))
+        }
+        EntityRef::Slug(slug) => Ok((
+            slug.to_string(),
+            repos.tools().get_by_slug(project_id, slug).await?,
+        )),
+    }
+}
+
+/// Airlock context for a tool call
+///
+/// The worker's cost estimate wins; otherwise the registered tool's
+/// estimate is used, if it has one.
+pub(crate) fn tool_inspection_context(
+    run_id: fd_core::RunId,
+    tool_name: String,
+    tool_input: serde_json::Value,
+    requested_cost_cents: Option<u64>,
+    tool: Option<&fd_storage::models::Tool>,
+) -> fd_policy::InspectionContext {
+    let registered_cost_cents = tool
+        .and_then(|tool| tool.estimated_cost_cents)
+        .and_then(|cents| u64::try_from(cents).ok());
+    fd_policy::InspectionContext {
+        run_id,
+        tool_name,
+        tool_input,
+        estimated_cost_cents: requested_cost_cents.or(registered_cost_cents),
+    }
 }
 
 /// Check if a tool call is allowed by policy and Airlock security inspection
@@ -841,7 +2764,6 @@ pub async fn check_tool_policy(
     ValidatedJson(request): ValidatedJson<CheckToolRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
     use fd_core::RunId;
-    use fd_policy::InspectionContext;
     use fd_storage::models::{CreateThreat, CreateVelocityEvent};
     use sha2::{Digest, Sha256};
 
@@ -852,21 +2774,37 @@ pub async fn check_tool_policy(
         .get(&run_id)
         .await?
         .ok_or_else(|| ApiError::not_found("Run", &run_id))?;
-
-    // Step 1: Check tool against policy allowlist
-    let decision = state.policy_engine.evaluate_tool_call(&request.tool_name);
+    let (tool_name, tool) = resolve_tool(repos, &run.project_id, &request.tool_name).await?;
+
+    // Step 1: Check tool against the kill switch and policy allowlist
+    state.refresh_kill_switch().await;
+    let mut decision = state.policy_engine.evaluate_tool_call(&tool_name);
+    if decision.needs_approval() && state.max_pending_approvals.is_some() {
+        let pending = repos.policies().count_pending_approvals(&run_id).await?;
+        decision = cap_pending_approvals(decision, pending, state.max_pending_approvals);
+        if decision.is_denied() {
+            warn!(run_id = %run_id, pending, "Approval backlog exceeded, denying tool call");
+        }
+    }
+    let break_glass =
+        break_glass_override(&auth, request.break_glass_reason.as_deref(), &decision)?;
 
     // Step 2: Run Airlock inspection on the tool input payload
     let tool_input = request.tool_input.clone().unwrap_or(serde_json::json!({}));
     let parsed_run_id = RunId::parse(&run_id).unwrap_or_else(|_| RunId::new());
-    let inspection_ctx = InspectionContext {
-        run_id: parsed_run_id,
-        tool_name: request.tool_name.clone(),
-        tool_input: tool_input.clone(),
-        estimated_cost_cents: request.estimated_cost_cents,
-    };
-
-    let airlock_result = state.airlock.inspect(&inspection_ctx).await;
+    let inspection_ctx = tool_inspection_context(
+        parsed_run_id,
+        tool_name.clone(),
+        tool_input.clone(),
+        request.estimated_cost_cents,
+        tool.as_ref(),
+    );
+
+    // Inspect with the tenant's airlock config, honoring the run's mode
+    // override (validated at creation time). Calls that proceed are recorded
+    // for velocity tracking.
+    let airlock = run_airlock(&state.airlock_for_tenant(&auth.tenant_id).await?, &run);
+    let airlock_result = airlock.inspect_and_record(&inspection_ctx).await;
 
     // Step 3: Persist threat if detected
     if let Some(ref violation) = airlock_result.violation {
@@ -876,7 +2814,7 @@ pub async fn check_tool_policy(
             id: threat_id.clone(),
             run_id: run_id.clone(),
             step_id: None, // We don't have step_id at this point
-            tool_name: request.tool_name.clone(),
+            tool_name: tool_name.clone(),
             risk_score: violation.risk_score as i32,
             risk_level: violation.risk_level.as_str().to_string(),
             violation_type: format!("{:?}", violation.violation_type).to_lowercase(),
@@ -902,27 +2840,18 @@ pub async fn check_tool_policy(
         });
 
         // Audit the Airlock violation
-        let audit_event = AuditEventBuilder::new("airlock.violation_detected", resource::RUN)
-            .actor(actor::SYSTEM, None)
-            .resource_id(&run_id)
-            .run(&run_id)
-            .project(&run.project_id)
-            .tenant(auth.tenant_id.clone())
-            .details(serde_json::json!({
-                "tool_name": request.tool_name,
-                "violation_type": format!("{:?}", violation.violation_type),
-                "risk_score": violation.risk_score,
-                "risk_level": violation.risk_level.as_str(),
-                "trigger": violation.trigger,
-                "shadow_mode": airlock_result.shadow_mode,
-                "blocked": !airlock_result.allowed,
-            }))
-            .build();
+        let audit_event = airlock_violation_audit(
+            &run,
+            &auth.tenant_id,
+            &tool_name,
+            &airlock_result,
+            violation,
+        );
         repos.spawn_audit(audit_event);
 
         warn!(
             run_id = %run_id,
-            tool_name = %request.tool_name,
+            tool_name = %tool_name,
             violation_type = ?violation.violation_type,
             risk_score = violation.risk_score,
             shadow_mode = airlock_result.shadow_mode,
@@ -931,8 +2860,12 @@ pub async fn check_tool_policy(
     }
 
     // Step 4: Record velocity event for successful calls
-    if airlock_result.allowed && airlock_result.violation.is_none() {
-        if let Some(cost) = request.estimated_cost_cents {
+    let advisory_only = airlock_result
+        .violation
+        .as_ref()
+        .map_or(true, |v| v.violation_type.is_advisory());
+    if airlock_result.allowed && advisory_only {
+        if let Some(cost) = inspection_ctx.estimated_cost_cents {
             // Use SHA256 for input hashing
             let mut hasher = Sha256::new();
             hasher.update(tool_input.to_string().as_bytes());
@@ -940,7 +2873,7 @@ pub async fn check_tool_policy(
 
             let velocity_event = CreateVelocityEvent {
                 run_id: run_id.clone(),
-                tool_name: request.tool_name.clone(),
+                tool_name: tool_name.clone(),
                 tool_input_hash: input_hash,
                 cost_cents: cost as i32,
             };
@@ -969,18 +2902,18 @@ pub async fn check_tool_policy(
         .run(&run_id)
         .project(&run.project_id)
         .details(serde_json::json!({
-            "tool_name": request.tool_name,
+            "tool_name": tool_name,
             "decision": format!("{:?}", decision.kind),
             "reason": decision.reason,
             "airlock_risk_score": airlock_result.risk_score,
             "airlock_blocked": !airlock_result.allowed,
         }))
         .build();
-    repos.spawn_audit(audit_event);
 
     // Step 6: Determine final allowed status
-    // Tool is allowed if: policy allows AND (airlock allows OR airlock is in shadow mode)
-    let policy_allowed = decision.is_allowed();
+    // Tool is allowed if: policy allows (or is overridden via break-glass)
+    // AND (airlock allows OR airlock is in shadow mode)
+    let policy_allowed = decision.is_allowed() || break_glass;
     let airlock_blocked = !airlock_result.allowed;
     let final_allowed = policy_allowed && !airlock_blocked;
 
@@ -999,14 +2932,15 @@ pub async fn check_tool_policy(
 
         warn!(
             run_id = %run_id,
-            tool_name = %request.tool_name,
+            tool_name = %tool_name,
             reason = %reason,
             "Tool call blocked"
         );
 
-        repos
+        // The decision's audit event commits together with the block
+        let blocked = repos
             .runs()
-            .update(
+            .update_with_audit(
                 &run_id,
                 UpdateRun {
                     status: Some(status),
@@ -1014,6 +2948,46 @@ pub async fn check_tool_policy(
                     completed_at: Some(Utc::now()),
                     ..Default::default()
                 },
+                &audit_event,
+            )
+            .await?;
+        let blocked = applied_transition(blocked, &run_id, status)?;
+        cancel_outstanding_steps(repos, &run_id).await?;
+        spawn_run_summary(repos, &blocked);
+    } else {
+        repos.spawn_audit(audit_event);
+    }
+
+    if final_allowed && break_glass {
+        let break_glass_reason = request.break_glass_reason.as_deref().unwrap_or_default();
+        warn!(
+            run_id = %run_id,
+            tool_name = %tool_name,
+            reason = %break_glass_reason,
+            "Policy deny overridden via break-glass"
+        );
+
+        // Leave a note on the run without changing its status; the override
+        // is only audited once it has actually let the call through
+        repos
+            .runs()
+            .update_with_audit(
+                &run_id,
+                UpdateRun {
+                    status_reason: Some(format!(
+                        "break-glass override for '{}': {}",
+                        tool_name, break_glass_reason
+                    )),
+                    ..Default::default()
+                },
+                &break_glass_audit(
+                    &run,
+                    &auth,
+                    &tool_name,
+                    break_glass_reason,
+                    &decision,
+                    airlock_result.risk_score,
+                ),
             )
             .await?;
     }
@@ -1045,5 +3019,170 @@ pub async fn check_tool_policy(
         violation_details,
         blocked_by_airlock: airlock_blocked,
         shadow_mode: airlock_result.shadow_mode,
+        break_glass: break_glass && final_allowed,
+    }))
+}
+
+fn default_block_run_on_deny() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CheckToolsRequest {
+    /// Tool names the step plans to call
+    #[validate(length(min = 1, max = 100, message = "tools must contain 1-100 tool names"))]
+    pub tools: Vec<String>,
+
+    /// Whether a hard deny on any tool blocks the run (default: true)
+    #[serde(default = "default_block_run_on_deny")]
+    pub block_run_on_deny: bool,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ToolCheckDecision {
+    /// Tool name that was checked
+    pub tool_name: String,
+    /// Whether the tool call is allowed
+    pub allowed: bool,
+    /// Whether approval is required before execution
+    pub requires_approval: bool,
+    /// Whether the tool is hard-denied by policy
+    pub denied: bool,
+    /// Unique decision ID for audit trail
+    pub decision_id: String,
+    /// Human-readable reason for the decision
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CheckToolsResponse {
+    /// Per-tool decisions, in request order
+    pub decisions: Vec<ToolCheckDecision>,
+    /// Whether the run was blocked because of a hard deny
+    pub run_blocked: bool,
+}
+
+/// Evaluate each tool name against the policy allowlist
+pub(crate) fn evaluate_tools(
+    engine: &fd_policy::PolicyEngine,
+    tools: &[String],
+) -> Vec<(ToolCheckDecision, fd_policy::PolicyDecision)> {
+    tools
+        .iter()
+        .map(|tool_name| {
+            let decision = engine.evaluate_tool_call(tool_name);
+            let summary = ToolCheckDecision {
+                tool_name: tool_name.clone(),
+                allowed: decision.is_allowed(),
+                requires_approval: decision.needs_approval(),
+                denied: decision.is_denied(),
+                decision_id: decision.id.to_string(),
+                reason: decision.reason.clone(),
+            };
+            (summary, decision)
+        })
+        .collect()
+}
+
+/// Reason to block the run for a bulk tool check, if any
+///
+/// Only a hard deny blocks the run; tools needing approval do not.
+pub(crate) fn bulk_check_block_reason(
+    decisions: &[ToolCheckDecision],
+    block_run_on_deny: bool,
+) -> Option<String> {
+    if !block_run_on_deny {
+        return None;
+    }
+    decisions
+        .iter()
+        .find(|d| d.denied)
+        .map(|d| d.reason.clone())
+}
+
+/// Check several tools against policy in one round-trip
+///
+/// Workers planning a multi-tool step call this instead of `check-tool` per
+/// tool. Only a hard deny blocks the run (unless `block_run_on_deny` is false).
+#[instrument(skip(state, auth, request), fields(run_id = %run_id))]
+pub async fn check_tools_policy(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(run_id): Path<String>,
+    ValidatedJson(request): ValidatedJson<CheckToolsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if request.tools.iter().any(|t| t.is_empty() || t.len() > 255) {
+        return Err(ApiError::bad_request(
+            "each tool name must be 1-255 characters",
+        ));
+    }
+
+    let repos = state.repos();
+
+    let run = repos
+        .runs()
+        .get(&run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Run", &run_id))?;
+
+    let mut tool_names = Vec::with_capacity(request.tools.len());
+    for tool_ref in &request.tools {
+        tool_names.push(resolve_tool_name(repos, &run.project_id, tool_ref).await?);
+    }
+    state.refresh_kill_switch().await;
+    let evaluated = evaluate_tools(&state.policy_engine, &tool_names);
+
+    for (summary, decision) in &evaluated {
+        let audit_action = if decision.is_allowed() {
+            action::POLICY_ALLOWED
+        } else if decision.needs_approval() {
+            action::POLICY_APPROVAL_REQUIRED
+        } else {
+            action::POLICY_DENIED
+        };
+
+        let audit_event = AuditEventBuilder::new(audit_action, resource::RUN)
+            .actor(actor::SYSTEM, None)
+            .resource_id(&run_id)
+            .run(&run_id)
+            .project(&run.project_id)
+            .tenant(auth.tenant_id.clone())
+            .details(serde_json::json!({
+                "tool_name": summary.tool_name,
+                "decision": format!("{:?}", decision.kind),
+                "reason": decision.reason,
+                "bulk": true,
+            }))
+            .build();
+        repos.spawn_audit(audit_event);
+    }
+
+    let decisions: Vec<ToolCheckDecision> = evaluated.into_iter().map(|(d, _)| d).collect();
+    let block_reason = bulk_check_block_reason(&decisions, request.block_run_on_deny);
+
+    if let Some(reason) = &block_reason {
+        warn!(run_id = %run_id, reason = %reason, "Bulk tool check blocked run");
+
+        let blocked = repos
+            .runs()
+            .update(
+                &run_id,
+                UpdateRun {
+                    status: Some(RunStatus::PolicyBlocked),
+                    status_reason: Some(reason.clone()),
+                    completed_at: Some(Utc::now()),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        cancel_outstanding_steps(repos, &run_id).await?;
+        if let Some(blocked) = &blocked {
+            spawn_run_summary(repos, blocked);
+        }
+    }
+
+    Ok(Json(CheckToolsResponse {
+        decisions,
+        run_blocked: block_reason.is_some(),
     }))
 }
@@ -1,12 +1,13 @@
 //! Security and Airlock handlers
 
-use axum::{extract::State, Json};
-use fd_policy::AirlockMode;
+use axum::{extract::State, Extension, Json};
+use fd_policy::{AirlockConfig, AirlockMode};
 use fd_storage::models::threats::Threat;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 use crate::handlers::{ApiError, ValidatedQuery};
+use crate::middleware::AuthContext;
 use crate::state::AppState;
 
 // =============================================================================
@@ -52,6 +53,7 @@ pub struct ListThreatsResponse {
 pub struct AirlockConfigResponse {
     pub mode: String,
     pub rce_detection_enabled: bool,
+    pub rce_target_tools: Vec<String>,
     pub velocity_tracking_enabled: bool,
     pub exfiltration_shield_enabled: bool,
     pub max_cost_cents_per_window: u64,
@@ -66,6 +68,10 @@ pub struct AirlockConfigResponse {
 pub struct UpdateAirlockConfigRequest {
     /// Mode: "shadow" or "enforce"
     pub mode: Option<String>,
+    /// Tools subject to RCE pattern detection
+    pub rce_target_tools: Option<Vec<String>>,
+    /// Domains network tools may reach
+    pub allowed_domains: Option<Vec<String>>,
 }
 
 // =============================================================================
@@ -130,19 +136,80 @@ pub async fn get_threat(
     Ok(Json(threat))
 }
 
-/// Get Airlock configuration
+/// Get the caller's tenant Airlock configuration
 ///
 /// GET /v1/security/config
 #[axum::debug_handler]
-pub async fn get_config(State(state): State<AppState>) -> Json<AirlockConfigResponse> {
-    let config = state.airlock.config();
+pub async fn get_config(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<AirlockConfigResponse>, ApiError> {
+    let airlock = state.airlock_for_tenant(&auth.tenant_id).await?;
+
+    Ok(Json(config_to_response(airlock.config())))
+}
+
+/// Update the caller's tenant Airlock configuration
+///
+/// PUT /v1/security/config
+///
+/// Unset fields keep the tenant's current value. The stored config takes
+/// effect on the next inspection for the tenant.
+#[axum::debug_handler]
+pub async fn update_config(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Json(request): Json<UpdateAirlockConfigRequest>,
+) -> Result<Json<AirlockConfigResponse>, ApiError> {
+    let current = state.airlock_for_tenant(&auth.tenant_id).await?;
+    let config = apply_config_update(current.config().clone(), request)?;
+
+    let stored = serde_json::to_value(&config)
+        .map_err(|e| ApiError::internal(format!("Failed to serialize airlock config: {}", e)))?;
+    state
+        .repos()
+        .threats()
+        .upsert_tenant_airlock_config(&auth.tenant_id, &stored)
+        .await?;
+    state.tenant_airlocks.invalidate(&auth.tenant_id).await;
 
-    Json(AirlockConfigResponse {
+    Ok(Json(config_to_response(&config)))
+}
+
+/// Apply a partial update to a tenant's Airlock configuration
+pub(crate) fn apply_config_update(
+    mut config: AirlockConfig,
+    request: UpdateAirlockConfigRequest,
+) -> Result<AirlockConfig, ApiError> {
+    if let Some(mode) = &request.mode {
+        config.mode = match mode.as_str() {
+            "shadow" => AirlockMode::Shadow,
+            "enforce" => AirlockMode::Enforce,
+            _ => {
+                return Err(ApiError::bad_request(
+                    "Invalid mode. Must be 'shadow' or 'enforce'",
+                ))
+            }
+        };
+    }
+    if let Some(target_tools) = request.rce_target_tools {
+        config.rce.target_tools = target_tools;
+    }
+    if let Some(allowed_domains) = request.allowed_domains {
+        config.exfiltration.allowed_domains = allowed_domains;
+    }
+
+    Ok(config)
+}
+
+fn config_to_response(config: &AirlockConfig) -> AirlockConfigResponse {
+    AirlockConfigResponse {
         mode: match config.mode {
             AirlockMode::Shadow => "shadow".to_string(),
             AirlockMode::Enforce => "enforce".to_string(),
         },
         rce_detection_enabled: config.rce.enabled,
+        rce_target_tools: config.rce.target_tools.clone(),
         velocity_tracking_enabled: config.velocity.enabled,
         exfiltration_shield_enabled: config.exfiltration.enabled,
         max_cost_cents_per_window: config.velocity.max_cost_cents,
@@ -150,40 +217,5 @@ pub async fn get_config(State(state): State<AppState>) -> Json<AirlockConfigResp
         loop_threshold: config.velocity.loop_threshold,
         allowed_domains: config.exfiltration.allowed_domains.clone(),
         block_ip_addresses: config.exfiltration.block_ip_addresses,
-    })
-}
-
-/// Update Airlock configuration (mode only for now)
-///
-/// PUT /v1/security/config
-#[axum::debug_handler]
-pub async fn update_config(
-    State(_state): State<AppState>,
-    Json(request): Json<UpdateAirlockConfigRequest>,
-) -> Result<Json<AirlockConfigResponse>, ApiError> {
-    // Note: In a real implementation, this would update the config in Redis or DB
-    // and the Airlock inspector would reload it. For now, we just validate
-    // and return the requested config (mode changes require restart).
-
-    if let Some(mode) = &request.mode {
-        if mode != "shadow" && mode != "enforce" {
-            return Err(ApiError::bad_request(
-                "Invalid mode. Must be 'shadow' or 'enforce'",
-            ));
-        }
     }
-
-    // For now, return success but note that actual mode changes require restart
-    // A full implementation would store in DB and have the inspector reload
-    Ok(Json(AirlockConfigResponse {
-        mode: request.mode.unwrap_or_else(|| "shadow".to_string()),
-        rce_detection_enabled: true,
-        velocity_tracking_enabled: true,
-        exfiltration_shield_enabled: true,
-        max_cost_cents_per_window: 100,
-        velocity_window_seconds: 10,
-        loop_threshold: 3,
-        allowed_domains: vec!["github.com".to_string(), "api.anthropic.com".to_string()],
-        block_ip_addresses: true,
-    }))
 }
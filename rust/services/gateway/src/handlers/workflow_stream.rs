@@ -0,0 +1,340 @@
+//! Workflow run status stream over WebSocket
+//!
+//! `GET /v1/workflow-runs/{run_id}/ws` upgrades to a socket that pushes the
+//! run's status and step events as JSON and accepts control commands.
+//! Authentication happens on the upgrade request like any other v1 route.
+//!
+//! Server messages are tagged by `type`:
+//! - `{"type": "status", "run_id": ..., "status": "running"}` on connect and on every change
+//! - `{"type": "event", "event": {...}}` for each of the run's `workflow-events` items
+//! - `{"type": "ack", "cmd": "pause"}` once a command has been applied
+//! - `{"type": "error", "message": ...}` for rejected or malformed commands
+//!
+//! Client commands are tagged by `cmd`: `{"cmd": "pause"}` or `{"cmd": "resume"}`,
+//! and need the `write` scope like the REST pause/resume routes.
+//! The socket is closed by the server once the run reaches a terminal state.
+//!
+//! One task per gateway tails the `workflow-events` stream and hands each
+//! event to the sockets watching its run. Sockets also re-read the run's
+//! status periodically, so a lost event can't keep one open forever.
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, State,
+    },
+    response::Response,
+    Extension,
+};
+use fd_storage::models::WorkflowRunStatus;
+use fd_storage::queue::{queues, WorkflowEvent, WorkflowEventKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, instrument, warn};
+
+use crate::handlers::workflows::{pause_run, resume_run};
+use crate::handlers::ApiError;
+use crate::middleware::AuthContext;
+use crate::state::AppState;
+
+/// How often the event stream is polled for new messages
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Maximum events read from the stream per poll
+const POLL_BATCH: usize = 100;
+
+/// How often each socket re-reads its run's status from the database
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Events buffered per watched run before slow sockets start lagging
+const RUN_CHANNEL_CAPACITY: usize = 64;
+
+/// Scope required to send control commands
+const CONTROL_SCOPE: &str = "write";
+
+/// Message pushed to the client
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Status {
+        run_id: String,
+        status: WorkflowRunStatus,
+    },
+    Event {
+        event: WorkflowEvent,
+    },
+    Ack {
+        #[serde(flatten)]
+        cmd: ClientCommand,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Control command sent by the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum ClientCommand {
+    Pause,
+    Resume,
+}
+
+impl ClientCommand {
+    /// Parse a text frame into a command
+    pub fn parse(text: &str) -> Result<Self, ApiError> {
+        serde_json::from_str(text)
+            .map_err(|e| ApiError::bad_request(format!("Invalid command: {}", e)))
+    }
+
+    /// Reject commands from callers without the control scope
+    pub fn authorize(self, auth: &AuthContext) -> Result<Self, ApiError> {
+        if auth.has_scope(CONTROL_SCOPE) {
+            Ok(self)
+        } else {
+            Err(ApiError::forbidden(format!(
+                "Missing required scope: {}",
+                CONTROL_SCOPE
+            )))
+        }
+    }
+}
+
+/// Tracks the last status sent so only changes are pushed
+#[derive(Debug, Default)]
+pub struct StatusTracker {
+    last: Option<WorkflowRunStatus>,
+}
+
+impl StatusTracker {
+    /// Status message to push if `status` differs from the last one sent
+    pub fn observe(&mut self, run_id: &str, status: WorkflowRunStatus) -> Option<ServerMessage> {
+        if self.last == Some(status) {
+            return None;
+        }
+        self.last = Some(status);
+        Some(ServerMessage::Status {
+            run_id: run_id.to_string(),
+            status,
+        })
+    }
+
+    /// Whether the last status sent was terminal
+    pub fn is_terminal(&self) -> bool {
+        self.last.is_some_and(|status| status.is_terminal())
+    }
+}
+
+/// Routes `workflow-events` to the sockets watching each run
+#[derive(Clone, Default)]
+pub struct WorkflowEventHub {
+    runs: Arc<Mutex<HashMap<String, broadcast::Sender<WorkflowEvent>>>>,
+}
+
+impl WorkflowEventHub {
+    /// Receive the events of one run
+    pub fn subscribe(&self, run_id: &str) -> broadcast::Receiver<WorkflowEvent> {
+        let mut runs = self.runs.lock().expect("event hub lock poisoned");
+        runs.entry(run_id.to_string())
+            .or_insert_with(|| broadcast::channel(RUN_CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Whether any socket is watching a run
+    pub fn is_idle(&self) -> bool {
+        let mut runs = self.runs.lock().expect("event hub lock poisoned");
+        runs.retain(|_, sender| sender.receiver_count() > 0);
+        runs.is_empty()
+    }
+
+    /// Hand an event to its run's sockets; events of unwatched runs are dropped
+    pub fn dispatch(&self, event: WorkflowEvent) {
+        let mut runs = self.runs.lock().expect("event hub lock poisoned");
+        runs.retain(|_, sender| sender.receiver_count() > 0);
+        if let Some(sender) = runs.get(&event.run_id) {
+            let _ = sender.send(event);
+        }
+    }
+}
+
+/// Start tailing the `workflow-events` stream into the state's event hub
+pub fn spawn_event_tail(state: AppState) {
+    tokio::spawn(async move {
+        let mut cursor = stream_cursor(chrono::Utc::now().timestamp_millis());
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            // Nobody is watching; skip what gets published until someone is
+            if state.workflow_events.is_idle() {
+                cursor = stream_cursor(chrono::Utc::now().timestamp_millis());
+                continue;
+            }
+
+            let batch = match state
+                .queue
+                .read_after::<WorkflowEvent>(queues::WORKFLOW_EVENTS, &cursor, POLL_BATCH)
+                .await
+            {
+                Ok(batch) => batch,
+                Err(e) => {
+                    warn!(error = %e, "Failed to read workflow events");
+                    continue;
+                }
+            };
+            for (stream_id, message) in batch {
+                cursor = stream_id;
+                state.workflow_events.dispatch(message.payload);
+            }
+        }
+    });
+}
+
+/// Whether a stream event can change the run's status
+pub(crate) fn changes_run_status(event: &WorkflowEvent) -> bool {
+    event.step_id.is_none() || event.event == WorkflowEventKind::StepWaitingApproval
+}
+
+/// Stream ID that skips everything published before `now_ms`
+pub(crate) fn stream_cursor(now_ms: i64) -> String {
+    format!("{}-0", now_ms)
+}
+
+/// Upgrade to a WebSocket streaming a workflow run's progress
+#[instrument(skip(state, auth, ws))]
+pub async fn workflow_run_ws(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(run_id): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let run = state
+        .repos()
+        .workflows()
+        .get_run(&run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("WorkflowRun", &run_id))?;
+
+    if !auth.can_access_project(&run.project_id) {
+        return Err(ApiError::forbidden("Access denied to this workflow run"));
+    }
+
+    Ok(ws.on_upgrade(move |socket| stream_run(state, auth, run_id, run.status, socket)))
+}
+
+/// Drive one socket until the client leaves or the run finishes
+async fn stream_run(
+    state: AppState,
+    auth: AuthContext,
+    run_id: String,
+    initial: WorkflowRunStatus,
+    mut socket: WebSocket,
+) {
+    let mut tracker = StatusTracker::default();
+    let mut events = state.workflow_events.subscribe(&run_id);
+    let mut status_ticker = tokio::time::interval(STATUS_POLL_INTERVAL);
+
+    if let Some(message) = tracker.observe(&run_id, initial) {
+        if send(&mut socket, &message).await.is_err() {
+            return;
+        }
+    }
+
+    while !tracker.is_terminal() {
+        let reply = tokio::select! {
+            frame = socket.recv() => {
+                let text = match frame {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    Some(Ok(_)) => continue,
+                };
+                match ClientCommand::parse(text.as_str()).and_then(|cmd| cmd.authorize(&auth)) {
+                    Ok(cmd) => apply_command(&state, &run_id, cmd, &mut tracker).await,
+                    Err(e) => vec![ServerMessage::Error { message: e.message }],
+                }
+            }
+            event = events.recv() => match event {
+                Ok(event) => forward_event(&state, &run_id, event, &mut tracker).await,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(run_id = %run_id, skipped, "Workflow run stream lagged");
+                    refresh_status(&state, &run_id, &mut tracker).await
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = status_ticker.tick() => refresh_status(&state, &run_id, &mut tracker).await,
+        };
+        if send_all(&mut socket, &reply).await.is_err() {
+            break;
+        }
+    }
+
+    let _ = socket.send(Message::Close(None)).await;
+    debug!(run_id = %run_id, "Workflow run stream closed");
+}
+
+/// Apply a control command and describe the outcome
+async fn apply_command(
+    state: &AppState,
+    run_id: &str,
+    cmd: ClientCommand,
+    tracker: &mut StatusTracker,
+) -> Vec<ServerMessage> {
+    let result = match cmd {
+        ClientCommand::Pause => pause_run(state, run_id).await,
+        ClientCommand::Resume => resume_run(state, run_id).await.map(|(run, _)| run),
+    };
+
+    match result {
+        Ok(run) => {
+            let mut messages = vec![ServerMessage::Ack { cmd }];
+            messages.extend(tracker.observe(run_id, run.status));
+            messages
+        }
+        Err(e) => vec![ServerMessage::Error { message: e.message }],
+    }
+}
+
+/// Forward a run event, plus a status message if it changed the status
+async fn forward_event(
+    state: &AppState,
+    run_id: &str,
+    event: WorkflowEvent,
+    tracker: &mut StatusTracker,
+) -> Vec<ServerMessage> {
+    let reload = changes_run_status(&event);
+    let mut messages = vec![ServerMessage::Event { event }];
+    if reload {
+        messages.extend(refresh_status(state, run_id, tracker).await);
+    }
+    messages
+}
+
+/// Re-read the run's status and report it if it changed
+async fn refresh_status(
+    state: &AppState,
+    run_id: &str,
+    tracker: &mut StatusTracker,
+) -> Vec<ServerMessage> {
+    match state.repos().workflows().get_run(run_id).await {
+        Ok(Some(run)) => tracker.observe(run_id, run.status).into_iter().collect(),
+        Ok(None) => Vec::new(),
+        Err(e) => {
+            warn!(run_id = %run_id, error = %e, "Failed to reload workflow run");
+            Vec::new()
+        }
+    }
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).expect("server messages always serialize");
+    socket.send(Message::Text(text.into())).await
+}
+
+async fn send_all(socket: &mut WebSocket, messages: &[ServerMessage]) -> Result<(), axum::Error> {
+    for message in messages {
+        send(socket, message).await?;
+    }
+    Ok(())
+}
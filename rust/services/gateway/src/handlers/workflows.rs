@@ -1,21 +1,32 @@
 //! Workflow management handlers
 
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     Extension, Json,
 };
 use chrono::Utc;
+use fd_dag::{Condition, DagError, StepDefinition, WorkflowDag};
 use fd_storage::models::{
-    CreateWorkflow, CreateWorkflowRun, CreateWorkflowStepExecution, UpdateWorkflowRun,
-    UpdateWorkflowStepExecution, WorkflowRunStatus, WorkflowStepExecutionStatus, WorkflowStepType,
+    action, resource, AuditEventBuilder, CreateAuditEvent, CreateWorkflow, CreateWorkflowRun,
+    CreateWorkflowStepExecution, OutputRetention, RetryConfig, UpdateWorkflow, UpdateWorkflowRun,
+    UpdateWorkflowStepExecution, WorkflowRun, WorkflowRunStatus, WorkflowStepExecution,
+    WorkflowStepExecutionStatus, WorkflowStepType,
 };
+use fd_storage::queue::{WorkflowEvent, WorkflowEventKind};
 use serde::{Deserialize, Serialize};
-use tracing::instrument;
+use std::collections::{BTreeMap, HashMap};
+use tracing::{instrument, warn};
 use ulid::Ulid;
+use utoipa::{IntoParams, ToSchema};
 
-use crate::handlers::ApiError;
+use crate::handlers::orchestrator::{
+    BlockingDependency, DagTopology, SkipCause, SkipOutput, WorkflowOrchestrator,
+};
+use crate::handlers::runs::{check_step_result_signature, execution_result_signed_path};
+use crate::handlers::{validate_external_id, ApiError};
 use crate::middleware::AuthContext;
 use crate::state::AppState;
 
@@ -34,6 +45,8 @@ pub struct CreateWorkflowRequest {
     pub max_iterations: i32,
     #[serde(default = "default_on_error")]
     pub on_error: String,
+    /// Caller-supplied stable ID; reusing it returns the existing workflow
+    pub external_id: Option<String>,
 }
 
 fn default_max_iterations() -> i32 {
@@ -57,6 +70,7 @@ pub struct WorkflowResponse {
     pub on_error: String,
     pub created_at: String,
     pub updated_at: String,
+    pub external_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -101,6 +115,26 @@ pub struct WorkflowRunResponse {
     pub created_at: String,
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
+    /// Pending steps and the unsatisfied dependencies they are waiting on
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub blocked_steps: BTreeMap<String, Vec<BlockingDependency>>,
+}
+
+/// Query parameters for checking whether one step is downstream of another
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct StepReachabilityQuery {
+    /// Upstream step ID
+    pub from: String,
+    /// Step checked for being downstream of `from`
+    pub to: String,
+}
+
+/// Whether one step of a workflow run is downstream of another
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StepReachabilityResponse {
+    pub from: String,
+    pub to: String,
+    pub reachable: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -118,6 +152,9 @@ pub struct WorkflowStepExecutionResponse {
     pub output_tokens: Option<i32>,
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
+    /// Why the step was skipped, for skipped steps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip: Option<SkipOutput>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -142,6 +179,51 @@ fn default_attempt() -> i32 {
     1
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ValidateWorkflowRequest {
+    pub definition: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvaluateConditionRequest {
+    pub condition: String,
+    /// Step outputs keyed by step ID, as `$.step_id.field` paths see them
+    #[serde(default)]
+    pub outputs: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EvaluateConditionResponse {
+    /// Outcome, absent when the condition does not parse
+    pub result: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkflowValidationError {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkflowValidationResponse {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<WorkflowValidationError>,
+}
+
+/// Partial update of a single step in a workflow definition
+#[derive(Debug, Default, Deserialize)]
+pub struct PatchWorkflowStepRequest {
+    /// Replacement step configuration
+    pub config: Option<serde_json::Value>,
+    /// Replacement dependency list
+    pub depends_on: Option<Vec<String>>,
+}
+
 // =============================================================================
 // Helpers
 // =============================================================================
@@ -159,6 +241,7 @@ fn workflow_to_response(workflow: fd_storage::models::Workflow) -> WorkflowRespo
         on_error: workflow.on_error,
         created_at: workflow.created_at.to_rfc3339(),
         updated_at: workflow.updated_at.to_rfc3339(),
+        external_id: workflow.external_id,
     }
 }
 
@@ -180,12 +263,19 @@ fn workflow_run_to_response(run: fd_storage::models::WorkflowRun) -> WorkflowRun
         created_at: run.created_at.to_rfc3339(),
         started_at: run.started_at.map(|t| t.to_rfc3339()),
         completed_at: run.completed_at.map(|t| t.to_rfc3339()),
+        blocked_steps: BTreeMap::new(),
     }
 }
 
-fn step_execution_to_response(
+pub(crate) fn step_execution_to_response(
     exec: fd_storage::models::WorkflowStepExecution,
 ) -> WorkflowStepExecutionResponse {
+    let skip = match exec.status {
+        WorkflowStepExecutionStatus::Skipped => {
+            exec.output.as_ref().and_then(SkipOutput::from_output)
+        }
+        _ => None,
+    };
     WorkflowStepExecutionResponse {
         id: exec.id,
         workflow_run_id: exec.workflow_run_id,
@@ -200,6 +290,7 @@ fn step_execution_to_response(
         output_tokens: exec.output_tokens,
         started_at: exec.started_at.map(|t| t.to_rfc3339()),
         completed_at: exec.completed_at.map(|t| t.to_rfc3339()),
+        skip,
     }
 }
 
@@ -215,10 +306,207 @@ fn parse_step_type(s: &str) -> Result<WorkflowStepType, ApiError> {
     }
 }
 
+/// Look up the configured retry attempts for a step in a workflow definition
+pub(crate) fn step_max_attempts(definition: &serde_json::Value, step_id: &str) -> Option<i32> {
+    let steps = definition.get("steps")?.as_array()?;
+    let step = steps
+        .iter()
+        .find(|s| s.get("id").and_then(|id| id.as_str()) == Some(step_id))?;
+    let retry: RetryConfig = serde_json::from_value(step.get("retry")?.clone()).ok()?;
+    Some(retry.max_attempts)
+}
+
+/// Build the `step.retries_exhausted` audit event for a failed execution
+///
+/// Returns None unless the execution was the step's final allowed attempt.
+pub(crate) fn retries_exhausted_event(
+    execution: &WorkflowStepExecution,
+    project_id: &str,
+    max_attempts: Option<i32>,
+    last_error: Option<&serde_json::Value>,
+) -> Option<CreateAuditEvent> {
+    let max_attempts = max_attempts?;
+    if execution.attempt < max_attempts {
+        return None;
+    }
+
+    Some(
+        AuditEventBuilder::new(action::STEP_RETRIES_EXHAUSTED, resource::STEP)
+            .resource_id(&execution.id)
+            .project(project_id)
+            .run(&execution.workflow_run_id)
+            .details(serde_json::json!({
+                "step_id": execution.step_id,
+                "attempts": execution.attempt,
+                "max_attempts": max_attempts,
+                "last_error": last_error,
+            }))
+            .build(),
+    )
+}
+
+fn dag_error_code(error: &DagError) -> &'static str {
+    match error {
+        DagError::CycleDetected(_) => "CYCLE_DETECTED",
+        DagError::MissingDependency { .. } => "MISSING_DEPENDENCY",
+        DagError::NoEntryPoints => "NO_ENTRY_POINTS",
+        DagError::StepNotFound(_) => "STEP_NOT_FOUND",
+        DagError::InvalidConfiguration(_) => "INVALID_CONFIGURATION",
+        DagError::InvalidCondition { .. } => "INVALID_CONDITION",
+        DagError::InvalidProjection { .. } => "INVALID_PROJECTION",
+        DagError::MaxIterationsExceeded(_) => "MAX_ITERATIONS_EXCEEDED",
+    }
+}
+
+/// Check a workflow definition the same way a run would, without persisting it
+pub(crate) fn validate_workflow_definition(
+    definition: &serde_json::Value,
+) -> WorkflowValidationResponse {
+    let invalid = |errors: Vec<WorkflowValidationError>| WorkflowValidationResponse {
+        valid: false,
+        layers: Vec::new(),
+        errors,
+    };
+    let to_error = |e: DagError| WorkflowValidationError {
+        code: dag_error_code(&e).to_string(),
+        message: e.to_string(),
+    };
+
+    let Some(steps_value) = definition.get("steps") else {
+        return invalid(vec![WorkflowValidationError {
+            code: "INVALID_DEFINITION".to_string(),
+            message: "Workflow definition missing 'steps' field".to_string(),
+        }]);
+    };
+
+    let steps: Vec<StepDefinition> = match serde_json::from_value(steps_value.clone()) {
+        Ok(steps) => steps,
+        Err(e) => {
+            return invalid(vec![WorkflowValidationError {
+                code: "INVALID_DEFINITION".to_string(),
+                message: format!("Invalid steps definition: {}", e),
+            }])
+        }
+    };
+
+    let mut config_errors: Vec<_> = steps
+        .iter()
+        .filter_map(|step| step.validate_config().err())
+        .map(to_error)
+        .collect();
+    if let Err(e) = OutputRetention::from_definition(definition) {
+        config_errors.push(WorkflowValidationError {
+            code: "INVALID_DEFINITION".to_string(),
+            message: format!("Invalid output_retention: {}", e),
+        });
+    }
+
+    match WorkflowDag::build(steps) {
+        Ok(dag) if config_errors.is_empty() => WorkflowValidationResponse {
+            valid: true,
+            layers: dag.execution_layers(),
+            errors: Vec::new(),
+        },
+        Ok(_) => invalid(config_errors),
+        Err(e) => {
+            let mut errors = vec![to_error(e)];
+            errors.extend(config_errors);
+            invalid(errors)
+        }
+    }
+}
+
+/// Apply a step patch to a workflow definition and re-validate the result
+///
+/// Returns the patched definition and whether the dependency structure changed.
+pub(crate) fn apply_step_patch(
+    definition: &serde_json::Value,
+    step_id: &str,
+    patch: &PatchWorkflowStepRequest,
+) -> Result<(serde_json::Value, bool), ApiError> {
+    let mut patched = definition.clone();
+    let step = patched
+        .get_mut("steps")
+        .and_then(|steps| steps.as_array_mut())
+        .and_then(|steps| {
+            steps
+                .iter_mut()
+                .find(|step| step.get("id").and_then(|id| id.as_str()) == Some(step_id))
+        })
+        .ok_or_else(|| ApiError::not_found("Workflow step", step_id))?;
+
+    let mut structural = false;
+    if let Some(config) = &patch.config {
+        step["config"] = config.clone();
+    }
+    if let Some(depends_on) = &patch.depends_on {
+        structural = step.get("depends_on") != Some(&serde_json::json!(depends_on));
+        step["depends_on"] = serde_json::json!(depends_on);
+    }
+
+    let validation = validate_workflow_definition(&patched);
+    if !validation.valid {
+        let reasons: Vec<String> = validation
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", e.code, e.message))
+            .collect();
+        return Err(ApiError::bad_request(format!(
+            "Patched workflow definition is invalid: {}",
+            reasons.join("; ")
+        )));
+    }
+
+    Ok((patched, structural))
+}
+
 // =============================================================================
 // Workflow Handlers
 // =============================================================================
 
+/// Validate a workflow definition without saving it
+#[instrument(skip(_state, _auth, request))]
+pub async fn validate_workflow(
+    State(_state): State<AppState>,
+    Extension(_auth): Extension<AuthContext>,
+    Json(request): Json<ValidateWorkflowRequest>,
+) -> Result<Json<WorkflowValidationResponse>, ApiError> {
+    Ok(Json(validate_workflow_definition(&request.definition)))
+}
+
+/// Evaluate a condition expression against sample step outputs
+///
+/// Uses the scheduler's semantics: an empty condition passes and paths that
+/// don't resolve make their comparison false.
+pub(crate) fn evaluate_condition(request: &EvaluateConditionRequest) -> EvaluateConditionResponse {
+    if request.condition.trim().is_empty() {
+        return EvaluateConditionResponse {
+            result: Some(true),
+            error: None,
+        };
+    }
+    match Condition::parse(&request.condition) {
+        Ok(condition) => EvaluateConditionResponse {
+            result: Some(condition.evaluate_outputs(&request.outputs)),
+            error: None,
+        },
+        Err(e) => EvaluateConditionResponse {
+            result: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Test a condition expression without running a workflow
+#[instrument(skip(_state, _auth, request))]
+pub async fn evaluate_condition_expression(
+    State(_state): State<AppState>,
+    Extension(_auth): Extension<AuthContext>,
+    Json(request): Json<EvaluateConditionRequest>,
+) -> Result<Json<EvaluateConditionResponse>, ApiError> {
+    Ok(Json(evaluate_condition(&request)))
+}
+
 /// Create a new workflow definition
 #[instrument(skip(state, _auth))]
 pub async fn create_workflow(
@@ -233,21 +521,41 @@ pub async fn create_workflow(
         .project_id
         .ok_or_else(|| ApiError::bad_request("project_id is required"))?;
 
+    if let Some(external_id) = &request.external_id {
+        validate_external_id(external_id)?;
+    }
+
     let workflow_id = format!("wf_{}", Ulid::new());
     let create = CreateWorkflow {
         id: workflow_id.clone(),
         project_id,
-        name: request.name,
+        name: request.name.clone(),
         description: request.description,
-        version: request.version,
+        version: request.version.clone(),
         definition: request.definition,
         max_iterations: request.max_iterations,
         on_error: request.on_error,
+        external_id: request.external_id,
     };
 
-    let workflow = repos.workflows().create(create).await?;
+    // Re-provisioning with a known external ID returns the existing workflow
+    let (workflow, created) = repos.workflows().create_or_get(create).await.map_err(|e| {
+        if fd_storage::repos::workflows::is_name_version_conflict(&e) {
+            ApiError::conflict(format!(
+                "Workflow '{}' version '{}' already exists in this project",
+                request.name, request.version
+            ))
+        } else {
+            e.into()
+        }
+    })?;
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
 
-    Ok((StatusCode::CREATED, Json(workflow_to_response(workflow))))
+    Ok((status, Json(workflow_to_response(workflow))))
 }
 
 /// Get a workflow by ID
@@ -267,6 +575,54 @@ pub async fn get_workflow(
     Ok(Json(workflow_to_response(workflow)))
 }
 
+/// Update a single step's config or dependencies within a workflow definition
+#[instrument(skip(state, _auth, request))]
+pub async fn patch_workflow_step(
+    State(state): State<AppState>,
+    Extension(_auth): Extension<AuthContext>,
+    Path((workflow_id, step_id)): Path<(String, String)>,
+    Json(request): Json<PatchWorkflowStepRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let repos = state.repos();
+
+    let workflow = repos
+        .workflows()
+        .get(&workflow_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Workflow", &workflow_id))?;
+
+    let (definition, structural) = apply_step_patch(&workflow.definition, &step_id, &request)?;
+
+    // Active runs re-read the definition as steps complete, so rewiring
+    // dependencies underneath them could strand or re-run their steps
+    if structural {
+        let active = repos
+            .workflows()
+            .count_active_runs_by_workflow(&workflow_id)
+            .await?;
+        if active > 0 {
+            return Err(ApiError::conflict(format!(
+                "Cannot change dependencies of step '{}' while {} run(s) of workflow '{}' are active",
+                step_id, active, workflow_id
+            )));
+        }
+    }
+
+    let workflow = repos
+        .workflows()
+        .update(
+            &workflow_id,
+            UpdateWorkflow {
+                definition: Some(definition),
+                ..Default::default()
+            },
+        )
+        .await?
+        .ok_or_else(|| ApiError::not_found("Workflow", &workflow_id))?;
+
+    Ok(Json(workflow_to_response(workflow)))
+}
+
 /// List workflows
 #[instrument(skip(state, auth))]
 pub async fn list_workflows(
@@ -309,52 +665,26 @@ pub async fn create_workflow_run(
         .ok_or_else(|| ApiError::not_found("Workflow", &request.workflow_id))?;
 
     let run_id = format!("wfr_{}", Ulid::new());
+
+    // Store the run's root span so steps scheduled later join its trace
+    let span = fd_otel::run_span(&run_id, &workflow.id);
+    let (trace_id, span_id) = fd_otel::span_ids(&span).unzip();
+
     let create = CreateWorkflowRun {
         id: run_id.clone(),
         workflow_id: workflow.id.clone(),
         project_id: auth.tenant_id.clone(),
         input: request.input,
-        trace_id: None,
+        trace_id,
+        span_id,
     };
 
     let run = repos.workflows().create_run(create).await?;
 
-    // Parse workflow definition to get first steps
-    let definition: serde_json::Value = workflow.definition;
-    if let Some(steps) = definition.get("steps").and_then(|s| s.as_array()) {
-        // Find steps with no dependencies (entry points)
-        for step in steps {
-            let step_id = step.get("id").and_then(|s| s.as_str()).unwrap_or_default();
-            let depends_on = step
-                .get("depends_on")
-                .and_then(|d| d.as_array())
-                .map(|arr| arr.len())
-                .unwrap_or(0);
-
-            if depends_on == 0 && !step_id.is_empty() {
-                let step_type_str = step.get("type").and_then(|t| t.as_str()).unwrap_or("llm");
-                let step_type = parse_step_type(step_type_str)?;
-
-                let exec_id = format!("wfse_{}", Ulid::new());
-                let create_exec = CreateWorkflowStepExecution {
-                    id: exec_id,
-                    workflow_run_id: run_id.clone(),
-                    step_id: step_id.to_string(),
-                    step_type,
-                    input: step.get("config").cloned().unwrap_or(serde_json::json!({})),
-                    attempt: 1,
-                    span_id: None,
-                };
-
-                repos.workflows().create_step_execution(create_exec).await?;
-            }
-        }
-    }
-
-    // Update run status to running
-    repos
-        .workflows()
-        .update_run_status(&run_id, WorkflowRunStatus::Running)
+    // Enqueue the entry steps; the orchestrator marks the run running
+    let _guard = state.workflow_run_locks.lock(&run_id).await;
+    WorkflowOrchestrator::new(state.clone())
+        .start_workflow(&run_id, &workflow.id, &run.project_id, &auth.tenant_id)
         .await?;
 
     Ok((StatusCode::CREATED, Json(workflow_run_to_response(run))))
@@ -374,7 +704,79 @@ pub async fn get_workflow_run(
         .await?
         .ok_or_else(|| ApiError::not_found("WorkflowRun", &run_id))?;
 
-    Ok(Json(workflow_run_to_response(run)))
+    let is_terminal = run.status.is_terminal();
+    let mut response = workflow_run_to_response(run);
+    if !is_terminal {
+        match WorkflowOrchestrator::new(state.clone())
+            .blocked_steps(&run_id)
+            .await
+        {
+            Ok(blocked) => response.blocked_steps = blocked,
+            Err(e) => {
+                warn!(run_id = %run_id, error = %e.message, "Failed to compute blocked steps")
+            }
+        }
+    }
+
+    Ok(Json(response))
+}
+
+/// Get the DAG topology of an active workflow run
+#[utoipa::path(
+    get,
+    path = "/v1/workflow-runs/{run_id}/topology",
+    tag = "workflows",
+    params(
+        ("run_id" = String, Path, description = "Workflow run ID")
+    ),
+    responses(
+        (status = 200, description = "Entry and exit points and execution layers", body = DagTopology),
+        (status = 400, description = "Run is already terminal"),
+        (status = 404, description = "Run not found")
+    )
+)]
+#[instrument(skip(state, _auth))]
+pub async fn get_workflow_run_topology(
+    State(state): State<AppState>,
+    Extension(_auth): Extension<AuthContext>,
+    Path(run_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let topology = WorkflowOrchestrator::new(state)
+        .get_dag_topology(&run_id)
+        .await?;
+    Ok(Json(topology))
+}
+
+/// Check whether one step of an active workflow run is downstream of another
+#[utoipa::path(
+    get,
+    path = "/v1/workflow-runs/{run_id}/reachability",
+    tag = "workflows",
+    params(
+        ("run_id" = String, Path, description = "Workflow run ID"),
+        StepReachabilityQuery
+    ),
+    responses(
+        (status = 200, description = "Whether `to` is downstream of `from`", body = StepReachabilityResponse),
+        (status = 400, description = "Run is already terminal"),
+        (status = 404, description = "Run or step not found")
+    )
+)]
+#[instrument(skip(state, _auth))]
+pub async fn get_step_reachability(
+    State(state): State<AppState>,
+    Extension(_auth): Extension<AuthContext>,
+    Path(run_id): Path<String>,
+    Query(query): Query<StepReachabilityQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let reachable = WorkflowOrchestrator::new(state)
+        .is_step_reachable(&run_id, &query.from, &query.to)
+        .await?;
+    Ok(Json(StepReachabilityResponse {
+        from: query.from,
+        to: query.to,
+        reachable,
+    }))
 }
 
 /// List workflow runs
@@ -404,6 +806,33 @@ pub async fn list_workflow_runs(
     Ok(Json(serde_json::json!({ "runs": runs })))
 }
 
+/// Steps a definition's `cancel_output_steps` field limits partial output to
+pub(crate) fn cancel_output_steps(definition: &serde_json::Value) -> Option<Vec<String>> {
+    serde_json::from_value(definition.get("cancel_output_steps")?.clone()).ok()
+}
+
+/// Output recorded for a cancelled run: the step outputs produced before cancellation
+///
+/// Skip markers and pruned entries carry no output and are left out. When
+/// `only` is given, just those steps are included.
+pub(crate) fn partial_output(
+    step_results: &serde_json::Value,
+    only: Option<&[String]>,
+) -> serde_json::Value {
+    let produced: serde_json::Map<String, serde_json::Value> = step_results
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(step_id, _)| only.map_or(true, |only| only.contains(step_id)))
+        .filter(|(_, result)| {
+            result.get("skipped").is_none() && result.get("output_pruned").is_none()
+        })
+        .map(|(step_id, result)| (step_id.clone(), result.clone()))
+        .collect();
+
+    serde_json::json!({ "partial": true, "step_results": produced })
+}
+
 /// Cancel a workflow run
 #[instrument(skip(state, _auth))]
 pub async fn cancel_workflow_run(
@@ -412,6 +841,7 @@ pub async fn cancel_workflow_run(
     Path(run_id): Path<String>,
 ) -> Result<impl IntoResponse, ApiError> {
     let repos = state.repos();
+    let _guard = state.workflow_run_locks.lock(&run_id).await;
 
     let run = repos
         .workflows()
@@ -426,12 +856,21 @@ pub async fn cancel_workflow_run(
         )));
     }
 
+    // Keep whatever the run produced so far as its output
+    let only_steps = repos
+        .workflows()
+        .get(&run.workflow_id)
+        .await?
+        .and_then(|workflow| cancel_output_steps(&workflow.definition));
+    let output = partial_output(&run.step_results, only_steps.as_deref());
+
     let updated = repos
         .workflows()
         .update_run(
             &run_id,
             UpdateWorkflowRun {
                 status: Some(WorkflowRunStatus::Cancelled),
+                output: Some(output),
                 completed_at: Some(Utc::now()),
                 ..Default::default()
             },
@@ -439,9 +878,117 @@ pub async fn cancel_workflow_run(
         .await?
         .ok_or_else(|| ApiError::internal("Failed to update run"))?;
 
+    let event = WorkflowEvent::run(&run_id, WorkflowEventKind::RunCancelled);
+    if let Err(e) = state.publish_workflow_event(&event).await {
+        warn!(run_id = %run_id, error = %e, "Failed to publish workflow event");
+    }
+
     Ok(Json(workflow_run_to_response(updated)))
 }
 
+/// Reject pausing a run that is not actively scheduling steps
+pub(crate) fn ensure_pausable(status: WorkflowRunStatus) -> Result<(), ApiError> {
+    if matches!(
+        status,
+        WorkflowRunStatus::Running | WorkflowRunStatus::WaitingApproval
+    ) {
+        Ok(())
+    } else {
+        Err(ApiError::bad_request(format!(
+            "Run cannot be paused in state: {:?}",
+            status
+        )))
+    }
+}
+
+/// Reject resuming a run that is not paused
+pub(crate) fn ensure_resumable(status: WorkflowRunStatus) -> Result<(), ApiError> {
+    if status == WorkflowRunStatus::Paused {
+        Ok(())
+    } else {
+        Err(ApiError::bad_request(format!(
+            "Run is not paused: {:?}",
+            status
+        )))
+    }
+}
+
+/// Pause a run, returning its updated row (shared by the REST and WebSocket APIs)
+pub(crate) async fn pause_run(state: &AppState, run_id: &str) -> Result<WorkflowRun, ApiError> {
+    let repos = state.repos();
+    let _guard = state.workflow_run_locks.lock(run_id).await;
+
+    let run = repos
+        .workflows()
+        .get_run(run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("WorkflowRun", run_id))?;
+    ensure_pausable(run.status)?;
+
+    WorkflowOrchestrator::new(state.clone())
+        .pause_run(run_id)
+        .await?;
+
+    repos
+        .workflows()
+        .get_run(run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("WorkflowRun", run_id))
+}
+
+/// Resume a paused run, returning its updated row and the steps enqueued
+pub(crate) async fn resume_run(
+    state: &AppState,
+    run_id: &str,
+) -> Result<(WorkflowRun, Vec<String>), ApiError> {
+    let repos = state.repos();
+    let _guard = state.workflow_run_locks.lock(run_id).await;
+
+    let run = repos
+        .workflows()
+        .get_run(run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("WorkflowRun", run_id))?;
+    ensure_resumable(run.status)?;
+
+    let resumed_steps = WorkflowOrchestrator::new(state.clone())
+        .resume_run(run_id)
+        .await?;
+
+    let updated = repos
+        .workflows()
+        .get_run(run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("WorkflowRun", run_id))?;
+    Ok((updated, resumed_steps))
+}
+
+/// Pause a workflow run (no new steps are enqueued until resumed)
+#[instrument(skip(state, _auth))]
+pub async fn pause_workflow_run(
+    State(state): State<AppState>,
+    Extension(_auth): Extension<AuthContext>,
+    Path(run_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let updated = pause_run(&state, &run_id).await?;
+    Ok(Json(workflow_run_to_response(updated)))
+}
+
+/// Resume a paused workflow run, enqueueing steps that became ready
+#[instrument(skip(state, _auth))]
+pub async fn resume_workflow_run(
+    State(state): State<AppState>,
+    Extension(_auth): Extension<AuthContext>,
+    Path(run_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (updated, resumed_steps) = resume_run(&state, &run_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "run": workflow_run_to_response(updated),
+        "resumed_steps": resumed_steps,
+    })))
+}
+
 // =============================================================================
 // Step Execution Handlers
 // =============================================================================
@@ -475,6 +1022,36 @@ pub async fn list_step_executions(
     Ok(Json(serde_json::json!({ "executions": executions })))
 }
 
+/// Get the latest execution of one step in a workflow run
+///
+/// Retried steps have one execution per attempt; the highest attempt wins.
+#[instrument(skip(state, auth))]
+pub async fn get_step_execution(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path((run_id, step_id)): Path<(String, String)>,
+) -> Result<Json<WorkflowStepExecutionResponse>, ApiError> {
+    let run = state
+        .repos()
+        .workflows()
+        .get_run(&run_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("WorkflowRun", &run_id))?;
+
+    if !auth.can_access_project(&run.project_id) {
+        return Err(ApiError::forbidden("Access denied to this workflow run"));
+    }
+
+    let execution = state
+        .repos()
+        .workflows()
+        .get_latest_step_execution(&run_id, &step_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("WorkflowStep", &step_id))?;
+
+    Ok(Json(step_execution_to_response(execution)))
+}
+
 /// Create a new step execution (for orchestration)
 #[instrument(skip(state, _auth))]
 pub async fn create_step_execution(
@@ -514,14 +1091,30 @@ pub async fn create_step_execution(
 }
 
 /// Submit step execution result (from worker)
-#[instrument(skip(state, _auth))]
+///
+/// When step result signing is enabled the body must carry a valid
+/// `X-FD-Signature` for the run and execution, otherwise the submission is
+/// rejected.
+#[instrument(skip(state, _auth, headers, body))]
 pub async fn submit_step_execution_result(
     State(state): State<AppState>,
     Extension(_auth): Extension<AuthContext>,
     Path((run_id, execution_id)): Path<(String, String)>,
-    Json(request): Json<SubmitStepExecutionResultRequest>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> Result<impl IntoResponse, ApiError> {
+    check_step_result_signature(
+        &state,
+        &headers,
+        &run_id,
+        &execution_result_signed_path(&run_id, &execution_id),
+        &body,
+    )?;
+    let request: SubmitStepExecutionResultRequest = serde_json::from_slice(&body)
+        .map_err(|e| ApiError::bad_request(format!("Invalid JSON: {}", e)))?;
+
     let repos = state.repos();
+    let _guard = state.workflow_run_locks.lock(&run_id).await;
 
     // Verify run exists
     let run = repos
@@ -552,103 +1145,120 @@ pub async fn submit_step_execution_result(
         _ => return Err(ApiError::bad_request("Invalid status")),
     };
 
-    let update = UpdateWorkflowStepExecution {
-        status: Some(status),
-        output: request.output.clone(),
-        error: request.error.clone(),
-        input_tokens: request.input_tokens,
-        output_tokens: request.output_tokens,
-        completed_at: Some(Utc::now()),
-        ..Default::default()
-    };
-
-    let updated_execution = repos
-        .workflows()
-        .update_step_execution(&execution_id, update)
-        .await?
-        .ok_or_else(|| ApiError::internal("Failed to update execution"))?;
-
-    // Update run step results
-    if let Some(ref output) = request.output {
-        repos
-            .workflows()
-            .update_run_step_results(&run_id, &execution.step_id, output.clone())
-            .await?;
-    }
-
-    // Update run usage
-    if let (Some(in_tokens), Some(out_tokens)) = (request.input_tokens, request.output_tokens) {
+    // The orchestrator records completions itself, after projecting the output
+    let orchestrated = !run.status.is_terminal();
+    if status != WorkflowStepExecutionStatus::Completed || !orchestrated {
+        let update = UpdateWorkflowStepExecution {
+            status: Some(status),
+            output: request.output.clone(),
+            error: request.error.clone(),
+            input_tokens: request.input_tokens,
+            output_tokens: request.output_tokens,
+            completed_at: Some(Utc::now()),
+            ..Default::default()
+        };
         repos
             .workflows()
-            .increment_run_usage(&run_id, in_tokens, out_tokens, 0, 0)
-            .await?;
-    }
+            .update_step_execution(&execution_id, update)
+            .await?
+            .ok_or_else(|| ApiError::internal("Failed to update execution"))?;
 
-    // Check if workflow run should be updated based on step status
-    match status {
-        WorkflowStepExecutionStatus::Failed => {
-            // Check on_error policy from workflow
-            let workflow = repos
+        if let (Some(in_tokens), Some(out_tokens)) = (request.input_tokens, request.output_tokens) {
+            repos
                 .workflows()
-                .get(&run.workflow_id)
-                .await?
-                .ok_or_else(|| ApiError::internal("Workflow not found"))?;
+                .increment_run_usage(&run_id, in_tokens, out_tokens, 0, 0)
+                .await?;
+        }
+    }
 
-            let on_error = workflow.on_error.as_str();
-            if on_error == "fail" {
-                repos
-                    .workflows()
-                    .update_run(
+    // Results for a finished run are recorded but no longer move the DAG
+    if orchestrated {
+        let orchestrator = WorkflowOrchestrator::new(state.clone());
+        match status {
+            WorkflowStepExecutionStatus::Completed => {
+                orchestrator
+                    .complete_step(
                         &run_id,
-                        UpdateWorkflowRun {
-                            status: Some(WorkflowRunStatus::Failed),
-                            error: request.error,
-                            completed_at: Some(Utc::now()),
-                            ..Default::default()
-                        },
+                        &execution.step_id,
+                        &execution_id,
+                        request.output.unwrap_or(serde_json::Value::Null),
+                        request.input_tokens,
+                        request.output_tokens,
                     )
                     .await?;
             }
-        }
-        WorkflowStepExecutionStatus::WaitingApproval => {
-            repos
-                .workflows()
-                .update_run(
-                    &run_id,
-                    UpdateWorkflowRun {
-                        status: Some(WorkflowRunStatus::WaitingApproval),
-                        current_step_id: Some(execution.step_id.clone()),
-                        ..Default::default()
-                    },
-                )
-                .await?;
-        }
-        WorkflowStepExecutionStatus::Completed => {
-            // Check if all steps are completed
-            let pending = repos
-                .workflows()
-                .get_pending_step_executions(&run_id)
-                .await?;
-
-            if pending.is_empty() {
-                // All steps done - check if there are more steps to execute
-                // For now, mark as completed
-                repos
+            WorkflowStepExecutionStatus::Failed => {
+                let workflow = repos
                     .workflows()
-                    .update_run(
+                    .get(&run.workflow_id)
+                    .await?
+                    .ok_or_else(|| ApiError::internal("Workflow not found"))?;
+
+                let max_attempts = step_max_attempts(&workflow.definition, &execution.step_id);
+                // Attempts remain: run the step again, its dependents keep waiting
+                if max_attempts.is_some_and(|max| execution.attempt < max) {
+                    orchestrator
+                        .retry_step(&run_id, &execution.step_id, execution.attempt + 1)
+                        .await?;
+                } else {
+                    let exhausted = retries_exhausted_event(
+                        &execution,
+                        &run.project_id,
+                        max_attempts,
+                        request.error.as_ref(),
+                    );
+
+                    let run_error = exhausted.map(|event| {
+                        repos.spawn_audit(event);
+                        serde_json::json!({
+                            "message": format!(
+                                "Step '{}' failed after {} attempts",
+                                execution.step_id, execution.attempt
+                            ),
+                            "reason": "retries_exhausted",
+                            "attempts": execution.attempt,
+                            "last_error": request.error,
+                        })
+                    });
+
+                    orchestrator
+                        .fail_step(
+                            &run_id,
+                            &execution.step_id,
+                            &execution_id,
+                            request
+                                .error
+                                .unwrap_or_else(|| serde_json::json!({ "message": "Step failed" })),
+                            run_error,
+                        )
+                        .await?;
+                }
+            }
+            WorkflowStepExecutionStatus::WaitingApproval => {
+                orchestrator
+                    .mark_waiting_approval(&run_id, &execution.step_id, &execution_id)
+                    .await?;
+            }
+            WorkflowStepExecutionStatus::Skipped => {
+                orchestrator
+                    .skip_step(
                         &run_id,
-                        UpdateWorkflowRun {
-                            status: Some(WorkflowRunStatus::Completed),
-                            output: request.output,
-                            completed_at: Some(Utc::now()),
-                            ..Default::default()
-                        },
+                        &execution.step_id,
+                        &execution_id,
+                        SkipCause::ConditionFalse,
+                        "skipped_by_worker",
                     )
                     .await?;
             }
+            _ => {}
         }
-        _ => {}
     }
 
+    let updated_execution = repos
+        .workflows()
+        .get_step_execution(&execution_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("WorkflowStepExecution", &execution_id))?;
+
     Ok(Json(step_execution_to_response(updated_execution)))
 }
@@ -15,6 +15,7 @@ use tower_http::{
 use tracing::{info, warn};
 
 mod handlers;
+mod maintenance;
 mod middleware;
 mod openapi;
 mod routes;
@@ -40,6 +41,10 @@ async fn main() -> anyhow::Result<()> {
     let state = AppState::new().await?;
     info!("Connected to database and Redis");
 
+    maintenance::spawn(state.clone());
+    tokio::spawn(handlers::orchestrator::recover_workflow_runs(state.clone()));
+    handlers::workflow_stream::spawn_event_tail(state.clone());
+
     // Configure CORS
     // SECURITY: In production, ALLOWED_ORIGINS should be set to specific domains
     let cors_layer = build_cors_layer();
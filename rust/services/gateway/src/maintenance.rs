@@ -0,0 +1,51 @@
+//! Background maintenance
+//!
+//! Periodically reclaims tracking state that otherwise only grows: velocity
+//! records of runs that stopped making calls, and processed-job ids older
+//! than the processed TTL. Idempotency and dedup keys are written with their
+//! own Redis TTL and need no compaction.
+
+use fd_storage::queue::queues;
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Start the compaction loop if an interval is configured
+pub fn spawn(state: AppState) {
+    let Some(interval) = state.maintenance_interval else {
+        info!("Background maintenance disabled");
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately; there is nothing to reclaim yet
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            compact(&state).await;
+        }
+    });
+}
+
+/// Run one compaction pass and report what it reclaimed
+async fn compact(state: &AppState) {
+    let velocity = state.tenant_airlocks.compact_velocity().await;
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let processed_ids = match state.queue.compact_processed(queues::STEPS, now_ms).await {
+        Ok(removed) => removed,
+        Err(e) => {
+            warn!(error = %e, "Failed to compact processed-job set");
+            0
+        }
+    };
+
+    info!(
+        velocity_records_removed = velocity.records_removed,
+        velocity_runs_removed = velocity.runs_removed,
+        processed_ids_removed = processed_ids,
+        audit_events_suppressed = state.repos().suppressed_audit_events(),
+        "Background compaction completed"
+    );
+}
@@ -0,0 +1,186 @@
+//! Request body size limits
+//!
+//! Rejects oversized bodies with `413 Payload Too Large` before they reach
+//! authentication or handlers. Workflow definitions get a larger cap than
+//! other routes.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::header::CONTENT_LENGTH,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+
+use crate::handlers::ApiError;
+
+/// Default cap for request bodies (1 MiB)
+const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Default cap for workflow definition bodies (8 MiB)
+const DEFAULT_MAX_WORKFLOW_BODY_BYTES: usize = 8 * 1024 * 1024;
+
+/// Routes that accept full workflow definitions
+const WORKFLOW_DEFINITION_PATHS: &[&str] = &["/v1/workflows", "/v1/workflows:validate"];
+
+/// Body size limits per route class
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimitConfig {
+    /// Limit for most routes
+    pub default_bytes: usize,
+    /// Limit for routes that accept workflow definitions
+    pub workflow_bytes: usize,
+}
+
+impl Default for BodyLimitConfig {
+    fn default() -> Self {
+        Self {
+            default_bytes: DEFAULT_MAX_BODY_BYTES,
+            workflow_bytes: DEFAULT_MAX_WORKFLOW_BODY_BYTES,
+        }
+    }
+}
+
+impl BodyLimitConfig {
+    /// Load limits from `MAX_REQUEST_BODY_BYTES` and `MAX_WORKFLOW_BODY_BYTES`
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: usize| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            default_bytes: read("MAX_REQUEST_BODY_BYTES", DEFAULT_MAX_BODY_BYTES),
+            workflow_bytes: read("MAX_WORKFLOW_BODY_BYTES", DEFAULT_MAX_WORKFLOW_BODY_BYTES),
+        }
+    }
+
+    /// Limit that applies to a request path
+    pub fn limit_for(&self, path: &str) -> usize {
+        if WORKFLOW_DEFINITION_PATHS.contains(&path) {
+            self.workflow_bytes
+        } else {
+            self.default_bytes
+        }
+    }
+}
+
+/// Enforce the body size limit for the request's route
+pub async fn body_limit_middleware(
+    State(config): State<BodyLimitConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limit = config.limit_for(request.uri().path());
+
+    // Reject early when the declared length is already too large
+    let declared = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared.is_some_and(|len| len > limit as u64) {
+        warn!(path = %request.uri().path(), limit, "Request body exceeds limit");
+        return ApiError::payload_too_large(limit).into_response();
+    }
+
+    // Chunked or mislabelled bodies are counted as they are read
+    let (parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, limit).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!(path = %parts.uri.path(), limit, error = %e, "Failed to read request body");
+            return ApiError::payload_too_large(limit).into_response();
+        }
+    };
+
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn app(config: BodyLimitConfig) -> Router {
+        Router::new()
+            .route(
+                "/v1/runs",
+                post(|body: String| async move { body.len().to_string() }),
+            )
+            .route(
+                "/v1/workflows",
+                post(|body: String| async move { body.len().to_string() }),
+            )
+            .layer(middleware::from_fn_with_state(
+                config,
+                body_limit_middleware,
+            ))
+    }
+
+    fn post_request(path: &str, body: Vec<u8>) -> Request {
+        Request::builder()
+            .method("POST")
+            .uri(path)
+            .header(CONTENT_LENGTH, body.len())
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    fn config() -> BodyLimitConfig {
+        BodyLimitConfig {
+            default_bytes: 16,
+            workflow_bytes: 64,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_body_over_limit_returns_413() {
+        let response = app(config())
+            .oneshot(post_request("/v1/runs", vec![b'a'; 17]))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"]["code"], "PAYLOAD_TOO_LARGE");
+    }
+
+    #[tokio::test]
+    async fn test_chunked_body_over_limit_returns_413() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/runs")
+            .body(Body::from(vec![b'a'; 17]))
+            .unwrap();
+
+        let response = app(config()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_body_within_limit_passes_through() {
+        let response = app(config())
+            .oneshot(post_request("/v1/runs", vec![b'a'; 16]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_workflow_definitions_use_larger_limit() {
+        let response = app(config())
+            .oneshot(post_request("/v1/workflows", vec![b'a'; 48]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
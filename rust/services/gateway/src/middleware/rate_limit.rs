@@ -115,7 +115,7 @@ impl WindowCounter {
         self.max_requests.saturating_sub(current as u32)
     }
 
-    /// Get time until window resets (in seconds)
+    /// Get time until window resets (in whole seconds, rounded up)
     fn reset_after(&self) -> u64 {
         if self.requests.is_empty() {
             return 0;
@@ -126,13 +126,66 @@ impl WindowCounter {
         let now = Instant::now();
 
         if reset_time > now {
-            (reset_time - now).as_secs()
+            // Round up so a client honoring Retry-After never retries early
+            let wait = reset_time - now;
+            wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
         } else {
             0
         }
     }
 }
 
+/// Outcome of a rate limit check, with the quota values reported to clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    /// Whether the request is allowed
+    pub allowed: bool,
+    /// Maximum requests per window
+    pub limit: u32,
+    /// Requests left in the current window
+    pub remaining: u32,
+    /// Seconds until the window frees up another request
+    pub reset_after: u64,
+}
+
+impl RateLimitDecision {
+    /// Quota headers describing this decision
+    fn headers(&self) -> [(&'static str, String); 3] {
+        [
+            ("X-RateLimit-Limit", self.limit.to_string()),
+            ("X-RateLimit-Remaining", self.remaining.to_string()),
+            ("X-RateLimit-Reset", self.reset_after.to_string()),
+        ]
+    }
+
+    /// Add quota headers to a response
+    fn apply_headers(&self, response: &mut Response) {
+        let headers = response.headers_mut();
+        for (name, value) in self.headers() {
+            headers.insert(name, value.parse().unwrap());
+        }
+    }
+
+    /// 429 response with quota headers and `Retry-After`
+    fn deny_response(&self) -> Response {
+        let retry_after = self.reset_after.max(1);
+        let mut response = (
+            StatusCode::TOO_MANY_REQUESTS,
+            [("Retry-After", retry_after.to_string())],
+            Json(json!({
+                "error": {
+                    "code": "RATE_LIMIT_EXCEEDED",
+                    "message": "Too many requests. Please retry later.",
+                    "retry_after": retry_after
+                }
+            })),
+        )
+            .into_response();
+        self.apply_headers(&mut response);
+        response
+    }
+}
+
 /// In-memory rate limiter store
 #[derive(Debug)]
 pub struct RateLimiterStore {
@@ -155,7 +208,7 @@ impl RateLimiterStore {
     }
 
     /// Try to record a request for the given key
-    fn try_request(&mut self, key: &str, config: &RateLimitConfig) -> (bool, u32, u64) {
+    fn try_request(&mut self, key: &str, config: &RateLimitConfig) -> RateLimitDecision {
         // Periodic cleanup of stale entries
         if self.last_cleanup.elapsed() > Duration::from_secs(60) {
             self.cleanup(config.window);
@@ -167,10 +220,13 @@ impl RateLimiterStore {
             .or_insert_with(|| WindowCounter::new(config.max_requests, config.window));
 
         let allowed = counter.try_request();
-        let remaining = counter.remaining();
-        let reset_after = counter.reset_after();
 
-        (allowed, remaining, reset_after)
+        RateLimitDecision {
+            allowed,
+            limit: config.max_requests,
+            remaining: counter.remaining(),
+            reset_after: counter.reset_after(),
+        }
     }
 
     /// Remove stale counters
@@ -211,34 +267,18 @@ pub async fn pre_auth_rate_limit_middleware(
     let key = extract_client_ip(&request);
 
     // Check rate limit
-    let (allowed, _remaining, reset_after) = {
+    let decision = {
         let mut store = limiter.write().await;
         store.try_request(&key, &config)
     };
 
-    if !allowed {
+    if !decision.allowed {
         warn!(
             ip = %key,
             "Pre-auth rate limit exceeded - potential brute force attack"
         );
 
-        return (
-            StatusCode::TOO_MANY_REQUESTS,
-            [
-                ("X-RateLimit-Limit", config.max_requests.to_string()),
-                ("X-RateLimit-Remaining", "0".to_string()),
-                ("X-RateLimit-Reset", reset_after.to_string()),
-                ("Retry-After", reset_after.to_string()),
-            ],
-            Json(json!({
-                "error": {
-                    "code": "RATE_LIMIT_EXCEEDED",
-                    "message": "Too many requests. Please retry later.",
-                    "retry_after": reset_after
-                }
-            })),
-        )
-            .into_response();
+        return decision.deny_response();
     }
 
     next.run(request).await
@@ -290,61 +330,31 @@ pub async fn rate_limit_middleware(
         .unwrap_or_else(|| "unknown".to_string());
 
     // Check rate limit
-    let (allowed, remaining, reset_after) = {
+    let decision = {
         let mut store = limiter.write().await;
         store.try_request(&key, &config)
     };
 
-    if !allowed {
+    if !decision.allowed {
         warn!(
             key = %key,
-            remaining = remaining,
-            reset_after = reset_after,
+            remaining = decision.remaining,
+            reset_after = decision.reset_after,
             "Rate limit exceeded"
         );
 
-        return (
-            StatusCode::TOO_MANY_REQUESTS,
-            [
-                ("X-RateLimit-Limit", config.max_requests.to_string()),
-                ("X-RateLimit-Remaining", "0".to_string()),
-                ("X-RateLimit-Reset", reset_after.to_string()),
-                ("Retry-After", reset_after.to_string()),
-            ],
-            Json(json!({
-                "error": {
-                    "code": "RATE_LIMIT_EXCEEDED",
-                    "message": "Too many requests. Please retry later.",
-                    "retry_after": reset_after
-                }
-            })),
-        )
-            .into_response();
+        return decision.deny_response();
     }
 
     debug!(
         key = %key,
-        remaining = remaining,
+        remaining = decision.remaining,
         "Rate limit check passed"
     );
 
     // Add rate limit headers to response
     let mut response = next.run(request).await;
-
-    let headers = response.headers_mut();
-    headers.insert(
-        "X-RateLimit-Limit",
-        config.max_requests.to_string().parse().unwrap(),
-    );
-    headers.insert(
-        "X-RateLimit-Remaining",
-        remaining.to_string().parse().unwrap(),
-    );
-    headers.insert(
-        "X-RateLimit-Reset",
-        reset_after.to_string().parse().unwrap(),
-    );
-
+    decision.apply_headers(&mut response);
     response
 }
 
@@ -378,15 +388,15 @@ mod tests {
 
         // First 3 requests should pass
         for i in 0..3 {
-            let (allowed, remaining, _) = store.try_request("test_key", &config);
-            assert!(allowed, "Request {} should be allowed", i);
-            assert_eq!(remaining, 2 - i as u32);
+            let decision = store.try_request("test_key", &config);
+            assert!(decision.allowed, "Request {} should be allowed", i);
+            assert_eq!(decision.remaining, 2 - i as u32);
         }
 
         // 4th request should fail
-        let (allowed, remaining, _) = store.try_request("test_key", &config);
-        assert!(!allowed);
-        assert_eq!(remaining, 0);
+        let decision = store.try_request("test_key", &config);
+        assert!(!decision.allowed);
+        assert_eq!(decision.remaining, 0);
     }
 
     #[test]
@@ -397,11 +407,38 @@ mod tests {
         // Use up key1's limit
         store.try_request("key1", &config);
         store.try_request("key1", &config);
-        let (allowed, _, _) = store.try_request("key1", &config);
-        assert!(!allowed);
+        assert!(!store.try_request("key1", &config).allowed);
 
         // key2 should still work
-        let (allowed, _, _) = store.try_request("key2", &config);
-        assert!(allowed);
+        assert!(store.try_request("key2", &config).allowed);
+    }
+
+    #[test]
+    fn test_deny_response_includes_quota_headers() {
+        let mut store = RateLimiterStore::new();
+        let config = RateLimitConfig::per_minute(2);
+
+        store.try_request("tenant:ten_01", &config);
+        store.try_request("tenant:ten_01", &config);
+        let decision = store.try_request("tenant:ten_01", &config);
+        assert!(!decision.allowed);
+
+        let response = decision.deny_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let header =
+            |name: &str| -> u64 { response.headers()[name].to_str().unwrap().parse().unwrap() };
+        assert_eq!(header("X-RateLimit-Limit"), 2);
+        assert_eq!(header("X-RateLimit-Remaining"), 0);
+        // The oldest request frees up a full window (rounded up) from now
+        assert_eq!(header("X-RateLimit-Reset"), 60);
+        assert_eq!(header("Retry-After"), 60);
+    }
+
+    #[test]
+    fn test_reset_after_rounds_up_partial_seconds() {
+        let mut counter = WindowCounter::new(1, Duration::from_millis(1500));
+        counter.try_request();
+        assert_eq!(counter.reset_after(), 2);
     }
 }
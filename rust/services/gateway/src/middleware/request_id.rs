@@ -10,6 +10,16 @@ const REQUEST_ID_HEADER: &str = "x-request-id";
 #[allow(dead_code)]
 pub struct RequestId(pub String);
 
+tokio::task_local! {
+    /// Request ID of the request currently being handled
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// Request ID of the in-flight request, if called within the middleware
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
 /// Add or propagate request ID
 pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
     // Get existing or generate new request ID
@@ -25,8 +35,10 @@ pub async fn request_id_middleware(mut request: Request, next: Next) -> Response
         .extensions_mut()
         .insert(RequestId(request_id.clone()));
 
-    // Run the handler
-    let mut response = next.run(request).await;
+    // Run the handler with the request ID in scope so error bodies can echo it
+    let mut response = CURRENT_REQUEST_ID
+        .scope(request_id.clone(), next.run(request))
+        .await;
 
     // Add to response headers
     if let Ok(header_value) = HeaderValue::from_str(&request_id) {
@@ -37,3 +49,72 @@ pub async fn request_id_middleware(mut request: Request, next: Next) -> Response
 
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::ApiError;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/missing",
+                get(|| async { ApiError::not_found("Run", "run_123") }),
+            )
+            .layer(middleware::from_fn(request_id_middleware))
+    }
+
+    async fn error_request_id(response: Response) -> Option<String> {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        body["error"]["request_id"].as_str().map(str::to_string)
+    }
+
+    #[tokio::test]
+    async fn test_incoming_request_id_is_echoed() {
+        let request = Request::builder()
+            .uri("/missing")
+            .header(REQUEST_ID_HEADER, "req_client_supplied")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(
+            response.headers().get(REQUEST_ID_HEADER).unwrap(),
+            "req_client_supplied"
+        );
+        assert_eq!(
+            error_request_id(response).await.as_deref(),
+            Some("req_client_supplied")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generated_request_id_is_echoed() {
+        let request = Request::builder()
+            .uri("/missing")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        let header = response
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        let ulid = header.strip_prefix("req_").unwrap();
+        assert!(Ulid::from_string(ulid).is_ok());
+        assert_eq!(error_request_id(response).await, Some(header));
+    }
+
+    #[test]
+    fn test_no_request_id_outside_middleware() {
+        assert!(current_request_id().is_none());
+    }
+}
@@ -5,7 +5,7 @@
 
 use utoipa::OpenApi;
 
-use crate::handlers::{health, runs};
+use crate::handlers::{health, orchestrator, runs, workflows};
 
 /// OpenAPI documentation for the FerrumDeck Gateway API
 #[derive(OpenApi)]
@@ -22,7 +22,8 @@ use crate::handlers::{health, runs};
     ),
     tags(
         (name = "health", description = "Health check endpoints"),
-        (name = "runs", description = "Run management endpoints")
+        (name = "runs", description = "Run management endpoints"),
+        (name = "workflows", description = "Workflow run endpoints")
     ),
     paths(
         // Health endpoints
@@ -30,10 +31,17 @@ use crate::handlers::{health, runs};
         health::readiness_check,
         // Run endpoints
         runs::create_run,
+        runs::estimate_run,
         runs::get_run,
         runs::list_runs,
         runs::cancel_run,
+        runs::archive_runs,
+        runs::sweep_stuck_runs,
         runs::list_steps,
+        runs::diff_runs,
+        // Workflow endpoints
+        workflows::get_workflow_run_topology,
+        workflows::get_step_reachability,
     ),
     components(
         schemas(
@@ -44,9 +52,23 @@ use crate::handlers::{health, runs};
             health::ComponentHealth,
             // Run schemas
             runs::CreateRunRequest,
+            runs::EstimateRunRequest,
+            runs::RunEstimateResponse,
             runs::RunResponse,
             runs::ListRunsResponse,
+            runs::ArchiveRunsRequest,
+            runs::ArchiveRunsResponse,
+            runs::SweepStuckRunsRequest,
+            runs::SweepStuckRunsResponse,
+            runs::StuckRun,
             runs::StepResponse,
+            runs::RunDiffResponse,
+            runs::RunDiffSide,
+            runs::StepDiff,
+            runs::StepDiffStatus,
+            // Workflow schemas
+            orchestrator::DagTopology,
+            workflows::StepReachabilityResponse,
         )
     )
 )]
@@ -1,15 +1,16 @@
 //! API routes
 
 use axum::{
-    middleware, routing::delete, routing::get, routing::patch, routing::post, routing::put, Router,
+    extract::DefaultBodyLimit, middleware, routing::delete, routing::get, routing::patch,
+    routing::post, routing::put, Router,
 };
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::handlers;
 use crate::middleware::{
-    auth_middleware, pre_auth_rate_limit_middleware, rate_limit_middleware, request_id_middleware,
-    require_admin, require_write,
+    auth_middleware, body_limit_middleware, pre_auth_rate_limit_middleware, rate_limit_middleware,
+    request_id_middleware, require_admin, require_write,
 };
 use crate::openapi::ApiDoc;
 use crate::state::AppState;
@@ -46,6 +47,20 @@ pub fn build_router(state: AppState) -> Router {
                             "/policies/{policy_id}",
                             delete(handlers::policies::delete_policy),
                         )
+                        .route("/policy/explain", get(handlers::policies::explain_policy))
+                        // Global tool kill switch (admin only)
+                        .route(
+                            "/policy/kill-switch",
+                            get(handlers::policies::list_killed_tools),
+                        )
+                        .route(
+                            "/policy/kill-switch/{tool_name}",
+                            put(handlers::policies::kill_tool)
+                                .delete(handlers::policies::revive_tool),
+                        )
+                        // Run archival (admin only)
+                        .route("/runs:archive", post(handlers::runs::archive_runs))
+                        .route("/runs:sweep-stuck", post(handlers::runs::sweep_stuck_runs))
                         // Security config update (admin only)
                         .route("/security/config", put(handlers::security::update_config))
                         .layer(middleware::from_fn(require_admin())),
@@ -61,9 +76,26 @@ pub fn build_router(state: AppState) -> Router {
                             "/registry/agents/{agent_id}/versions",
                             post(handlers::registry::create_agent_version),
                         )
+                        .route(
+                            "/registry/agents/{agent_id}/versions/{version_id}/promote",
+                            post(handlers::registry::promote_agent_version),
+                        )
                         .route("/registry/tools", post(handlers::registry::create_tool))
                         // Workflow creation
                         .route("/workflows", post(handlers::workflows::create_workflow))
+                        .route(
+                            "/workflows/{workflow_id}/steps/{step_id}",
+                            patch(handlers::workflows::patch_workflow_step),
+                        )
+                        // Workflow run control
+                        .route(
+                            "/workflow-runs/{run_id}/pause",
+                            post(handlers::workflows::pause_workflow_run),
+                        )
+                        .route(
+                            "/workflow-runs/{run_id}/resume",
+                            post(handlers::workflows::resume_workflow_run),
+                        )
                         .layer(middleware::from_fn(require_write())),
                 )
                 // ========================================
@@ -71,18 +103,33 @@ pub fn build_router(state: AppState) -> Router {
                 // ========================================
                 // Runs
                 .route("/runs", post(handlers::runs::create_run))
+                .route("/runs:estimate", post(handlers::runs::estimate_run))
                 .route("/runs", get(handlers::runs::list_runs))
+                .route("/runs/diff", get(handlers::runs::diff_runs))
                 .route("/runs/{run_id}", get(handlers::runs::get_run))
                 .route("/runs/{run_id}/cancel", post(handlers::runs::cancel_run))
+                .route("/runs/{run_id}/export", get(handlers::runs::export_run))
                 .route("/runs/{run_id}/steps", get(handlers::runs::list_steps))
                 .route(
                     "/runs/{run_id}/steps/{step_id}",
                     post(handlers::runs::submit_step_result),
                 )
+                .route(
+                    "/runs/{run_id}/steps/{step_id}/job",
+                    get(handlers::runs::get_step_job),
+                )
+                .route(
+                    "/runs/{run_id}/steps:batch",
+                    post(handlers::runs::submit_step_results_batch),
+                )
                 .route(
                     "/runs/{run_id}/check-tool",
                     post(handlers::runs::check_tool_policy),
                 )
+                .route(
+                    "/runs/{run_id}/tools:check",
+                    post(handlers::runs::check_tools_policy),
+                )
                 // Approvals
                 .route(
                     "/approvals",
@@ -105,6 +152,10 @@ pub fn build_router(state: AppState) -> Router {
                     "/registry/agents/{agent_id}/versions",
                     get(handlers::registry::list_agent_versions),
                 )
+                .route(
+                    "/registry/agents/{agent_id}/versions/{version_id}/tools",
+                    get(handlers::registry::list_agent_version_tools),
+                )
                 .route(
                     "/registry/agents/{agent_id}/stats",
                     get(handlers::registry::get_agent_stats),
@@ -123,6 +174,14 @@ pub fn build_router(state: AppState) -> Router {
                 .route("/api-keys/{key_id}", get(handlers::api_keys::get_api_key))
                 // Workflows (read)
                 .route("/workflows", get(handlers::workflows::list_workflows))
+                .route(
+                    "/workflows:validate",
+                    post(handlers::workflows::validate_workflow),
+                )
+                .route(
+                    "/conditions:evaluate",
+                    post(handlers::workflows::evaluate_condition_expression),
+                )
                 .route(
                     "/workflows/{workflow_id}",
                     get(handlers::workflows::get_workflow),
@@ -140,14 +199,30 @@ pub fn build_router(state: AppState) -> Router {
                     "/workflow-runs/{run_id}",
                     get(handlers::workflows::get_workflow_run),
                 )
+                .route(
+                    "/workflow-runs/{run_id}/topology",
+                    get(handlers::workflows::get_workflow_run_topology),
+                )
+                .route(
+                    "/workflow-runs/{run_id}/reachability",
+                    get(handlers::workflows::get_step_reachability),
+                )
                 .route(
                     "/workflow-runs/{run_id}/cancel",
                     post(handlers::workflows::cancel_workflow_run),
                 )
+                .route(
+                    "/workflow-runs/{run_id}/ws",
+                    get(handlers::workflow_stream::workflow_run_ws),
+                )
                 .route(
                     "/workflow-runs/{run_id}/executions",
                     get(handlers::workflows::list_step_executions),
                 )
+                .route(
+                    "/workflow-runs/{run_id}/steps/{step_id}",
+                    get(handlers::workflows::get_step_execution),
+                )
                 .route(
                     "/workflow-runs/{run_id}/executions",
                     post(handlers::workflows::create_step_execution),
@@ -179,6 +254,12 @@ pub fn build_router(state: AppState) -> Router {
                     pre_auth_rate_limit_middleware,
                 )),
         )
+        // Body size limits are enforced by body_limit_middleware, not extractors
+        .layer(DefaultBodyLimit::disable())
+        .layer(middleware::from_fn_with_state(
+            state.body_limits,
+            body_limit_middleware,
+        ))
         // Request ID middleware for all routes
         .layer(middleware::from_fn(request_id_middleware))
         .with_state(state)
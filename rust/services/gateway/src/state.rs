@@ -1,16 +1,31 @@
 //! Application state
 
-use fd_policy::{AirlockConfig, AirlockInspector, AirlockMode, PolicyEngine};
+use fd_audit::AuditSampler;
+use fd_policy::{AirlockConfig, AirlockInspector, AirlockMode, PolicyEngine, TenantAirlockCache};
 use fd_storage::{
-    AgentsRepo, ApiKeysRepo, AuditRepo, DbPool, PoliciesRepo, QueueClient, RunsRepo, StepsRepo,
-    ThreatsRepo, ToolsRepo, WorkflowsRepo,
+    AgentsRepo, ApiKeysRepo, AuditRepo, DbPool, KeyTtlConfig, PoliciesRepo, QueueClient, RunsRepo,
+    StepsRepo, ThreatsRepo, ToolsRepo, WorkflowsRepo,
 };
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::handlers::orchestrator::WorkflowRunLocks;
+use crate::handlers::workflow_stream::WorkflowEventHub;
 use crate::middleware::{
-    create_oauth2_validator, create_rate_limiter, OAuth2Validator, RateLimiter,
+    create_oauth2_validator, create_rate_limiter, BodyLimitConfig, OAuth2Validator, RateLimiter,
 };
 
+type HmacSha256 = Hmac<Sha256>;
+
+/// Default cap on stored step output size (1 MiB)
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Default interval between background compaction passes (5 minutes)
+const DEFAULT_MAINTENANCE_INTERVAL_SECS: u64 = 5 * 60;
+
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState {
@@ -20,8 +35,9 @@ pub struct AppState {
     /// Policy engine for authorization
     pub policy_engine: Arc<PolicyEngine>,
 
-    /// Airlock security inspector
-    pub airlock: Arc<AirlockInspector>,
+    /// Airlock security inspectors: the gateway-wide default plus per-tenant
+    /// overrides loaded from storage on first use
+    pub tenant_airlocks: Arc<TenantAirlockCache>,
 
     /// Queue client for job publishing (lock-free, uses multiplexed connection)
     pub queue: Arc<QueueClient>,
@@ -35,6 +51,34 @@ pub struct AppState {
     /// API key secret for HMAC hashing (for secure API key verification)
     pub api_key_secret: Arc<Vec<u8>>,
 
+    /// Request body size limits
+    pub body_limits: BodyLimitConfig,
+
+    /// Maximum serialized size of a stored step output (0 disables the cap)
+    pub max_output_bytes: usize,
+
+    /// Pending approvals a run may accumulate before approval-gated calls
+    /// are denied (None disables the cap)
+    pub max_pending_approvals: Option<i64>,
+
+    /// Interval between background compaction passes (None disables them)
+    pub maintenance_interval: Option<Duration>,
+
+    /// Variables available to `${VAR}` references in tool MCP server URLs
+    pub mcp_server_vars: Arc<HashMap<String, String>>,
+
+    /// Key for deriving per-run step result signing secrets (None disables signing)
+    pub step_signing_key: Option<Arc<Vec<u8>>>,
+
+    /// OTLP exporter endpoint probed by readiness checks (None when not configured)
+    pub otel_endpoint: Option<String>,
+
+    /// Workflow events for the run status WebSockets
+    pub workflow_events: WorkflowEventHub,
+
+    /// Locks serializing scheduler transitions of each workflow run
+    pub workflow_run_locks: WorkflowRunLocks,
+
     /// Repositories (lazy-initialized from db pool)
     repos: Repos,
 }
@@ -43,16 +87,36 @@ pub struct AppState {
 #[derive(Clone)]
 pub struct Repos {
     db: DbPool,
+    audit_sampler: Arc<AuditSampler>,
 }
 
 impl Repos {
     pub fn new(db: DbPool) -> Self {
-        Self { db }
+        Self {
+            db,
+            audit_sampler: Arc::new(AuditSampler::new()),
+        }
+    }
+
+    /// Sample background audit writes per action
+    pub fn with_audit_sampler(mut self, sampler: AuditSampler) -> Self {
+        self.audit_sampler = Arc::new(sampler);
+        self
+    }
+
+    /// Audit events dropped by sampling since startup
+    pub fn suppressed_audit_events(&self) -> u64 {
+        self.audit_sampler.suppressed()
     }
 
     /// Spawn an audit event write in the background (fire-and-forget).
     /// This reduces API latency by not waiting for audit writes to complete.
+    ///
+    /// Events of sampled actions may be dropped; see [`AuditSampler`].
     pub fn spawn_audit(&self, event: fd_storage::models::CreateAuditEvent) {
+        if !self.audit_sampler.should_record(&event.action, &event.id) {
+            return;
+        }
         let audit_repo = self.audit();
         tokio::spawn(async move {
             if let Err(e) = audit_repo.create(event).await {
@@ -181,7 +245,14 @@ impl AppState {
             "Airlock security inspector initialized"
         );
 
-        let airlock = Arc::new(AirlockInspector::new(airlock_config));
+        let mut airlock = AirlockInspector::new(airlock_config);
+        if let Some(max) = std::env::var("FERRUMDECK_AIRLOCK_MAX_CONCURRENT_INSPECTIONS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+        {
+            airlock = airlock.with_max_concurrent_inspections(max);
+        }
+        let airlock = Arc::new(airlock);
 
         // Create rate limiter
         let rate_limiter = create_rate_limiter();
@@ -189,15 +260,69 @@ impl AppState {
         // Create OAuth2 validator (if enabled via environment)
         let oauth2_validator = create_oauth2_validator();
 
+        let max_output_bytes = std::env::var("MAX_STEP_OUTPUT_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_OUTPUT_BYTES);
+
+        let max_pending_approvals = std::env::var("MAX_PENDING_APPROVALS_PER_RUN")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|&cap| cap > 0);
+
+        let maintenance_interval = std::env::var("MAINTENANCE_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map_or(Some(DEFAULT_MAINTENANCE_INTERVAL_SECS), |secs| {
+                (secs > 0).then_some(secs)
+            })
+            .map(Duration::from_secs);
+
+        let mcp_server_vars =
+            parse_mcp_server_vars(&std::env::var("MCP_SERVER_VARS").unwrap_or_default());
+
+        let audit_sampler = match AuditSampler::from_spec(
+            &std::env::var("AUDIT_SAMPLE_RATES").unwrap_or_default(),
+        ) {
+            Ok(sampler) => sampler,
+            Err(e) => {
+                tracing::warn!(error = %e, "Ignoring AUDIT_SAMPLE_RATES, recording all audit events");
+                AuditSampler::new()
+            }
+        };
+
+        let key_ttls = KeyTtlConfig::from_lookup(|name| std::env::var(name).ok());
+
+        let step_signing_key = std::env::var("STEP_RESULT_SIGNING_KEY")
+            .ok()
+            .filter(|key| !key.is_empty())
+            .map(|key| Arc::new(key.into_bytes()));
+        if step_signing_key.is_some() {
+            tracing::info!("Step result signing enabled");
+        }
+
+        let otel_endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .ok()
+            .filter(|endpoint| !endpoint.is_empty());
+
         Ok(Self {
             db: db.clone(),
             policy_engine,
-            airlock,
-            queue: Arc::new(queue),
+            tenant_airlocks: Arc::new(TenantAirlockCache::new(airlock)),
+            queue: Arc::new(queue.with_key_ttls(key_ttls)),
             rate_limiter,
             oauth2_validator,
             api_key_secret: Arc::new(api_key_secret.into_bytes()),
-            repos: Repos::new(db),
+            body_limits: BodyLimitConfig::from_env(),
+            max_output_bytes,
+            max_pending_approvals,
+            maintenance_interval,
+            mcp_server_vars: Arc::new(mcp_server_vars),
+            step_signing_key,
+            otel_endpoint,
+            workflow_events: WorkflowEventHub::default(),
+            workflow_run_locks: WorkflowRunLocks::default(),
+            repos: Repos::new(db).with_audit_sampler(audit_sampler),
         })
     }
 
@@ -206,13 +331,120 @@ impl AppState {
         &self.repos
     }
 
+    /// Get the Airlock inspector for a tenant
+    ///
+    /// Uses the tenant's stored configuration when present, otherwise the
+    /// gateway default. Results are cached until the tenant's configuration
+    /// is updated.
+    pub async fn airlock_for_tenant(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Arc<AirlockInspector>, sqlx::Error> {
+        if let Some(inspector) = self.tenant_airlocks.get(tenant_id).await {
+            return Ok(inspector);
+        }
+
+        let stored = self
+            .repos
+            .threats()
+            .get_tenant_airlock_config(tenant_id)
+            .await?;
+
+        let config = stored.and_then(|row| {
+            serde_json::from_value::<AirlockConfig>(row.config)
+                .map_err(|e| {
+                    tracing::warn!(
+                        tenant_id = %tenant_id,
+                        error = %e,
+                        "Invalid tenant airlock config, using gateway default"
+                    );
+                })
+                .ok()
+        });
+
+        Ok(self.tenant_airlocks.insert(tenant_id, config).await)
+    }
+
+    /// Refresh the global tool kill switch from Redis once its cache expires
+    ///
+    /// Redis errors keep the last known set rather than failing tool checks.
+    pub async fn refresh_kill_switch(&self) {
+        let kill_switch = self.policy_engine.kill_switch();
+        if !kill_switch.needs_refresh() {
+            return;
+        }
+
+        match self
+            .queue
+            .set_members(fd_storage::queue::sets::KILLED_TOOLS)
+            .await
+        {
+            Ok(tools) => kill_switch.refresh(tools),
+            Err(e) => tracing::warn!(error = %e, "Failed to refresh tool kill switch"),
+        }
+    }
+
+    /// Secret a worker must sign a run's step results with (None when signing is disabled)
+    pub fn run_signing_secret(&self, run_id: &str) -> Option<String> {
+        self.step_signing_key
+            .as_ref()
+            .map(|key| derive_run_signing_secret(key, run_id))
+    }
+
+    /// Key used to sign allow-once approval tokens
+    ///
+    /// Reuses the API key secret; tokens are domain-separated by their prefix.
+    pub fn approval_token_key(&self) -> &[u8] {
+        &self.api_key_secret
+    }
+
     /// Publish a step job to the queue
     ///
     /// This method is lock-free and can be called concurrently from multiple tasks.
+    /// When step result signing is enabled the run's signing secret is attached
+    /// to the job context.
     pub async fn enqueue_step(
         &self,
         message: &fd_storage::QueueMessage<fd_storage::queue::StepJob>,
     ) -> Result<String, redis::RedisError> {
-        self.queue.enqueue("steps", message).await
+        match self.run_signing_secret(&message.payload.run_id) {
+            Some(secret) => {
+                let mut signed = message.clone();
+                signed.payload.context.signing_secret = Some(secret);
+                self.queue.enqueue("steps", &signed).await
+            }
+            None => self.queue.enqueue("steps", message).await,
+        }
     }
+
+    /// Publish a workflow progress event to the workflow-events stream
+    pub async fn publish_workflow_event(
+        &self,
+        event: &fd_storage::queue::WorkflowEvent,
+    ) -> Result<String, redis::RedisError> {
+        let message = fd_storage::QueueMessage::new(format!("wfe_{}", ulid::Ulid::new()), event);
+        self.queue
+            .enqueue(fd_storage::queue::queues::WORKFLOW_EVENTS, &message)
+            .await
+    }
+}
+
+/// Derive a run's signing secret from the gateway signing key
+///
+/// Deterministic, so the gateway can verify submissions without storing
+/// secrets; a leaked secret only lets results be forged for that one run.
+pub(crate) fn derive_run_signing_secret(key: &[u8], run_id: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(run_id.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Parse a comma-separated `KEY=value` list into the MCP server variable map.
+/// Entries without `=` are ignored.
+pub(crate) fn parse_mcp_server_vars(raw: &str) -> HashMap<String, String> {
+    raw.split(',')
+        .filter_map(|entry| entry.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
 }